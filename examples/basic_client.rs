@@ -1,7 +1,9 @@
 use std::io;
 
-use bincode;
-use kodasql::network_protocol::types::{MessageType, Request, Response, ResponseStatus};
+use kodasql::network_protocol::constants::DEFAULT_MAX_MESSAGE_SIZE;
+use kodasql::network_protocol::framing::FrameDecoder;
+use kodasql::network_protocol::parsing::{format_request, parse_response};
+use kodasql::network_protocol::types::{MessageType, Request, Response, ResponseStatus, ResultSet};
 use tokio::net::TcpStream;
 use tokio::io::{AsyncReadExt, AsyncWriteExt, AsyncBufReadExt};
 use tokio::io::BufReader;
@@ -11,8 +13,10 @@ async fn main() -> io::Result<()> {
     let mut stream = TcpStream::connect("127.0.0.1:8080").await?;
     let mut stdin = BufReader::new(tokio::io::stdin());
     let mut input_string = String::new();
+    let mut decoder = FrameDecoder::new(DEFAULT_MAX_MESSAGE_SIZE);
+    let mut buffer = vec![0; 4096];
 
-    loop {
+    'repl: loop {
         println!("Enter SQL query (or type 'exit' to quit):");
         input_string.clear();
 
@@ -29,41 +33,89 @@ async fn main() -> io::Result<()> {
         };
 
         // Serialize and send request
-        let serialized_request = bincode::serialize(&request).unwrap();
+        let serialized_request = format_request(&request);
         stream.write_all(&serialized_request).await?;
 
-        // Await and read the response
-        let mut buffer = vec![0; 4096];
-        let n = stream.read(&mut buffer).await?;
+        // Await and read the response, which may span several reads and, for large results, several frames
+        let mut response: Option<Response> = None;
+        loop {
+            let payload = loop {
+                match decoder.next_frame() {
+                    Ok(Some(payload)) => break Some(payload),
+                    Ok(None) => {},
+                    Err(e) => {
+                        println!("Failed to decode response: {}", e);
+                        continue 'repl;
+                    }
+                }
 
-        // Indicate connection was closed on n == 0
-        if n == 0 {
-            println!("Server closed the connection.");
-            break;
-        }
-        match bincode::deserialize::<Response>(&buffer[..n]) {
-            Ok(response) => {
-                // Handle response
-                match response.status {
-                    ResponseStatus::Success => {
-                        if let Some(data) = response.data {
-                            println!("Data: {}", data);
-                        }
-                    },
-                    ResponseStatus::Error => {
-                        if let Some(error) = response.error {
-                            println!("Error: {}", error);
-                            continue;
-                        }
-                    },
+                let n = stream.read(&mut buffer).await?;
+                if n == 0 {
+                    break None;
+                }
+                decoder.extend(&buffer[..n]);
+            };
+
+            // Indicate connection was closed on missing payload
+            let payload = match payload {
+                Some(payload) => payload,
+                None => {
+                    println!("Server closed the connection.");
+                    break 'repl;
                 }
-            },
-            Err(e) => {
-                println!("Failed to deserialize response: {}", e);
-                continue;
+            };
+            match parse_response(&payload) {
+                Ok(batch) => match response.as_mut() {
+                    Some(response) => response.append_batch(batch),
+                    None => response = Some(batch),
+                },
+                Err(e) => {
+                    println!("Failed to deserialize response: {}", e);
+                    continue 'repl;
+                }
+            }
+
+            if response.as_ref().is_some_and(|response| !matches!(response.status, ResponseStatus::Partial)) {
+                break;
+            }
+        }
+
+        // Handle response
+        if let Some(response) = response {
+            match response.status {
+                ResponseStatus::Success | ResponseStatus::Partial => {
+                    for result_set in response.data.iter() {
+                        print_result_set(result_set);
+                    }
+                },
+                ResponseStatus::Error => {
+                    if let Some(error) = response.error {
+                        println!("Error: {}", error);
+                    }
+                },
             }
         }
     }
 
     Ok(())
+}
+
+fn print_result_set(result_set: &ResultSet) {
+    if !result_set.columns.is_empty() {
+        let headers: Vec<String> = result_set.columns.iter().map(|column| column.name.clone()).collect();
+        println!("{}", headers.join(" | "));
+
+        for row in result_set.rows.iter() {
+            let values: Vec<String> = row.iter().map(|value| value.to_string()).collect();
+            println!("{}", values.join(" | "));
+        }
+        println!("({} rows)", result_set.rows.len());
+    }
+
+    if let Some(message) = &result_set.message {
+        println!("{}", message);
+    }
+    if let Some(rows_affected) = result_set.rows_affected {
+        println!("Rows affected: {}", rows_affected);
+    }
 }
\ No newline at end of file
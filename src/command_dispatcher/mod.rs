@@ -1,2 +1,4 @@
 pub mod request_handler;
-pub mod statement_dispatcher;
\ No newline at end of file
+pub mod statement_dispatcher;
+pub mod postgres_request_handler;
+pub mod session;
\ No newline at end of file
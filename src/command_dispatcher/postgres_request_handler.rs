@@ -0,0 +1,356 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use sqlparser::ast::{ObjectType, Statement};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::command_dispatcher::request_handler;
+use crate::command_dispatcher::session::Session;
+use crate::database::database_loader;
+use crate::database::types::DataType;
+use crate::database::utils::find_database_schema;
+use crate::network_protocol::constants::RESPONSE_BATCH_SIZE;
+use crate::network_protocol::postgres::constants::{self, BOOL_OID, FLOAT8_OID, INT8_OID, SERVER_VERSION, TEXT_OID};
+use crate::network_protocol::postgres::messages::{self, BackendMessage, FieldDescription, FrontendMessage, TransactionStatus};
+use crate::network_protocol::result_stream::{self, ResultChunk};
+use crate::network_protocol::types::{CellValue, ResultSet};
+use crate::shared::errors::Error;
+
+static NEXT_PROCESS_ID: AtomicI32 = AtomicI32::new(1);
+
+/*
+ * PostgreSQL frontend/backend protocol (v3), simple query flow only
+ */
+pub async fn handle_postgres_connection(socket: &mut TcpStream, max_message_size: usize) {
+    let mut buffer = [0; 4096];
+    let mut incoming: Vec<u8> = Vec::new();
+    let mut is_started = false;
+    let mut discard_until_sync = false;
+    let mut session = Session::default(); // Dropping the session rolls back any open transaction
+
+    loop {
+        // Handle all complete messages received so far
+        loop {
+            let decoded = if is_started {
+                messages::decode_message(&mut incoming, max_message_size)
+            } else {
+                messages::decode_startup_message(&mut incoming, max_message_size)
+            };
+
+            let message = match decoded {
+                Ok(Some(message)) => message,
+                Ok(None) => break,
+                Err(e) => {
+                    // Framing can't be recovered, so report and close
+                    let _ = send_messages(socket, &[error_response(&e)]).await;
+                    return;
+                }
+            };
+
+            let responses = match message {
+                FrontendMessage::SslRequest | FrontendMessage::GssEncRequest => {
+                    // Encryption is not supported; the client continues in plain text
+                    if socket.write_all(b"N").await.is_err() {
+                        return;
+                    }
+                    continue;
+                },
+                FrontendMessage::CancelRequest => return,
+                FrontendMessage::Startup { parameters } => {
+                    is_started = true;
+                    session.search_path = get_startup_search_path(&parameters);
+                    get_startup_messages()
+                },
+                FrontendMessage::Query(sql) => {
+                    discard_until_sync = false;
+                    match handle_simple_query(socket, &sql, &mut session).await {
+                        Ok(responses) => responses,
+                        Err(_) => {
+                            eprintln!("Failed to write response to socket");
+                            return;
+                        }
+                    }
+                },
+                FrontendMessage::Sync => {
+                    discard_until_sync = false;
+                    vec![get_ready_for_query(&session)]
+                },
+                FrontendMessage::Terminate => return,
+                FrontendMessage::Unsupported(message_type) => {
+                    // Extended query messages are rejected once, then skipped until Sync
+                    if discard_until_sync {
+                        continue;
+                    }
+                    discard_until_sync = true;
+                    vec![BackendMessage::ErrorResponse {
+                        code: String::from(constants::FEATURE_NOT_SUPPORTED),
+                        message: format!("Message type '{}' is not supported; use the simple query protocol.", message_type as char),
+                    }]
+                },
+            };
+
+            if send_messages(socket, &responses).await.is_err() {
+                eprintln!("Failed to write response to socket");
+                return;
+            }
+        }
+
+        let n = match socket.read(&mut buffer).await {
+            Ok(0) => {
+                // Client closed the connection
+                return;
+            },
+            Ok(n) => n,
+            Err(e) => {
+                eprintln!("Error reading from socket: {}", e);
+                return;
+            },
+        };
+
+        incoming.extend_from_slice(&buffer[..n]);
+    }
+}
+
+// Connecting to a "database" named like a schema starts the session in that schema
+fn get_startup_search_path(parameters: &[(String, String)]) -> Vec<String> {
+    let database = match database_loader::get_database() {
+        Ok(database) => database,
+        Err(_) => return Vec::new(),
+    };
+
+    parameters.iter()
+        .filter(|(name, value)| name == "database" && find_database_schema(&database, value).is_some())
+        .map(|(_, value)| value.clone())
+        .collect()
+}
+
+fn get_startup_messages() -> Vec<BackendMessage> {
+    let mut startup_messages = vec![BackendMessage::AuthenticationOk];
+
+    let parameters = [
+        ("server_version", SERVER_VERSION),
+        ("server_encoding", "UTF8"),
+        ("client_encoding", "UTF8"),
+        ("DateStyle", "ISO, MDY"),
+        ("integer_datetimes", "on"),
+        ("standard_conforming_strings", "on"),
+    ];
+    for (name, value) in parameters {
+        startup_messages.push(BackendMessage::ParameterStatus { name: String::from(name), value: String::from(value) });
+    }
+
+    startup_messages.push(BackendMessage::BackendKeyData {
+        process_id: NEXT_PROCESS_ID.fetch_add(1, Ordering::Relaxed),
+        secret_key: 0,
+    });
+    startup_messages.push(BackendMessage::ReadyForQuery(TransactionStatus::Idle));
+
+    startup_messages
+}
+
+// Results are sent as the statements run, the rows of queries in batches as they are read; the messages ending the query are returned
+async fn handle_simple_query(socket: &mut TcpStream, sql: &str, session: &mut Session) -> Result<Vec<BackendMessage>, Error> {
+    let mut responses: Vec<BackendMessage> = Vec::new();
+
+    let statements = match request_handler::parse_sql(sql) {
+        Ok(statements) => statements,
+        Err(e) => return Ok(vec![error_response(&e), get_ready_for_query(session)]),
+    };
+    if statements.is_empty() {
+        return Ok(vec![BackendMessage::EmptyQueryResponse, get_ready_for_query(session)]);
+    }
+
+    let (sender, mut receiver) = result_stream::create_result_stream();
+    let processing = result_stream::run_with_result_stream(sender, session.execute_statements(&statements));
+
+    let sending = async {
+        let mut statements = statements.iter();
+        let mut sent_rows = 0; // Rows of the running statement sent ahead
+        while let Some(chunk) = receiver.recv().await {
+            let messages = match chunk {
+                ResultChunk::Partial(result_set) => {
+                    let messages = format_result_rows(&result_set, sent_rows == 0);
+                    sent_rows += result_set.rows.len();
+                    messages
+                },
+                ResultChunk::Complete(result_set) => {
+                    let statement = statements.next().ok_or(Error::ServerError)?;
+                    let messages = format_statement_result(statement, &result_set, sent_rows);
+                    sent_rows = 0;
+                    messages
+                },
+            };
+            send_messages(socket, &messages).await?;
+        }
+        Ok::<(), Error>(())
+    };
+
+    // Execution stops at the first error; earlier results are still reported
+    let ((_, error), sent) = tokio::join!(processing, sending);
+    sent?;
+    if let Some(e) = error {
+        responses.push(error_response(&e));
+    }
+
+    responses.push(get_ready_for_query(session));
+    Ok(responses)
+}
+
+fn get_ready_for_query(session: &Session) -> BackendMessage {
+    let status = if session.is_transaction_failed() {
+        TransactionStatus::Failed
+    } else if session.is_in_transaction() {
+        TransactionStatus::InTransaction
+    } else {
+        TransactionStatus::Idle
+    };
+
+    BackendMessage::ReadyForQuery(status)
+}
+
+// The rest of the statement's result, after the rows sent ahead
+fn format_statement_result(statement: &Statement, result_set: &ResultSet, sent_rows: usize) -> Vec<BackendMessage> {
+    if !matches!(statement, Statement::Query(_) | Statement::Explain { .. } | Statement::ShowVariable { .. }) {
+        return vec![BackendMessage::CommandComplete(get_command_tag(statement, result_set))];
+    }
+
+    let mut result_messages = format_result_rows(result_set, sent_rows == 0);
+
+    let command_tag = match statement {
+        Statement::ShowVariable { .. } => String::from("SHOW"),
+        Statement::Explain { .. } => String::from("EXPLAIN"),
+        _ => format!("SELECT {}", sent_rows + result_set.rows.len()),
+    };
+    result_messages.push(BackendMessage::CommandComplete(command_tag));
+    result_messages
+}
+
+// Data rows, preceded by the row description in the first part of a result
+fn format_result_rows(result_set: &ResultSet, is_first_part: bool) -> Vec<BackendMessage> {
+    let mut result_messages: Vec<BackendMessage> = Vec::new();
+
+    if is_first_part {
+        let fields = result_set.columns.iter()
+            .map(|column| {
+                let (type_oid, type_size) = get_type_oid(&column.data_type);
+                FieldDescription { name: column.name.clone(), type_oid, type_size }
+            })
+            .collect();
+        result_messages.push(BackendMessage::RowDescription(fields));
+    }
+
+    for row in result_set.rows.iter() {
+        let values = row.iter().map(format_cell_value).collect();
+        result_messages.push(BackendMessage::DataRow(values));
+    }
+
+    result_messages
+}
+
+fn get_type_oid(data_type: &DataType) -> (i32, i16) {
+    match data_type {
+        DataType::Integer => (INT8_OID, 8),
+        DataType::Float => (FLOAT8_OID, 8),
+        DataType::Boolean => (BOOL_OID, 1),
+        DataType::Text => (TEXT_OID, -1),
+    }
+}
+
+// Text format representation of a cell
+fn format_cell_value(value: &CellValue) -> Option<String> {
+    match value {
+        CellValue::Null => None,
+        CellValue::Boolean(value) => Some(String::from(if *value { "t" } else { "f" })),
+        value => Some(value.to_string()),
+    }
+}
+
+fn get_command_tag(statement: &Statement, result_set: &ResultSet) -> String {
+    let rows_affected = result_set.rows_affected.unwrap_or(0);
+
+    let tag = match statement {
+        Statement::Insert(_) => return format!("INSERT 0 {}", rows_affected),
+        Statement::Update { .. } => return format!("UPDATE {}", rows_affected),
+        Statement::Delete(_) => return format!("DELETE {}", rows_affected),
+        Statement::CreateTable(_) => "CREATE TABLE",
+        Statement::CreateSchema { .. } => "CREATE SCHEMA",
+        Statement::AlterTable { .. } => "ALTER TABLE",
+        Statement::CreateTrigger { .. } => "CREATE TRIGGER",
+        Statement::Analyze { .. } => "ANALYZE",
+        Statement::CreateIndex(_) => "CREATE INDEX",
+        Statement::Drop { object_type: ObjectType::Index, .. } => "DROP INDEX",
+        Statement::Drop { object_type: ObjectType::Table, .. } => "DROP TABLE",
+        Statement::Drop { object_type: ObjectType::Schema, .. } => "DROP SCHEMA",
+        Statement::StartTransaction { .. } => "BEGIN",
+        Statement::Commit { .. } => "COMMIT",
+        Statement::Rollback { .. } => "ROLLBACK",
+        Statement::SetVariable { .. } | Statement::Use { .. } => "SET",
+        _ => "OK",
+    };
+
+    String::from(tag)
+}
+
+fn error_response(error: &Error) -> BackendMessage {
+    BackendMessage::ErrorResponse {
+        code: String::from(get_sqlstate(error)),
+        message: format!("{}", error),
+    }
+}
+
+fn get_sqlstate(error: &Error) -> &'static str {
+    match error {
+        Error::InvalidSQLSyntax | Error::InvalidLimit { .. } | Error::InvalidOffset { .. } | Error::InvalidSubqueryColumnCount { .. } => constants::SYNTAX_ERROR,
+        Error::InvalidOrderPosition { .. } => constants::INVALID_COLUMN_REFERENCE,
+        Error::InvalidMessageFormat | Error::UnsupportedProtocolVersion { .. } => constants::PROTOCOL_VIOLATION,
+        Error::MessageTooLarge { .. } => constants::PROGRAM_LIMIT_EXCEEDED,
+        Error::TableDoesNotExist { .. } | Error::MissingTableName => constants::UNDEFINED_TABLE,
+        Error::ColumnDoesNotExist { .. } => constants::UNDEFINED_COLUMN,
+        Error::SchemaDoesNotExist { .. } | Error::MissingSchemaName => constants::UNDEFINED_SCHEMA,
+        Error::IndexDoesNotExist { .. } => constants::UNDEFINED_OBJECT,
+        Error::TableNameAlreadyExists { .. } | Error::IndexAlreadyExists { .. } => constants::DUPLICATE_TABLE,
+        Error::ColumnNameAlreadyExists { .. } => constants::DUPLICATE_COLUMN,
+        Error::ForeignKeyAlreadyExists { .. } | Error::ConstraintAlreadyExists { .. } => constants::DUPLICATE_OBJECT,
+        Error::DuplicateTableReference { .. } => constants::DUPLICATE_ALIAS,
+        Error::AmbiguousColumnName { .. } => constants::AMBIGUOUS_COLUMN,
+        Error::ColumnTypeDoesNotMatch { .. } | Error::InvalidOperandTypes { .. } => constants::DATATYPE_MISMATCH,
+        Error::InvalidCast { .. } => constants::INVALID_TEXT_REPRESENTATION,
+        Error::DivisionByZero => constants::DIVISION_BY_ZERO,
+        Error::ColumnNotGrouped { .. } | Error::AggregateNotAllowed { .. } => constants::GROUPING_ERROR,
+        Error::NumericValueOutOfRange => constants::NUMERIC_VALUE_OUT_OF_RANGE,
+        Error::SubqueryReturnedMultipleRows => constants::CARDINALITY_VIOLATION,
+        Error::ColumnNotNull { .. } => constants::NOT_NULL_VIOLATION,
+        Error::ForeignKeyConstraintNotSatisfied { .. } => constants::FOREIGN_KEY_VIOLATION,
+        Error::InvalidForeignKey { .. } => constants::INVALID_FOREIGN_KEY,
+        Error::ColumnReferencedByForeignKey { .. } => constants::DEPENDENT_OBJECTS_STILL_EXIST,
+        Error::MultiplePrimaryKeys { .. } => constants::INVALID_TABLE_DEFINITION,
+        Error::CheckConstraintNotSatisfied { .. } => constants::CHECK_VIOLATION,
+        Error::ColumnUniquenessNotSatisfied { .. } | Error::IndexUniquenessNotSatisfied { .. } => constants::UNIQUE_VIOLATION,
+        Error::TransactionAborted => constants::IN_FAILED_SQL_TRANSACTION,
+        Error::TransactionAlreadyActive => constants::ACTIVE_SQL_TRANSACTION,
+        Error::NoActiveTransaction => constants::NO_ACTIVE_SQL_TRANSACTION,
+        Error::TransactionConflict { .. } | Error::ReadConflict { .. } => constants::SERIALIZATION_FAILURE,
+        Error::LockTimeout { .. } => constants::LOCK_NOT_AVAILABLE,
+        Error::GenericUnsupported
+        | Error::NotSupportedUpdateTableOperation
+        | Error::UnsupportedSelectClause
+        | Error::UnsupportedValueType { .. }
+        | Error::UnsupportedOperationType { .. }
+        | Error::UnsupportedFilter
+        | Error::UnsupportedColumnDataType { .. }
+        | Error::UnsupportedConstraint { .. }
+        | Error::UnsupportedIndexExpression { .. }
+        | Error::UnsupportedExpression { .. } => constants::FEATURE_NOT_SUPPORTED,
+        _ => constants::INTERNAL_ERROR,
+    }
+}
+
+// Large results are written in batches of rows rather than encoded all at once
+async fn send_messages(socket: &mut TcpStream, backend_messages: &[BackendMessage]) -> Result<(), Error> {
+    for batch in backend_messages.chunks(RESPONSE_BATCH_SIZE) {
+        let bytes: Vec<u8> = batch.iter().flat_map(|message| message.encode()).collect();
+        socket.write_all(&bytes).await?;
+    }
+
+    Ok(())
+}
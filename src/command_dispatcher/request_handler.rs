@@ -1,18 +1,59 @@
 use tokio::net::TcpStream;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use sqlparser::parser::Parser;
-use sqlparser::dialect::PostgreSqlDialect;
+use sqlparser::ast::{ObjectName, Statement};
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::dialect::{Dialect, PostgreSqlDialect};
+use sqlparser::tokenizer::Token;
 
 
-use crate::command_dispatcher::statement_dispatcher;
+use crate::command_dispatcher::session::Session;
 use crate::network_protocol;
-use crate::network_protocol::types::{Request, Response, ResponseStatus};
+use crate::network_protocol::constants::RESPONSE_BATCH_SIZE;
+use crate::network_protocol::framing::FrameDecoder;
+use crate::network_protocol::result_stream::{self, ResultChunk};
+use crate::network_protocol::types::{Request, Response, ResponseStatus, ResultSet};
 use crate::shared::errors::Error;
 
-pub async fn handle_request(socket: &mut TcpStream) {
+pub async fn handle_request(socket: &mut TcpStream, max_message_size: usize) {
     let mut buffer = [0; 4096];
+    let mut decoder = FrameDecoder::new(max_message_size);
+    let mut session = Session::default(); // Dropping the session rolls back any open transaction
 
     loop {
+        // Handle all complete frames received so far
+        loop {
+            let payload = match decoder.next_frame() {
+                Ok(Some(payload)) => payload,
+                Ok(None) => break,
+                Err(e) => {
+                    // Frame rejected by the decoder (e.g. exceeds max message size)
+                    if send_response(socket, &Response::from(e)).await.is_err() {
+                        eprintln!("Failed to send error response");
+                        return;
+                    }
+                    continue;
+                }
+            };
+
+            let request = match network_protocol::parsing::parse_request(&payload) {
+                Ok(req) => req,
+                Err(e) => {
+                    if send_response(socket, &Response::from(e)).await.is_err() {
+                        eprintln!("Failed to send error response");
+                        return;
+                    }
+                    continue; // Proceed to next frame
+                },
+            };
+
+            // Process request and send the response back to the client, large results in several frames
+            if process_streamed_request(socket, request, &mut session).await.is_err() {
+                eprintln!("Failed to write response to socket");
+                return;
+            }
+        }
+
         let read_result = socket.read(&mut buffer).await;
 
         let n = match read_result {
@@ -27,60 +68,106 @@ pub async fn handle_request(socket: &mut TcpStream) {
             },
         };
 
-        let request = match network_protocol::parsing::parse_request(&buffer[..n]) {
-            Ok(req) => req,
-            Err(e) => {
-                let error_response = Response::from(e);
-                let response_bytes = network_protocol::parsing::format_response(&error_response);
-                if socket.write_all(&response_bytes).await.is_err() {
-                    eprintln!("Failed to send error response");
-                }
-                continue; // Proceed to next iteration to handle more requests
-            },
-        };
+        decoder.extend(&buffer[..n]);
+    }
+}
 
-        // Process request and obtain response
-        let response = process_request(request).await.map_or_else(
-            |e| e.into(),
-            |data| Response {
-                status: ResponseStatus::Success,
-                data: Some(data),
-                error: None,
-            },
-        );
+// Rows of queries are sent as soon as a batch is read, the rest of the results with the final response
+async fn process_streamed_request(socket: &mut TcpStream, request: Request, session: &mut Session) -> Result<(), Error> {
+    let (sender, mut receiver) = result_stream::create_result_stream();
+    let processing = result_stream::run_with_result_stream(sender, process_request(request, session));
 
-        // Send response back to the client
-        let response_bytes = network_protocol::parsing::format_response(&response);
-        if socket.write_all(&response_bytes).await.is_err() {
-            eprintln!("Failed to write response to socket");
-            return;
+    let sending = async {
+        let mut data: Vec<ResultSet> = Vec::new();
+        while let Some(chunk) = receiver.recv().await {
+            match chunk {
+                // The client adds the next batch's first result set to the last one of a partial response
+                ResultChunk::Partial(result_set) => {
+                    data.push(result_set);
+                    send_response(socket, &Response { status: ResponseStatus::Partial, data: std::mem::take(&mut data), error: None }).await?;
+                },
+                ResultChunk::Complete(result_set) => data.push(result_set),
+            }
         }
+        Ok::<Vec<ResultSet>, Error>(data)
+    };
+
+    let (result, data) = tokio::join!(processing, sending);
+    let response = match result {
+        Ok(_) => Response { status: ResponseStatus::Success, data: data?, error: None },
+        Err(e) => e.into(),
+    };
+    for batch in response.into_batches(RESPONSE_BATCH_SIZE) {
+        send_response(socket, &batch).await?;
     }
+
+    Ok(())
 }
 
+async fn send_response(socket: &mut TcpStream, response: &Response) -> Result<(), Error> {
+    let response_bytes = network_protocol::parsing::format_response(response);
+    socket.write_all(&response_bytes).await?;
 
-pub async fn process_request(request: Request) -> Result<String, Error> {
-    let sql = &request.sql;
+    Ok(())
+}
 
+pub async fn process_request(request: Request, session: &mut Session) -> Result<Vec<ResultSet>, Error> {
     // Parse request into AST
+    let ast = parse_sql(&request.sql)?;
+
+    // Process AST and dispatch statements
+    let (results, error) = session.execute_statements(&ast).await;
+    match error {
+        Some(e) => Err(e),
+        None => Ok(results),
+    }
+}
+
+pub fn parse_sql(sql: &str) -> Result<Vec<Statement>, Error> {
     let dialect = PostgreSqlDialect {};
-    let ast = Parser::parse_sql(&dialect, sql);
+    let ast = parse_statements(&dialect, sql);
     println!("AST: {:?}", ast);
     // match error
-    let ast = match ast {
-        Ok(ast) => ast,
-        Err(_e) => return Err(Error::InvalidSQLSyntax),
-    };
+    match ast {
+        Ok(ast) => Ok(ast),
+        Err(_e) => Err(Error::InvalidSQLSyntax),
+    }
+}
 
-    // Process AST and dispatch statements
-    let mut results = Vec::new();
-    for statement in ast {
-        let result = statement_dispatcher::dispatch_statement(&statement).await;
-        match result {
-            Ok(msg) => results.push(msg),
-            Err(e) => return Err(e),
+// Like Parser::parse_sql, but also accepting Postgres' ANALYZE [table], which sqlparser only knows as ANALYZE TABLE <table>
+fn parse_statements(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Statement>, ParserError> {
+    let mut parser = Parser::new(dialect).try_with_sql(sql)?;
+    let mut statements: Vec<Statement> = Vec::new();
+    let mut expecting_statement_delimiter = false;
+
+    loop {
+        while parser.consume_token(&Token::SemiColon) {
+            expecting_statement_delimiter = false;
+        }
+        if parser.peek_token().token == Token::EOF {
+            break;
+        }
+        if expecting_statement_delimiter {
+            return parser.expected("end of statement", parser.peek_token());
         }
+
+        let is_analyze = matches!(parser.peek_token().token, Token::Word(word) if word.keyword == Keyword::ANALYZE);
+        let is_analyze_table = matches!(parser.peek_nth_token(1).token, Token::Word(word) if word.keyword == Keyword::TABLE);
+        let statement = match is_analyze && !is_analyze_table {
+            true => {
+                parser.next_token();
+                // No table name means every table
+                let table_name = match parser.peek_token().token {
+                    Token::EOF | Token::SemiColon => ObjectName(Vec::new()),
+                    _ => parser.parse_object_name(false)?,
+                };
+                Statement::Analyze { table_name, partitions: None, for_columns: false, columns: Vec::new(), cache_metadata: false, noscan: false, compute_statistics: false }
+            },
+            false => parser.parse_statement()?,
+        };
+        statements.push(statement);
+        expecting_statement_delimiter = true;
     }
 
-    Ok(results.join("\n"))
-}
\ No newline at end of file
+    Ok(statements)
+}
@@ -0,0 +1,200 @@
+use sqlparser::ast::{Expr, Ident, ObjectName, OneOrManyWithParens, Statement, Value};
+use tokio::sync::mpsc::Sender;
+
+use crate::command_dispatcher::statement_dispatcher;
+use crate::database::{database_loader, session_context::run_with_search_path, types::DataType, utils::find_database_schema};
+use crate::network_protocol::result_stream::{self, ResultChunk};
+use crate::network_protocol::types::{CellValue, ResultColumn, ResultSet};
+use crate::shared::errors::Error;
+use crate::transaction_manager::transaction_context::{run_in_transaction, SharedTransaction};
+use crate::transaction_manager::transaction_handler::{begin_transaction, commit_transaction};
+
+/*
+ * Per-connection state
+ */
+#[derive(Default)]
+pub struct Session {
+    pub transaction: Option<SharedTransaction>, // Explicit or implicit transaction in progress
+    pub search_path: Vec<String>, // Schemas for unqualified names, the configured default schema if empty
+}
+
+impl Session {
+    pub fn is_in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+
+    pub fn is_transaction_failed(&self) -> bool {
+        match &self.transaction {
+            Some(transaction) => transaction.lock().map_or(true, |transaction| transaction.is_failed),
+            None => false,
+        }
+    }
+
+    // Execute a batch of statements; results of the statements before a failure are kept, or sent as they come if the connection streams them
+    pub async fn execute_statements(&mut self, statements: &Vec<Statement>) -> (Vec<ResultSet>, Option<Error>) {
+        // Statements sent together outside of a transaction succeed or fail together
+        let is_implicit_transaction = self.transaction.is_none() && !statements.iter().any(is_transaction_control);
+        if is_implicit_transaction {
+            self.transaction = Some(begin_transaction());
+        }
+
+        // Results of a batch that writes are held back until its work is committed, only queries stream theirs
+        let client_stream = result_stream::get_current_result_stream();
+        let holds_results = is_implicit_transaction && !statements.iter().all(|statement| matches!(statement, Statement::Query(_)));
+        let result_stream = client_stream.clone().filter(|_| !holds_results);
+
+        let mut results: Vec<ResultSet> = Vec::new();
+        for statement in statements {
+            let result = match (result_stream::run_with_optional_result_stream(result_stream.clone(), self.execute_statement(statement)).await, &result_stream) {
+                (Ok(result_set), Some(result_stream)) => result_stream::send_result_chunk(result_stream, ResultChunk::Complete(result_set)).await,
+                (Ok(result_set), None) => {
+                    results.push(result_set);
+                    Ok(())
+                },
+                (Err(e), _) => Err(e),
+            };
+            if let Err(e) = result {
+                if is_implicit_transaction {
+                    self.transaction = None;
+                }
+                return hand_over_results(results, &client_stream, Some(e)).await;
+            }
+        }
+
+        if is_implicit_transaction {
+            if let Some(transaction) = self.transaction.take() {
+                if let Err(e) = commit_transaction(transaction).await {
+                    return (Vec::new(), Some(e));
+                }
+            }
+        }
+
+        hand_over_results(results, &client_stream, None).await
+    }
+
+    async fn execute_statement(&mut self, statement: &Statement) -> Result<ResultSet, Error> {
+        match statement {
+            Statement::StartTransaction { .. } => self.begin(),
+            Statement::Commit { .. } => self.commit().await,
+            Statement::Rollback { .. } => self.rollback(),
+            Statement::SetVariable { variables, value, .. } if is_search_path(variables) => self.set_search_path(value),
+            Statement::Use { db_name } => self.set_search_path(&[Expr::Identifier(db_name.clone())]),
+            Statement::ShowVariable { variable } if is_search_path_ident(variable) => self.show_search_path(),
+            _ => {
+                let search_path = self.search_path.clone();
+                match &self.transaction {
+                    Some(transaction) => run_with_search_path(search_path, execute_in_transaction(transaction.clone(), statement)).await,
+                    None => run_with_search_path(search_path, statement_dispatcher::dispatch_statement(statement)).await,
+                }
+            }
+        }
+    }
+
+    // Applies to the connection right away, regardless of transactions
+    fn set_search_path(&mut self, value: &[Expr]) -> Result<ResultSet, Error> {
+        let mut search_path: Vec<String> = Vec::new();
+        for expr in value {
+            match expr {
+                // SET search_path TO DEFAULT
+                Expr::Identifier(ident) if ident.quote_style.is_none() && ident.value.eq_ignore_ascii_case("DEFAULT") => search_path.clear(),
+                Expr::Identifier(ident) => search_path.push(ident.value.clone()),
+                Expr::Value(Value::SingleQuotedString(schema_names)) => {
+                    search_path.extend(schema_names.split(',').map(|schema_name| schema_name.trim().to_string()).filter(|schema_name| !schema_name.is_empty()));
+                },
+                Expr::Value(Value::DoubleQuotedString(schema_name)) => search_path.push(schema_name.clone()),
+                _ => return Err(Error::UnsupportedValueType { value: expr.to_string() }),
+            }
+        }
+
+        // Schemas must exist when switching to them explicitly
+        let database = database_loader::get_database()?;
+        if let Some(schema_name) = search_path.iter().find(|schema_name| find_database_schema(&database, schema_name).is_none()) {
+            return Err(Error::SchemaDoesNotExist { schema_name: schema_name.clone() });
+        }
+
+        self.search_path = search_path;
+
+        Ok(ResultSet::from_message(String::from("SET")))
+    }
+
+    fn show_search_path(&self) -> Result<ResultSet, Error> {
+        let database = database_loader::get_database()?;
+        let search_path = match self.search_path.is_empty() {
+            true => vec![database.configuration.default_schema.clone()],
+            false => self.search_path.clone(),
+        };
+
+        let columns = vec![ResultColumn { name: String::from("search_path"), data_type: DataType::Text }];
+        Ok(ResultSet::from_rows(columns, vec![vec![CellValue::Text(search_path.join(", "))]]))
+    }
+
+    fn begin(&mut self) -> Result<ResultSet, Error> {
+        if self.transaction.is_some() {
+            return Err(Error::TransactionAlreadyActive);
+        }
+        self.transaction = Some(begin_transaction());
+
+        Ok(ResultSet::from_message(String::from("Success: transaction has been started.")))
+    }
+
+    async fn commit(&mut self) -> Result<ResultSet, Error> {
+        let is_failed = self.is_transaction_failed();
+        let transaction = self.transaction.take().ok_or(Error::NoActiveTransaction)?;
+
+        // Like PostgreSQL, committing an aborted transaction rolls it back
+        if is_failed {
+            return Ok(ResultSet::from_message(String::from("Transaction was aborted and has been rolled back.")));
+        }
+
+        commit_transaction(transaction).await?;
+
+        Ok(ResultSet::from_message(String::from("Success: transaction has been committed.")))
+    }
+
+    fn rollback(&mut self) -> Result<ResultSet, Error> {
+        // Staged changes never reached the disk, so dropping them is enough
+        self.transaction.take().ok_or(Error::NoActiveTransaction)?;
+
+        Ok(ResultSet::from_message(String::from("Success: transaction has been rolled back.")))
+    }
+}
+
+async fn execute_in_transaction(transaction: SharedTransaction, statement: &Statement) -> Result<ResultSet, Error> {
+    if transaction.lock().map_err(|_| Error::ServerError)?.is_failed {
+        return Err(Error::TransactionAborted);
+    }
+
+    let result = run_in_transaction(transaction.clone(), statement_dispatcher::dispatch_statement(statement)).await;
+    if result.is_err() {
+        transaction.lock().map_err(|_| Error::ServerError)?.is_failed = true;
+    }
+
+    result
+}
+
+fn is_search_path(variables: &OneOrManyWithParens<ObjectName>) -> bool {
+    matches!(variables, OneOrManyWithParens::One(variable) if is_search_path_ident(&variable.0))
+}
+
+fn is_search_path_ident(variable: &[Ident]) -> bool {
+    matches!(variable, [ident] if ident.value.eq_ignore_ascii_case("search_path"))
+}
+
+// Results that weren't streamed are sent once the batch is done, if the connection streams them
+async fn hand_over_results(results: Vec<ResultSet>, result_stream: &Option<Sender<ResultChunk>>, error: Option<Error>) -> (Vec<ResultSet>, Option<Error>) {
+    let result_stream = match result_stream {
+        Some(result_stream) => result_stream,
+        None => return (results, error),
+    };
+    for result_set in results {
+        if let Err(e) = result_stream::send_result_chunk(result_stream, ResultChunk::Complete(result_set)).await {
+            return (Vec::new(), Some(e));
+        }
+    }
+
+    (Vec::new(), error)
+}
+
+pub fn is_transaction_control(statement: &Statement) -> bool {
+    matches!(statement, Statement::StartTransaction { .. } | Statement::Commit { .. } | Statement::Rollback { .. })
+}
\ No newline at end of file
@@ -1,36 +1,63 @@
 use sqlparser::ast::{ObjectType, Statement};
 
+use crate::network_protocol::types::ResultSet;
 use crate::shared::errors::Error;
 use crate::storage_engine::alter_table::alter_table_dispatcher;
-use crate::storage_engine::delete::{delete_records, delete_schema};
+use crate::storage_engine::delete::{delete_index, delete_records, delete_schema};
 use crate::storage_engine::delete::delete_table;
 use crate::storage_engine::insert::insert_into;
+use crate::storage_engine::planner::explain_handler;
 use crate::storage_engine::select::select_handler;
-use crate::storage_engine::create::{create_schema, create_table};
+use crate::storage_engine::statistics::analyze_handler;
+use crate::storage_engine::create::{create_index, create_schema, create_table};
 use crate::storage_engine::trigger::create_trigger;
 use crate::storage_engine::update::update_records;
+use crate::transaction_manager::transaction_context::{get_current_transaction, run_in_transaction};
+use crate::transaction_manager::transaction_handler::{begin_transaction, commit_transaction};
 
-pub async fn dispatch_statement(statement: &Statement) -> Result<String, Error> {
+pub async fn dispatch_statement(statement: &Statement) -> Result<ResultSet, Error> {
+    if get_current_transaction().is_some() {
+        return execute_statement(statement).await;
+    }
+
+    // Autocommit: outside of a transaction, each statement is logged and applied atomically
+    let transaction = begin_transaction();
+    let result_set = run_in_transaction(transaction.clone(), execute_statement(statement)).await?;
+    commit_transaction(transaction).await?;
+
+    Ok(result_set)
+}
+
+async fn execute_statement(statement: &Statement) -> Result<ResultSet, Error> {
     match statement {
         Statement::Query(statement) => {
             select_handler::handle_select(statement).await
         }
+        Statement::Explain { statement, analyze, .. } => {
+            explain_handler::handle_explain(statement, *analyze).await
+        }
         Statement::CreateTable(args) => {
-            create_table::create_table(&args.name, &args.columns).await
+            create_table::create_table(&args.name, &args.columns, &args.constraints, &args.with_options).await.map(ResultSet::from_message)
+        }
+        Statement::CreateIndex(args) => {
+            create_index::create_index(args).await.map(ResultSet::from_message)
         }
         Statement::CreateSchema { schema_name, .. } => {
-            create_schema::create_schema(schema_name).await
+            create_schema::create_schema(schema_name).await.map(ResultSet::from_message)
         }
         Statement::Insert(args) => {
             insert_into::insert_into_table(&args.table_name, &args.columns, &args.source).await
         }
-        Statement::Drop { object_type, names, .. } => {
+        Statement::Drop { object_type, names, if_exists, .. } => {
             match object_type {
                 ObjectType::Schema => {
-                    delete_schema::delete_schema(names).await
+                    delete_schema::delete_schema(names).await.map(ResultSet::from_message)
                 },
                 ObjectType::Table => {
-                    delete_table::delete_table(names).await
+                    delete_table::delete_table(names).await.map(ResultSet::from_message)
+                },
+                ObjectType::Index => {
+                    delete_index::delete_index(names, *if_exists).await.map(ResultSet::from_message)
                 },
                 _ => Err(Error::GenericUnsupported)
             }
@@ -43,10 +70,13 @@ pub async fn dispatch_statement(statement: &Statement) -> Result<String, Error>
         }
         Statement::AlterTable { name, operations, .. } => {
             // update_table::update_table(name, operations).await
-            alter_table_dispatcher::dispatch_alter_table_statement(name, operations).await
+            alter_table_dispatcher::dispatch_alter_table_statement(name, operations).await.map(ResultSet::from_message)
+        }
+        Statement::Analyze { table_name, .. } => {
+            analyze_handler::analyze(table_name).await.map(ResultSet::from_message)
         }
         Statement::CreateTrigger { name, period, events, table_name, exec_body, .. } => {
-            create_trigger::create_trigger(&name, &table_name, &period, &events, &exec_body).await
+            create_trigger::create_trigger(&name, &table_name, &period, &events, &exec_body).await.map(ResultSet::from_message)
         }
         _ => Err(Error::GenericUnsupported)
     }
@@ -1 +1,7 @@
-pub const DATABASE_DIR: &str = "database";
\ No newline at end of file
+pub const DATABASE_DIR: &str = "database";
+
+// Marker stored in data files for NULL values
+pub const NULL_VALUE: &str = "Null";
+
+// Prefix of stored text that would otherwise read as the NULL marker or start with this prefix
+pub const TEXT_ESCAPE: char = '\\';
\ No newline at end of file
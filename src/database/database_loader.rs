@@ -1,16 +1,27 @@
 use lazy_static::lazy_static;
-use std::sync::Mutex;
+use std::sync::{Arc, RwLock};
 
-use crate::{database::types::Database, shared::{errors::Error, file_manager}};
+use crate::{database::types::Database, shared::{errors::Error, file_manager}, storage_engine::index::index_manager, transaction_manager::{transaction_context::{get_current_transaction, with_current_transaction}, transaction_handler}};
 
 use super::{database_navigator::{get_database_configuration_path, get_schema_configuration_path, get_table_schema_path}, types::{DatabaseConfiguration, Schema, SchemaConfiguration, TableSchema}};
 
 lazy_static! {
-    pub static ref DATABASE: Mutex<Database> = Mutex::new(Database::default());
+    // Readers take a cheap snapshot; writers replace the catalog as a whole
+    pub static ref DATABASE: RwLock<Arc<Database>> = RwLock::new(Arc::new(Database::default()));
 }
 
 // Load
 pub async fn load_database() -> Result<(), Error> {
+    // Restore a consistent state on disk before reading it
+    transaction_handler::recover_from_wal()?;
+
+    reload_database().await?;
+
+    let database = get_database()?;
+    index_manager::create_missing_indexes(&database)
+}
+
+pub async fn reload_database() -> Result<(), Error> {
     let config = load_database_configuration().await?;
     let schema_names = config.schemas.clone();
     let mut schemas: Vec<Schema> = Vec::new();
@@ -25,9 +36,9 @@ pub async fn load_database() -> Result<(), Error> {
     }
 
     // Load database into global variable
-    let mut db = DATABASE.lock().map_err(|_| Error::FailedDatabaseLoading)?;
-    db.schemas = schemas;
-    db.configuration = config;
+    let database = Database { schemas, configuration: config };
+    let mut db = DATABASE.write().map_err(|_| Error::FailedDatabaseLoading)?;
+    *db = Arc::new(database);
 
     Ok(())
 }
@@ -68,23 +79,27 @@ pub async fn load_schema_configuration(schema_name: &String) -> Result<SchemaCon
     Ok(configuration)
 }
 
-pub async fn load_table(schema_name: &String, table_name: &String) -> Result<TableSchema, Error> {
+pub async fn load_table(schema_name: &str, table_name: &String) -> Result<TableSchema, Error> {
     let table_schema_path = get_table_schema_path(schema_name, &table_name);
     let table_schema = file_manager::read_json_file::<TableSchema>(&table_schema_path)?;
     Ok(table_schema)
 }
 
-// Get database
-pub fn get_database() -> Result<Database, Error> {
-    let db_lock = DATABASE.lock().map_err(|_| Error::FailedDatabaseLoading)?;
+// Get a snapshot of the database, as seen by the current transaction if any
+pub fn get_database() -> Result<Arc<Database>, Error> {
+    let transaction_catalog = with_current_transaction(|transaction| transaction.catalog.clone())?.flatten();
+    if let Some(catalog) = transaction_catalog {
+        return Ok(catalog);
+    }
+
+    let db_lock = DATABASE.read().map_err(|_| Error::FailedDatabaseLoading)?;
     Ok(db_lock.clone())
 }
 
 // Save
-pub async fn save_schema_configuration(schema_name: &String, config: &SchemaConfiguration) -> Result<(), Error> {
+pub async fn save_schema_configuration(schema_name: &str, config: &SchemaConfiguration) -> Result<(), Error> {
     let configuration_path = get_schema_configuration_path(schema_name);
-    let content = serde_json::to_string(config).map_err(|_| Error::FailedDatabaseLoading)?;
-    tokio::fs::write(&configuration_path, content).await.map_err(|_| Error::FailedDatabaseLoading)?;
+    file_manager::write_json_into_file(&configuration_path, config).map_err(|_| Error::FailedDatabaseLoading)?;
     Ok(())
 }
 
@@ -93,36 +108,57 @@ pub async fn reload_schema(schema_name: &String) -> Result<(), Error> {
     // Load schema
     let updated_schema = load_schema(schema_name).await?;
 
-    // Acquire lock on database
-    let mut database = DATABASE.lock().map_err(|_| Error::FailedDatabaseLoading)?;
-
-    // Find schema to update
-    if let Some(schema) = database.schemas.iter_mut().find(|s| &s.name == schema_name) {
-        *schema = updated_schema;
-    } else {
-        database.schemas.push(updated_schema);
-    }
+    update_catalog(|database| {
+        // Find schema to update
+        if let Some(schema) = database.schemas.iter_mut().find(|s| &s.name == schema_name) {
+            *schema = updated_schema;
+        } else {
+            database.schemas.push(updated_schema);
+        }
 
-    Ok(())
+        Ok(())
+    })
 }
 
 pub async fn reload_table_schema(schema_name: &String, table_name: &String) -> Result<(), Error> {
     // Load table schema
     let updated_table_schema = load_table(schema_name, table_name).await?;
 
-    // Acquire lock on database
-    let mut database = DATABASE.lock().map_err(|_| Error::FailedDatabaseLoading)?;
-
-    // Find schema and table to update
-    if let Some(schema) = database.schemas.iter_mut().find(|s| &s.name == schema_name) {
-        if let Some(table) = schema.tables.iter_mut().find(|t| &t.name == table_name) {
-            *table = updated_table_schema;
+    update_catalog(|database| {
+        // Find schema and table to update
+        if let Some(schema) = database.schemas.iter_mut().find(|s| &s.name == schema_name) {
+            if let Some(table) = schema.tables.iter_mut().find(|t| &t.name == table_name) {
+                *table = updated_table_schema;
+            } else {
+                schema.tables.push(updated_table_schema);
+            }
         } else {
-            schema.tables.push(updated_table_schema);
+            return Err(Error::SchemaDoesNotExist { schema_name: schema_name.clone() });
         }
-    } else {
-        return Err(Error::SchemaDoesNotExist { schema_name: schema_name.clone() });
-    }
 
-    Ok(())
+        Ok(())
+    })
 }
+
+// Inside a transaction, changes go to its private copy of the catalog until commit
+fn update_catalog(update: impl FnOnce(&mut Database) -> Result<(), Error>) -> Result<(), Error> {
+    match get_current_transaction() {
+        Some(transaction) => {
+            let mut transaction = transaction.lock().map_err(|_| Error::ServerError)?;
+            if transaction.catalog.is_none() {
+                let global_database = DATABASE.read().map_err(|_| Error::FailedDatabaseLoading)?;
+                transaction.catalog = Some(global_database.clone());
+            }
+
+            match transaction.catalog.as_mut() {
+                Some(catalog) => update(Arc::make_mut(catalog)),
+                None => Err(Error::FailedDatabaseLoading),
+            }
+        },
+        None => {
+            // Copy on write, so existing snapshots are left untouched
+            let mut database = DATABASE.write().map_err(|_| Error::FailedDatabaseLoading)?;
+            update(Arc::make_mut(&mut database))
+        }
+    }
+}
\ No newline at end of file
@@ -9,54 +9,75 @@ pub fn get_schemas_dir_path() -> String {
     format!("{}/schemas", constants::DATABASE_DIR)
 }
 
-pub fn get_schema_path(schema_name: &String) -> String {
+pub fn get_schema_path(schema_name: &str) -> String {
     format!("{}/{}", get_schemas_dir_path(), schema_name)
 }
 
-pub fn get_schema_configuration_dir_path(schema_name: &String) -> String {
+pub fn get_schema_configuration_dir_path(schema_name: &str) -> String {
     format!("{}/configuration", get_schema_path(schema_name))
 }
 
-pub fn get_schema_configuration_path(schema_name: &String) -> String {
+pub fn get_schema_configuration_path(schema_name: &str) -> String {
     format!("{}/configuration.json", get_schema_configuration_dir_path(schema_name))
 }
 
 // Tables
-pub fn get_tables_dir_path(schema_name: &String) -> String {
+pub fn get_tables_dir_path(schema_name: &str) -> String {
     format!("{}/tables", get_schema_path(schema_name))
 }
 
-pub fn get_table_path(schema_name: &String, table_name: &String) -> String {
+pub fn get_table_path(schema_name: &str, table_name: &str) -> String {
     format!("{}/{}", get_tables_dir_path(schema_name), table_name)
 }
 
 // Table schema
-pub fn get_table_schema_dir_path(schema_name: &String, table_name: &String) -> String {
+pub fn get_table_schema_dir_path(schema_name: &str, table_name: &str) -> String {
     format!("{}/table_schema", get_table_path(schema_name, table_name))
 }
 
-pub fn get_table_schema_path(schema_name: &String, table_name: &String) -> String {
+pub fn get_table_schema_path(schema_name: &str, table_name: &str) -> String {
     format!("{}/{}.schema.json", get_table_schema_dir_path(schema_name, table_name), table_name)
 }
 
+pub fn get_table_statistics_path(schema_name: &str, table_name: &str) -> String {
+    format!("{}/{}.statistics.json", get_table_schema_dir_path(schema_name, table_name), table_name)
+}
+
 // Table data
-pub fn get_table_data_dir_path(schema_name: &String, table_name: &String) -> String {
+pub fn get_table_data_dir_path(schema_name: &str, table_name: &str) -> String {
     format!("{}/data", get_table_path(schema_name, table_name))
 }
 
-pub fn get_table_data_path(schema_name: &String, table_name: &String) -> String {
+pub fn get_table_data_path(schema_name: &str, table_name: &str) -> String {
     format!("{}/{}.csv", get_table_data_dir_path(schema_name, table_name), table_name)
 }
 
+pub fn get_table_pages_path(schema_name: &str, table_name: &str) -> String {
+    format!("{}/{}.pages", get_table_data_dir_path(schema_name, table_name), table_name)
+}
+
+pub fn get_table_free_space_map_path(schema_name: &str, table_name: &str) -> String {
+    format!("{}/{}.fsm.json", get_table_data_dir_path(schema_name, table_name), table_name)
+}
+
 // Table indexes
-pub fn get_table_indexes_dir_path(schema_name: &String, table_name: &String) -> String {
+pub fn get_table_indexes_dir_path(schema_name: &str, table_name: &str) -> String {
     format!("{}/indexes", get_table_path(schema_name, table_name))
 }
 
-pub fn get_table_index_path(schema_name: &String, table_name: &String, column_name: &String) -> String {
-    format!("{}/{}.index.json", get_table_indexes_dir_path(schema_name, table_name), column_name)
+pub fn get_table_index_path(schema_name: &str, table_name: &str, index_name: &str) -> String {
+    format!("{}/{}.btree", get_table_indexes_dir_path(schema_name, table_name), index_name)
 }
 
-pub fn get_table_row_index_path(schema_name: &String, table_name: &String) -> String {
+pub fn get_table_row_index_path(schema_name: &str, table_name: &str) -> String {
     format!("{}/{}.index.json", get_table_indexes_dir_path(schema_name, table_name), String::from("row_offsets"))
+}
+
+// Write-ahead log
+pub fn get_wal_dir_path() -> String {
+    format!("{}/wal", constants::DATABASE_DIR)
+}
+
+pub fn get_wal_path() -> String {
+    format!("{}/wal.log", get_wal_dir_path())
 }
\ No newline at end of file
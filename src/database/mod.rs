@@ -2,4 +2,5 @@ pub mod constants;
 pub mod types;
 pub mod utils;
 pub mod database_navigator;
-pub mod database_loader;
\ No newline at end of file
+pub mod database_loader;
+pub mod session_context;
\ No newline at end of file
@@ -0,0 +1,14 @@
+use std::future::Future;
+
+tokio::task_local! {
+    // Search path of the connection whose statement is currently running
+    static CURRENT_SEARCH_PATH: Vec<String>;
+}
+
+pub async fn run_with_search_path<F: Future>(search_path: Vec<String>, future: F) -> F::Output {
+    CURRENT_SEARCH_PATH.scope(search_path, future).await
+}
+
+pub fn get_current_search_path() -> Option<Vec<String>> {
+    CURRENT_SEARCH_PATH.try_with(|search_path| search_path.clone()).ok()
+}
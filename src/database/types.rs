@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::network_protocol::constants::DEFAULT_MAX_MESSAGE_SIZE;
+
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Database {
@@ -20,6 +22,8 @@ impl Default for Database {
 pub struct DatabaseConfiguration {
     pub schemas: Vec<String>,
     pub default_schema: String,
+    #[serde(default = "default_max_message_size")]
+    pub max_message_size: usize,
 }
 
 impl Default for DatabaseConfiguration {
@@ -27,10 +31,15 @@ impl Default for DatabaseConfiguration {
         DatabaseConfiguration {
             schemas: Vec::new(),
             default_schema: String::from("NONE"),
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
         }
     }
 }
 
+fn default_max_message_size() -> usize {
+    DEFAULT_MAX_MESSAGE_SIZE
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Schema {
     pub name: String,
@@ -47,8 +56,25 @@ pub struct SchemaConfiguration {
 pub struct TableSchema {
     pub name: String,
     pub columns: Vec<Column>,
+    #[serde(default)]
     pub foreign_keys: Vec<ForeignKey>,
+    #[serde(default)]
     pub triggers: Vec<Trigger>,
+    #[serde(default)]
+    pub storage_engine: StorageEngine,
+    #[serde(default)]
+    pub indexes: Vec<Index>,
+    #[serde(default)]
+    pub primary_key: Vec<String>, // Columns of a primary key spanning several columns, single ones being a column constraint
+    #[serde(default)]
+    pub checks: Vec<CheckConstraint>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub enum StorageEngine {
+    #[default]
+    Csv,
+    Paged,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -74,7 +100,8 @@ pub enum Constraint {
     Unique,
     PrimaryKey,
     DefaultValue(String),
-    ForeignKey(ForeignKey)
+    ForeignKey(ForeignKey),
+    Check(String), // Expression as SQL
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -83,6 +110,8 @@ pub struct ForeignKey {
     pub local_table: String,
     pub local_columns: Vec<String>,
     pub foreign_table: String,
+    #[serde(default)]
+    pub foreign_schema: Option<String>, // None if in the local table's schema
     pub foreign_columns: Vec<String>,
     pub on_delete: ReferentialAction,
     pub on_update: ReferentialAction,
@@ -97,10 +126,19 @@ pub enum ReferentialAction {
     SetDefault,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+// Named index created with CREATE INDEX or for a PRIMARY KEY or UNIQUE table constraint, on one or more columns
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Index {
-    pub key: String,
-    pub offsets: Vec<u64>
+    pub name: String,
+    pub columns: Vec<String>,
+    pub is_unique: bool,
+}
+
+// CHECK constraint of the table, kept as SQL and parsed when rows are validated
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CheckConstraint {
+    pub name: String,
+    pub expression: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -108,10 +146,31 @@ pub struct RowsIndex {
     pub row_offsets: Vec<u64>
 }
 
+// Free bytes in each page of a paged table
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct FreeSpaceMap {
+    pub free_space: Vec<u16>
+}
+
+// Planner statistics gathered by ANALYZE
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct TableStatistics {
+    pub row_count: u64,
+    pub columns: Vec<ColumnStatistics>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ColumnStatistics {
+    pub column_name: String,
+    pub distinct_count: u64, // Among non-null values
+    pub null_fraction: f64,
+    pub histogram_bounds: Vec<String>, // Equi-depth bucket bounds of the non-null values, lowest first
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct InsertedRowColumn {
     pub name: String,
-    pub value: String,
+    pub value: Option<String>, // As stored, None for NULL
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -1,23 +1,20 @@
-use sqlparser::ast::{ColumnOption, ColumnOptionDef, DataType, Expr, Value};
+use sqlparser::ast::{ColumnOption, ColumnOptionDef, DataType, ObjectName};
 
 use crate::database::types::{Constraint as CustomConstraint, DataType as CustomDataType};
 use crate::shared::errors::Error;
+use crate::storage_engine::expressions::{expression_evaluator::evaluate_expression, operation_handler::get_storage_value, types::RowContext};
 
+use super::session_context::get_current_search_path;
 use super::types::{Database, Schema, TableSchema};
 
 pub fn get_column_custom_data_type(column_type: &DataType, column_name: &String) -> Result<CustomDataType, Error> {
     match column_type {
-        DataType::Int(_) => {
-            return Ok(CustomDataType::Integer);
-        },
-        DataType::Text => {
-            return Ok(CustomDataType::Text);
-        },
-        DataType::Bool => {
-            return Ok(CustomDataType::Boolean);
-        },
-        _ => return Err(Error::UnsupportedColumnDataType { column_name: column_name.clone(), column_type: format!("{:?}", column_type) }),
-    }    
+        DataType::Int(_) | DataType::Integer(_) | DataType::BigInt(_) => Ok(CustomDataType::Integer),
+        DataType::Float(_) | DataType::Real | DataType::Double | DataType::DoublePrecision => Ok(CustomDataType::Float),
+        DataType::Text => Ok(CustomDataType::Text),
+        DataType::Bool | DataType::Boolean => Ok(CustomDataType::Boolean),
+        _ => Err(Error::UnsupportedColumnDataType { column_name: column_name.clone(), column_type: format!("{:?}", column_type) }),
+    }
 }
 
 pub fn get_column_custom_constraints(column_constraints: &Vec<ColumnOptionDef>, column_name: &String) -> Result<Vec<CustomConstraint>, Error> {
@@ -34,13 +31,13 @@ pub fn get_column_custom_constraints(column_constraints: &Vec<ColumnOptionDef>,
                 }
             },
             ColumnOption::Default(expr) => {
-                let default_value = match expr {
-                    Expr::Value(Value::Number(n, _)) => n.clone(),
-                    Expr::Value(Value::SingleQuotedString(s)) => s.clone(),
-                    _ => return Err(Error::UnsupportedConstraint { column_name: column_name.clone(), column_constraint: format!("{:?}", expr) }),
-                }; // TODO: Ensure default_value type coincides with column type
-                custom_constraints.push(CustomConstraint::DefaultValue(default_value))
+                // Constant expressions are evaluated once, when the table is created
+                let default_value = evaluate_expression(expr, &RowContext::empty())
+                    .map_err(|_| Error::UnsupportedConstraint { column_name: column_name.clone(), column_constraint: expr.to_string() })?; // TODO: Ensure default_value type coincides with column type
+                custom_constraints.push(CustomConstraint::DefaultValue(get_storage_value(&default_value)))
             }
+            ColumnOption::ForeignKey { .. } => {}, // Stored on the table schema once the table exists
+            ColumnOption::Check(expr) => custom_constraints.push(CustomConstraint::Check(expr.to_string())), // Validated along with the table's other columns
             _ => return Err(Error::UnsupportedConstraint { column_name: column_name.clone(), column_constraint: format!("{:?}", constraint.option) })
         }
     }
@@ -54,13 +51,51 @@ pub fn find_database_schema<'a>(database: &'a Database, schema_name: &str) -> Op
 }
 
 
-pub fn find_database_table<'a>(database: &'a Database, table_name: &str) -> Option<&'a TableSchema> {
-    let default_schema = &database.configuration.default_schema;
-    database.schemas.iter()
-        .find(|schema| &schema.name == default_schema)
+pub fn find_database_table<'a>(database: &'a Database, schema_name: &str, table_name: &str) -> Option<&'a TableSchema> {
+    find_database_schema(database, schema_name)
         .and_then(|schema| schema.tables.iter().find(|table| &table.name == table_name))
 }
 
+// Schema and table of a possibly qualified name, unqualified names resolving against the search path
+pub fn get_schema_and_table_name(database: &Database, name: &ObjectName) -> Result<(String, String), Error> {
+    if let [table] = name.0.as_slice() {
+        let schema_name = get_search_path(database).into_iter()
+            .find(|schema_name| find_database_table(database, schema_name, &table.value).is_some())
+            .unwrap_or_else(|| get_current_schema_name(database));
+        return Ok((schema_name, table.value.clone()));
+    }
+
+    split_table_name(name, &get_current_schema_name(database))
+}
+
+// Schemas searched for unqualified names: the session's search path, or the configured default schema
+pub fn get_search_path(database: &Database) -> Vec<String> {
+    match get_current_search_path() {
+        Some(search_path) if !search_path.is_empty() => search_path,
+        _ => vec![database.configuration.default_schema.clone()],
+    }
+}
+
+// First existing schema on the search path, where new objects are created
+pub fn get_current_schema_name(database: &Database) -> String {
+    let search_path = get_search_path(database);
+
+    search_path.iter()
+        .find(|schema_name| find_database_schema(database, schema_name).is_some())
+        .or(search_path.first())
+        .cloned()
+        .unwrap_or_else(|| database.configuration.default_schema.clone())
+}
+
+pub fn split_table_name(name: &ObjectName, default_schema_name: &str) -> Result<(String, String), Error> {
+    match name.0.as_slice() {
+        [table] => Ok((default_schema_name.to_string(), table.value.clone())),
+        [schema, table] => Ok((schema.value.clone(), table.value.clone())),
+        [] => Err(Error::MissingTableName),
+        _ => Err(Error::InvalidTableName { table_name: name.to_string() }),
+    }
+}
+
 pub fn get_headers_from_table_schema(table_schema: &TableSchema) -> Vec<String> {
     let mut all_columns = table_schema.columns.clone();
     all_columns.sort_by(|a, b| a.order.cmp(&b.order));
@@ -3,4 +3,5 @@ pub mod database;
 pub mod server;
 pub mod storage_engine;
 pub mod shared;
-pub mod network_protocol;
\ No newline at end of file
+pub mod network_protocol;
+pub mod transaction_manager;
\ No newline at end of file
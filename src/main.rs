@@ -1,9 +1,18 @@
-use kodasql::server::{initialize_server, run_server};
+use kodasql::server::{initialize_postgres_server, initialize_server, run_postgres_server, run_server};
 use tokio;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let listener = initialize_server().await?;
+
+    // The PostgreSQL front end is optional; keep serving the native protocol if it can't bind
+    match initialize_postgres_server().await {
+        Ok(postgres_listener) => {
+            tokio::spawn(run_postgres_server(postgres_listener));
+        },
+        Err(e) => eprintln!("Failed to start PostgreSQL protocol server: {}", e),
+    }
+
     run_server(listener).await;
     Ok(())
 }
@@ -0,0 +1,10 @@
+pub const PROTOCOL_VERSION: u8 = 1;
+
+// 4 bytes for the payload length (big endian) followed by 1 byte for the protocol version
+pub const FRAME_LENGTH_SIZE: usize = 4;
+pub const FRAME_HEADER_SIZE: usize = FRAME_LENGTH_SIZE + 1;
+
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+// Rows per response frame, larger results are sent as several frames
+pub const RESPONSE_BATCH_SIZE: usize = 1000;
@@ -0,0 +1,81 @@
+use crate::shared::errors::Error;
+
+use super::constants::{FRAME_HEADER_SIZE, FRAME_LENGTH_SIZE, PROTOCOL_VERSION};
+
+/*
+ * Frame layout: [payload length: u32 BE][version: u8][payload]
+ */
+pub fn encode_frame(payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(FRAME_HEADER_SIZE + payload.len());
+    frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    frame.push(PROTOCOL_VERSION);
+    frame.extend_from_slice(payload);
+
+    frame
+}
+
+// Reassembles frames from the bytes returned by successive socket reads
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    max_message_size: usize,
+    bytes_to_discard: usize,
+}
+
+impl FrameDecoder {
+    pub fn new(max_message_size: usize) -> Self {
+        FrameDecoder {
+            buffer: Vec::new(),
+            max_message_size,
+            bytes_to_discard: 0,
+        }
+    }
+
+    pub fn extend(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    // Returns the next complete payload, None if more bytes are needed,
+    // or an error for a frame that was rejected (its payload is skipped)
+    pub fn next_frame(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        self.discard_rejected_payload();
+        if self.bytes_to_discard > 0 || self.buffer.len() < FRAME_HEADER_SIZE {
+            return Ok(None);
+        }
+
+        let mut length_bytes = [0; FRAME_LENGTH_SIZE];
+        length_bytes.copy_from_slice(&self.buffer[..FRAME_LENGTH_SIZE]);
+        let payload_length = u32::from_be_bytes(length_bytes) as usize;
+        let version = self.buffer[FRAME_LENGTH_SIZE];
+
+        // Reject frame before its payload arrives, skipping the payload bytes
+        if version != PROTOCOL_VERSION {
+            self.reject_frame(payload_length);
+            return Err(Error::UnsupportedProtocolVersion { version });
+        }
+        if payload_length > self.max_message_size {
+            self.reject_frame(payload_length);
+            return Err(Error::MessageTooLarge { size: payload_length, max_size: self.max_message_size });
+        }
+
+        if self.buffer.len() < FRAME_HEADER_SIZE + payload_length {
+            return Ok(None);
+        }
+
+        let payload = self.buffer[FRAME_HEADER_SIZE..FRAME_HEADER_SIZE + payload_length].to_vec();
+        self.buffer.drain(..FRAME_HEADER_SIZE + payload_length);
+
+        Ok(Some(payload))
+    }
+
+    fn reject_frame(&mut self, payload_length: usize) {
+        self.buffer.drain(..FRAME_HEADER_SIZE);
+        self.bytes_to_discard = payload_length;
+        self.discard_rejected_payload();
+    }
+
+    fn discard_rejected_payload(&mut self) {
+        let discarded = self.bytes_to_discard.min(self.buffer.len());
+        self.buffer.drain(..discarded);
+        self.bytes_to_discard -= discarded;
+    }
+}
@@ -1,2 +1,6 @@
 pub mod types;
-pub mod parsing;
\ No newline at end of file
+pub mod parsing;
+pub mod framing;
+pub mod constants;
+pub mod postgres;
+pub mod result_stream;
\ No newline at end of file
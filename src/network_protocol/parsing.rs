@@ -1,12 +1,24 @@
 use crate::shared::errors::Error;
 
+use super::framing;
 use super::types::{Request, Response};
 use bincode;
 
 pub fn parse_request(data: &[u8]) -> Result<Request, Error> {
-    bincode::deserialize(data).map_err(|_| Error::UnknownError)
+    bincode::deserialize(data).map_err(|_| Error::InvalidMessageFormat)
 }
 
 pub fn format_response(response: &Response) -> Vec<u8> {
-    bincode::serialize(response).unwrap_or_else(|_| vec![])
-}
\ No newline at end of file
+    let payload = bincode::serialize(response).unwrap_or_else(|_| vec![]);
+    framing::encode_frame(&payload)
+}
+
+// Client side
+pub fn format_request(request: &Request) -> Vec<u8> {
+    let payload = bincode::serialize(request).unwrap_or_else(|_| vec![]);
+    framing::encode_frame(&payload)
+}
+
+pub fn parse_response(data: &[u8]) -> Result<Response, Error> {
+    bincode::deserialize(data).map_err(|_| Error::InvalidMessageFormat)
+}
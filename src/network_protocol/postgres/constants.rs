@@ -0,0 +1,48 @@
+// Startup request codes
+pub const PROTOCOL_VERSION_3: i32 = 196608;
+pub const SSL_REQUEST_CODE: i32 = 80877103;
+pub const GSSENC_REQUEST_CODE: i32 = 80877104;
+pub const CANCEL_REQUEST_CODE: i32 = 80877102;
+
+pub const SERVER_VERSION: &str = "14.0 (KodaSQL)";
+
+// Type OIDs
+pub const BOOL_OID: i32 = 16;
+pub const INT8_OID: i32 = 20;
+pub const TEXT_OID: i32 = 25;
+pub const FLOAT8_OID: i32 = 701;
+
+// SQLSTATE codes
+pub const SYNTAX_ERROR: &str = "42601";
+pub const UNDEFINED_TABLE: &str = "42P01";
+pub const UNDEFINED_COLUMN: &str = "42703";
+pub const UNDEFINED_SCHEMA: &str = "3F000";
+pub const UNDEFINED_OBJECT: &str = "42704";
+pub const DUPLICATE_TABLE: &str = "42P07";
+pub const DUPLICATE_COLUMN: &str = "42701";
+pub const DUPLICATE_OBJECT: &str = "42710";
+pub const DUPLICATE_ALIAS: &str = "42712";
+pub const AMBIGUOUS_COLUMN: &str = "42702";
+pub const INVALID_COLUMN_REFERENCE: &str = "42P10";
+pub const DATATYPE_MISMATCH: &str = "42804";
+pub const GROUPING_ERROR: &str = "42803";
+pub const INVALID_FOREIGN_KEY: &str = "42830";
+pub const DEPENDENT_OBJECTS_STILL_EXIST: &str = "2BP01";
+pub const INVALID_TABLE_DEFINITION: &str = "42P16";
+pub const INVALID_TEXT_REPRESENTATION: &str = "22P02";
+pub const DIVISION_BY_ZERO: &str = "22012";
+pub const NUMERIC_VALUE_OUT_OF_RANGE: &str = "22003";
+pub const CARDINALITY_VIOLATION: &str = "21000";
+pub const NOT_NULL_VIOLATION: &str = "23502";
+pub const FOREIGN_KEY_VIOLATION: &str = "23503";
+pub const UNIQUE_VIOLATION: &str = "23505";
+pub const CHECK_VIOLATION: &str = "23514";
+pub const ACTIVE_SQL_TRANSACTION: &str = "25001";
+pub const NO_ACTIVE_SQL_TRANSACTION: &str = "25P01";
+pub const IN_FAILED_SQL_TRANSACTION: &str = "25P02";
+pub const SERIALIZATION_FAILURE: &str = "40001";
+pub const LOCK_NOT_AVAILABLE: &str = "55P03";
+pub const FEATURE_NOT_SUPPORTED: &str = "0A000";
+pub const PROTOCOL_VIOLATION: &str = "08P01";
+pub const PROGRAM_LIMIT_EXCEEDED: &str = "54000";
+pub const INTERNAL_ERROR: &str = "XX000";
@@ -0,0 +1,223 @@
+use crate::shared::errors::Error;
+
+use super::constants::{CANCEL_REQUEST_CODE, GSSENC_REQUEST_CODE, PROTOCOL_VERSION_3, SSL_REQUEST_CODE};
+
+#[derive(Debug, PartialEq)]
+pub enum FrontendMessage {
+    Startup { parameters: Vec<(String, String)> },
+    SslRequest,
+    GssEncRequest,
+    CancelRequest,
+    Query(String),
+    Sync,
+    Terminate,
+    Unsupported(u8),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BackendMessage {
+    AuthenticationOk,
+    ParameterStatus { name: String, value: String },
+    BackendKeyData { process_id: i32, secret_key: i32 },
+    ReadyForQuery(TransactionStatus),
+    RowDescription(Vec<FieldDescription>),
+    DataRow(Vec<Option<String>>),
+    CommandComplete(String),
+    EmptyQueryResponse,
+    ErrorResponse { code: String, message: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransactionStatus {
+    Idle,
+    InTransaction,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDescription {
+    pub name: String,
+    pub type_oid: i32,
+    pub type_size: i16,
+}
+
+/*
+ * Decoding
+ */
+// Startup packets have no type byte: [length: i32][code: i32][body]
+pub fn decode_startup_message(buffer: &mut Vec<u8>, max_message_size: usize) -> Result<Option<FrontendMessage>, Error> {
+    let length = match read_length(buffer, 0, max_message_size)? {
+        Some(length) => length,
+        None => return Ok(None),
+    };
+    if length < 8 {
+        return Err(Error::InvalidMessageFormat);
+    }
+    if buffer.len() < length {
+        return Ok(None);
+    }
+
+    let message: Vec<u8> = buffer.drain(..length).collect();
+    let code = i32::from_be_bytes([message[4], message[5], message[6], message[7]]);
+
+    let frontend_message = match code {
+        SSL_REQUEST_CODE => FrontendMessage::SslRequest,
+        GSSENC_REQUEST_CODE => FrontendMessage::GssEncRequest,
+        CANCEL_REQUEST_CODE => FrontendMessage::CancelRequest,
+        PROTOCOL_VERSION_3 => {
+            let strings = read_strings(&message[8..])?;
+            let parameters = strings.chunks_exact(2)
+                .map(|pair| (pair[0].clone(), pair[1].clone()))
+                .collect();
+            FrontendMessage::Startup { parameters }
+        },
+        _ => return Err(Error::UnsupportedProtocolVersion { version: (code >> 16) as u8 }),
+    };
+
+    Ok(Some(frontend_message))
+}
+
+// Regular messages: [type: u8][length: i32][body], length includes itself but not the type
+pub fn decode_message(buffer: &mut Vec<u8>, max_message_size: usize) -> Result<Option<FrontendMessage>, Error> {
+    let length = match read_length(buffer, 1, max_message_size)? {
+        Some(length) => length,
+        None => return Ok(None),
+    };
+    if length < 4 {
+        return Err(Error::InvalidMessageFormat);
+    }
+    if buffer.len() < length + 1 {
+        return Ok(None);
+    }
+
+    let message: Vec<u8> = buffer.drain(..length + 1).collect();
+    let body = &message[5..];
+
+    let frontend_message = match message[0] {
+        b'Q' => {
+            let query_bytes = body.split(|&byte| byte == 0).next().unwrap_or_default();
+            let query = String::from_utf8(query_bytes.to_vec()).map_err(|_| Error::InvalidMessageFormat)?;
+            FrontendMessage::Query(query)
+        },
+        b'S' => FrontendMessage::Sync,
+        b'X' => FrontendMessage::Terminate,
+        message_type => FrontendMessage::Unsupported(message_type),
+    };
+
+    Ok(Some(frontend_message))
+}
+
+fn read_length(buffer: &[u8], position: usize, max_message_size: usize) -> Result<Option<usize>, Error> {
+    if buffer.len() < position + 4 {
+        return Ok(None);
+    }
+    let length = i32::from_be_bytes([buffer[position], buffer[position + 1], buffer[position + 2], buffer[position + 3]]);
+    if length < 0 {
+        return Err(Error::InvalidMessageFormat);
+    }
+    if length as usize > max_message_size {
+        return Err(Error::MessageTooLarge { size: length as usize, max_size: max_message_size });
+    }
+
+    Ok(Some(length as usize))
+}
+
+// Read null-terminated strings until the terminating empty string or the end of the body
+fn read_strings(body: &[u8]) -> Result<Vec<String>, Error> {
+    let mut strings: Vec<String> = Vec::new();
+
+    for part in body.split(|&byte| byte == 0) {
+        if part.is_empty() {
+            break;
+        }
+        let string = String::from_utf8(part.to_vec()).map_err(|_| Error::InvalidMessageFormat)?;
+        strings.push(string);
+    }
+
+    Ok(strings)
+}
+
+/*
+ * Encoding
+ */
+impl BackendMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body: Vec<u8> = Vec::new();
+
+        let message_type = match self {
+            BackendMessage::AuthenticationOk => {
+                body.extend_from_slice(&0i32.to_be_bytes());
+                b'R'
+            },
+            BackendMessage::ParameterStatus { name, value } => {
+                write_string(&mut body, name);
+                write_string(&mut body, value);
+                b'S'
+            },
+            BackendMessage::BackendKeyData { process_id, secret_key } => {
+                body.extend_from_slice(&process_id.to_be_bytes());
+                body.extend_from_slice(&secret_key.to_be_bytes());
+                b'K'
+            },
+            BackendMessage::ReadyForQuery(status) => {
+                body.push(match status {
+                    TransactionStatus::Idle => b'I',
+                    TransactionStatus::InTransaction => b'T',
+                    TransactionStatus::Failed => b'E',
+                });
+                b'Z'
+            },
+            BackendMessage::RowDescription(fields) => {
+                body.extend_from_slice(&(fields.len() as i16).to_be_bytes());
+                for field in fields {
+                    write_string(&mut body, &field.name);
+                    body.extend_from_slice(&0i32.to_be_bytes()); // Table OID
+                    body.extend_from_slice(&0i16.to_be_bytes()); // Column attribute number
+                    body.extend_from_slice(&field.type_oid.to_be_bytes());
+                    body.extend_from_slice(&field.type_size.to_be_bytes());
+                    body.extend_from_slice(&(-1i32).to_be_bytes()); // Type modifier
+                    body.extend_from_slice(&0i16.to_be_bytes()); // Text format
+                }
+                b'T'
+            },
+            BackendMessage::DataRow(values) => {
+                body.extend_from_slice(&(values.len() as i16).to_be_bytes());
+                for value in values {
+                    match value {
+                        Some(value) => {
+                            body.extend_from_slice(&(value.len() as i32).to_be_bytes());
+                            body.extend_from_slice(value.as_bytes());
+                        },
+                        None => body.extend_from_slice(&(-1i32).to_be_bytes()),
+                    }
+                }
+                b'D'
+            },
+            BackendMessage::CommandComplete(tag) => {
+                write_string(&mut body, tag);
+                b'C'
+            },
+            BackendMessage::EmptyQueryResponse => b'I',
+            BackendMessage::ErrorResponse { code, message } => {
+                for (field_type, value) in [(b'S', "ERROR"), (b'V', "ERROR"), (b'C', code.as_str()), (b'M', message.as_str())] {
+                    body.push(field_type);
+                    write_string(&mut body, value);
+                }
+                body.push(0);
+                b'E'
+            },
+        };
+
+        let mut message = Vec::with_capacity(body.len() + 5);
+        message.push(message_type);
+        message.extend_from_slice(&(body.len() as i32 + 4).to_be_bytes());
+        message.extend_from_slice(&body);
+
+        message
+    }
+}
+
+fn write_string(body: &mut Vec<u8>, value: &str) {
+    body.extend_from_slice(value.as_bytes());
+    body.push(0);
+}
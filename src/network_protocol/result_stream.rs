@@ -0,0 +1,40 @@
+use std::future::Future;
+
+use tokio::sync::mpsc::{self, Receiver, Sender};
+
+use crate::network_protocol::types::ResultSet;
+use crate::shared::errors::Error;
+
+// Results sent to the client while the statements run: the rows of the running statement so far, or what remains of a statement's result once it's done
+pub enum ResultChunk {
+    Partial(ResultSet),
+    Complete(ResultSet),
+}
+
+tokio::task_local! {
+    // Stream of the connection whose statements are currently running, if it sends results as they come
+    static CURRENT_RESULT_STREAM: Option<Sender<ResultChunk>>;
+}
+
+// Chunks are handed over one at a time, so a slow client holds the query back instead of rows piling up
+pub fn create_result_stream() -> (Sender<ResultChunk>, Receiver<ResultChunk>) {
+    mpsc::channel(1)
+}
+
+pub async fn run_with_result_stream<F: Future>(sender: Sender<ResultChunk>, future: F) -> F::Output {
+    CURRENT_RESULT_STREAM.scope(Some(sender), future).await
+}
+
+// Without a stream, the statements' results are returned whole, e.g. until it's known whether their work is committed
+pub fn run_with_optional_result_stream<F: Future>(sender: Option<Sender<ResultChunk>>, future: F) -> impl Future<Output = F::Output> {
+    CURRENT_RESULT_STREAM.scope(sender, future)
+}
+
+pub fn get_current_result_stream() -> Option<Sender<ResultChunk>> {
+    CURRENT_RESULT_STREAM.try_with(|sender| sender.clone()).ok().flatten()
+}
+
+// Fails once the client is gone, which stops the statement
+pub async fn send_result_chunk(sender: &Sender<ResultChunk>, chunk: ResultChunk) -> Result<(), Error> {
+    sender.send(chunk).await.map_err(|_| Error::ServerError)
+}
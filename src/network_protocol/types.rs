@@ -1,5 +1,8 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
+use crate::database::types::DataType;
 use crate::shared::errors::Error;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -11,7 +14,7 @@ pub struct Request {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Response {
     pub status: ResponseStatus,
-    pub data: Option<String>,
+    pub data: Vec<ResultSet>, // One per executed statement
     pub error: Option<String>
 }
 
@@ -25,13 +28,110 @@ pub enum MessageType {
 pub enum ResponseStatus {
     Success,
     Error,
+    Partial, // More responses follow, the first result set of the next one continues the last of this one
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultSet {
+    pub columns: Vec<ResultColumn>, // In projection order
+    pub rows: Vec<Vec<CellValue>>,
+    pub rows_affected: Option<u64>, // Set for INSERT, UPDATE and DELETE
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultColumn {
+    pub name: String,
+    pub data_type: DataType,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CellValue {
+    Null,
+    Integer(i64),
+    Float(f64),
+    Text(String),
+    Boolean(bool),
+}
+
+impl ResultSet {
+    pub fn from_rows(columns: Vec<ResultColumn>, rows: Vec<Vec<CellValue>>) -> Self {
+        ResultSet { columns, rows, rows_affected: None, message: None }
+    }
+
+    pub fn from_rows_affected(rows_affected: u64, message: String) -> Self {
+        ResultSet { columns: Vec::new(), rows: Vec::new(), rows_affected: Some(rows_affected), message: Some(message) }
+    }
+
+    pub fn from_message(message: String) -> Self {
+        ResultSet { columns: Vec::new(), rows: Vec::new(), rows_affected: None, message: Some(message) }
+    }
+}
+
+impl Response {
+    // Split into responses of at most batch_size rows, sent one after the other
+    pub fn into_batches(self, batch_size: usize) -> Vec<Response> {
+        if !matches!(self.status, ResponseStatus::Success) {
+            return vec![self];
+        }
+        let batch_size = batch_size.max(1);
+
+        let mut batches: Vec<Response> = Vec::new();
+        let mut data: Vec<ResultSet> = Vec::new();
+        let mut batch_rows = 0;
+        for mut result_set in self.data {
+            // Cut the result set where the batch is full, the rest continues in the next batch
+            while batch_rows + result_set.rows.len() > batch_size {
+                let remaining_rows = result_set.rows.split_off(batch_size - batch_rows);
+                data.push(ResultSet {
+                    columns: result_set.columns.clone(),
+                    rows: std::mem::replace(&mut result_set.rows, remaining_rows),
+                    rows_affected: result_set.rows_affected,
+                    message: result_set.message.clone(),
+                });
+                batches.push(Response { status: ResponseStatus::Partial, data: std::mem::take(&mut data), error: None });
+                batch_rows = 0;
+            }
+            batch_rows += result_set.rows.len();
+            data.push(result_set);
+        }
+        batches.push(Response { status: ResponseStatus::Success, data, error: None });
+
+        batches
+    }
+
+    // Add the next batch of a response received in parts
+    pub fn append_batch(&mut self, batch: Response) {
+        let mut data = batch.data.into_iter();
+        if matches!(self.status, ResponseStatus::Partial) {
+            if let (Some(last_result_set), Some(continued_result_set)) = (self.data.last_mut(), data.next()) {
+                last_result_set.rows.extend(continued_result_set.rows);
+            }
+        }
+
+        self.data.extend(data);
+        self.status = batch.status;
+        self.error = batch.error;
+    }
+}
+
+impl fmt::Display for CellValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellValue::Null => write!(f, "NULL"),
+            CellValue::Integer(value) => write!(f, "{}", value),
+            CellValue::Float(value) => write!(f, "{}", value),
+            CellValue::Text(value) => write!(f, "{}", value),
+            CellValue::Boolean(value) => write!(f, "{}", value),
+        }
+    }
 }
 
 impl From<Error> for Response {
     fn from(error: Error) -> Self {
         Response {
             status: ResponseStatus::Error,
-            data: None,
+            data: Vec::new(),
             error: Some(format!("{}", error)),
         }
     }
@@ -1,7 +1,8 @@
 use tokio::net::TcpListener;
 
-use crate::database::database_loader::load_database;
-use crate::command_dispatcher::request_handler;
+use crate::database::database_loader::{get_database, load_database};
+use crate::command_dispatcher::{postgres_request_handler, request_handler};
+use crate::network_protocol::constants::DEFAULT_MAX_MESSAGE_SIZE;
 
 pub async fn initialize_server() -> Result<TcpListener, Box<dyn std::error::Error>> {
     load_database().await?;
@@ -13,16 +14,47 @@ pub async fn initialize_server() -> Result<TcpListener, Box<dyn std::error::Erro
     Ok(listener)
 }
 
+pub async fn initialize_postgres_server() -> Result<TcpListener, Box<dyn std::error::Error>> {
+    let address = "127.0.0.1:5432";
+    let listener = TcpListener::bind(address).await?;
+    println!("PostgreSQL protocol server running on {}", address);
+
+    Ok(listener)
+}
+
 // Start listening for requests and handling them
 pub async fn run_server(listener: TcpListener) {
+    let max_message_size = get_max_message_size();
+
+    loop {
+        match listener.accept().await {
+            Ok((mut socket, _)) => {
+                tokio::spawn(async move {
+                    request_handler::handle_request(&mut socket, max_message_size).await;
+                });
+            },
+            Err(e) => eprintln!("Failed to accept connection: {}", e),
+        }
+    }
+}
+
+pub async fn run_postgres_server(listener: TcpListener) {
+    let max_message_size = get_max_message_size();
+
     loop {
         match listener.accept().await {
             Ok((mut socket, _)) => {
                 tokio::spawn(async move {
-                    request_handler::handle_request(&mut socket).await;
+                    postgres_request_handler::handle_postgres_connection(&mut socket, max_message_size).await;
                 });
             },
             Err(e) => eprintln!("Failed to accept connection: {}", e),
         }
     }
+}
+
+fn get_max_message_size() -> usize {
+    get_database()
+        .map(|database| database.configuration.max_message_size)
+        .unwrap_or(DEFAULT_MAX_MESSAGE_SIZE)
 }
\ No newline at end of file
@@ -16,11 +16,23 @@ pub enum Error {
     SerdeJsonError(serde_json::Error),
     FailedTableRead { table_name: String },
     FailedTableWrite { table_name: String },
+    TransactionConflict { file_path: String },
+    FailedWalWrite,
+    FailedIndexRead { index_name: String },
 
     // Invalid
     InvalidSQLSyntax,
     InvalidTableName { table_name: String },
     InvalidLimit { limit: String },
+    InvalidOffset { offset: String },
+    InvalidOrderPosition { position: String },
+    InvalidMessageFormat,
+    InvalidStorageEngine { storage_engine: String },
+    InvalidOperandTypes { operation: String },
+    InvalidCast { value: String, data_type: String },
+    AmbiguousColumnName { column_name: String },
+    InvalidSubqueryColumnCount { column_count: usize },
+    InvalidForeignKey { foreign_key_name: String },
 
     // Missing
     MissingSchemaName,
@@ -36,9 +48,30 @@ pub enum Error {
     ColumnNotNull { column_name: String },
     ForeignKeyAlreadyExists { foreign_key_name: String },
     ForeignKeyConstraintNotSatisfied { foreign_key_name: String },
+    MessageTooLarge { size: usize, max_size: usize },
+    TransactionAlreadyActive,
+    NoActiveTransaction,
+    TransactionAborted,
+    LockTimeout { table_name: String },
+    ReadConflict { table_name: String },
+    RowTooLarge { size: usize, max_size: usize },
+    IndexKeyTooLarge { size: usize, max_size: usize },
+    IndexAlreadyExists { index_name: String },
+    IndexUniquenessNotSatisfied { index_name: String, value: String },
+    DivisionByZero,
+    DuplicateTableReference { table_name: String },
+    ColumnNotGrouped { column_name: String },
+    AggregateNotAllowed { function_name: String },
+    NumericValueOutOfRange,
+    SubqueryReturnedMultipleRows,
+    ColumnReferencedByForeignKey { column_name: String, foreign_key_name: String },
+    MultiplePrimaryKeys { table_name: String },
+    CheckConstraintNotSatisfied { constraint_name: String },
+    ConstraintAlreadyExists { constraint_name: String },
 
     // Not supported
     GenericUnsupported,
+    UnsupportedProtocolVersion { version: u8 },
     
     NotSupportedUpdateTableOperation,
 
@@ -49,11 +82,14 @@ pub enum Error {
 
     UnsupportedColumnDataType { column_name: String, column_type: String },
     UnsupportedConstraint { column_name: String, column_constraint: String },
+    UnsupportedIndexExpression { expression: String },
+    UnsupportedExpression { expression: String },
 
     // Missing
     SchemaDoesNotExist { schema_name: String },
     TableDoesNotExist { table_name: String },
     ColumnDoesNotExist { column_name: String, table_name: String },
+    IndexDoesNotExist { index_name: String },
 }
 
 impl fmt::Display for Error {
@@ -70,11 +106,23 @@ impl fmt::Display for Error {
             Error::SerdeJsonError(e) => write!(f, "Serde JSON error: {}", e),
             Error::FailedTableRead { table_name } => write!(f, "Failed to read data from table {}", table_name),
             Error::FailedTableWrite { table_name } => write!(f, "Failed to write data into table {}", table_name),
+            Error::TransactionConflict { file_path } => write!(f, "The transaction could not be committed: {} has been modified by another transaction.", file_path),
+            Error::FailedWalWrite => write!(f, "Failed to write to the write-ahead log."),
+            Error::FailedIndexRead { index_name } => write!(f, "Failed to read index {}.", index_name),
 
             // Invalid
             Error::InvalidSQLSyntax => write!(f, "You have an error in your SQL syntax"),
             Error::InvalidTableName { table_name } => write!(f, "The table name {} is invalid.", table_name),
             Error::InvalidLimit { limit } => write!(f, "The provided limit {} is invalid.", limit),
+            Error::InvalidOffset { offset } => write!(f, "The provided offset {} is invalid.", offset),
+            Error::InvalidOrderPosition { position } => write!(f, "ORDER BY position {} is not in the select list.", position),
+            Error::InvalidMessageFormat => write!(f, "The message could not be decoded."),
+            Error::InvalidStorageEngine { storage_engine } => write!(f, "The storage engine {} is invalid.", storage_engine),
+            Error::InvalidOperandTypes { operation } => write!(f, "The operands of {} have incompatible types.", operation),
+            Error::InvalidCast { value, data_type } => write!(f, "The value {} cannot be cast to {}.", value, data_type),
+            Error::AmbiguousColumnName { column_name } => write!(f, "The column reference {} is ambiguous.", column_name),
+            Error::InvalidSubqueryColumnCount { column_count } => write!(f, "The subquery returns {} columns instead of one.", column_count),
+            Error::InvalidForeignKey { foreign_key_name } => write!(f, "The columns referenced by foreign key {} are not a unique key of the referenced table.", foreign_key_name),
             
             // Missing
             Error::MissingSchemaName => write!(f, "Missing schema name."),
@@ -90,9 +138,30 @@ impl fmt::Display for Error {
             Error::ColumnNotNull { column_name } => write!(f, "A null value has been provided for the column {} having a non-null constraint", column_name),
             Error::ForeignKeyAlreadyExists { foreign_key_name } => write!(f, "Foreign key {} already exists.", foreign_key_name),
             Error::ForeignKeyConstraintNotSatisfied { foreign_key_name } => write!(f, "The foreign key constraint {} is not satisfied.", foreign_key_name),
+            Error::MessageTooLarge { size, max_size } => write!(f, "The message of {} bytes exceeds the maximum message size of {} bytes.", size, max_size),
+            Error::TransactionAlreadyActive => write!(f, "There is already a transaction in progress."),
+            Error::NoActiveTransaction => write!(f, "There is no transaction in progress."),
+            Error::TransactionAborted => write!(f, "The current transaction is aborted, statements are ignored until ROLLBACK."),
+            Error::LockTimeout { table_name } => write!(f, "Timed out waiting for a lock on table {}.", table_name),
+            Error::ReadConflict { table_name } => write!(f, "The query could not finish reading table {}: it has been modified by another transaction.", table_name),
+            Error::RowTooLarge { size, max_size } => write!(f, "The row of {} bytes exceeds the maximum row size of {} bytes.", size, max_size),
+            Error::IndexKeyTooLarge { size, max_size } => write!(f, "The index entry of {} bytes exceeds the maximum index entry size of {} bytes.", size, max_size),
+            Error::IndexAlreadyExists { index_name } => write!(f, "Index {} already exists.", index_name),
+            Error::IndexUniquenessNotSatisfied { index_name, value } => write!(f, "The uniqueness constraint of index {} is not satisfied by the value ({}).", index_name, value),
+            Error::DivisionByZero => write!(f, "Division by zero."),
+            Error::DuplicateTableReference { table_name } => write!(f, "Table name {} is specified more than once.", table_name),
+            Error::ColumnNotGrouped { column_name } => write!(f, "Column {} must appear in the GROUP BY clause or be used in an aggregate function.", column_name),
+            Error::AggregateNotAllowed { function_name } => write!(f, "The aggregate function {} is not allowed here.", function_name),
+            Error::NumericValueOutOfRange => write!(f, "The numeric value is out of range."),
+            Error::SubqueryReturnedMultipleRows => write!(f, "More than one row was returned by a subquery used as an expression."),
+            Error::ColumnReferencedByForeignKey { column_name, foreign_key_name } => write!(f, "Column {} is referenced by foreign key {}.", column_name, foreign_key_name),
+            Error::MultiplePrimaryKeys { table_name } => write!(f, "Multiple primary keys for table {} are not allowed.", table_name),
+            Error::CheckConstraintNotSatisfied { constraint_name } => write!(f, "The check constraint {} is not satisfied.", constraint_name),
+            Error::ConstraintAlreadyExists { constraint_name } => write!(f, "Constraint {} already exists.", constraint_name),
 
             // Not supported
             Error::GenericUnsupported => write!(f, "You're attempting an SQL operation that is not currently supported."),
+            Error::UnsupportedProtocolVersion { version } => write!(f, "The protocol version {} is not supported.", version),
             Error::NotSupportedUpdateTableOperation => write!(f, "The Update Table operation you're attempting is not currently supported."),
             Error::UnsupportedSelectClause => write!(f, "The SELECT clause is not currently supported."),
             Error::UnsupportedValueType { value } => write!(f, "The value {} is not currently supported.", value),
@@ -100,11 +169,14 @@ impl fmt::Display for Error {
             Error::UnsupportedConstraint { column_name, column_constraint } => write!(f, "The constraint {} for column {} is not supported.", column_constraint, column_name),
             Error::UnsupportedOperationType { operation } => write!(f, "The operation {} in the WHERE clause is not currently supported.", operation),
             Error::UnsupportedFilter => write!(f, "The filter you are attempting to use is not currently supported."),
+            Error::UnsupportedIndexExpression { expression } => write!(f, "The index expression {} is not supported, only plain columns can be indexed.", expression),
+            Error::UnsupportedExpression { expression } => write!(f, "The expression {} is not currently supported.", expression),
 
             // Missing
             Error::SchemaDoesNotExist { schema_name } => write!(f, "Schema {} does not exist.", schema_name),
             Error::TableDoesNotExist { table_name } => write!(f, "Table {} does not exist.", table_name),
             Error::ColumnDoesNotExist { column_name, table_name } => write!(f, "Column {} does not exist in table '{}'.", column_name, table_name),
+            Error::IndexDoesNotExist { index_name } => write!(f, "Index {} does not exist.", index_name),
         }
     }
 }
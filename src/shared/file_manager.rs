@@ -1,34 +1,84 @@
-use std::{fs::{self, File}, io::{BufReader, Read}};
+use std::{fs::File, io::{self, BufReader, Cursor, ErrorKind, Read, Seek}};
 
 use serde::{de::DeserializeOwned, Serialize};
 
+use crate::transaction_manager::{transaction_context::with_current_transaction, types::Transaction};
+
 use super::errors::Error;
 
-pub fn read_file_to_string(file_path: &String) -> Result<String, Error> {
-    let file = File::open(file_path).map_err(|e| Error::IOError(e))?;
-    let mut buf_reader = BufReader::new(file);
-    let mut contents = String::new();
-    buf_reader.read_to_string(&mut contents).map_err(|e| Error::IOError(e))?;
+/*
+ * File access goes through the current transaction: reads see its staged changes, and writes are staged until it commits them through the write-ahead log
+ */
+pub trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+pub fn open_file_reader(file_path: &str) -> Result<Box<dyn ReadSeek>, Error> {
+    if let Some(contents) = with_current_transaction(|transaction| transaction.read_staged_file(file_path))?.transpose()?.flatten() {
+        return Ok(Box::new(Cursor::new(contents)));
+    }
+
+    let file = File::open(file_path).map_err(Error::IOError)?;
+    Ok(Box::new(file))
+}
+
+pub fn read_file_bytes(file_path: &str) -> Result<Vec<u8>, Error> {
+    let mut buf_reader = BufReader::new(open_file_reader(file_path)?);
+    let mut contents = Vec::new();
+    buf_reader.read_to_end(&mut contents).map_err(Error::IOError)?;
 
     Ok(contents)
 }
 
-pub fn read_json_file<T: DeserializeOwned>(file_path: &String) -> Result<T, Error> {
+pub fn read_file_to_string(file_path: &str) -> Result<String, Error> {
+    let contents = read_file_bytes(file_path)?;
+
+    String::from_utf8(contents).map_err(|e| Error::IOError(io::Error::new(ErrorKind::InvalidData, e)))
+}
+
+pub fn read_json_file<T: DeserializeOwned>(file_path: &str) -> Result<T, Error> {
     let content_string = read_file_to_string(file_path)?;
-    let content: T = serde_json::from_str(&content_string).map_err(|e| Error::SerdeJsonError(e))?;
+    let content: T = serde_json::from_str(&content_string).map_err(Error::SerdeJsonError)?;
 
     Ok(content)
 }
 
-pub fn write_string_into_file(file_path: &String, contents: &String) -> Result<(), Error> {
-    fs::write(file_path, contents).map_err(|e| Error::IOError(e))?;
+pub fn write_bytes_into_file(file_path: &str, contents: Vec<u8>) -> Result<(), Error> {
+    stage_in_current_transaction(|transaction| transaction.stage_write(file_path, contents))
+}
 
-    Ok(())
+pub fn append_bytes_to_file(file_path: &str, contents: Vec<u8>) -> Result<(), Error> {
+    stage_in_current_transaction(|transaction| transaction.stage_append(file_path, contents))?
+}
+
+pub fn write_bytes_at(file_path: &str, offset: u64, contents: Vec<u8>) -> Result<(), Error> {
+    stage_in_current_transaction(|transaction| transaction.stage_write_at(file_path, offset, contents))?
+}
+
+pub fn write_string_into_file(file_path: &str, contents: &str) -> Result<(), Error> {
+    write_bytes_into_file(file_path, contents.as_bytes().to_vec())
 }
 
-pub fn write_json_into_file<T: Serialize>(file_path: &String, contents: &T) -> Result<(), Error> {
-    let contents_string = serde_json::to_string(contents).map_err(|e| Error::SerdeJsonError(e))?;
+pub fn write_json_into_file<T: Serialize>(file_path: &str, contents: &T) -> Result<(), Error> {
+    let contents_string = serde_json::to_string(contents).map_err(Error::SerdeJsonError)?;
     write_string_into_file(file_path, &contents_string)?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn create_dir_all(directory_path: &str) -> Result<(), Error> {
+    stage_in_current_transaction(|transaction| transaction.stage_create_directory(directory_path))
+}
+
+pub fn remove_dir_all(directory_path: &str) -> Result<(), Error> {
+    stage_in_current_transaction(|transaction| transaction.stage_remove_directory(directory_path))
+}
+
+pub fn remove_file(file_path: &str) -> Result<(), Error> {
+    stage_in_current_transaction(|transaction| transaction.stage_remove_file(file_path))
+}
+
+// Writing outside of a transaction would bypass the write-ahead log
+fn stage_in_current_transaction<T>(f: impl FnOnce(&mut Transaction) -> T) -> Result<T, Error> {
+    with_current_transaction(f)?.ok_or(Error::NoActiveTransaction)
+}
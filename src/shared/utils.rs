@@ -1,15 +1,11 @@
+// Rows of unequal length are transposed into shorter columns instead of panicking
 pub fn transpose_matrix<T: Clone>(matrix: Vec<Vec<T>>) -> Vec<Vec<T>> {
-    if matrix.is_empty() || matrix[0].is_empty() {
-        return vec![];
-    }
-
-    let row_count = matrix[0].len();
-    let col_count = matrix.len();
-    let mut transposed = vec![vec![matrix[0][0].clone(); col_count]; row_count];
+    let row_count = matrix.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut transposed: Vec<Vec<T>> = vec![Vec::new(); row_count];
 
-    for (i, row) in matrix.iter().enumerate() {
-        for (j, item) in row.iter().enumerate() { 
-            transposed[j][i] = item.clone(); 
+    for row in matrix {
+        for (j, item) in row.into_iter().enumerate() {
+            transposed[j].push(item);
         }
     }
 
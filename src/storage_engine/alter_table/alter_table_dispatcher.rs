@@ -1,30 +1,27 @@
 use sqlparser::ast::{AlterTableOperation, ObjectName, TableConstraint};
 
-use crate::{database::database_loader, shared::errors::Error, storage_engine::foreign_key::foreign_key_manager};
+use crate::{shared::errors::Error, storage_engine::foreign_key::foreign_key_manager, transaction_manager::lock_manager};
 
+use super::handle_add_check;
 use super::handle_bulk_operations::handle_bulk_operations;
 
 
 pub async fn dispatch_alter_table_statement(name: &ObjectName, operations: &Vec<AlterTableOperation>) -> Result<String, Error> {
-    let first_identifier = name.0.first().ok_or(Error::MissingTableName)?;
-    let table_name = first_identifier.value.clone();
-    
-    // Get database blueprint
-    let database = database_loader::get_database()?;
+    let (database, schema_name, table_name) = lock_manager::lock_table_for_write_and_get_database(name).await?;
 
     // Divide operations into bulk and other
     let bulk_operations: Vec<AlterTableOperation> = operations.into_iter().filter(|op| bulk_operation_strategy(op)).cloned().collect();
     let other_operations: Vec<AlterTableOperation> = operations.iter().filter(|op| !bulk_operation_strategy(op)).cloned().collect();
 
     // Handle bulk operations
-    handle_bulk_operations(&table_name, &bulk_operations, &database).await?;
+    handle_bulk_operations(&schema_name, &table_name, &bulk_operations, &database).await?;
 
     // Handle other operations
     for operation in other_operations {
         match operation {
             // TODO: Add support for RLS, triggers etc
             AlterTableOperation::AddConstraint(table_constraint) => {
-                dispatch_add_constraint_statement(&table_name, table_constraint).await?;
+                dispatch_add_constraint_statement(&schema_name, &table_name, table_constraint).await?;
             }
             _ => return Err(Error::NotSupportedUpdateTableOperation)
         }
@@ -43,12 +40,15 @@ fn bulk_operation_strategy(operation: &AlterTableOperation) -> bool {
     }
 }
 
-async fn dispatch_add_constraint_statement(table_name: &String, table_constraint: TableConstraint) -> Result<String, Error> {
+async fn dispatch_add_constraint_statement(schema_name: &String, table_name: &String, table_constraint: TableConstraint) -> Result<String, Error> {
     println!("Add constraint: {:?}", table_constraint);
     
     match table_constraint {
-        TableConstraint::ForeignKey { name, columns, foreign_table, referred_columns, on_delete, on_update, .. } => {
-            foreign_key_manager::handle_add_foreign_key(table_name, name, columns, foreign_table, referred_columns, on_delete, on_update).await?;
+        TableConstraint::ForeignKey { .. } => {
+            foreign_key_manager::handle_add_foreign_key(schema_name, table_name, &table_constraint).await?;
+        },
+        TableConstraint::Check { .. } => {
+            handle_add_check::handle_add_check(schema_name, table_name, &table_constraint).await?;
         },
         _ => return Err(Error::UnsupportedConstraint { column_name: table_name.clone(), column_constraint: table_constraint.to_string() })
     }
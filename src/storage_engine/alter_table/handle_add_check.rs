@@ -0,0 +1,35 @@
+use csv::StringRecord;
+use sqlparser::ast::TableConstraint;
+
+use crate::database::{database_loader, database_navigator::get_table_schema_path, utils::find_database_table};
+use crate::shared::{errors::Error, file_manager::write_json_into_file};
+use crate::storage_engine::{storage_backend::table_storage::get_table_storage, validation::check_constraints};
+
+// Adds a CHECK constraint, provided the table's rows satisfy it
+pub async fn handle_add_check(schema_name: &String, table_name: &String, table_constraint: &TableConstraint) -> Result<(), Error> {
+    let (name, expr) = match table_constraint {
+        TableConstraint::Check { name, expr } => (name, expr),
+        _ => return Err(Error::UnsupportedConstraint { column_name: table_name.clone(), column_constraint: table_constraint.to_string() }),
+    };
+
+    let database = database_loader::get_database()?;
+    let table_schema = find_database_table(&database, schema_name, table_name)
+        .ok_or_else(|| Error::TableDoesNotExist { table_name: table_name.clone() })?;
+
+    let check = check_constraints::create_check_constraint(table_schema, name, expr)?;
+    if check_constraints::get_table_checks(table_schema).iter().any(|existing_check| existing_check.name == check.name) {
+        return Err(Error::ConstraintAlreadyExists { constraint_name: check.name });
+    }
+
+    let mut new_table_schema = table_schema.clone();
+    new_table_schema.checks.push(check);
+
+    // Existing rows must satisfy it
+    let records: Vec<StringRecord> = get_table_storage(table_schema).scan_rows(schema_name, table_schema)?.into_iter()
+        .map(|(_, record)| record)
+        .collect();
+    check_constraints::validate_check_constraints(&new_table_schema, &records)?;
+
+    write_json_into_file(&get_table_schema_path(schema_name, table_name), &new_table_schema)?;
+    database_loader::reload_table_schema(schema_name, table_name).await
+}
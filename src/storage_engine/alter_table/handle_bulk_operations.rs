@@ -1,11 +1,9 @@
-use std::fs::OpenOptions;
-
-use csv::{StringRecord, WriterBuilder};
+use csv::StringRecord;
 use sqlparser::ast::AlterTableOperation;
 
-use crate::database::{self, database_loader, database_navigator::{get_table_data_path, get_table_schema_path}, types::{Column, Database, TableSchema}, utils::get_headers_from_table_schema};
-use crate::storage_engine::{index::{index_manager, index_updater}, select::{table_reader, utils::get_column_indices}};
-use crate::shared::{errors::Error, file_manager::write_json_into_file};
+use crate::database::{self, constants::NULL_VALUE, database_loader, database_navigator::{get_table_index_path, get_table_schema_path}, types::{CheckConstraint, Column, Constraint, Database, Index, TableSchema}, utils::get_headers_from_table_schema};
+use crate::storage_engine::{select::utils::get_column_indices, storage_backend::table_storage::get_table_storage, validation::{self, check_constraints}};
+use crate::shared::{errors::Error, file_manager::{remove_file, write_json_into_file}};
 
 use super::validator;
 
@@ -13,45 +11,43 @@ use super::validator;
  * Function to add, delete or change table columns in bulk.
  * It will be reworked in the future
  */
-pub async fn handle_bulk_operations(table_name: &String, operations: &Vec<AlterTableOperation>, database: &Database) -> Result<(), Error> {
-    let schema_name = database.configuration.default_schema.clone();
-    let table_schema = match database::utils::find_database_table(&database, &table_name) {
+pub async fn handle_bulk_operations(schema_name: &String, table_name: &String, operations: &Vec<AlterTableOperation>, database: &Database) -> Result<(), Error> {
+    let table_schema = match database::utils::find_database_table(database, schema_name, table_name) {
         Some(schema) => schema,
         None => return Err(Error::TableDoesNotExist { table_name: table_name.clone() }),
     };
     
     // Validate operations and get schema types from it
-    let (delete_columns_names, old_column_names, changed_columns, new_columns) = validator::validate_bulk_operations(table_name, table_schema, operations, database)?;
+    let (delete_columns_names, old_column_names, changed_columns, new_columns) = validator::validate_bulk_operations(schema_name, table_name, table_schema, operations, database)?;
     let new_columns_names: Vec<String> = new_columns.iter().map(|col| col.name.clone()).collect();
     
     // Update table schema
-    let mut new_table_schema = update_table_schema_in_bulk(table_schema, &delete_columns_names, &old_column_names, &changed_columns, &new_columns)?;
-    let table_schema_file_path = get_table_schema_path(&schema_name, table_name);
+    let new_table_schema = update_table_schema_in_bulk(table_schema, &delete_columns_names, &old_column_names, &changed_columns, &new_columns)?;
+    let table_schema_file_path = get_table_schema_path(schema_name, table_name);
     write_json_into_file(&table_schema_file_path, &new_table_schema)?;
 
-    // Get headers from schemas and indices of deleted columns
+    // Remove files of indexes dropped along with their columns
+    for index in table_schema.indexes.iter().filter(|index| !new_table_schema.indexes.iter().any(|new_index| new_index.name == index.name)) {
+        remove_file(&get_table_index_path(schema_name, table_name, &index.name))?;
+    }
+
+    // Get indices of deleted columns
     let old_headers = get_headers_from_table_schema(&table_schema);
-    let new_headers = get_headers_from_table_schema(&new_table_schema);
     let deleted_columns_indices = get_column_indices(&old_headers, &delete_columns_names);
 
     // Read current records into memory
-    let mut records = table_reader::read_table(&schema_name, table_name, &None, true).await?;
+    let storage = get_table_storage(table_schema);
+    let mut records: Vec<StringRecord> = storage.scan_rows(schema_name, table_schema)?.into_iter()
+        .map(|(_, record)| record)
+        .collect();
 
-    // Update data file
-    update_table_data_in_bulk(&mut records, &schema_name, table_name, &new_headers, &deleted_columns_indices, &new_columns_names)?;
-
-    // Create indexes if necessary (deferring offset update to last function)
-    for column in changed_columns {
-        if column.is_indexed {
-            index_manager::create_index(&schema_name, table_name, &column.name, &mut new_table_schema, false, false, &records).await?;
-        }
-    }
-
-    // Update indexes
-    index_updater::update_indexes_on_update_or_delete(&records, &schema_name, table_name, &new_table_schema)?;
+    // Update records and rewrite them along with their indexes
+    update_table_data_in_bulk(&mut records, &deleted_columns_indices, &new_columns_names);
+    check_constraints::validate_check_constraints(&new_table_schema, &records)?;
+    storage.rewrite_rows(schema_name, &new_table_schema, &records)?;
 
     // Reload table schema
-    database_loader::reload_table_schema(&schema_name, table_name).await?;
+    database_loader::reload_table_schema(schema_name, table_name).await?;
 
     Ok(())
 }
@@ -75,6 +71,51 @@ fn update_table_schema_in_bulk(table_schema: &TableSchema, delete_column_names:
     // Add new columns to the schema
     updated_columns.extend(new_columns.iter().cloned());
 
+    // Drop indexes on deleted columns and follow renamed ones
+    let updated_indexes: Vec<Index> = table_schema.indexes.iter()
+        .filter(|index| !index.columns.iter().any(|column_name| delete_column_names.contains(column_name)))
+        .map(|index| {
+            let columns = index.columns.iter().map(|column_name| {
+                match old_column_names.iter().position(|old_name| old_name == column_name) {
+                    Some(position) => changed_columns[position].name.clone(),
+                    None => column_name.clone(),
+                }
+            }).collect();
+            Index { columns, ..index.clone() }
+        })
+        .collect();
+
+    // Drop the primary key and checks on deleted columns, and follow renamed ones
+    let renamed_columns: Vec<(String, String)> = old_column_names.iter().cloned()
+        .zip(changed_columns.iter().map(|column| column.name.clone()))
+        .collect();
+    let updated_primary_key: Vec<String> = match table_schema.primary_key.iter().any(|column_name| delete_column_names.contains(column_name)) {
+        true => Vec::new(),
+        false => table_schema.primary_key.iter()
+            .map(|column_name| renamed_columns.iter().find(|(old_name, _)| old_name == column_name).map_or(column_name.clone(), |(_, new_name)| new_name.clone()))
+            .collect(),
+    };
+    let mut updated_checks: Vec<CheckConstraint> = Vec::new();
+    for check in table_schema.checks.iter() {
+        if let Some(expression) = update_check_expression(&check.expression, delete_column_names, &renamed_columns)? {
+            updated_checks.push(CheckConstraint { name: check.name.clone(), expression });
+        }
+    }
+    for column in updated_columns.iter_mut() {
+        let mut constraints: Vec<Constraint> = Vec::new();
+        for constraint in column.constraints.iter() {
+            match constraint {
+                Constraint::Check(expression) => {
+                    if let Some(expression) = update_check_expression(expression, delete_column_names, &renamed_columns)? {
+                        constraints.push(Constraint::Check(expression));
+                    }
+                },
+                constraint => constraints.push(constraint.clone()),
+            }
+        }
+        column.constraints = constraints;
+    }
+
     // Adjust order
     for (index, column) in updated_columns.iter_mut().enumerate() {
         column.order = index;
@@ -83,26 +124,28 @@ fn update_table_schema_in_bulk(table_schema: &TableSchema, delete_column_names:
     // Construct a new TableSchema with the updated columns
     let new_table_schema = TableSchema {
         columns: updated_columns,
+        indexes: updated_indexes,
+        primary_key: updated_primary_key,
+        checks: updated_checks,
         ..table_schema.clone()
     };
+    validation::common::validate_primary_key_count(&new_table_schema)?;
+    check_constraints::validate_column_checks(&new_table_schema)?;
 
     Ok(new_table_schema)
 }
 
+// The check's expression following renamed columns, None if it refers to a deleted one
+fn update_check_expression(expression: &str, delete_column_names: &[String], renamed_columns: &[(String, String)]) -> Result<Option<String>, Error> {
+    if check_constraints::get_check_columns(expression)?.iter().any(|column_name| delete_column_names.contains(column_name)) {
+        return Ok(None);
+    }
 
-fn update_table_data_in_bulk(records: &mut Vec<StringRecord>, schema_name: &String, table_name: &String, new_headers: &Vec<String>, deleted_columns_indices: &Vec<usize>, new_columns_names: &Vec<String>) -> Result<(), Error> {
-    // Start rewriting CSV file
-    let table_data_file_path = get_table_data_path(&schema_name, table_name);
-    let modified_file = OpenOptions::new()
-        .write(true).truncate(true).create(true).open(table_data_file_path)
-        .map_err(|_| Error::TableDoesNotExist { table_name: table_name.clone() })?;
-
-    let mut wtr = WriterBuilder::new().has_headers(false).from_writer(modified_file);
+    check_constraints::rename_check_columns(expression, renamed_columns).map(Some)
+}
 
-    // Write new headers
-    wtr.write_record(new_headers)
-        .map_err(|_| Error::FailedTableWrite { table_name: table_name.clone() })?;
 
+fn update_table_data_in_bulk(records: &mut [StringRecord], deleted_columns_indices: &[usize], new_columns_names: &[String]) {
     // Rewrite records
     for record in records.iter_mut() {
         // Convert StringRecord to Vec<String> for easier manipulation
@@ -121,7 +164,7 @@ fn update_table_data_in_bulk(records: &mut Vec<StringRecord>, schema_name: &Stri
         // TODO: Check for default values in the future
         let new_columns_indices: Vec<usize> = (fields.len()..(fields.len() + new_columns_names.len())).collect();
         for _ in new_columns_indices {
-            fields.push(String::from("Null"));
+            fields.push(String::from(NULL_VALUE));
         }
 
         // Update record
@@ -129,13 +172,5 @@ fn update_table_data_in_bulk(records: &mut Vec<StringRecord>, schema_name: &Stri
         for field in &fields {
             record.push_field(field);
         }
-
-        // Write record
-        wtr.write_record(&*record)
-            .map_err(|_| Error::FailedTableWrite { table_name: table_name.clone() })?;
     }
-
-    wtr.flush().map_err(|_| Error::FailedTableWrite { table_name: table_name.clone() })?;
-
-    Ok(())
 }
\ No newline at end of file
@@ -1,3 +1,4 @@
 pub mod alter_table_dispatcher;
 pub mod handle_bulk_operations;
+pub mod handle_add_check;
 mod validator;
\ No newline at end of file
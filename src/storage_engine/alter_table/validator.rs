@@ -1,11 +1,11 @@
 use sqlparser::ast::AlterTableOperation;
 
-use crate::{database::types::{Column, Database, TableSchema}, shared::errors::Error, storage_engine::{utils::ast_unwrapper, validation}};
+use crate::{database::types::{Column, Database, TableSchema}, shared::errors::Error, storage_engine::{foreign_key::foreign_key_manager::get_foreign_schema_name, utils::ast_unwrapper, validation}};
 
 
-pub fn validate_bulk_operations(table_name: &String, table_schema: &TableSchema, operations: &Vec<AlterTableOperation>, database: &Database) -> Result<(Vec<String>, Vec<String>, Vec<Column>, Vec<Column>), Error> {
+pub fn validate_bulk_operations(schema_name: &String, table_name: &String, table_schema: &TableSchema, operations: &Vec<AlterTableOperation>, database: &Database) -> Result<(Vec<String>, Vec<String>, Vec<Column>, Vec<Column>), Error> {
     // Validate table exists
-    validation::common::validate_table_exists(database, table_name)?;
+    validation::common::validate_table_exists(database, schema_name, table_name)?;
     
     // Divide operations: delete, new, changed
     let delete_columns_ops: Vec<AlterTableOperation> = operations.iter()
@@ -34,6 +34,16 @@ pub fn validate_bulk_operations(table_name: &String, table_schema: &TableSchema,
         }
     }
 
+    // Validate no foreign key references the columns to be deleted or changed
+    for op in delete_columns_ops.iter().chain(changed_columns_ops.iter()) {
+        match op {
+            AlterTableOperation::DropColumn { column_name, .. } | AlterTableOperation::ChangeColumn { old_name: column_name, .. } => {
+                validate_column_not_referenced(schema_name, table_name, &column_name.value, database)?;
+            }
+            _ => continue
+        }
+    }
+
     // Validate column definitions for columns to be changed
     let (old_changed_column_names, changed_columns_definitions) = ast_unwrapper::get_column_definitions_from_change_columns_ops(&changed_columns_ops);
     let changed_columns = validation::common::validate_column_definitions(
@@ -55,4 +65,19 @@ pub fn validate_bulk_operations(table_name: &String, table_schema: &TableSchema,
     )?;
 
     Ok((delete_column_names, old_changed_column_names, changed_columns, new_columns))
-}
\ No newline at end of file
+}
+
+fn validate_column_not_referenced(schema_name: &String, table_name: &String, column_name: &String, database: &Database) -> Result<(), Error> {
+    for schema in database.schemas.iter() {
+        for foreign_key in schema.tables.iter().flat_map(|table_schema| table_schema.foreign_keys.iter()) {
+            let is_referenced = get_foreign_schema_name(foreign_key, &schema.name) == schema_name
+                && &foreign_key.foreign_table == table_name
+                && foreign_key.foreign_columns.contains(column_name);
+            if is_referenced {
+                return Err(Error::ColumnReferencedByForeignKey { column_name: column_name.clone(), foreign_key_name: foreign_key.name.clone() });
+            }
+        }
+    }
+
+    Ok(())
+}
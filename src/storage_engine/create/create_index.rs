@@ -0,0 +1,94 @@
+use sqlparser::ast::{CreateIndex, Expr};
+
+use crate::database::database_navigator::get_table_schema_path;
+use crate::database::types::{Database, Index, TableSchema};
+use crate::database::{database_loader, utils::find_database_table};
+use crate::shared::{errors::Error, file_manager};
+use crate::storage_engine::index::index_manager;
+use crate::storage_engine::storage_backend::table_storage::get_table_storage;
+use crate::transaction_manager::lock_manager;
+
+pub async fn create_index(create_index: &CreateIndex) -> Result<String, Error> {
+    let (database, schema_name, table_name) = lock_manager::lock_table_for_write_and_get_database(&create_index.table_name).await?;
+
+    let table_schema = find_database_table(&database, &schema_name, &table_name).ok_or(Error::TableDoesNotExist { table_name: table_name.clone() })?;
+    let index = validate_create_index(&database, &schema_name, table_schema, create_index)?;
+    if create_index.if_not_exists && index_exists(&database, &schema_name, table_schema, &index.name) {
+        return Ok(format!("Notice: index {} already exists, skipping.", index.name));
+    }
+    validate_index_doesnt_exist(&database, &schema_name, table_schema, &index.name)?;
+
+    // Build the index from existing rows
+    let rows = get_table_storage(table_schema).scan_rows(&schema_name, table_schema)?;
+    index_manager::create_index(&schema_name, table_schema, &index, &rows)?;
+
+    // Record it in the table schema
+    let mut new_table_schema = table_schema.clone();
+    new_table_schema.indexes.push(index.clone());
+    file_manager::write_json_into_file(&get_table_schema_path(&schema_name, &table_name), &new_table_schema)?;
+
+    database_loader::reload_table_schema(&schema_name, &table_name).await?;
+
+    Ok(format!("Success: index {} has been created successfully.", index.name))
+}
+
+fn validate_create_index(database: &Database, schema_name: &String, table_schema: &TableSchema, create_index: &CreateIndex) -> Result<Index, Error> {
+    if create_index.predicate.is_some() || !create_index.include.is_empty() {
+        return Err(Error::GenericUnsupported);
+    }
+
+    // Only plain columns can be indexed
+    let mut columns: Vec<String> = Vec::new();
+    for order_by_expr in create_index.columns.iter() {
+        let column_name = match &order_by_expr.expr {
+            Expr::Identifier(ident) => ident.value.clone(),
+            expr => return Err(Error::UnsupportedIndexExpression { expression: expr.to_string() }),
+        };
+        if !table_schema.columns.iter().any(|column| column.name == column_name) {
+            return Err(Error::ColumnDoesNotExist { column_name, table_name: table_schema.name.clone() });
+        }
+        columns.push(column_name);
+    }
+    if columns.is_empty() {
+        return Err(Error::InvalidSQLSyntax);
+    }
+
+    // Name it like Postgres does if no name is given
+    let name = match create_index.name.as_ref().and_then(|name| name.0.last()) {
+        Some(ident) => ident.value.clone(),
+        None => get_default_index_name(database, schema_name, table_schema, &columns),
+    };
+
+    Ok(Index { name, columns, is_unique: create_index.unique })
+}
+
+fn get_default_index_name(database: &Database, schema_name: &String, table_schema: &TableSchema, columns: &[String]) -> String {
+    let base_name = format!("{}_{}_idx", table_schema.name, columns.join("_"));
+
+    let mut name = base_name.clone();
+    let mut suffix = 1;
+    while index_exists(database, schema_name, table_schema, &name) {
+        name = format!("{}{}", base_name, suffix);
+        suffix += 1;
+    }
+
+    name
+}
+
+// Index names are unique within the schema, and can't shadow the implicit indexes of the table's indexed columns
+pub fn index_exists(database: &Database, schema_name: &String, table_schema: &TableSchema, index_name: &String) -> bool {
+    let is_named_index = database.schemas.iter()
+        .filter(|schema| &schema.name == schema_name)
+        .flat_map(|schema| schema.tables.iter())
+        .any(|table_schema| table_schema.indexes.iter().any(|index| &index.name == index_name));
+
+    is_named_index || table_schema.columns.iter().any(|column| column.is_indexed && &column.name == index_name)
+}
+
+fn validate_index_doesnt_exist(database: &Database, schema_name: &String, table_schema: &TableSchema, index_name: &String) -> Result<(), Error> {
+    if index_exists(database, schema_name, table_schema, index_name) {
+        return Err(Error::IndexAlreadyExists { index_name: index_name.clone() });
+    }
+
+    Ok(())
+}
@@ -1,8 +1,6 @@
-use std::fs::{self};
-
 use sqlparser::ast::SchemaName;
 
-use crate::{database::{database_navigator::{get_database_configuration_path, get_schema_configuration_dir_path, get_schema_configuration_path, get_tables_dir_path}, types::{DatabaseConfiguration, SchemaConfiguration}}, shared::{errors::Error, file_manager}};
+use crate::{database::{database_loader, database_navigator::{get_database_configuration_path, get_schema_configuration_dir_path, get_schema_configuration_path, get_tables_dir_path}, types::{DatabaseConfiguration, SchemaConfiguration}}, shared::{errors::Error, file_manager}};
 
 pub async fn create_schema(schema_name: &SchemaName) -> Result<String, Error> {
     let schema_name_string = get_schema_name_string(schema_name)?;
@@ -11,7 +9,7 @@ pub async fn create_schema(schema_name: &SchemaName) -> Result<String, Error> {
 
     update_database_configuration(&schema_name_string)?;
 
-    // TODO: Reload database into memory
+    database_loader::reload_schema(&schema_name_string).await?;
     
     Ok(format!("Success: schema {} has been created.", schema_name_string))
 }
@@ -33,8 +31,8 @@ fn create_schema_folders_and_files(schema_name_string: &String) -> Result<(), Er
     // Create folders
     let schema_tables_file_path = get_tables_dir_path(&schema_name_string);
     let schema_configuration_dir_file_path = get_schema_configuration_dir_path(&schema_name_string);
-    fs::create_dir_all(schema_tables_file_path).map_err(|e| Error::IOError(e))?;
-    fs::create_dir_all(schema_configuration_dir_file_path).map_err(|e| Error::IOError(e))?;
+    file_manager::create_dir_all(&schema_tables_file_path)?;
+    file_manager::create_dir_all(&schema_configuration_dir_file_path)?;
 
     // Create schema configuration
     let schema_configuration_file_path = get_schema_configuration_path(&schema_name_string);
@@ -1,94 +1,207 @@
-use std::fs::{self};
-use sqlparser::ast::{ColumnDef, ObjectName};
+use sqlparser::ast::{ColumnDef, Expr, Ident, ObjectName, SqlOption, TableConstraint, Value};
 
-use crate::database::database_navigator::{get_table_data_path, get_table_path, get_table_schema_path};
-use crate::database::types::Database;
+use crate::database::database_navigator::{get_table_path, get_table_schema_path};
+use crate::database::types::{Constraint, Database, Index, StorageEngine};
+use crate::database::utils::{get_current_schema_name, split_table_name};
 use crate::shared::errors::Error;
 use crate::database::database_loader;
 use crate::database::types::TableSchema;
 use crate::shared::file_manager;
+use crate::storage_engine::create::create_index;
 use crate::storage_engine::foreign_key::foreign_key_manager;
 use crate::storage_engine::index::index_manager;
+use crate::storage_engine::storage_backend::table_storage::get_table_storage;
 use crate::storage_engine::validation;
 
 pub async fn create_table(
     name: &ObjectName, 
     columns: &Vec<ColumnDef>,
+    constraints: &[TableConstraint],
+    with_options: &[SqlOption],
 ) -> Result<String, Error> {
     let database = database_loader::get_database()?;
-    let default_schema_name = database.configuration.default_schema.clone();
+    // New tables go to the current schema, even if a table of the same name is further on the search path
+    let (schema_name, table_name) = split_table_name(name, &get_current_schema_name(&database))?;
 
-    let table_schema = validate_create_table(&database, name, columns)?;
+    let table_schema = validate_create_table(&database, &schema_name, &table_name, columns, constraints, with_options)?;
     
-    create_table_folders(&default_schema_name, &table_schema.name).await?;
+    create_table_folders(&schema_name, &table_schema.name).await?;
 
-    create_table_files(&default_schema_name, &table_schema).await?;
+    create_table_files(&schema_name, &table_schema).await?;
 
-    create_foreign_keys(&table_schema.name, columns).await?;
+    update_schema_configuration(&schema_name, &table_schema.name).await?;
 
-    index_manager::create_default_indexes(&default_schema_name, &table_schema).await?;
+    // Once the table is in the catalog
+    create_foreign_keys(&schema_name, &table_schema.name, columns, constraints).await?;
 
-    update_schema_configuration(&default_schema_name, &table_schema.name).await
+    Ok(format!("Success: table {} has been created successfully.", table_schema.name))
 }
 
 fn validate_create_table(
     database: &Database, 
-    name: &ObjectName, 
+    schema_name: &String,
+    table_name: &String,
     columns: &Vec<ColumnDef>,
+    constraints: &[TableConstraint],
+    with_options: &[SqlOption],
 ) -> Result<TableSchema, Error> {
-    let first_identifier = name.0.first().ok_or(Error::MissingTableName)?;
-    let table_name = first_identifier.value.clone();
-    
-    // Ensure table doesn't already exist
-    validation::common::validate_table_doesnt_exist(database, &table_name)?;
+    // Ensure schema exists and table doesn't already exist
+    validation::common::validate_schema_exists(database, schema_name)?;
+    validation::common::validate_table_doesnt_exist(database, schema_name, table_name)?;
 
     // Validate query columns and transform to custom schema types
     let schema_columns = validation::common::validate_column_definitions(columns, &(0..columns.len()).collect())?;
     
-    Ok(TableSchema { name: table_name, columns: schema_columns, foreign_keys: Vec::new(), triggers: Vec::new() })
+    let storage_engine = get_storage_engine(with_options)?;
+
+    let mut table_schema = TableSchema { name: table_name.clone(), columns: schema_columns, foreign_keys: Vec::new(), triggers: Vec::new(), storage_engine, indexes: Vec::new(), primary_key: Vec::new(), checks: Vec::new() };
+    add_table_constraints(database, schema_name, &mut table_schema, constraints)?;
+    validation::check_constraints::validate_column_checks(&table_schema)?;
+    validation::common::validate_primary_key_count(&table_schema)?;
+
+    Ok(table_schema)
 }
 
-async fn create_table_folders(schema_name: &String, table_name: &String) -> Result<(), Error> {
+// PRIMARY KEY, UNIQUE and CHECK clauses, FOREIGN KEY ones being added once the table exists
+fn add_table_constraints(database: &Database, schema_name: &String, table_schema: &mut TableSchema, constraints: &[TableConstraint]) -> Result<(), Error> {
+    for constraint in constraints {
+        match constraint {
+            TableConstraint::PrimaryKey { name, columns, .. } => {
+                let column_names = get_constraint_columns(table_schema, columns)?;
+                if !table_schema.primary_key.is_empty() || table_schema.columns.iter().any(|column| column.constraints.contains(&Constraint::PrimaryKey)) {
+                    return Err(Error::MultiplePrimaryKeys { table_name: table_schema.name.clone() });
+                }
+
+                // Keys spanning several columns are enforced through a unique index, single columns like column constraints
+                if let [column_name] = column_names.as_slice() {
+                    add_column_constraint(table_schema, column_name, Constraint::PrimaryKey);
+                    continue;
+                }
+                for column_name in column_names.iter() {
+                    add_column_constraint(table_schema, column_name, Constraint::NotNull);
+                }
+                let index_name = name.as_ref().map(|ident| ident.value.clone()).unwrap_or_else(|| format!("{}_pkey", table_schema.name));
+                add_unique_index(database, schema_name, table_schema, index_name, column_names.clone())?;
+                table_schema.primary_key = column_names;
+            },
+            TableConstraint::Unique { name, columns, .. } => {
+                let column_names = get_constraint_columns(table_schema, columns)?;
+                if let [column_name] = column_names.as_slice() {
+                    add_column_constraint(table_schema, column_name, Constraint::Unique);
+                    continue;
+                }
+                let index_name = name.as_ref().map(|ident| ident.value.clone()).unwrap_or_else(|| format!("{}_{}_key", table_schema.name, column_names.join("_")));
+                add_unique_index(database, schema_name, table_schema, index_name, column_names)?;
+            },
+            TableConstraint::Check { name, expr } => {
+                let check = validation::check_constraints::create_check_constraint(table_schema, name, expr)?;
+                if table_schema.checks.iter().any(|existing_check| existing_check.name == check.name) {
+                    return Err(Error::ConstraintAlreadyExists { constraint_name: check.name });
+                }
+                table_schema.checks.push(check);
+            },
+            TableConstraint::ForeignKey { .. } => continue,
+            _ => return Err(Error::UnsupportedConstraint { column_name: table_schema.name.clone(), column_constraint: constraint.to_string() }),
+        }
+    }
+
+    Ok(())
+}
+
+fn get_constraint_columns(table_schema: &TableSchema, columns: &[Ident]) -> Result<Vec<String>, Error> {
+    let column_names: Vec<String> = columns.iter().map(|ident| ident.value.clone()).collect();
+    validation::common::validate_columns_exist(table_schema, &column_names)?;
+    if column_names.is_empty() {
+        return Err(Error::InvalidSQLSyntax);
+    }
+
+    Ok(column_names)
+}
+
+fn add_column_constraint(table_schema: &mut TableSchema, column_name: &String, constraint: Constraint) {
+    if let Some(column) = table_schema.columns.iter_mut().find(|column| &column.name == column_name) {
+        if !column.constraints.contains(&constraint) {
+            column.constraints.push(constraint);
+        }
+        column.is_indexed = index_manager::index_strategy(&column.constraints);
+    }
+}
+
+fn add_unique_index(database: &Database, schema_name: &String, table_schema: &mut TableSchema, name: String, columns: Vec<String>) -> Result<(), Error> {
+    if create_index::index_exists(database, schema_name, table_schema, &name) || table_schema.indexes.iter().any(|index| index.name == name) {
+        return Err(Error::IndexAlreadyExists { index_name: name });
+    }
+
+    table_schema.indexes.push(Index { name, columns, is_unique: true });
+    Ok(())
+}
+
+// WITH (storage_engine = 'paged'), CSV by default
+fn get_storage_engine(with_options: &[SqlOption]) -> Result<StorageEngine, Error> {
+    let option = match with_options.iter().find(|option| option.name.value.eq_ignore_ascii_case("storage_engine")) {
+        Some(option) => option,
+        None => return Ok(StorageEngine::default()),
+    };
+
+    let storage_engine = match &option.value {
+        Expr::Value(Value::SingleQuotedString(value)) => value.clone(),
+        Expr::Identifier(ident) => ident.value.clone(),
+        value => value.to_string(),
+    };
+
+    match storage_engine.to_lowercase().as_str() {
+        "csv" => Ok(StorageEngine::Csv),
+        "paged" => Ok(StorageEngine::Paged),
+        _ => Err(Error::InvalidStorageEngine { storage_engine }),
+    }
+}
+
+async fn create_table_folders(schema_name: &str, table_name: &str) -> Result<(), Error> {
     let table_filepath = get_table_path(schema_name, table_name);
     for folder in vec!["table_schema", "data", "indexes"] {
         let folder_filepath = format!("{}/{}", table_filepath, folder);
-        fs::create_dir_all(folder_filepath)?;
+        file_manager::create_dir_all(&folder_filepath)?;
     }
     Ok(())
 }
 
-async fn create_table_files(schema_name: &String, table_schema: &TableSchema) -> Result<(), Error> {
+async fn create_table_files(schema_name: &str, table_schema: &TableSchema) -> Result<(), Error> {
     // Table schema file
     let table_schema_filepath = get_table_schema_path(schema_name, &table_schema.name);
     file_manager::write_json_into_file(&table_schema_filepath, &table_schema)?;
     
-    // Table data file
-    let table_data_filepath = get_table_data_path(schema_name, &table_schema.name);
-    let table_data_headers = table_schema.columns.iter().map(|column| column.name.as_str()).collect::<Vec<&str>>().join(",") + "\n";
-    fs::write(&table_data_filepath, table_data_headers.as_bytes()).map_err(Error::IOError)?;
+    // Table data files
+    get_table_storage(table_schema).create_storage(schema_name, table_schema)?;
 
     Ok(())
 }
 
 async fn create_foreign_keys(
+    schema_name: &String,
     table_name: &String,
     columns: &Vec<ColumnDef>,
+    constraints: &[TableConstraint],
 ) -> Result<(), Error> {
     for column in columns {
         for option in column.options.clone() {
             match option.option {
-                sqlparser::ast::ColumnOption::ForeignKey { foreign_table, referred_columns, on_delete, on_update, .. } => {
-                    foreign_key_manager::handle_add_foreign_key(&table_name.clone(), None, vec![column.name.clone()], foreign_table.clone(), referred_columns.clone(), on_delete.clone(), on_update.clone()).await?;
+                sqlparser::ast::ColumnOption::ForeignKey { foreign_table, referred_columns, on_delete, on_update, characteristics } => {
+                    let table_constraint = TableConstraint::ForeignKey { name: None, columns: vec![column.name.clone()], foreign_table, referred_columns, on_delete, on_update, characteristics };
+                    foreign_key_manager::handle_add_foreign_key(schema_name, table_name, &table_constraint).await?;
                 },
                 _ => continue
             }
         }
     }
 
+    for constraint in constraints.iter().filter(|constraint| matches!(constraint, TableConstraint::ForeignKey { .. })) {
+        foreign_key_manager::handle_add_foreign_key(schema_name, table_name, constraint).await?;
+    }
+
     Ok(())
 }
 
-async fn update_schema_configuration(schema_name: &String, table_name: &String) -> Result<String, Error> {
+async fn update_schema_configuration(schema_name: &String, table_name: &String) -> Result<(), Error> {
     let mut schema_config = database_loader::load_schema_configuration(schema_name).await?;
     
     if !schema_config.tables.contains(table_name) {
@@ -97,9 +210,7 @@ async fn update_schema_configuration(schema_name: &String, table_name: &String)
 
     database_loader::save_schema_configuration(&schema_name, &schema_config).await?;
 
-    database_loader::reload_schema(schema_name).await?;
-    
-    Ok(format!("Success: table {} has been created successfully.", table_name))
+    database_loader::reload_schema(schema_name).await
 }
 
 /*
@@ -1,2 +1,3 @@
 pub mod create_table;
-pub mod create_schema;
\ No newline at end of file
+pub mod create_schema;
+pub mod create_index;
\ No newline at end of file
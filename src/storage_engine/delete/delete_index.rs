@@ -0,0 +1,49 @@
+use sqlparser::ast::ObjectName;
+
+use crate::{database::{database_loader, database_navigator::{get_table_index_path, get_table_schema_path}, types::{Database, TableSchema}, utils::get_search_path}, shared::{errors::Error, file_manager}, transaction_manager::lock_manager};
+
+
+pub async fn delete_index(names: &[ObjectName], if_exists: bool) -> Result<String, Error> {
+    // Find the table the index belongs to
+    let database = database_loader::get_database()?;
+    let (schema_names, index_name) = match names.first().map(|name| name.0.as_slice()) {
+        Some([index]) => (get_search_path(&database), index.value.clone()),
+        Some([schema, index]) => (vec![schema.value.clone()], index.value.clone()),
+        _ => return Err(Error::InvalidSQLSyntax),
+    };
+    let index_table = schema_names.into_iter()
+        .find_map(|schema_name| find_index_table(&database, &schema_name, &index_name).map(|table_name| (schema_name, table_name)));
+    let (schema_name, table_name) = match index_table {
+        Some(index_table) => index_table,
+        None if if_exists => return Ok(format!("Notice: index {} does not exist, skipping.", index_name)),
+        None => return Err(Error::IndexDoesNotExist { index_name }),
+    };
+
+    // Lock table, then check the index is still there
+    lock_manager::lock_table_for_write(&schema_name, &table_name).await?;
+    let database = database_loader::get_database()?;
+    if find_index_table(&database, &schema_name, &index_name).as_ref() != Some(&table_name) {
+        return Err(Error::IndexDoesNotExist { index_name });
+    }
+
+    // Remove the index from the table schema, then its file
+    let table_schema_file_path = get_table_schema_path(&schema_name, &table_name);
+    let mut table_schema = file_manager::read_json_file::<TableSchema>(&table_schema_file_path)?;
+    table_schema.indexes.retain(|index| index.name != index_name);
+    file_manager::write_json_into_file(&table_schema_file_path, &table_schema)?;
+
+    file_manager::remove_file(&get_table_index_path(&schema_name, &table_name, &index_name))?;
+
+    database_loader::reload_table_schema(&schema_name, &table_name).await?;
+
+    Ok(format!("Success: index {} has been deleted.", index_name))
+}
+
+// Only indexes created with CREATE INDEX can be dropped, column indexes go with their constraint
+fn find_index_table(database: &Database, schema_name: &String, index_name: &String) -> Option<String> {
+    database.schemas.iter()
+        .filter(|schema| &schema.name == schema_name)
+        .flat_map(|schema| schema.tables.iter())
+        .find(|table_schema| table_schema.indexes.iter().any(|index| &index.name == index_name))
+        .map(|table_schema| table_schema.name.clone())
+}
@@ -1,50 +1,48 @@
+use csv::StringRecord;
 use sqlparser::ast::{Expr, FromTable};
 
-use crate::{database::{self, database_loader, types::Database, utils::find_database_table}, shared::errors::Error, storage_engine::{filters::filter_column_finder, index::index_updater, select::{record_handler, table_reader}, utils::ast_unwrapper}};
+use crate::{database::{self, types::Database, utils::find_database_table}, network_protocol::types::ResultSet, shared::errors::Error, storage_engine::{foreign_key::referential_action_handler, select::{subquery_handler, table_reader}, storage_backend::table_storage::{get_table_storage, RowId}, utils::ast_unwrapper}, transaction_manager::lock_manager};
 
-pub async fn delete_records(from_table: &FromTable, filters: &Option<Expr>) -> Result<String, Error> {
+pub async fn delete_records(from_table: &FromTable, filters: &Option<Expr>) -> Result<ResultSet, Error> {
     // Unwrap table name
     let from_vec = match from_table {
         FromTable::WithFromKeyword(from) | FromTable::WithoutKeyword(from) => from,
     };
-    let table_name = ast_unwrapper::get_table_name_from_from_vector(from_vec)?;
+    let name = ast_unwrapper::get_table_name_from_from_vector(from_vec)?;
     
-    // Prepare: get database blueprint and necessary data from it
-    let database = database_loader::get_database()?;
-    let schema_name = database.configuration.default_schema.clone();
-    let table_schema = match database::utils::find_database_table(&database, &table_name) {
+    // Prepare: lock table, get database blueprint and necessary data from it
+    let (database, schema_name, table_name) = lock_manager::lock_table_for_write_and_get_database(&name).await?;
+    let table_schema = match database::utils::find_database_table(&database, &schema_name, &table_name) {
         Some(schema) => schema,
         None => return Err(Error::TableDoesNotExist { table_name: table_name.clone() }),
     };
     
     // Perform validation
-    validate_delete(&database, &table_name)?;
+    validate_delete(&database, &schema_name, &table_name)?;
 
-    // Get columns used for filtering
-    let filter_columns = filter_column_finder::find_filter_columns(&filters)?;
+    // Subqueries in the filters read the tables as they were before the delete, and only until the rows are found
+    let subquery_exprs: Vec<&Expr> = filters.iter().collect();
+    let read_guards = lock_manager::lock_tables_for_read(&subquery_handler::get_subquery_tables(&database, &subquery_exprs)?).await?;
 
-    // Use indexes only if all filter columns are indexed
-    let use_indexes = filter_column_finder::use_indexes(&filter_columns, table_schema);
-    
-    // Read from table and filter
-    let remaining_rows = if use_indexes {
-        table_reader::read_table_with_indexes(&schema_name, &table_name, filters, &filter_columns, false).await?
-    } else {
-        table_reader::read_table(&schema_name, &table_name, filters, false).await?
-    };
-    
-    // Rewrite CSV file with remaining rows
-    record_handler::rewrite_records(&remaining_rows, &schema_name, &table_name)?;
+    // Find rows matching the filters and remove them from storage
+    let storage = get_table_storage(table_schema);
+    let (row_ids, records): (Vec<RowId>, Vec<StringRecord>) = subquery_handler::with_subquery_cache(|| table_reader::read_table(&schema_name, table_schema, filters, true))?
+        .into_iter()
+        .unzip();
+    drop(read_guards);
 
-    index_updater::update_indexes_on_update_or_delete(&remaining_rows, &schema_name, &table_name, table_schema)?;
-    
-    Ok(format!("Success: records have been deleted."))
+    storage.delete_rows(&schema_name, table_schema, &row_ids)?;
+
+    // Apply the foreign keys of the tables referencing the deleted rows
+    referential_action_handler::apply_delete_actions(&schema_name, table_schema, records).await?;
+
+    Ok(ResultSet::from_rows_affected(row_ids.len() as u64, String::from("Success: records have been deleted.")))
 }
 
 
-fn validate_delete(database: &Database, table_name: &String) -> Result<(), Error> {
+fn validate_delete(database: &Database, schema_name: &str, table_name: &String) -> Result<(), Error> {
     // Ensure table exists
-    let _ = match find_database_table(database, table_name) {
+    let _ = match find_database_table(database, schema_name, table_name) {
         Some(schema) => schema,
         None => return Err(Error::TableDoesNotExist { table_name: table_name.clone() }),
     };
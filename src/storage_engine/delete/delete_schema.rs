@@ -1,5 +1,3 @@
-use std::fs;
-
 use sqlparser::ast::ObjectName;
 
 use crate::{database::{database_navigator::{get_database_configuration_path, get_schema_path}, types::DatabaseConfiguration}, shared::{errors::Error, file_manager}};
@@ -9,7 +7,7 @@ pub async fn delete_schema(names: &Vec<ObjectName>) -> Result<String, Error> {
 
     // Delete schema directory
     let schema_dir_file_path = get_schema_path(&schema_name);
-    fs::remove_dir_all(schema_dir_file_path).map_err(|e| Error::IOError(e))?;
+    file_manager::remove_dir_all(&schema_dir_file_path)?;
 
     // Update schema configuration
     let database_configuration_file_path = get_database_configuration_path();
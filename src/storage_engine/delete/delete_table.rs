@@ -1,20 +1,17 @@
-use std::fs;
-
 use sqlparser::ast::ObjectName;
 
-use crate::{database::{database_loader, database_navigator::{get_schema_configuration_path, get_table_path}, types::SchemaConfiguration}, shared::{errors::Error, file_manager}};
+use crate::{database::{database_loader, database_navigator::{get_schema_configuration_path, get_table_path}, types::{Database, SchemaConfiguration}, utils::get_schema_and_table_name}, shared::{errors::Error, file_manager}, transaction_manager::lock_manager};
 
 
 pub async fn delete_table(names: &Vec<ObjectName>) -> Result<String, Error> {
     // Get database blueprint
     let database = database_loader::get_database()?;
-    let schema_name = database.configuration.default_schema.clone();
-    
-    let table_name = get_table_name(names)?;
+    let (schema_name, table_name) = get_table_name(&database, names)?;
+    lock_manager::lock_table_for_write(&schema_name, &table_name).await?;
 
     // Delete table directory
     let table_dir_file_path = get_table_path(&schema_name, &table_name);
-    fs::remove_dir_all(table_dir_file_path).map_err(|e| Error::IOError(e))?;
+    file_manager::remove_dir_all(&table_dir_file_path)?;
 
     // Update schema configuration
     let schema_configuration_file_path = get_schema_configuration_path(&schema_name);
@@ -27,14 +24,9 @@ pub async fn delete_table(names: &Vec<ObjectName>) -> Result<String, Error> {
     Ok(format!("Success: table {} has been deleted.", table_name))
 }
 
-pub fn get_table_name(names: &Vec<ObjectName>) -> Result<String, Error> {
-    if let Some(first_name) = names.first() {
-        if let Some(first_ident) = first_name.0.first() {
-            Ok(first_ident.value.clone())
-        } else {
-            Err(Error::MissingTableName)
-        }
-    } else {
-        Err(Error::MissingTableName)
+pub fn get_table_name(database: &Database, names: &Vec<ObjectName>) -> Result<(String, String), Error> {
+    match names.first() {
+        Some(first_name) => get_schema_and_table_name(database, first_name),
+        None => Err(Error::MissingTableName),
     }
 }
\ No newline at end of file
@@ -1,3 +1,4 @@
 pub mod delete_schema;
 pub mod delete_table;
-pub mod delete_records;
\ No newline at end of file
+pub mod delete_records;
+pub mod delete_index;
\ No newline at end of file
@@ -0,0 +1,146 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use sqlparser::ast::{BinaryOperator, DuplicateTreatment, Expr, Function, FunctionArg, FunctionArgExpr, FunctionArguments};
+
+use crate::database::types::DataType;
+use crate::network_protocol::types::CellValue;
+use crate::shared::errors::Error;
+
+use super::{expression_evaluator, expression_walker::get_function_arguments, operation_handler, type_resolver, types::RowContext};
+
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    pub fn from_function(function: &Function) -> Option<AggregateFunction> {
+        let name = function.name.0.last()?.value.to_lowercase();
+
+        match name.as_str() {
+            "count" => Some(AggregateFunction::Count),
+            "sum" => Some(AggregateFunction::Sum),
+            "avg" => Some(AggregateFunction::Avg),
+            "min" => Some(AggregateFunction::Min),
+            "max" => Some(AggregateFunction::Max),
+            _ => None,
+        }
+    }
+}
+
+pub fn is_aggregate(expr: &Expr) -> bool {
+    match expr {
+        Expr::Function(function) => function.over.is_none() && AggregateFunction::from_function(function).is_some(),
+        _ => false,
+    }
+}
+
+// COUNT is always an integer, AVG a float; the others keep their argument's type
+pub fn get_aggregate_type(function: &Function, context: &RowContext) -> Result<DataType, Error> {
+    let aggregate_function = get_aggregate_function(function)?;
+    let argument = get_aggregate_argument(function)?;
+    let argument_type = match argument {
+        Some(argument) => type_resolver::get_expression_type(argument, context)?,
+        None => DataType::Integer,
+    };
+    if let Some(filter) = &function.filter {
+        type_resolver::get_expression_type(filter, context)?;
+    }
+
+    Ok(match aggregate_function {
+        AggregateFunction::Count => DataType::Integer,
+        AggregateFunction::Avg => DataType::Float,
+        AggregateFunction::Sum | AggregateFunction::Min | AggregateFunction::Max => argument_type,
+    })
+}
+
+// Aggregate over the rows of a group, skipping NULL arguments as Postgres does
+pub fn compute_aggregate(function: &Function, rows: &[RowContext]) -> Result<CellValue, Error> {
+    let aggregate_function = get_aggregate_function(function)?;
+    let argument = get_aggregate_argument(function)?;
+    let is_distinct = matches!(&function.args, FunctionArguments::List(list) if list.duplicate_treatment == Some(DuplicateTreatment::Distinct));
+
+    // Collect the argument values, COUNT(*) counting every row
+    let mut values: Vec<CellValue> = Vec::new();
+    let mut seen_values: HashSet<String> = HashSet::new();
+    for row in rows {
+        if let Some(filter) = &function.filter {
+            if !expression_evaluator::evaluate_condition(filter, row)? {
+                continue;
+            }
+        }
+
+        let value = match argument {
+            Some(argument) => expression_evaluator::evaluate_expression(argument, row)?,
+            None => CellValue::Boolean(true),
+        };
+        if value == CellValue::Null || (is_distinct && !seen_values.insert(format!("{:?}", value))) {
+            continue;
+        }
+        values.push(value);
+    }
+
+    if values.is_empty() {
+        return Ok(match aggregate_function {
+            AggregateFunction::Count => CellValue::Integer(0),
+            _ => CellValue::Null,
+        });
+    }
+
+    match aggregate_function {
+        AggregateFunction::Count => Ok(CellValue::Integer(values.len() as i64)),
+        AggregateFunction::Sum => sum_values(&values),
+        AggregateFunction::Avg => {
+            let sum = operation_handler::cast_value(&sum_values(&values)?, &DataType::Float)?;
+            operation_handler::apply_arithmetic(&sum, &BinaryOperator::Divide, &CellValue::Integer(values.len() as i64))
+        },
+        AggregateFunction::Min => find_extreme_value(values, Ordering::Less),
+        AggregateFunction::Max => find_extreme_value(values, Ordering::Greater),
+    }
+}
+
+fn get_aggregate_function(function: &Function) -> Result<AggregateFunction, Error> {
+    AggregateFunction::from_function(function)
+        .ok_or_else(|| Error::UnsupportedExpression { expression: function.to_string() })
+}
+
+// None for COUNT(*)
+fn get_aggregate_argument(function: &Function) -> Result<Option<&Expr>, Error> {
+    if let FunctionArguments::List(list) = &function.args {
+        if let [FunctionArg::Unnamed(FunctionArgExpr::Wildcard)] = list.args.as_slice() {
+            return match AggregateFunction::from_function(function) {
+                Some(AggregateFunction::Count) => Ok(None),
+                _ => Err(Error::UnsupportedExpression { expression: function.to_string() }),
+            };
+        }
+    }
+
+    match get_function_arguments(&function.args).as_slice() {
+        [argument] => Ok(Some(argument)),
+        _ => Err(Error::UnsupportedExpression { expression: function.to_string() }),
+    }
+}
+
+fn sum_values(values: &[CellValue]) -> Result<CellValue, Error> {
+    let mut sum = CellValue::Integer(0);
+    for value in values {
+        sum = operation_handler::apply_arithmetic(&sum, &BinaryOperator::Plus, value)?;
+    }
+
+    Ok(sum)
+}
+
+fn find_extreme_value(values: Vec<CellValue>, wanted_ordering: Ordering) -> Result<CellValue, Error> {
+    let mut extreme_value = CellValue::Null;
+    for value in values {
+        if extreme_value == CellValue::Null || operation_handler::compare_values(&value, &extreme_value)? == Some(wanted_ordering) {
+            extreme_value = value;
+        }
+    }
+
+    Ok(extreme_value)
+}
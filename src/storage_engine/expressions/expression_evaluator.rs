@@ -0,0 +1,255 @@
+use std::cmp::Ordering;
+
+use sqlparser::ast::{BinaryOperator, CastKind, Expr, UnaryOperator, Value};
+
+use crate::network_protocol::types::CellValue;
+use crate::shared::errors::Error;
+use crate::storage_engine::select::subquery_handler;
+
+use super::{aggregate_functions, operation_handler::{self, from_boolean, get_boolean}, type_resolver, types::RowContext};
+
+pub fn evaluate_expression(expr: &Expr, context: &RowContext) -> Result<CellValue, Error> {
+    match expr {
+        Expr::Identifier(ident) => {
+            let (table_row, column) = context.find_column(std::slice::from_ref(ident))?;
+            Ok(table_row.get_value(column))
+        },
+        Expr::CompoundIdentifier(idents) => {
+            let (table_row, column) = context.find_column(idents)?;
+            Ok(table_row.get_value(column))
+        },
+        Expr::Value(value) => get_literal_value(value),
+        Expr::Nested(nested_expr) => evaluate_expression(nested_expr, context),
+        Expr::UnaryOp { op, expr } => {
+            let value = evaluate_expression(expr, context)?;
+
+            match op {
+                UnaryOperator::Minus => operation_handler::negate(&value),
+                UnaryOperator::Plus => Ok(value),
+                UnaryOperator::Not => Ok(from_boolean(get_boolean(&value)?.map(|value| !value))),
+                _ => Err(Error::UnsupportedOperationType { operation: op.to_string() }),
+            }
+        },
+        Expr::BinaryOp { left, op, right } => evaluate_binary_operation(left, op, right, context),
+        Expr::IsNull(expr) => Ok(CellValue::Boolean(evaluate_expression(expr, context)? == CellValue::Null)),
+        Expr::IsNotNull(expr) => Ok(CellValue::Boolean(evaluate_expression(expr, context)? != CellValue::Null)),
+        Expr::IsTrue(expr) => Ok(CellValue::Boolean(get_boolean(&evaluate_expression(expr, context)?)? == Some(true))),
+        Expr::IsNotTrue(expr) => Ok(CellValue::Boolean(get_boolean(&evaluate_expression(expr, context)?)? != Some(true))),
+        Expr::IsFalse(expr) => Ok(CellValue::Boolean(get_boolean(&evaluate_expression(expr, context)?)? == Some(false))),
+        Expr::IsNotFalse(expr) => Ok(CellValue::Boolean(get_boolean(&evaluate_expression(expr, context)?)? != Some(false))),
+        Expr::IsDistinctFrom(left, right) => Ok(CellValue::Boolean(!is_not_distinct(left, right, context)?)),
+        Expr::IsNotDistinctFrom(left, right) => Ok(CellValue::Boolean(is_not_distinct(left, right, context)?)),
+        Expr::Between { expr, negated, low, high } => {
+            let value = evaluate_expression(expr, context)?;
+            let above_low = operation_handler::compare_values(&value, &evaluate_expression(low, context)?)?.map(|ordering| ordering != Ordering::Less);
+            let below_high = operation_handler::compare_values(&value, &evaluate_expression(high, context)?)?.map(|ordering| ordering != Ordering::Greater);
+
+            Ok(from_boolean(negate_if(and(above_low, below_high), *negated)))
+        },
+        Expr::InList { expr, list, negated } => {
+            let value = evaluate_expression(expr, context)?;
+            let list_values = list.iter().map(|list_expr| evaluate_expression(list_expr, context));
+
+            Ok(from_boolean(negate_if(is_in_values(&value, list_values)?, *negated)))
+        },
+        Expr::InSubquery { expr, subquery, negated } => {
+            let value = evaluate_expression(expr, context)?;
+            let subquery_values = subquery_handler::get_subquery_values(subquery, context, None)?;
+
+            Ok(from_boolean(negate_if(is_in_values(&value, subquery_values.into_iter().map(Ok))?, *negated)))
+        },
+        Expr::Exists { subquery, negated } => Ok(CellValue::Boolean(subquery_handler::subquery_exists(subquery, context)? != *negated)),
+        Expr::Subquery(subquery) => {
+            // A second row is read only to tell that there is one
+            let mut subquery_values = subquery_handler::get_subquery_values(subquery, context, Some(2))?;
+            match subquery_values.len() {
+                0 => Ok(CellValue::Null),
+                1 => Ok(subquery_values.remove(0)),
+                _ => Err(Error::SubqueryReturnedMultipleRows),
+            }
+        },
+        Expr::Like { negated, expr, pattern, escape_char } => evaluate_like(expr, pattern, escape_char.as_deref(), false, *negated, context),
+        Expr::ILike { negated, expr, pattern, escape_char } => evaluate_like(expr, pattern, escape_char.as_deref(), true, *negated, context),
+        Expr::Case { operand, conditions, results, else_result } => {
+            let operand_value = match operand {
+                Some(operand) => Some(evaluate_expression(operand, context)?),
+                None => None,
+            };
+
+            for (condition, result) in conditions.iter().zip(results) {
+                let condition_value = evaluate_expression(condition, context)?;
+                let is_match = match &operand_value {
+                    Some(operand_value) => operation_handler::compare_values(operand_value, &condition_value)? == Some(Ordering::Equal),
+                    None => get_boolean(&condition_value)? == Some(true),
+                };
+
+                if is_match {
+                    return evaluate_expression(result, context);
+                }
+            }
+
+            match else_result {
+                Some(else_result) => evaluate_expression(else_result, context),
+                None => Ok(CellValue::Null),
+            }
+        },
+        Expr::Cast { kind, expr: cast_expr, data_type, .. } => {
+            let value = evaluate_expression(cast_expr, context)?;
+            let data_type = type_resolver::get_cast_type(cast_expr, data_type)?;
+
+            match kind {
+                CastKind::TryCast | CastKind::SafeCast => Ok(operation_handler::cast_value(&value, &data_type).unwrap_or(CellValue::Null)),
+                _ => operation_handler::cast_value(&value, &data_type),
+            }
+        },
+        Expr::Function(function) => {
+            if let Some((_, value)) = context.aggregate_values.iter().find(|(aggregate_expr, _)| aggregate_expr == expr) {
+                return Ok(value.clone());
+            }
+
+            match aggregate_functions::is_aggregate(expr) {
+                true => Err(Error::AggregateNotAllowed { function_name: function.name.to_string() }),
+                false => Err(Error::UnsupportedExpression { expression: expr.to_string() }),
+            }
+        },
+        _ => Err(Error::UnsupportedExpression { expression: expr.to_string() }),
+    }
+}
+
+// True on the first match; unknown if nothing matched but a NULL was involved
+fn is_in_values(value: &CellValue, values: impl Iterator<Item = Result<CellValue, Error>>) -> Result<Option<bool>, Error> {
+    let mut result = Some(false);
+    for other_value in values {
+        match operation_handler::compare_values(value, &other_value?)? {
+            Some(Ordering::Equal) => return Ok(Some(true)),
+            Some(_) => {},
+            None => result = None,
+        }
+    }
+
+    Ok(result)
+}
+
+// Conditions pass only if true, not false or unknown (NULL)
+pub fn evaluate_condition(expr: &Expr, context: &RowContext) -> Result<bool, Error> {
+    let value = evaluate_expression(expr, context)?;
+
+    Ok(get_boolean(&value)? == Some(true))
+}
+
+// Checks fail only if false, passing when unknown (NULL)
+pub fn evaluate_check(expr: &Expr, context: &RowContext) -> Result<bool, Error> {
+    let value = evaluate_expression(expr, context)?;
+
+    Ok(get_boolean(&value)? != Some(false))
+}
+
+fn evaluate_binary_operation(left: &Expr, op: &BinaryOperator, right: &Expr, context: &RowContext) -> Result<CellValue, Error> {
+    match op {
+        // Three-valued logic, skipping the right side when the left one decides
+        BinaryOperator::And => {
+            let left_result = get_boolean(&evaluate_expression(left, context)?)?;
+            if left_result == Some(false) {
+                return Ok(CellValue::Boolean(false));
+            }
+            let right_result = get_boolean(&evaluate_expression(right, context)?)?;
+            Ok(from_boolean(and(left_result, right_result)))
+        },
+        BinaryOperator::Or => {
+            let left_result = get_boolean(&evaluate_expression(left, context)?)?;
+            if left_result == Some(true) {
+                return Ok(CellValue::Boolean(true));
+            }
+            let right_result = get_boolean(&evaluate_expression(right, context)?)?;
+            Ok(from_boolean(match (left_result, right_result) {
+                (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            }))
+        },
+        _ => {
+            let left_value = evaluate_expression(left, context)?;
+            let right_value = evaluate_expression(right, context)?;
+
+            match op {
+                BinaryOperator::Eq | BinaryOperator::NotEq |
+                BinaryOperator::Lt | BinaryOperator::LtEq |
+                BinaryOperator::Gt | BinaryOperator::GtEq => {
+                    let ordering = operation_handler::compare_values(&left_value, &right_value)?;
+                    Ok(from_boolean(ordering.map(|ordering| matches_operator(ordering, op))))
+                },
+                BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Multiply |
+                BinaryOperator::Divide | BinaryOperator::Modulo => operation_handler::apply_arithmetic(&left_value, op, &right_value),
+                BinaryOperator::StringConcat => Ok(operation_handler::concatenate(&left_value, &right_value)),
+                _ => Err(Error::UnsupportedOperationType { operation: op.to_string() }),
+            }
+        },
+    }
+}
+
+fn evaluate_like(expr: &Expr, pattern: &Expr, escape_char: Option<&str>, case_insensitive: bool, negated: bool, context: &RowContext) -> Result<CellValue, Error> {
+    let value = evaluate_expression(expr, context)?;
+    let pattern = evaluate_expression(pattern, context)?;
+    if value == CellValue::Null || pattern == CellValue::Null {
+        return Ok(CellValue::Null);
+    }
+
+    // Postgres uses backslash unless told otherwise
+    let escape_char = match escape_char {
+        Some(escape_char) => escape_char.chars().next(),
+        None => Some('\\'),
+    };
+    let is_match = operation_handler::matches_like_pattern(&value.to_string(), &pattern.to_string(), escape_char, case_insensitive);
+
+    Ok(CellValue::Boolean(is_match != negated))
+}
+
+// NULLs are equal to each other here
+fn is_not_distinct(left: &Expr, right: &Expr, context: &RowContext) -> Result<bool, Error> {
+    let left_value = evaluate_expression(left, context)?;
+    let right_value = evaluate_expression(right, context)?;
+
+    match (&left_value, &right_value) {
+        (CellValue::Null, CellValue::Null) => Ok(true),
+        (CellValue::Null, _) | (_, CellValue::Null) => Ok(false),
+        _ => Ok(operation_handler::compare_values(&left_value, &right_value)? == Some(Ordering::Equal)),
+    }
+}
+
+fn get_literal_value(value: &Value) -> Result<CellValue, Error> {
+    let literal = match value {
+        Value::Number(n, _) => match n.parse::<i64>() {
+            Ok(i) => CellValue::Integer(i),
+            Err(_) => CellValue::Float(n.parse::<f64>().map_err(|_| Error::UnsupportedValueType { value: n.clone() })?),
+        },
+        Value::SingleQuotedString(s) | Value::EscapedStringLiteral(s) => CellValue::Text(s.clone()),
+        Value::Boolean(b) => CellValue::Boolean(*b),
+        Value::Null => CellValue::Null,
+        _ => return Err(Error::UnsupportedValueType { value: value.to_string() }),
+    };
+
+    Ok(literal)
+}
+
+fn matches_operator(ordering: Ordering, op: &BinaryOperator) -> bool {
+    match op {
+        BinaryOperator::Eq => ordering == Ordering::Equal,
+        BinaryOperator::NotEq => ordering != Ordering::Equal,
+        BinaryOperator::Lt => ordering == Ordering::Less,
+        BinaryOperator::LtEq => ordering != Ordering::Greater,
+        BinaryOperator::Gt => ordering == Ordering::Greater,
+        _ => ordering != Ordering::Less,
+    }
+}
+
+fn and(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    }
+}
+
+fn negate_if(result: Option<bool>, negated: bool) -> Option<bool> {
+    result.map(|result| result != negated)
+}
@@ -0,0 +1,87 @@
+use sqlparser::ast::{Expr, FunctionArg, FunctionArgExpr, FunctionArguments};
+
+// Direct operands of the expressions the evaluator supports, not looking inside subqueries
+pub fn get_child_expressions(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Nested(expr) | Expr::UnaryOp { expr, .. } | Expr::Cast { expr, .. } | Expr::InSubquery { expr, .. } |
+        Expr::IsNull(expr) | Expr::IsNotNull(expr) |
+        Expr::IsTrue(expr) | Expr::IsNotTrue(expr) | Expr::IsFalse(expr) | Expr::IsNotFalse(expr) => vec![expr],
+        Expr::BinaryOp { left, right, .. } | Expr::IsDistinctFrom(left, right) | Expr::IsNotDistinctFrom(left, right) => vec![left, right],
+        Expr::Between { expr, low, high, .. } => vec![expr, low, high],
+        Expr::InList { expr, list, .. } => std::iter::once(expr.as_ref()).chain(list).collect(),
+        Expr::Like { expr, pattern, .. } | Expr::ILike { expr, pattern, .. } => vec![expr, pattern],
+        Expr::Case { operand, conditions, results, else_result } => operand.as_deref().into_iter()
+            .chain(conditions)
+            .chain(results)
+            .chain(else_result.as_deref())
+            .collect(),
+        Expr::Function(function) => {
+            let mut children = get_function_arguments(&function.args);
+            children.extend(function.filter.as_deref());
+            children
+        },
+        _ => Vec::new(),
+    }
+}
+
+pub fn get_child_expressions_mut(expr: &mut Expr) -> Vec<&mut Expr> {
+    match expr {
+        Expr::Nested(expr) | Expr::UnaryOp { expr, .. } | Expr::Cast { expr, .. } | Expr::InSubquery { expr, .. } |
+        Expr::IsNull(expr) | Expr::IsNotNull(expr) |
+        Expr::IsTrue(expr) | Expr::IsNotTrue(expr) | Expr::IsFalse(expr) | Expr::IsNotFalse(expr) => vec![expr],
+        Expr::BinaryOp { left, right, .. } | Expr::IsDistinctFrom(left, right) | Expr::IsNotDistinctFrom(left, right) => vec![left, right],
+        Expr::Between { expr, low, high, .. } => vec![expr, low, high],
+        Expr::InList { expr, list, .. } => std::iter::once(expr.as_mut()).chain(list).collect(),
+        Expr::Like { expr, pattern, .. } | Expr::ILike { expr, pattern, .. } => vec![expr, pattern],
+        Expr::Case { operand, conditions, results, else_result } => operand.as_deref_mut().into_iter()
+            .chain(conditions)
+            .chain(results)
+            .chain(else_result.as_deref_mut())
+            .collect(),
+        Expr::Function(function) => {
+            let mut children = match &mut function.args {
+                FunctionArguments::List(argument_list) => argument_list.args.iter_mut()
+                    .filter_map(|arg| match arg {
+                        FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) | FunctionArg::Named { arg: FunctionArgExpr::Expr(expr), .. } => Some(expr),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            children.extend(function.filter.as_deref_mut());
+            children
+        },
+        _ => Vec::new(),
+    }
+}
+
+pub fn get_function_arguments(args: &FunctionArguments) -> Vec<&Expr> {
+    match args {
+        FunctionArguments::List(argument_list) => argument_list.args.iter()
+            .filter_map(|arg| match arg {
+                FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) | FunctionArg::Named { arg: FunctionArgExpr::Expr(expr), .. } => Some(expr),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Expressions matching the predicate, without looking inside the matches
+pub fn find_expressions<'a>(expr: &'a Expr, predicate: &dyn Fn(&Expr) -> bool) -> Vec<&'a Expr> {
+    if predicate(expr) {
+        return vec![expr];
+    }
+
+    get_child_expressions(expr).into_iter()
+        .flat_map(|child| find_expressions(child, predicate))
+        .collect()
+}
+
+pub fn is_subquery(expr: &Expr) -> bool {
+    matches!(expr, Expr::InSubquery { .. } | Expr::Exists { .. } | Expr::Subquery(_))
+}
+
+pub fn contains_subquery(expr: &Expr) -> bool {
+    !find_expressions(expr, &is_subquery).is_empty()
+}
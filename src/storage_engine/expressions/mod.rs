@@ -0,0 +1,6 @@
+pub mod aggregate_functions;
+pub mod expression_evaluator;
+pub mod expression_walker;
+pub mod operation_handler;
+pub mod type_resolver;
+pub mod types;
\ No newline at end of file
@@ -0,0 +1,263 @@
+use std::cmp::Ordering;
+
+use sqlparser::ast::BinaryOperator;
+
+use crate::database::constants::{NULL_VALUE, TEXT_ESCAPE};
+use crate::database::types::DataType;
+use crate::network_protocol::types::CellValue;
+use crate::shared::errors::Error;
+
+// None if either value is NULL; text is coerced to the other side's type, so '10' > 9 compares numerically
+pub fn compare_values(left: &CellValue, right: &CellValue) -> Result<Option<Ordering>, Error> {
+    let ordering = match (left, right) {
+        (CellValue::Null, _) | (_, CellValue::Null) => return Ok(None),
+        (CellValue::Integer(a), CellValue::Integer(b)) => a.cmp(b),
+        (CellValue::Integer(_) | CellValue::Float(_), CellValue::Integer(_) | CellValue::Float(_)) => get_float(left).total_cmp(&get_float(right)),
+        (CellValue::Text(a), CellValue::Text(b)) => a.cmp(b),
+        (CellValue::Boolean(a), CellValue::Boolean(b)) => a.cmp(b),
+        (CellValue::Text(text), other) => {
+            let coerced = coerce_text(text, other, left, right)?;
+            return compare_values(&coerced, other);
+        },
+        (other, CellValue::Text(text)) => {
+            let coerced = coerce_text(text, other, left, right)?;
+            return compare_values(other, &coerced);
+        },
+        _ => return Err(Error::InvalidOperandTypes { operation: format!("{} and {}", left, right) }),
+    };
+
+    Ok(Some(ordering))
+}
+
+pub fn apply_arithmetic(left: &CellValue, op: &BinaryOperator, right: &CellValue) -> Result<CellValue, Error> {
+    let operation_error = || Error::InvalidOperandTypes { operation: format!("{} {} {}", left, op, right) };
+
+    let (left, right) = match (left, right) {
+        (CellValue::Null, _) | (_, CellValue::Null) => return Ok(CellValue::Null),
+        (CellValue::Text(_), CellValue::Text(_)) => return Err(operation_error()),
+        (CellValue::Text(text), other) => (parse_number(text).ok_or_else(operation_error)?, other.clone()),
+        (other, CellValue::Text(text)) => (other.clone(), parse_number(text).ok_or_else(operation_error)?),
+        _ => (left.clone(), right.clone()),
+    };
+
+    match (&left, &right) {
+        (CellValue::Integer(a), CellValue::Integer(b)) => {
+            let result = match op {
+                BinaryOperator::Plus => a.checked_add(*b),
+                BinaryOperator::Minus => a.checked_sub(*b),
+                BinaryOperator::Multiply => a.checked_mul(*b),
+                BinaryOperator::Divide | BinaryOperator::Modulo if *b == 0 => return Err(Error::DivisionByZero),
+                BinaryOperator::Divide => a.checked_div(*b),
+                BinaryOperator::Modulo => a.checked_rem(*b),
+                _ => return Err(Error::UnsupportedOperationType { operation: op.to_string() }),
+            };
+
+            result.map(CellValue::Integer).ok_or(Error::NumericValueOutOfRange)
+        },
+        (CellValue::Integer(_) | CellValue::Float(_), CellValue::Integer(_) | CellValue::Float(_)) => {
+            let (a, b) = (get_float(&left), get_float(&right));
+            let result = match op {
+                BinaryOperator::Plus => a + b,
+                BinaryOperator::Minus => a - b,
+                BinaryOperator::Multiply => a * b,
+                BinaryOperator::Divide | BinaryOperator::Modulo if b == 0.0 => return Err(Error::DivisionByZero),
+                BinaryOperator::Divide => a / b,
+                BinaryOperator::Modulo => a % b,
+                _ => return Err(Error::UnsupportedOperationType { operation: op.to_string() }),
+            };
+
+            Ok(CellValue::Float(result))
+        },
+        _ => Err(operation_error()),
+    }
+}
+
+pub fn concatenate(left: &CellValue, right: &CellValue) -> CellValue {
+    match (left, right) {
+        (CellValue::Null, _) | (_, CellValue::Null) => CellValue::Null,
+        _ => CellValue::Text(format!("{}{}", left, right)),
+    }
+}
+
+pub fn negate(value: &CellValue) -> Result<CellValue, Error> {
+    match value {
+        CellValue::Null => Ok(CellValue::Null),
+        CellValue::Integer(i) => i.checked_neg().map(CellValue::Integer).ok_or(Error::NumericValueOutOfRange),
+        CellValue::Float(f) => Ok(CellValue::Float(-f)),
+        CellValue::Text(text) => negate(&parse_number(text).ok_or_else(|| Error::InvalidOperandTypes { operation: format!("-'{}'", text) })?),
+        CellValue::Boolean(_) => Err(Error::InvalidOperandTypes { operation: format!("-{}", value) }),
+    }
+}
+
+// Three-valued logic: None stands for unknown (NULL)
+pub fn get_boolean(value: &CellValue) -> Result<Option<bool>, Error> {
+    match cast_value(value, &DataType::Boolean) {
+        Ok(CellValue::Boolean(b)) => Ok(Some(b)),
+        Ok(_) => Ok(None),
+        Err(_) => Err(Error::InvalidOperandTypes { operation: format!("{} used as a condition", value) }),
+    }
+}
+
+pub fn from_boolean(value: Option<bool>) -> CellValue {
+    value.map(CellValue::Boolean).unwrap_or(CellValue::Null)
+}
+
+pub fn cast_value(value: &CellValue, data_type: &DataType) -> Result<CellValue, Error> {
+    let cast_error = || Error::InvalidCast { value: value.to_string(), data_type: format!("{:?}", data_type) };
+
+    let cast = match (value, data_type) {
+        (CellValue::Null, _) => CellValue::Null,
+        (CellValue::Integer(i), DataType::Integer) => CellValue::Integer(*i),
+        (CellValue::Float(f), DataType::Integer) => {
+            let rounded = f.round();
+            if !rounded.is_finite() || rounded < i64::MIN as f64 || rounded > i64::MAX as f64 {
+                return Err(Error::NumericValueOutOfRange);
+            }
+            CellValue::Integer(rounded as i64)
+        },
+        (CellValue::Boolean(b), DataType::Integer) => CellValue::Integer(*b as i64),
+        (CellValue::Text(text), DataType::Integer) => CellValue::Integer(text.trim().parse::<i64>().map_err(|_| cast_error())?),
+        (CellValue::Integer(i), DataType::Float) => CellValue::Float(*i as f64),
+        (CellValue::Float(f), DataType::Float) => CellValue::Float(*f),
+        (CellValue::Text(text), DataType::Float) => CellValue::Float(text.trim().parse::<f64>().map_err(|_| cast_error())?),
+        (CellValue::Boolean(_), DataType::Float) => return Err(cast_error()),
+        (CellValue::Boolean(b), DataType::Boolean) => CellValue::Boolean(*b),
+        (CellValue::Integer(i), DataType::Boolean) => CellValue::Boolean(*i != 0),
+        (CellValue::Text(text), DataType::Boolean) => match text.trim().to_lowercase().as_str() {
+            "true" | "t" | "yes" | "y" | "on" | "1" => CellValue::Boolean(true),
+            "false" | "f" | "no" | "n" | "off" | "0" => CellValue::Boolean(false),
+            _ => return Err(cast_error()),
+        },
+        (CellValue::Float(_), DataType::Boolean) => return Err(cast_error()),
+        (_, DataType::Text) => CellValue::Text(value.to_string()),
+    };
+
+    Ok(cast)
+}
+
+// Value as written to table storage
+pub fn get_storage_value(value: &CellValue) -> String {
+    match value {
+        CellValue::Null => String::from(NULL_VALUE),
+        CellValue::Text(text) => escape_storage_text(text),
+        _ => value.to_string(),
+    }
+}
+
+// Text as written to table storage, so that text reading 'Null' isn't mistaken for NULL
+pub fn escape_storage_text(text: &str) -> String {
+    match text == NULL_VALUE || text.starts_with(TEXT_ESCAPE) {
+        true => format!("{}{}", TEXT_ESCAPE, text),
+        false => text.to_string(),
+    }
+}
+
+pub fn unescape_storage_text(value: &str) -> &str {
+    value.strip_prefix(TEXT_ESCAPE).unwrap_or(value)
+}
+
+pub fn get_value_type(value: &CellValue) -> Option<DataType> {
+    match value {
+        CellValue::Null => None,
+        CellValue::Integer(_) => Some(DataType::Integer),
+        CellValue::Float(_) => Some(DataType::Float),
+        CellValue::Text(_) => Some(DataType::Text),
+        CellValue::Boolean(_) => Some(DataType::Boolean),
+    }
+}
+
+fn get_float(value: &CellValue) -> f64 {
+    match value {
+        CellValue::Integer(i) => *i as f64,
+        CellValue::Float(f) => *f,
+        _ => f64::NAN,
+    }
+}
+
+fn parse_number(text: &str) -> Option<CellValue> {
+    let text = text.trim();
+
+    text.parse::<i64>().ok().map(CellValue::Integer)
+        .or_else(|| text.parse::<f64>().ok().map(CellValue::Float))
+}
+
+fn coerce_text(text: &str, other: &CellValue, left: &CellValue, right: &CellValue) -> Result<CellValue, Error> {
+    let data_type = get_value_type(other).unwrap_or(DataType::Text);
+    let coerced = match data_type {
+        DataType::Integer | DataType::Float => parse_number(text),
+        _ => cast_value(&CellValue::Text(text.to_string()), &data_type).ok(),
+    };
+
+    coerced.ok_or_else(|| Error::InvalidOperandTypes { operation: format!("{} and {}", left, right) })
+}
+
+/*
+ * LIKE patterns: % matches any sequence, _ any single character
+ */
+enum LikeToken {
+    AnySequence,
+    AnyChar,
+    Char(char),
+}
+
+pub fn matches_like_pattern(value: &str, pattern: &str, escape_char: Option<char>, case_insensitive: bool) -> bool {
+    let (value, pattern) = match case_insensitive {
+        true => (value.to_lowercase(), pattern.to_lowercase()),
+        false => (value.to_string(), pattern.to_string()),
+    };
+    let value: Vec<char> = value.chars().collect();
+    let pattern = parse_like_pattern(&pattern, escape_char);
+
+    // Backtrack to the last % on a mismatch
+    let (mut value_index, mut pattern_index) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while value_index < value.len() {
+        match pattern.get(pattern_index) {
+            Some(LikeToken::AnySequence) => {
+                backtrack = Some((pattern_index, value_index));
+                pattern_index += 1;
+                continue;
+            },
+            Some(LikeToken::AnyChar) => {
+                value_index += 1;
+                pattern_index += 1;
+                continue;
+            },
+            Some(LikeToken::Char(c)) if *c == value[value_index] => {
+                value_index += 1;
+                pattern_index += 1;
+                continue;
+            },
+            _ => {},
+        }
+
+        match backtrack {
+            Some((sequence_index, sequence_start)) => {
+                backtrack = Some((sequence_index, sequence_start + 1));
+                pattern_index = sequence_index + 1;
+                value_index = sequence_start + 1;
+            },
+            None => return false,
+        }
+    }
+
+    pattern[pattern_index..].iter().all(|token| matches!(token, LikeToken::AnySequence))
+}
+
+fn parse_like_pattern(pattern: &str, escape_char: Option<char>) -> Vec<LikeToken> {
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if Some(c) == escape_char => LikeToken::Char(chars.next().unwrap_or(c)),
+            '%' => LikeToken::AnySequence,
+            '_' => LikeToken::AnyChar,
+            c => LikeToken::Char(c),
+        };
+        tokens.push(token);
+    }
+
+    tokens
+}
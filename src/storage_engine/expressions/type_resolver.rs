@@ -0,0 +1,119 @@
+use sqlparser::ast::{BinaryOperator, Expr, UnaryOperator, Value};
+
+use crate::database::types::DataType;
+use crate::database::utils::get_column_custom_data_type;
+use crate::shared::errors::Error;
+use crate::storage_engine::select::subquery_handler;
+
+use super::{aggregate_functions, types::RowContext};
+
+// Type of an expression's result, checking the columns it references exist
+pub fn get_expression_type(expr: &Expr, context: &RowContext) -> Result<DataType, Error> {
+    let data_type = match expr {
+        Expr::Identifier(ident) => context.find_column(std::slice::from_ref(ident))?.1.data_type.clone(),
+        Expr::CompoundIdentifier(idents) => context.find_column(idents)?.1.data_type.clone(),
+        Expr::Value(value) => get_literal_type(value),
+        Expr::Nested(nested_expr) => get_expression_type(nested_expr, context)?,
+        Expr::UnaryOp { op: UnaryOperator::Not, expr } => {
+            get_expression_type(expr, context)?;
+            DataType::Boolean
+        },
+        Expr::UnaryOp { expr, .. } => get_expression_type(expr, context)?,
+        Expr::BinaryOp { left, op, right } => {
+            let left_type = get_expression_type(left, context)?;
+            let right_type = get_expression_type(right, context)?;
+
+            match op {
+                BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Multiply |
+                BinaryOperator::Divide | BinaryOperator::Modulo => get_arithmetic_type(&left_type, &right_type),
+                BinaryOperator::StringConcat => DataType::Text,
+                _ => DataType::Boolean,
+            }
+        },
+        Expr::IsNull(expr) | Expr::IsNotNull(expr) |
+        Expr::IsTrue(expr) | Expr::IsNotTrue(expr) | Expr::IsFalse(expr) | Expr::IsNotFalse(expr) => {
+            get_expression_type(expr, context)?;
+            DataType::Boolean
+        },
+        Expr::IsDistinctFrom(left, right) | Expr::IsNotDistinctFrom(left, right) => {
+            get_expression_type(left, context)?;
+            get_expression_type(right, context)?;
+            DataType::Boolean
+        },
+        Expr::Between { expr, low, high, .. } => {
+            for operand in [expr, low, high] {
+                get_expression_type(operand, context)?;
+            }
+            DataType::Boolean
+        },
+        Expr::InList { expr, list, .. } => {
+            get_expression_type(expr, context)?;
+            for list_expr in list {
+                get_expression_type(list_expr, context)?;
+            }
+            DataType::Boolean
+        },
+        Expr::InSubquery { expr, subquery, .. } => {
+            get_expression_type(expr, context)?;
+            subquery_handler::get_subquery_column(subquery, context)?;
+            DataType::Boolean
+        },
+        Expr::Exists { subquery, .. } => {
+            subquery_handler::get_subquery_columns(subquery, context)?;
+            DataType::Boolean
+        },
+        Expr::Subquery(subquery) => subquery_handler::get_subquery_column(subquery, context)?.data_type,
+        Expr::Like { expr, pattern, .. } | Expr::ILike { expr, pattern, .. } => {
+            get_expression_type(expr, context)?;
+            get_expression_type(pattern, context)?;
+            DataType::Boolean
+        },
+        Expr::Case { operand, conditions, results, else_result } => {
+            if let Some(operand) = operand {
+                get_expression_type(operand, context)?;
+            }
+            for condition in conditions {
+                get_expression_type(condition, context)?;
+            }
+
+            // Type of the first branch that isn't a bare NULL
+            let mut result_type: Option<DataType> = None;
+            for result in results.iter().chain(else_result.as_deref()) {
+                let branch_type = get_expression_type(result, context)?;
+                if result_type.is_none() && !matches!(result, Expr::Value(Value::Null)) {
+                    result_type = Some(branch_type);
+                }
+            }
+            result_type.unwrap_or(DataType::Text)
+        },
+        Expr::Cast { expr, data_type, .. } => {
+            get_expression_type(expr, context)?;
+            get_cast_type(expr, data_type)?
+        },
+        Expr::Function(function) if aggregate_functions::is_aggregate(expr) => aggregate_functions::get_aggregate_type(function, context)?,
+        _ => return Err(Error::UnsupportedExpression { expression: expr.to_string() }),
+    };
+
+    Ok(data_type)
+}
+
+pub fn get_cast_type(expr: &Expr, data_type: &sqlparser::ast::DataType) -> Result<DataType, Error> {
+    get_column_custom_data_type(data_type, &expr.to_string())
+}
+
+pub fn get_literal_type(value: &Value) -> DataType {
+    match value {
+        Value::Number(n, _) if n.parse::<i64>().is_ok() => DataType::Integer,
+        Value::Number(_, _) => DataType::Float,
+        Value::Boolean(_) => DataType::Boolean,
+        _ => DataType::Text,
+    }
+}
+
+// Integer only when both sides are; text operands are coerced to numbers
+fn get_arithmetic_type(left_type: &DataType, right_type: &DataType) -> DataType {
+    match (left_type, right_type) {
+        (DataType::Float, _) | (_, DataType::Float) => DataType::Float,
+        _ => DataType::Integer,
+    }
+}
@@ -0,0 +1,91 @@
+use csv::StringRecord;
+use sqlparser::ast::{Expr, Ident};
+
+use crate::database::constants::NULL_VALUE;
+use crate::database::types::{Column, TableSchema};
+use crate::network_protocol::types::CellValue;
+use crate::shared::errors::Error;
+use crate::storage_engine::select::record_handler::get_cell_value;
+use crate::storage_engine::select::types::{JoinedRow, TableSource};
+
+// Tables an expression can reference, with the row currently being evaluated
+pub struct RowContext<'a> {
+    pub tables: Vec<TableRow<'a>>,
+    pub aggregate_values: Vec<(Expr, CellValue)>, // Computed over the group the row stands for
+}
+
+pub struct TableRow<'a> {
+    pub name: &'a str, // Name qualified column references use
+    pub table_schema: &'a TableSchema,
+    pub record: Option<&'a StringRecord>, // None when only resolving column types
+}
+
+impl<'a> RowContext<'a> {
+    // For constant expressions, such as inserted values and defaults
+    pub fn empty() -> Self {
+        RowContext { tables: Vec::new(), aggregate_values: Vec::new() }
+    }
+
+    pub fn from_table(table_schema: &'a TableSchema, record: Option<&'a StringRecord>) -> Self {
+        RowContext { tables: vec![TableRow { name: &table_schema.name, table_schema, record }], aggregate_values: Vec::new() }
+    }
+
+    // One table per FROM source, all NULL when no row is given
+    pub fn from_sources(sources: &'a [TableSource], row: Option<&'a JoinedRow>) -> Self {
+        let tables = sources.iter().enumerate()
+            .map(|(index, source)| TableRow {
+                name: &source.name,
+                table_schema: source.table_schema,
+                record: row.and_then(|row| row.get(index)).and_then(|record| record.as_ref()),
+            })
+            .collect();
+
+        RowContext { tables, aggregate_values: Vec::new() }
+    }
+
+    // A single FROM source, for filters only involving it
+    pub fn from_source(source: &'a TableSource, record: Option<&'a StringRecord>) -> Self {
+        RowContext { tables: vec![TableRow { name: &source.name, table_schema: source.table_schema, record }], aggregate_values: Vec::new() }
+    }
+
+    // Plain names are looked up in every table, qualified ones (table.column, schema.table.column) in the named table
+    pub fn find_column(&self, idents: &[Ident]) -> Result<(&TableRow<'a>, &'a Column), Error> {
+        let (column_name, table_name) = match idents {
+            [column_name] => (column_name, None),
+            [.., table_name, column_name] => (column_name, Some(&table_name.value)),
+            [] => return Err(Error::UnsupportedExpression { expression: String::new() }),
+        };
+
+        let mut matches = self.tables.iter()
+            .filter(|table_row| table_name.is_none_or(|table_name| table_row.name == table_name))
+            .filter_map(|table_row| {
+                table_row.table_schema.columns.iter()
+                    .find(|column| column.name == column_name.value)
+                    .map(|column| (table_row, column))
+            });
+
+        let found = matches.next().ok_or_else(|| Error::ColumnDoesNotExist {
+            column_name: column_name.value.clone(),
+            table_name: table_name.cloned().or_else(|| self.tables.first().map(|table_row| table_row.name.to_string())).unwrap_or_default(),
+        })?;
+
+        // Plain names must not be found in two joined tables
+        if matches.next().is_some() {
+            return Err(Error::AmbiguousColumnName { column_name: column_name.value.clone() });
+        }
+
+        Ok(found)
+    }
+}
+
+impl TableRow<'_> {
+    pub fn get_value(&self, column: &Column) -> CellValue {
+        // Records hold the columns sorted by their order
+        let position = self.table_schema.columns.iter().filter(|other| other.order < column.order).count();
+
+        match self.record {
+            Some(record) => get_cell_value(record.get(position).map(|value| value.trim()).unwrap_or(NULL_VALUE), &column.data_type),
+            None => CellValue::Null,
+        }
+    }
+}
@@ -1,4 +1 @@
-pub mod filter_column_finder;
-pub mod filter_manager;
-pub mod operation_handler;
-mod types;
\ No newline at end of file
+pub mod filter_manager;
\ No newline at end of file
@@ -1,102 +1,142 @@
-use sqlparser::ast::{Ident, ObjectName, ReferentialAction};
+use sqlparser::ast::{Ident, ObjectName, TableConstraint};
 
-use crate::{database::{database_loader, database_navigator, types::{Database, ForeignKey, TableSchema}, utils::find_database_table}, shared::{errors::Error, file_manager}, storage_engine::utils::ast_unwrapper::get_referential_action};
+use crate::{database::{database_loader, database_navigator, types::{Constraint, Database, ForeignKey, Index, TableSchema}, utils::{find_database_table, split_table_name}}, shared::{errors::Error, file_manager}, storage_engine::{index::index_manager::{self, get_table_indexes}, utils::ast_unwrapper::get_referential_action, validation}};
 
-pub async fn handle_add_foreign_key(
-    table_name: &String,
-    name: Option<Ident>,
-    columns: Vec<Ident>,
-    foreign_table: ObjectName,
-    referred_columns: Vec<Ident>,
-    on_delete: Option<ReferentialAction>,
-    on_update: Option<ReferentialAction>,
-) -> Result<String, Error> {
-    validate_add_foreign_key(table_name, &name, &columns, &foreign_table)?;
+pub async fn handle_add_foreign_key(schema_name: &String, table_name: &String, table_constraint: &TableConstraint) -> Result<String, Error> {
+    let (name, columns, foreign_table, referred_columns, on_delete, on_update) = match table_constraint {
+        TableConstraint::ForeignKey { name, columns, foreign_table, referred_columns, on_delete, on_update, .. } => (name, columns, foreign_table, referred_columns, on_delete, on_update),
+        _ => return Err(Error::UnsupportedConstraint { column_name: table_name.clone(), column_constraint: table_constraint.to_string() }),
+    };
 
-    create_foreign_key(table_name, name, columns, foreign_table, referred_columns, on_delete, on_update).await?;
+    validate_add_foreign_key(schema_name, table_name, name, columns, foreign_table)?;
 
-    Ok(String::from("Foreign key added successfully"))
-}
-
-async fn create_foreign_key(
-    table_name: &String,
-    name: Option<Ident>,
-    columns: Vec<Ident>,
-    foreign_table: ObjectName,
-    referred_columns: Vec<Ident>,
-    on_delete: Option<ReferentialAction>,
-    on_update: Option<ReferentialAction>,
-) -> Result<(), Error> {
     let database = database_loader::get_database()?;
-    let table_schema = find_database_table(&database, table_name).unwrap();
+    let table_schema = find_database_table(&database, schema_name, table_name)
+        .ok_or_else(|| Error::TableDoesNotExist { table_name: table_name.clone() })?;
 
-    let foreign_table_name = foreign_table.0.first().ok_or(Error::MissingTableName)?;
+    // Unqualified references resolve against the local table's schema
+    let (foreign_schema_name, foreign_table_name) = split_table_name(foreign_table, schema_name)?;
+    let foreign_table_schema = find_database_table(&database, &foreign_schema_name, &foreign_table_name)
+        .ok_or_else(|| Error::TableDoesNotExist { table_name: foreign_table_name.clone() })?;
 
-    let foreign_table_schema = database.schemas.iter()
-        .find(|schema| schema.name == database.configuration.default_schema)
-        .ok_or_else(|| Error::SchemaDoesNotExist { schema_name: database.configuration.default_schema.clone() })?
-        .tables.iter()
-        .find(|table| table.name == foreign_table_name.value)
-        .ok_or_else(|| Error::TableDoesNotExist { table_name: foreign_table_name.value.clone() })?;
+    let foreign_key_name = name.as_ref().map(|ident| ident.value.clone()).unwrap_or_else(|| format!("fk_{}_{}_{}", table_name, foreign_table_name, table_schema.foreign_keys.len() + 1));
+    let local_columns: Vec<String> = columns.iter().map(|ident| ident.value.clone()).collect();
+    let foreign_columns = get_referenced_columns(foreign_table_schema, referred_columns)?;
+    validate_referenced_key(&foreign_key_name, foreign_table_schema, &local_columns, &foreign_columns)?;
 
     let foreign_key = ForeignKey {
-        name: name.map(|ident| ident.value.clone()).unwrap_or_else(|| format!("fk_{}_{}_{}", table_name, foreign_table_name.value, table_schema.foreign_keys.len() + 1)),
+        name: foreign_key_name,
         local_table: table_name.clone(),
-        local_columns: columns.iter().map(|ident| ident.value.clone()).collect(),
-        foreign_table: foreign_table_schema.name.clone(),
-        foreign_columns: referred_columns.iter().map(|ident| ident.value.clone()).collect(),
-        on_delete: get_referential_action(&on_delete)?,
-        on_update: get_referential_action(&on_update)?,
+        local_columns,
+        foreign_table: foreign_table_name,
+        foreign_schema: if &foreign_schema_name == schema_name { None } else { Some(foreign_schema_name) },
+        foreign_columns,
+        on_delete: get_referential_action(on_delete)?,
+        on_update: get_referential_action(on_update)?,
     };
 
+    create_foreign_key(schema_name, table_schema, foreign_key).await?;
+
+    Ok(String::from("Foreign key added successfully"))
+}
+
+// Schema of the referenced table
+pub fn get_foreign_schema_name<'a>(foreign_key: &'a ForeignKey, schema_name: &'a String) -> &'a String {
+    foreign_key.foreign_schema.as_ref().unwrap_or(schema_name)
+}
+
+// Index of the referenced table on exactly the referenced columns, in any order, to look keys up with
+pub fn find_referenced_index(table_schema: &TableSchema, columns: &[String]) -> Option<Index> {
+    get_table_indexes(table_schema).into_iter()
+        .find(|index| index.columns.len() == columns.len() && index.columns.iter().all(|column_name| columns.contains(column_name)))
+}
+
+// The named columns, or the primary key when none are named
+fn get_referenced_columns(foreign_table_schema: &TableSchema, referred_columns: &[Ident]) -> Result<Vec<String>, Error> {
+    if !referred_columns.is_empty() {
+        return Ok(referred_columns.iter().map(|ident| ident.value.clone()).collect());
+    }
+
+    let primary_key_columns: Vec<String> = foreign_table_schema.columns.iter()
+        .filter(|column| column.constraints.contains(&Constraint::PrimaryKey))
+        .map(|column| column.name.clone())
+        .collect();
+    match (primary_key_columns.is_empty(), foreign_table_schema.primary_key.is_empty()) {
+        (false, _) => Ok(primary_key_columns),
+        (true, false) => Ok(foreign_table_schema.primary_key.clone()),
+        (true, true) => Err(Error::NoPrimaryKeyPresent),
+    }
+}
+
+// Referenced columns must exist and form a unique key, with one column per local column
+fn validate_referenced_key(foreign_key_name: &str, foreign_table_schema: &TableSchema, local_columns: &[String], foreign_columns: &[String]) -> Result<(), Error> {
+    for column_name in foreign_columns {
+        validation::common::validate_column_exists(foreign_table_schema, column_name)?;
+    }
+
+    let is_unique_key = match foreign_columns {
+        [column_name] => foreign_table_schema.columns.iter().any(|column| &column.name == column_name && index_manager::index_strategy(&column.constraints)),
+        _ => false,
+    } || foreign_table_schema.indexes.iter().any(|index| {
+        index.is_unique && index.columns.len() == foreign_columns.len() && index.columns.iter().all(|column_name| foreign_columns.contains(column_name))
+    });
+
+    if local_columns.len() != foreign_columns.len() || !is_unique_key {
+        return Err(Error::InvalidForeignKey { foreign_key_name: foreign_key_name.to_string() });
+    }
+
+    Ok(())
+}
+
+async fn create_foreign_key(schema_name: &String, table_schema: &TableSchema, foreign_key: ForeignKey) -> Result<(), Error> {
     let mut updated_table_schema = table_schema.clone();
     updated_table_schema.foreign_keys.push(foreign_key);
 
     // Update table schema file
-    let schema_name = database.configuration.default_schema.clone();
-    let table_schema_file_path = database_navigator::get_table_schema_path(&schema_name, &table_name);
+    let table_schema_file_path = database_navigator::get_table_schema_path(schema_name, &table_schema.name);
     file_manager::write_json_into_file(&table_schema_file_path, &updated_table_schema)?;
 
-    database_loader::reload_table_schema(&schema_name, &table_name).await?;
+    database_loader::reload_table_schema(schema_name, &table_schema.name).await?;
 
     Ok(())
 }
 
 fn validate_add_foreign_key(
-    table_name: &String,
+    schema_name: &str,
+    table_name: &str,
     name: &Option<Ident>,
     columns: &Vec<Ident>,
     foreign_table: &ObjectName,
 ) -> Result<(), Error> {
     let database = database_loader::get_database()?;
-    let table_schema = match find_database_table(&database, &table_name) {
+    let table_schema = match find_database_table(&database, schema_name, table_name) {
         Some(schema) => schema,
-        None => return Err(Error::TableDoesNotExist { table_name: table_name.clone() }),
+        None => return Err(Error::TableDoesNotExist { table_name: table_name.to_string() }),
     };
 
-    validate_foreign_table(foreign_table, &database)?;
+    validate_foreign_table(schema_name, foreign_table, &database)?;
 
     validate_foreign_key_name(name.clone(), table_schema)?;
 
-    validate_columns(&columns, &table_schema)?;
+    validate_columns(columns, table_schema)?;
 
     Ok(())
 }
 
 fn validate_foreign_table(
+    schema_name: &str,
     foreign_table: &ObjectName,
     database: &Database,
 ) -> Result<(), Error> {
-    let schema_name = database.configuration.default_schema.clone();
-    let table_name = foreign_table.0.last().ok_or(Error::MissingTableName)?;
+    let (foreign_schema_name, table_name) = split_table_name(foreign_table, schema_name)?;
 
-    let schema = match database.schemas.iter().find(|schema| schema.name == schema_name) {
+    let schema = match database.schemas.iter().find(|schema| schema.name == foreign_schema_name) {
         Some(schema) => schema,
-        None => return Err(Error::SchemaDoesNotExist { schema_name: schema_name.clone() }),
+        None => return Err(Error::SchemaDoesNotExist { schema_name: foreign_schema_name.clone() }),
     };
 
-    if schema.tables.iter().all(|t| t.name != table_name.value) {
-        return Err(Error::TableDoesNotExist { table_name: table_name.value.clone() });
+    if schema.tables.iter().all(|t| t.name != table_name) {
+        return Err(Error::TableDoesNotExist { table_name: table_name.clone() });
     }
 
     Ok(())
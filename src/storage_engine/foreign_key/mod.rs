@@ -1 +1,2 @@
-pub mod foreign_key_manager;
\ No newline at end of file
+pub mod foreign_key_manager;
+pub mod referential_action_handler;
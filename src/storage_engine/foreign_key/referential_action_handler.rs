@@ -0,0 +1,207 @@
+use std::collections::{HashMap, VecDeque};
+
+use csv::StringRecord;
+
+use crate::database::constants::NULL_VALUE;
+use crate::database::database_loader;
+use crate::database::types::{Constraint, Database, ForeignKey, InsertedRowColumn, ReferentialAction, TableSchema};
+use crate::database::utils::{find_database_table, get_headers_from_table_schema};
+use crate::shared::errors::Error;
+use crate::storage_engine::storage_backend::table_storage::{get_table_storage, RowId};
+use crate::storage_engine::validation::column_constraints;
+use crate::transaction_manager::lock_manager;
+
+use super::foreign_key_manager::get_foreign_schema_name;
+
+/*
+ * ON DELETE and ON UPDATE actions of the foreign keys referencing changed rows.
+ * Rows the actions delete or update are handled in turn, so that cascades reach the tables referencing them
+ */
+enum RowChange {
+    Deleted { schema_name: String, table_name: String, records: Vec<StringRecord> },
+    Updated { schema_name: String, table_name: String, records: Vec<(StringRecord, StringRecord)> }, // Before and after
+}
+
+// Called once the rows have been deleted from the referenced table
+pub async fn apply_delete_actions(schema_name: &str, table_schema: &TableSchema, deleted_records: Vec<StringRecord>) -> Result<(), Error> {
+    let change = RowChange::Deleted { schema_name: schema_name.to_string(), table_name: table_schema.name.clone(), records: deleted_records };
+
+    apply_actions(change).await
+}
+
+// Called once the rows have been updated in the referenced table, with each row before and after
+pub async fn apply_update_actions(schema_name: &str, table_schema: &TableSchema, updated_records: Vec<(StringRecord, StringRecord)>) -> Result<(), Error> {
+    let change = RowChange::Updated { schema_name: schema_name.to_string(), table_name: table_schema.name.clone(), records: updated_records };
+
+    apply_actions(change).await
+}
+
+async fn apply_actions(change: RowChange) -> Result<(), Error> {
+    let mut pending_changes: VecDeque<RowChange> = VecDeque::from([change]);
+
+    while let Some(change) = pending_changes.pop_front() {
+        let (schema_name, table_name) = match &change {
+            RowChange::Deleted { schema_name, table_name, .. } | RowChange::Updated { schema_name, table_name, .. } => (schema_name, table_name),
+        };
+        let database = database_loader::get_database()?;
+        let table_schema = find_database_table(&database, schema_name, table_name)
+            .ok_or_else(|| Error::TableDoesNotExist { table_name: table_name.clone() })?;
+
+        for (local_schema_name, local_table_name, foreign_key) in find_referencing_foreign_keys(&database, schema_name, table_name) {
+            let key_changes = get_key_changes(&change, table_schema, &foreign_key.foreign_columns);
+            if key_changes.is_empty() {
+                continue;
+            }
+
+            lock_manager::lock_table_for_write(&local_schema_name, &local_table_name).await?;
+            let local_change = apply_action(&local_schema_name, &local_table_name, &foreign_key, &change, &key_changes).await?;
+            pending_changes.extend(local_change);
+        }
+    }
+
+    Ok(())
+}
+
+// Foreign keys of any table referencing the given one, with the referencing table
+fn find_referencing_foreign_keys(database: &Database, schema_name: &String, table_name: &String) -> Vec<(String, String, ForeignKey)> {
+    database.schemas.iter()
+        .flat_map(|schema| schema.tables.iter().map(move |table_schema| (schema, table_schema)))
+        .flat_map(|(schema, table_schema)| table_schema.foreign_keys.iter().map(move |foreign_key| (schema, table_schema, foreign_key)))
+        .filter(|(schema, _, foreign_key)| get_foreign_schema_name(foreign_key, &schema.name) == schema_name && &foreign_key.foreign_table == table_name)
+        .map(|(schema, table_schema, foreign_key)| (schema.name.clone(), table_schema.name.clone(), foreign_key.clone()))
+        .collect()
+}
+
+// Referenced keys that are gone, each mapped to the key replacing it (None if the row was deleted)
+fn get_key_changes(change: &RowChange, table_schema: &TableSchema, columns: &[String]) -> HashMap<Vec<String>, Option<Vec<String>>> {
+    let positions = get_column_positions(table_schema, columns);
+    let mut key_changes: HashMap<Vec<String>, Option<Vec<String>>> = HashMap::new();
+
+    match change {
+        RowChange::Deleted { records, .. } => {
+            for record in records {
+                key_changes.insert(get_key(record, &positions), None);
+            }
+        },
+        RowChange::Updated { records, .. } => {
+            for (old_record, new_record) in records {
+                let (old_key, new_key) = (get_key(old_record, &positions), get_key(new_record, &positions));
+                if old_key != new_key {
+                    key_changes.insert(old_key, Some(new_key));
+                }
+            }
+        },
+    }
+
+    // NULL keys reference nothing
+    key_changes.retain(|key, _| !key.iter().any(|value| value == NULL_VALUE));
+    key_changes
+}
+
+// Applies the foreign key's action to the rows referencing the changed keys, returning what it changed
+async fn apply_action(
+    schema_name: &String,
+    table_name: &str,
+    foreign_key: &ForeignKey,
+    change: &RowChange,
+    key_changes: &HashMap<Vec<String>, Option<Vec<String>>>,
+) -> Result<Option<RowChange>, Error> {
+    let database = database_loader::get_database()?;
+    let table_schema = find_database_table(&database, schema_name, table_name)
+        .ok_or_else(|| Error::TableDoesNotExist { table_name: table_name.to_string() })?;
+    let storage = get_table_storage(table_schema);
+
+    let positions = get_column_positions(table_schema, &foreign_key.local_columns);
+    let mut referencing_rows: Vec<(RowId, StringRecord, Option<Vec<String>>)> = Vec::new();
+    for row in storage.stream_rows(schema_name, table_schema)? {
+        let (row_id, record) = row?;
+        if let Some(new_key) = key_changes.get(&get_key(&record, &positions)) {
+            referencing_rows.push((row_id, record, new_key.clone()));
+        }
+    }
+    if referencing_rows.is_empty() {
+        return Ok(None);
+    }
+
+    let action = match change {
+        RowChange::Deleted { .. } => &foreign_key.on_delete,
+        RowChange::Updated { .. } => &foreign_key.on_update,
+    };
+    let new_values = match action {
+        // Checks can't be deferred, so NO ACTION rejects the change right away too
+        ReferentialAction::Restrict | ReferentialAction::NoAction => {
+            return Err(Error::ForeignKeyConstraintNotSatisfied { foreign_key_name: foreign_key.name.clone() });
+        },
+        ReferentialAction::Cascade => None,
+        ReferentialAction::SetNull => Some(get_replacement_values(table_schema, &foreign_key.local_columns, false)),
+        ReferentialAction::SetDefault => Some(get_replacement_values(table_schema, &foreign_key.local_columns, true)),
+    };
+
+    // Cascading a delete deletes the referencing rows, otherwise their key columns are updated
+    if let (RowChange::Deleted { .. }, None) = (change, &new_values) {
+        let row_ids: Vec<RowId> = referencing_rows.iter().map(|(row_id, _, _)| *row_id).collect();
+        storage.delete_rows(schema_name, table_schema, &row_ids)?;
+
+        let records = referencing_rows.into_iter().map(|(_, record, _)| record).collect();
+        return Ok(Some(RowChange::Deleted { schema_name: schema_name.clone(), table_name: table_name.to_string(), records }));
+    }
+
+    let mut updated_rows: Vec<(RowId, StringRecord)> = Vec::new();
+    let mut records: Vec<(StringRecord, StringRecord)> = Vec::new();
+    let mut changed_rows: Vec<Vec<InsertedRowColumn>> = Vec::new();
+    for (row_id, record, new_key) in referencing_rows {
+        let key_values = new_values.clone().or(new_key).unwrap_or_default();
+
+        let mut fields: Vec<String> = record.iter().map(|field| field.to_string()).collect();
+        let mut changed_row: Vec<InsertedRowColumn> = Vec::new();
+        for ((&position, column_name), value) in positions.iter().zip(&foreign_key.local_columns).zip(key_values) {
+            if let Some(field) = fields.get_mut(position) {
+                changed_row.push(InsertedRowColumn { name: column_name.clone(), value: (value != NULL_VALUE).then(|| value.clone()) });
+                *field = value;
+            }
+        }
+
+        let new_record = StringRecord::from(fields);
+        updated_rows.push((row_id, new_record.clone()));
+        records.push((record, new_record));
+        changed_rows.push(changed_row);
+    }
+
+    // The new key values must satisfy the table's constraints like any update does
+    column_constraints::validate_column_constraints(&changed_rows, schema_name, table_schema, false).await?;
+    column_constraints::validate_updated_records(schema_name, table_schema, &updated_rows, &changed_rows)?;
+
+    storage.update_rows(schema_name, table_schema, &updated_rows)?;
+
+    Ok(Some(RowChange::Updated { schema_name: schema_name.clone(), table_name: table_name.to_string(), records }))
+}
+
+// NULL, or the column's default with SET DEFAULT, for each key column
+fn get_replacement_values(table_schema: &TableSchema, columns: &[String], use_default: bool) -> Vec<String> {
+    columns.iter()
+        .map(|column_name| {
+            let column = table_schema.columns.iter().find(|column| &column.name == column_name);
+            let default_value = column.into_iter().flat_map(|column| column.constraints.iter()).find_map(|constraint| match constraint {
+                Constraint::DefaultValue(value) if use_default => Some(value.clone()),
+                _ => None,
+            });
+
+            default_value.unwrap_or_else(|| String::from(NULL_VALUE))
+        })
+        .collect()
+}
+
+// Positions of the columns in the table's records
+fn get_column_positions(table_schema: &TableSchema, columns: &[String]) -> Vec<usize> {
+    let headers = get_headers_from_table_schema(table_schema);
+
+    columns.iter()
+        .filter_map(|column_name| headers.iter().position(|header| header == column_name))
+        .collect()
+}
+
+fn get_key(record: &StringRecord, positions: &[usize]) -> Vec<String> {
+    positions.iter()
+        .map(|&position| record.get(position).map(|value| value.trim()).unwrap_or(NULL_VALUE).to_string())
+        .collect()
+}
@@ -0,0 +1,433 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Bound;
+
+use serde::{Deserialize, Serialize};
+
+use crate::database::constants::NULL_VALUE;
+use crate::database::types::DataType;
+use crate::shared::{errors::Error, file_manager::{self, ReadSeek}};
+use crate::storage_engine::expressions::operation_handler::unescape_storage_text;
+use crate::storage_engine::storage_backend::{page::PAGE_SIZE, table_storage::RowId};
+
+/*
+ * B+tree persisted in an index file: page 0 holds the header, every other page a single node.
+ * Entries are (key, row id) pairs, so duplicate keys are kept apart by their row ids
+ */
+pub const MAX_ENTRY_SIZE: usize = PAGE_SIZE / 4;
+const NODE_OVERHEAD: usize = 32;
+
+pub type IndexKey = Vec<IndexValue>;
+pub type IndexEntry = (IndexKey, RowId);
+
+// Typed column value, so that e.g. 9 sorts before 10
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum IndexValue {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl IndexValue {
+    pub fn from_value(value: &str, data_type: &DataType) -> IndexValue {
+        if value == NULL_VALUE {
+            return IndexValue::Null;
+        }
+
+        let typed_value = match data_type {
+            DataType::Integer => value.parse::<i64>().ok().map(IndexValue::Integer),
+            DataType::Float => value.parse::<f64>().ok().map(IndexValue::Float),
+            DataType::Boolean => value.parse::<bool>().ok().map(IndexValue::Boolean),
+            DataType::Text => None,
+        };
+
+        // Values that don't match the column type are kept as text
+        typed_value.unwrap_or_else(|| IndexValue::Text(unescape_storage_text(value).to_string()))
+    }
+
+    fn get_rank(&self) -> u8 {
+        match self {
+            IndexValue::Null => 0,
+            IndexValue::Boolean(_) => 1,
+            IndexValue::Integer(_) | IndexValue::Float(_) => 2,
+            IndexValue::Text(_) => 3,
+        }
+    }
+}
+
+impl Ord for IndexValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (IndexValue::Boolean(a), IndexValue::Boolean(b)) => a.cmp(b),
+            (IndexValue::Integer(a), IndexValue::Integer(b)) => a.cmp(b),
+            (IndexValue::Float(a), IndexValue::Float(b)) => a.total_cmp(b),
+            (IndexValue::Integer(a), IndexValue::Float(b)) => (*a as f64).total_cmp(b),
+            (IndexValue::Float(a), IndexValue::Integer(b)) => a.total_cmp(&(*b as f64)),
+            (IndexValue::Text(a), IndexValue::Text(b)) => a.cmp(b),
+            _ => self.get_rank().cmp(&other.get_rank()),
+        }
+    }
+}
+
+impl PartialOrd for IndexValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for IndexValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for IndexValue {}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct Header {
+    root_page: u64,
+    page_count: u64,
+}
+
+// Internal nodes route entries >= separators[i - 1] and < separators[i] to children[i]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+enum Node {
+    Leaf { entries: Vec<IndexEntry>, next_leaf: Option<u64> },
+    Internal { separators: Vec<IndexEntry>, children: Vec<u64> },
+}
+
+pub struct BTree {
+    file_path: String,
+    header: Header,
+    reader: Box<dyn ReadSeek>,
+    nodes: HashMap<u64, Node>,
+    dirty_pages: BTreeSet<u64>,
+}
+
+impl BTree {
+    // Bulk load a new index file from unsorted entries
+    pub fn create(file_path: &str, mut entries: Vec<IndexEntry>) -> Result<(), Error> {
+        entries.sort();
+        entries.dedup();
+        for entry in entries.iter() {
+            validate_entry_size(entry)?;
+        }
+
+        // Fill leaves in order
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut level: Vec<(u64, IndexEntry)> = Vec::new();
+        let mut leaf_entries: Vec<IndexEntry> = Vec::new();
+        let mut leaf_size = NODE_OVERHEAD;
+        for entry in entries {
+            let entry_size = get_serialized_size(&entry)?;
+            if !leaf_entries.is_empty() && leaf_size + entry_size > PAGE_SIZE {
+                level.push((nodes.len() as u64 + 1, leaf_entries[0].clone()));
+                nodes.push(Node::Leaf { entries: leaf_entries, next_leaf: Some(nodes.len() as u64 + 2) });
+                leaf_entries = Vec::new();
+                leaf_size = NODE_OVERHEAD;
+            }
+            leaf_size += entry_size;
+            leaf_entries.push(entry);
+        }
+        level.push((nodes.len() as u64 + 1, leaf_entries.first().cloned().unwrap_or_default()));
+        nodes.push(Node::Leaf { entries: leaf_entries, next_leaf: None });
+
+        // Build internal levels until a single root is left
+        while level.len() > 1 {
+            let mut next_level: Vec<(u64, IndexEntry)> = Vec::new();
+            let mut separators: Vec<IndexEntry> = Vec::new();
+            let mut children: Vec<u64> = Vec::new();
+            let mut first_entry = IndexEntry::default();
+            let mut node_size = NODE_OVERHEAD;
+
+            for (page_number, child_first_entry) in level {
+                let entry_size = get_serialized_size(&child_first_entry)? + 8;
+                if !children.is_empty() && node_size + entry_size > PAGE_SIZE {
+                    next_level.push((nodes.len() as u64 + 1, first_entry.clone()));
+                    nodes.push(Node::Internal { separators, children });
+                    separators = Vec::new();
+                    children = Vec::new();
+                    node_size = NODE_OVERHEAD;
+                }
+
+                if children.is_empty() {
+                    first_entry = child_first_entry;
+                } else {
+                    separators.push(child_first_entry);
+                }
+                children.push(page_number);
+                node_size += entry_size;
+            }
+            next_level.push((nodes.len() as u64 + 1, first_entry));
+            nodes.push(Node::Internal { separators, children });
+
+            level = next_level;
+        }
+
+        let header = Header { root_page: level[0].0, page_count: nodes.len() as u64 + 1 };
+        let mut contents = get_page_bytes(&header)?;
+        for node in nodes.iter() {
+            contents.extend(get_page_bytes(node)?);
+        }
+
+        file_manager::write_bytes_into_file(file_path, contents)
+    }
+
+    pub fn open(file_path: &str) -> Result<BTree, Error> {
+        let mut reader = file_manager::open_file_reader(file_path)?;
+        let header = read_page(&mut reader, 0, file_path)?;
+
+        Ok(BTree { file_path: file_path.to_string(), header, reader, nodes: HashMap::new(), dirty_pages: BTreeSet::new() })
+    }
+
+    pub fn insert(&mut self, key: IndexKey, row_id: RowId) -> Result<(), Error> {
+        let entry = (key, row_id);
+        validate_entry_size(&entry)?;
+
+        let mut path: Vec<(u64, usize)> = Vec::new();
+        let page_number = self.find_leaf(&entry, &mut path)?;
+        if let Some(Node::Leaf { entries, .. }) = self.nodes.get_mut(&page_number) {
+            match entries.binary_search(&entry) {
+                Ok(_) => return Ok(()),
+                Err(position) => entries.insert(position, entry),
+            }
+        }
+        self.dirty_pages.insert(page_number);
+
+        // Split full nodes bottom-up, growing a new root if needed
+        let mut split = self.split_node(page_number)?;
+        while let Some((separator, right_page_number)) = split {
+            split = match path.pop() {
+                Some((parent_page_number, child_index)) => {
+                    if let Some(Node::Internal { separators, children }) = self.nodes.get_mut(&parent_page_number) {
+                        separators.insert(child_index, separator);
+                        children.insert(child_index + 1, right_page_number);
+                    }
+                    self.dirty_pages.insert(parent_page_number);
+                    self.split_node(parent_page_number)?
+                },
+                None => {
+                    let root_page_number = self.allocate_page();
+                    let root = Node::Internal { separators: vec![separator], children: vec![self.header.root_page, right_page_number] };
+                    self.set_node(root_page_number, root);
+                    self.header.root_page = root_page_number;
+                    None
+                }
+            };
+        }
+
+        Ok(())
+    }
+
+    // Nodes aren't merged on removal, rebuilding the index compacts it
+    pub fn remove(&mut self, key: IndexKey, row_id: RowId) -> Result<(), Error> {
+        let entry = (key, row_id);
+        let page_number = self.find_leaf(&entry, &mut Vec::new())?;
+
+        if let Some(Node::Leaf { entries, .. }) = self.nodes.get_mut(&page_number) {
+            if let Ok(position) = entries.binary_search(&entry) {
+                entries.remove(position);
+                self.dirty_pages.insert(page_number);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn find(&mut self, key: &IndexKey) -> Result<Vec<RowId>, Error> {
+        self.range(Bound::Included(key), Bound::Included(key))
+    }
+
+    // Row ids in key order; bounds shorter than the keys match on the key prefix
+    pub fn range(&mut self, lower: Bound<&IndexKey>, upper: Bound<&IndexKey>) -> Result<Vec<RowId>, Error> {
+        let start_entry = match lower {
+            Bound::Included(key) => (key.clone(), 0),
+            Bound::Excluded(key) => (key.clone(), RowId::MAX),
+            Bound::Unbounded => IndexEntry::default(),
+        };
+
+        let mut row_ids: Vec<RowId> = Vec::new();
+        let mut page_number = Some(self.find_leaf(&start_entry, &mut Vec::new())?);
+        while let Some(current_page_number) = page_number {
+            let (entries, next_leaf) = match self.read_node(current_page_number)? {
+                Node::Leaf { entries, next_leaf } => (entries, next_leaf),
+                Node::Internal { .. } => return Err(Error::FailedIndexRead { index_name: self.file_path.clone() }),
+            };
+
+            for (key, row_id) in entries.iter() {
+                if !is_above_lower_bound(key, lower) {
+                    continue;
+                }
+                if !is_below_upper_bound(key, upper) {
+                    return Ok(row_ids);
+                }
+                row_ids.push(*row_id);
+            }
+
+            page_number = *next_leaf;
+        }
+
+        Ok(row_ids)
+    }
+
+    pub fn flush(self) -> Result<(), Error> {
+        if self.dirty_pages.is_empty() {
+            return Ok(());
+        }
+
+        for page_number in self.dirty_pages.iter() {
+            let node = self.nodes.get(page_number).ok_or(Error::ServerError)?;
+            file_manager::write_bytes_at(&self.file_path, page_number * PAGE_SIZE as u64, get_page_bytes(node)?)?;
+        }
+
+        file_manager::write_bytes_at(&self.file_path, 0, get_page_bytes(&self.header)?)
+    }
+
+    fn find_leaf(&mut self, entry: &IndexEntry, path: &mut Vec<(u64, usize)>) -> Result<u64, Error> {
+        let mut page_number = self.header.root_page;
+
+        loop {
+            let child = match self.read_node(page_number)? {
+                Node::Internal { separators, children } => {
+                    let child_index = separators.partition_point(|separator| separator <= entry);
+                    Some((child_index, children[child_index]))
+                },
+                Node::Leaf { .. } => None,
+            };
+
+            match child {
+                Some((child_index, child_page_number)) => {
+                    path.push((page_number, child_index));
+                    page_number = child_page_number;
+                },
+                None => return Ok(page_number),
+            }
+        }
+    }
+
+    // Split an overflowing node in two, returning the separator and page of the right half
+    fn split_node(&mut self, page_number: u64) -> Result<Option<(IndexEntry, u64)>, Error> {
+        let node = self.nodes.get(&page_number).ok_or(Error::ServerError)?.clone();
+        if get_serialized_size(&node)? <= PAGE_SIZE {
+            return Ok(None);
+        }
+
+        let right_page_number = self.allocate_page();
+        let (left_node, right_node, separator) = match node {
+            Node::Leaf { mut entries, next_leaf } => {
+                let right_entries = entries.split_off(get_split_index(&entries)?);
+                let separator = right_entries[0].clone();
+                (
+                    Node::Leaf { entries, next_leaf: Some(right_page_number) },
+                    Node::Leaf { entries: right_entries, next_leaf },
+                    separator,
+                )
+            },
+            Node::Internal { mut separators, mut children } => {
+                let split_index = get_split_index(&separators)?;
+                let mut right_separators = separators.split_off(split_index);
+                let separator = right_separators.remove(0);
+                let right_children = children.split_off(split_index + 1);
+                (
+                    Node::Internal { separators, children },
+                    Node::Internal { separators: right_separators, children: right_children },
+                    separator,
+                )
+            },
+        };
+
+        self.set_node(page_number, left_node);
+        self.set_node(right_page_number, right_node);
+
+        Ok(Some((separator, right_page_number)))
+    }
+
+    fn read_node(&mut self, page_number: u64) -> Result<&Node, Error> {
+        if !self.nodes.contains_key(&page_number) {
+            let node = read_page(&mut self.reader, page_number, &self.file_path)?;
+            self.nodes.insert(page_number, node);
+        }
+
+        self.nodes.get(&page_number).ok_or(Error::ServerError)
+    }
+
+    fn set_node(&mut self, page_number: u64, node: Node) {
+        self.nodes.insert(page_number, node);
+        self.dirty_pages.insert(page_number);
+    }
+
+    fn allocate_page(&mut self) -> u64 {
+        let page_number = self.header.page_count;
+        self.header.page_count += 1;
+        page_number
+    }
+}
+
+fn read_page<T: for<'de> Deserialize<'de>>(reader: &mut Box<dyn ReadSeek>, page_number: u64, file_path: &str) -> Result<T, Error> {
+    let mut page_bytes = vec![0; PAGE_SIZE];
+    reader.seek(SeekFrom::Start(page_number * PAGE_SIZE as u64))?;
+    reader.read_exact(&mut page_bytes)?;
+
+    bincode::deserialize(&page_bytes).map_err(|_| Error::FailedIndexRead { index_name: file_path.to_string() })
+}
+
+fn get_page_bytes<T: Serialize>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut page_bytes = bincode::serialize(value).map_err(|_| Error::ServerError)?;
+    if page_bytes.len() > PAGE_SIZE {
+        return Err(Error::ServerError);
+    }
+    page_bytes.resize(PAGE_SIZE, 0);
+
+    Ok(page_bytes)
+}
+
+fn get_serialized_size<T: Serialize>(value: &T) -> Result<usize, Error> {
+    bincode::serialized_size(value).map(|size| size as usize).map_err(|_| Error::ServerError)
+}
+
+fn validate_entry_size(entry: &IndexEntry) -> Result<(), Error> {
+    let size = get_serialized_size(entry)?;
+    if size > MAX_ENTRY_SIZE {
+        return Err(Error::IndexKeyTooLarge { size, max_size: MAX_ENTRY_SIZE });
+    }
+
+    Ok(())
+}
+
+// Split point halving the serialized size, leaving both halves non-empty
+fn get_split_index(entries: &[IndexEntry]) -> Result<usize, Error> {
+    let sizes = entries.iter().map(get_serialized_size).collect::<Result<Vec<usize>, Error>>()?;
+    let total_size: usize = sizes.iter().sum();
+
+    let mut left_size = 0;
+    let mut split_index = 0;
+    while split_index < sizes.len() && left_size < total_size / 2 {
+        left_size += sizes[split_index];
+        split_index += 1;
+    }
+
+    Ok(split_index.clamp(1, entries.len() - 1))
+}
+
+fn compare_key_prefix(key: &IndexKey, bound: &IndexKey) -> Ordering {
+    key.iter().take(bound.len()).cmp(bound.iter())
+}
+
+fn is_above_lower_bound(key: &IndexKey, lower: Bound<&IndexKey>) -> bool {
+    match lower {
+        Bound::Included(bound) => compare_key_prefix(key, bound) != Ordering::Less,
+        Bound::Excluded(bound) => compare_key_prefix(key, bound) == Ordering::Greater,
+        Bound::Unbounded => true,
+    }
+}
+
+fn is_below_upper_bound(key: &IndexKey, upper: Bound<&IndexKey>) -> bool {
+    match upper {
+        Bound::Included(bound) => compare_key_prefix(key, bound) != Ordering::Greater,
+        Bound::Excluded(bound) => compare_key_prefix(key, bound) == Ordering::Less,
+        Bound::Unbounded => true,
+    }
+}
\ No newline at end of file
@@ -1,65 +1,99 @@
-use csv::StringRecord;
+use std::path::Path;
 
-use crate::database::database_navigator::{get_table_index_path, get_table_row_index_path, get_table_schema_path};
-use crate::database::types::{Constraint, Index, RowsIndex, TableSchema};
-use crate::shared::{errors::Error, file_manager};
+use csv::StringRecord;
 
-use super::offset_counter;
+use crate::database::constants::NULL_VALUE;
+use crate::database::database_navigator::get_table_index_path;
+use crate::database::types::{Column, Constraint, Database, Index, TableSchema};
+use crate::shared::errors::Error;
+use crate::storage_engine::storage_backend::table_storage::{get_table_storage, RowId};
 
-pub async fn create_default_indexes(schema_name: &String, table_schema: &TableSchema) -> Result<(), Error> {
-    let indexable_columns = table_schema.columns.iter()
-        .filter(|col| index_strategy(&col.constraints));
+use super::btree::{BTree, IndexKey, IndexValue};
 
-    // Create column indexes
-    for column in indexable_columns {
-        create_index(
-            schema_name, &table_schema.name, &column.name, &mut table_schema.clone(), 
-        false, false, &Vec::new(), // No offset computations
-        ).await?;
+// Build all indexes of the table from the given rows
+pub fn create_indexes(schema_name: &str, table_schema: &TableSchema, rows: &[(RowId, StringRecord)]) -> Result<(), Error> {
+    for index in get_table_indexes(table_schema) {
+        create_index(schema_name, table_schema, &index, rows)?;
     }
 
-    // Create rows index
-    let row_index_filepath = get_table_row_index_path(schema_name, &table_schema.name);
-    let row_offsets = offset_counter::compute_headers_offset(table_schema);
-    let row_index = RowsIndex { row_offsets: vec![row_offsets] };
-    file_manager::write_json_into_file(&row_index_filepath, &row_index)?;
-
     Ok(())
 }
 
-pub async fn create_index(
-    schema_name: &String, table_name: &String, column_name: &String, table_schema: &mut TableSchema, 
-    update_schema: bool, calculate_offsets: bool, records: &Vec<StringRecord>
-) -> Result<(), Error> {
-    // Update schema if specified
-    if update_schema {
-        let table_schema_file_path = get_table_schema_path(schema_name, table_name);
-        if let Some(column) = table_schema.columns.iter_mut().find(|c| &c.name == column_name) {
-            column.is_indexed = true;
+pub fn create_index(schema_name: &str, table_schema: &TableSchema, index: &Index, rows: &[(RowId, StringRecord)]) -> Result<(), Error> {
+    let columns = get_index_columns(table_schema, index)?;
+    let mut entries: Vec<(IndexKey, RowId)> = rows.iter()
+        .map(|(row_id, row)| (get_index_key(row, &columns), *row_id))
+        .collect();
+
+    if index.is_unique {
+        entries.sort();
+        let duplicate_entry = entries.windows(2)
+            .find(|window| window[0].0 == window[1].0 && !is_null_key(&window[0].0))
+            .map(|window| window[1].1);
+        if let Some((_, row)) = duplicate_entry.and_then(|duplicate_row_id| rows.iter().find(|(row_id, _)| *row_id == duplicate_row_id)) {
+            return Err(Error::IndexUniquenessNotSatisfied { index_name: index.name.clone(), value: get_key_values(row, &columns) });
         }
-        file_manager::write_json_into_file(&table_schema_file_path, table_schema)?;
     }
 
-    // Create index file, computing column offsets if specified
-    let index_file_path = get_table_index_path(schema_name, table_name, column_name);
-    let column_order = if let Some(column) = table_schema.columns.iter().find(|c| &c.name == column_name) {
-        Ok(column.order)
-    } else {
-        Err(Error::ColumnDoesNotExist { column_name: column_name.clone(), table_name: table_name.clone() })
-    }?;
+    let index_file_path = get_table_index_path(schema_name, &table_schema.name, &index.name);
+    BTree::create(&index_file_path, entries)
+}
 
-    let offsets = if calculate_offsets {
-        offset_counter::compute_column_offsets(records, &table_schema, &column_order)
-    } else {
-        Vec::new()
-    };
+// Build indexes missing on disk, e.g. for tables written by older versions
+pub fn create_missing_indexes(database: &Database) -> Result<(), Error> {
+    for schema in database.schemas.iter() {
+        for table_schema in schema.tables.iter() {
+            let missing_indexes: Vec<Index> = get_table_indexes(table_schema).into_iter()
+                .filter(|index| !Path::new(&get_table_index_path(&schema.name, &table_schema.name, &index.name)).exists())
+                .collect();
+            if missing_indexes.is_empty() {
+                continue;
+            }
 
-    let index = Index { key: column_name.clone(), offsets: offsets };
-    file_manager::write_json_into_file(&index_file_path, &index)?;
+            let rows = get_table_storage(table_schema).scan_rows(&schema.name, table_schema)?;
+            for index in missing_indexes {
+                create_index(&schema.name, table_schema, &index, &rows)?;
+            }
+        }
+    }
 
     Ok(())
 }
 
+// Indexes of indexed columns (named after the column), followed by those created with CREATE INDEX
+pub fn get_table_indexes(table_schema: &TableSchema) -> Vec<Index> {
+    let column_indexes = table_schema.columns.iter()
+        .filter(|column| column.is_indexed)
+        .map(|column| Index { name: column.name.clone(), columns: vec![column.name.clone()], is_unique: false });
+
+    column_indexes.chain(table_schema.indexes.iter().cloned()).collect()
+}
+
+pub fn get_index_columns<'a>(table_schema: &'a TableSchema, index: &Index) -> Result<Vec<&'a Column>, Error> {
+    index.columns.iter()
+        .map(|column_name| table_schema.columns.iter().find(|column| &column.name == column_name)
+            .ok_or(Error::ColumnDoesNotExist { column_name: column_name.clone(), table_name: table_schema.name.clone() }))
+        .collect()
+}
+
+pub fn get_index_key(row: &StringRecord, columns: &[&Column]) -> IndexKey {
+    columns.iter()
+        .map(|column| IndexValue::from_value(row.get(column.order).unwrap_or(NULL_VALUE), &column.data_type))
+        .collect()
+}
+
+// Keys with a null value never conflict with each other
+pub fn is_null_key(key: &IndexKey) -> bool {
+    key.contains(&IndexValue::Null)
+}
+
+pub fn get_key_values(row: &StringRecord, columns: &[&Column]) -> String {
+    columns.iter()
+        .map(|column| row.get(column.order).unwrap_or(NULL_VALUE))
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
 pub fn index_strategy(constraints: &Vec<Constraint>) -> bool {
     constraints.contains(&Constraint::PrimaryKey) || constraints.contains(&Constraint::Unique)
-}
\ No newline at end of file
+}
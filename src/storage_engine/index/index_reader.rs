@@ -1,119 +1,192 @@
-use std::{fs::File, io::{BufReader, BufRead, Read, Seek, SeekFrom}};
+use std::collections::BTreeSet;
+use std::ops::Bound;
 
-use csv::{ReaderBuilder, StringRecord};
+use sqlparser::ast::{BinaryOperator, Expr, UnaryOperator, Value};
 
-use crate::{database::{database_navigator::{get_table_data_path, get_table_index_path}, types::{Index, RowsIndex}}, shared::{errors::Error, file_manager}};
+use crate::{database::{database_navigator::{get_table_index_path, get_table_row_index_path}, types::{Column, DataType, RowsIndex, TableSchema}}, shared::{errors::Error, file_manager}, storage_engine::{expressions::operation_handler::escape_storage_text, storage_backend::table_storage::RowId}};
 
-pub fn read_column_index(schema_name: &String, table_name: &String, column_name: &String) -> Result<Index, Error> {
-    let file_path = get_table_index_path(schema_name, table_name, column_name);
-    let index = file_manager::read_json_file::<Index>(&file_path)?;
+use super::{btree::{BTree, IndexKey, IndexValue}, index_manager::get_table_indexes};
 
-    Ok(index)
+pub fn open_index(schema_name: &str, table_name: &str, index_name: &str) -> Result<BTree, Error> {
+    let file_path = get_table_index_path(schema_name, table_name, index_name);
+
+    BTree::open(&file_path)
 }
 
-pub fn read_rows_index(schema_name: &String, table_name: &String) -> Result<RowsIndex, Error> {
-    let file_path = get_table_index_path(schema_name, table_name, &String::from("row_offsets"));
+pub fn read_rows_index(schema_name: &str, table_name: &str) -> Result<RowsIndex, Error> {
+    let file_path = get_table_row_index_path(schema_name, table_name);
     let index = file_manager::read_json_file::<RowsIndex>(&file_path)?;
 
     Ok(index)
 }
 
-pub fn get_column_values_from_index(index: &Index, schema_name: &String, table_name: &String) -> Result<Vec<String>, Error> {
-    let data_file_path = get_table_data_path(schema_name, table_name);
-    let file = File::open(data_file_path)?;
-    let mut reader = BufReader::new(file);
-    let mut column_values: Vec<String> = Vec::new();
-
-    for offset in &index.offsets {
-        reader.seek(SeekFrom::Start(*offset))?;
-
-        let mut column_value = String::new();
-        let mut buffer = [0; 1];
+// Index range scans narrowing down the rows of a filter
+pub enum IndexLookup {
+    Range { index_name: String, column_name: String, lower: Bound<IndexKey>, upper: Bound<IndexKey>, condition: Box<Expr> },
+    Intersection(Box<IndexLookup>, Box<IndexLookup>),
+    Union(Box<IndexLookup>, Box<IndexLookup>),
+}
 
-        // Read until encountering comma or end of line
-        loop {
-            let bytes_read = reader.read(&mut buffer)?;
-            if bytes_read == 0 || buffer[0] == b',' || buffer[0] == b'\n' {
-                break;
-            }
-            column_value.push(buffer[0] as char);
+impl IndexLookup {
+    pub fn get_index_names(&self) -> Vec<&String> {
+        match self {
+            IndexLookup::Range { index_name, .. } => vec![index_name],
+            IndexLookup::Intersection(left, right) | IndexLookup::Union(left, right) => {
+                let mut index_names = left.get_index_names();
+                for index_name in right.get_index_names() {
+                    if !index_names.contains(&index_name) {
+                        index_names.push(index_name);
+                    }
+                }
+                index_names
+            },
         }
-
-        column_values.push(column_value);
     }
 
-    Ok(column_values)   
+    // The part of the filter the indexes answer
+    pub fn get_condition(&self) -> String {
+        match self {
+            IndexLookup::Range { condition, .. } => condition.to_string(),
+            IndexLookup::Intersection(left, right) => format!("({}) AND ({})", left.get_condition(), right.get_condition()),
+            IndexLookup::Union(left, right) => format!("({}) OR ({})", left.get_condition(), right.get_condition()),
+        }
+    }
 }
 
-pub fn get_rows_from_row_offsets(row_offsets: &Vec<u64>, schema_name: &String, table_name: &String) -> Result<Vec<StringRecord>, Error> {
-    let data_file_path = get_table_data_path(schema_name, table_name);
-    let file = File::open(data_file_path).map_err(|e| Error::IOError(e))?;
-    let mut file_reader = BufReader::new(file);
-    let mut rows: Vec<StringRecord> = Vec::new();
+// Ids of the rows that may pass the filters, or None if indexes can't narrow them down
+pub fn find_row_ids(schema_name: &String, table_schema: &TableSchema, filters: &Option<Expr>) -> Result<Option<Vec<RowId>>, Error> {
+    let index_lookup = match filters {
+        Some(expr) => find_index_lookup(table_schema, expr),
+        None => None,
+    };
 
-    for &offset in row_offsets {
-        file_reader.seek(SeekFrom::Start(offset)).map_err(|e| Error::IOError(e))?;
-
-        let mut line = String::new();
-        file_reader.read_line(&mut line).map_err(|e| Error::IOError(e))?;
+    match index_lookup {
+        Some(index_lookup) => Ok(Some(find_lookup_row_ids(schema_name, table_schema, &index_lookup)?.into_iter().collect())),
+        None => Ok(None),
+    }
+}
 
-        // Parse the line into a StringRecord
-        let mut rdr = ReaderBuilder::new().has_headers(false).from_reader(line.as_bytes());
-        for result in rdr.records() {
-            let record = result?; // TODO: Better IO error handling
-            rows.push(record);
-            break;
-        }
+// Each predicate on an indexed column becomes a range scan; predicates without one are left to the filter
+pub fn find_index_lookup(table_schema: &TableSchema, expr: &Expr) -> Option<IndexLookup> {
+    match expr {
+        Expr::BinaryOp { left, op: BinaryOperator::And, right } => {
+            match (find_index_lookup(table_schema, left), find_index_lookup(table_schema, right)) {
+                (Some(left_lookup), Some(right_lookup)) => Some(IndexLookup::Intersection(Box::new(left_lookup), Box::new(right_lookup))),
+                (Some(index_lookup), None) | (None, Some(index_lookup)) => Some(index_lookup),
+                (None, None) => None,
+            }
+        },
+        Expr::BinaryOp { left, op: BinaryOperator::Or, right } => {
+            let left_lookup = find_index_lookup(table_schema, left)?;
+            let right_lookup = find_index_lookup(table_schema, right)?;
+
+            Some(IndexLookup::Union(Box::new(left_lookup), Box::new(right_lookup)))
+        },
+        Expr::BinaryOp { left, op, right } => {
+            // Column on either side of the comparison
+            let (column_expr, index_name, value, op) = match (get_leading_column_index(table_schema, left), get_index_value(table_schema, left, right)) {
+                (Some(index_name), Some(value)) => (left, index_name, value, op.clone()),
+                _ => match (get_leading_column_index(table_schema, right), get_index_value(table_schema, right, left)) {
+                    (Some(index_name), Some(value)) => (right, index_name, value, get_flipped_operator(op)),
+                    _ => return None,
+                },
+            };
+            let column_name = get_column(table_schema, column_expr)?.name.clone();
+
+            let key = vec![value];
+            let (lower, upper) = match op {
+                BinaryOperator::Eq => (Bound::Included(key.clone()), Bound::Included(key)),
+                BinaryOperator::Lt => (Bound::Unbounded, Bound::Excluded(key)),
+                BinaryOperator::LtEq => (Bound::Unbounded, Bound::Included(key)),
+                BinaryOperator::Gt => (Bound::Excluded(key), Bound::Unbounded),
+                BinaryOperator::GtEq => (Bound::Included(key), Bound::Unbounded),
+                _ => return None,
+            };
+
+            Some(IndexLookup::Range { index_name, column_name, lower, upper, condition: Box::new(expr.clone()) })
+        },
+        Expr::Between { expr: column_expr, negated: false, low, high } => {
+            let index_name = get_leading_column_index(table_schema, column_expr)?;
+            let low_value = get_index_value(table_schema, column_expr, low)?;
+            let high_value = get_index_value(table_schema, column_expr, high)?;
+            let column_name = get_column(table_schema, column_expr)?.name.clone();
+
+            Some(IndexLookup::Range { index_name, column_name, lower: Bound::Included(vec![low_value]), upper: Bound::Included(vec![high_value]), condition: Box::new(expr.clone()) })
+        },
+        Expr::Nested(nested_expr) => find_index_lookup(table_schema, nested_expr),
+        _ => None,
     }
+}
 
-    Ok(rows)
+pub fn find_lookup_row_ids(schema_name: &String, table_schema: &TableSchema, index_lookup: &IndexLookup) -> Result<BTreeSet<RowId>, Error> {
+    match index_lookup {
+        IndexLookup::Range { index_name, lower, upper, .. } => {
+            // Keys of multi-column indexes are compared on their leading value only
+            let mut index = open_index(schema_name, &table_schema.name, index_name)?;
+            Ok(index.range(lower.as_ref(), upper.as_ref())?.into_iter().collect())
+        },
+        IndexLookup::Intersection(left, right) => {
+            let left_row_ids = find_lookup_row_ids(schema_name, table_schema, left)?;
+            let right_row_ids = find_lookup_row_ids(schema_name, table_schema, right)?;
+            Ok(left_row_ids.intersection(&right_row_ids).copied().collect())
+        },
+        IndexLookup::Union(left, right) => {
+            let left_row_ids = find_lookup_row_ids(schema_name, table_schema, left)?;
+            let right_row_ids = find_lookup_row_ids(schema_name, table_schema, right)?;
+            Ok(left_row_ids.union(&right_row_ids).copied().collect())
+        },
+    }
 }
 
-pub fn get_restricted_rows(filter_columns: &Vec<String>, rows_index: &RowsIndex, schema_name: &String, table_name: &String) -> Result<Vec<Vec<String>>, Error> {
-    let number_of_rows = rows_index.row_offsets.len() - 1;
-    let number_of_columns = filter_columns.len();
+// Name of an index whose first column is the given column
+pub fn find_leading_column_index(table_schema: &TableSchema, column_name: &String) -> Option<String> {
+    get_table_indexes(table_schema).into_iter()
+        .find(|index| index.columns.first() == Some(column_name))
+        .map(|index| index.name)
+}
 
-    // Initialize restricted_rows with the exact size needed
-    let mut restricted_rows: Vec<Vec<String>> = vec![vec![String::new(); number_of_columns]; number_of_rows];
+fn get_leading_column_index(table_schema: &TableSchema, expr: &Expr) -> Option<String> {
+    let column = get_column(table_schema, expr)?;
 
-    for (col_index, column_name) in filter_columns.iter().enumerate() {
-        let column_index = read_column_index(schema_name, table_name, column_name)?;
-        let column_values = get_column_values_from_index(&column_index, schema_name, table_name)?;
+    find_leading_column_index(table_schema, &column.name)
+}
 
-        // Populate each row with the column's value
-        for (row_index, value) in column_values.into_iter().enumerate() {
-            if row_index < restricted_rows.len() {
-                restricted_rows[row_index][col_index] = value;
-            }
-        }
+fn get_column<'a>(table_schema: &'a TableSchema, expr: &Expr) -> Option<&'a Column> {
+    match expr {
+        Expr::Identifier(ident) => table_schema.columns.iter().find(|column| column.name == ident.value),
+        // Callers only pass conditions on this table, so a qualifier names it
+        Expr::CompoundIdentifier(idents) => table_schema.columns.iter().find(|column| idents.last().is_some_and(|ident| column.name == ident.value)),
+        _ => None,
     }
+}
 
-    Ok(restricted_rows)
+// Literal compared against the column, as stored, if it has the column's type
+fn get_index_value(table_schema: &TableSchema, column_expr: &Expr, value_expr: &Expr) -> Option<IndexValue> {
+    let column = get_column(table_schema, column_expr)?;
+    let value = match value_expr {
+        Expr::Value(Value::Number(n, _)) => n.clone(),
+        Expr::Value(Value::SingleQuotedString(s)) => escape_storage_text(s),
+        Expr::Value(Value::Boolean(b)) => b.to_string(),
+        Expr::UnaryOp { op: UnaryOperator::Minus, expr } => match expr.as_ref() {
+            Expr::Value(Value::Number(n, _)) => format!("-{}", n),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    match IndexValue::from_value(&value, &column.data_type) {
+        IndexValue::Null => None,
+        IndexValue::Text(_) if column.data_type != DataType::Text => None,
+        index_value => Some(index_value),
+    }
 }
 
-// pub fn get_rows_from_row_offsets(row_offsets: &Vec<u64>, schema_name: &String, table_name: &String) -> Result<Vec<String>, Error> {
-//     let data_file_path = get_table_data_path(schema_name, table_name);
-//     let file = File::open(data_file_path)?;
-//     let mut reader = BufReader::new(file);
-//     let mut rows: Vec<String> = Vec::new();
-    
-//     for row_offset in row_offsets {
-//         reader.seek(SeekFrom::Start(*row_offset))?;
-
-//         let mut row = String::new();
-//         let mut buffer = [0; 1];
-
-//         // Read until encountering end of line
-//         loop {
-//             let bytes_read = reader.read(&mut buffer)?;
-//             if bytes_read == 0 || buffer[0] == b'\n' {
-//                 break;
-//             }
-//             row.push(buffer[0] as char);
-//         }
-
-//         rows.push(row);
-//     }
-
-//     Ok(rows)
-// }
\ No newline at end of file
+fn get_flipped_operator(op: &BinaryOperator) -> BinaryOperator {
+    match op {
+        BinaryOperator::Lt => BinaryOperator::Gt,
+        BinaryOperator::LtEq => BinaryOperator::GtEq,
+        BinaryOperator::Gt => BinaryOperator::Lt,
+        BinaryOperator::GtEq => BinaryOperator::LtEq,
+        _ => op.clone(),
+    }
+}
\ No newline at end of file
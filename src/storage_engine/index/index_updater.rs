@@ -1,71 +1,40 @@
 use csv::StringRecord;
 
-use crate::{database::{database_navigator::{get_table_index_path, get_table_row_index_path}, types::{Index, InsertedRowColumn, RowsIndex, TableSchema}}, shared::{errors::Error, file_manager}};
-
-use super::{index_reader::{read_column_index, read_rows_index}, offset_counter};
-
-pub fn update_indexes_on_insert(complete_inserted_rows: &Vec<Vec<InsertedRowColumn>>, schema_name: &String, table_name: &String, table_schema: &TableSchema) -> Result<(), Error> {
-    let mut rows_index = read_rows_index(schema_name, table_name)?;
-    let end_of_file_offset = match rows_index.row_offsets.last() {
-        Some(&offset) => offset,
-        None => 0
-    };
-
-    let (new_column_value_offsets, mut new_row_offsets) = offset_counter::compute_insertion_offsets(complete_inserted_rows, end_of_file_offset, table_schema.columns.len());
-
-    // Update rows index
-    rows_index.row_offsets.append(&mut new_row_offsets);
-    let row_index_file_path = get_table_row_index_path(&schema_name, &table_name);
-    file_manager::write_json_into_file(&row_index_file_path, &rows_index)?;
-
-    // Update column indexes
-    for (column_index, column_offsets) in new_column_value_offsets.iter().enumerate() {
-        let column = match table_schema.columns.get(column_index) {
-            Some(col) => col,
-            None => continue
-        };
-        if !column.is_indexed {
-            continue;
+use crate::{database::{database_navigator::{get_table_index_path, get_table_row_index_path}, types::{RowsIndex, TableSchema}}, shared::{errors::Error, file_manager}, storage_engine::storage_backend::table_storage::RowId};
+
+use super::{btree::BTree, index_manager::{get_index_columns, get_index_key, get_key_values, get_table_indexes, is_null_key}};
+
+pub fn insert_index_entries(schema_name: &str, table_schema: &TableSchema, rows: &[(RowId, StringRecord)]) -> Result<(), Error> {
+    for table_index in get_table_indexes(table_schema) {
+        let columns = get_index_columns(table_schema, &table_index)?;
+        let mut index = BTree::open(&get_table_index_path(schema_name, &table_schema.name, &table_index.name))?;
+        for (row_id, row) in rows {
+            let key = get_index_key(row, &columns);
+            if table_index.is_unique && !is_null_key(&key) && !index.find(&key)?.is_empty() {
+                return Err(Error::IndexUniquenessNotSatisfied { index_name: table_index.name.clone(), value: get_key_values(row, &columns) });
+            }
+            index.insert(key, *row_id)?;
         }
-
-        let column_name = &column.name;
-        let mut column_index = read_column_index(schema_name, table_name, column_name)?;
-        column_index.offsets.append(&mut column_offsets.clone());
-        let column_index_file_path = get_table_index_path(schema_name, table_name, column_name);
-        file_manager::write_json_into_file(&column_index_file_path, &column_index)?;
+        index.flush()?;
     }
 
-  Ok(())
+    Ok(())
 }
 
-pub fn update_indexes_on_update_or_delete(records: &Vec<StringRecord>, schema_name: &String, table_name: &String, table_schema: &TableSchema) -> Result<(), Error> {
-    let (new_column_value_offsets, new_row_offsets) = offset_counter::compute_records_offsets(records, table_schema);
-
-    // Update rows index
-    let row_index_file_path = get_table_index_path(schema_name, &table_name, &String::from("row_offsets"));
-    let rows_index = RowsIndex {
-        row_offsets: new_row_offsets
-    };
-    file_manager::write_json_into_file(&row_index_file_path, &rows_index)?;
-
-    // Update column indexes
-    for (column_index, column_offsets) in new_column_value_offsets.iter().enumerate() {
-        let column = match table_schema.columns.get(column_index) {
-            Some(col) => col,
-            None => continue
-        };
-        if !column.is_indexed {
-            continue;
+pub fn delete_index_entries(schema_name: &str, table_schema: &TableSchema, rows: &[(RowId, StringRecord)]) -> Result<(), Error> {
+    for table_index in get_table_indexes(table_schema) {
+        let columns = get_index_columns(table_schema, &table_index)?;
+        let mut index = BTree::open(&get_table_index_path(schema_name, &table_schema.name, &table_index.name))?;
+        for (row_id, row) in rows {
+            index.remove(get_index_key(row, &columns), *row_id)?;
         }
-
-        let column_name = &column.name;
-        let column_index = Index {
-            key: column_name.clone(),
-            offsets: column_offsets.to_vec()
-        };
-        let column_index_file_path = get_table_index_path(schema_name, &table_name, column_name);
-        file_manager::write_json_into_file(&column_index_file_path, &column_index)?;
+        index.flush()?;
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn write_rows_index(schema_name: &str, table_name: &str, rows_index: &RowsIndex) -> Result<(), Error> {
+    let row_index_file_path = get_table_row_index_path(schema_name, table_name);
+    file_manager::write_json_into_file(&row_index_file_path, rows_index)
+}
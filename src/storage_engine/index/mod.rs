@@ -1,4 +1,4 @@
+pub mod btree;
 pub mod index_manager;
 pub mod index_reader;
-pub mod index_updater;
-mod offset_counter;
\ No newline at end of file
+pub mod index_updater;
\ No newline at end of file
@@ -1,47 +1,34 @@
 use sqlparser::ast::{Ident, ObjectName, Query};
-use csv::WriterBuilder;
-use std::fs::OpenOptions;
+use csv::StringRecord;
 
-use crate::database::database_loader;
-use crate::database::database_navigator::get_table_data_path;
+use crate::database::constants::NULL_VALUE;
 use crate::database::utils::find_database_table;
+use crate::network_protocol::types::ResultSet;
 use crate::shared::errors::Error;
-use crate::storage_engine::index::index_updater;
+use crate::storage_engine::storage_backend::table_storage::get_table_storage;
+use crate::transaction_manager::lock_manager;
 
 use super::validator;
 
-pub async fn insert_into_table(name: &ObjectName, columns: &Vec<Ident>, source: &Option<Box<Query>>) -> Result<String, Error> {
-    // Get database blueprint
-    let database = database_loader::get_database()?;
+pub async fn insert_into_table(name: &ObjectName, columns: &Vec<Ident>, source: &Option<Box<Query>>) -> Result<ResultSet, Error> {
+    // Lock table before validating uniqueness against its rows
+    let (database, schema_name, table_name) = lock_manager::lock_table_for_write_and_get_database(name).await?;
     
     // Validate insert
     // Needs additional checks from newly introduced features
-    let (table_name, _, complete_inserted_rows) = validator::validate_insert_into(&database, name, columns, source).await?;
+    let (_, complete_inserted_rows) = validator::validate_insert_into(&database, &schema_name, &table_name, columns, source).await?;
     
-    let table_schema = match find_database_table(&database, &table_name) {
+    let table_schema = match find_database_table(&database, &schema_name, &table_name) {
         Some(schema) => schema,
         None => return Err(Error::TableDoesNotExist { table_name: table_name.clone() }),
     };
 
-    // Open CSV file in append mode
-    let file_path = get_table_data_path(&database.configuration.default_schema, &table_name);
-    let modified_file = OpenOptions::new()
-        .write(true).append(true).open(file_path)
-        .map_err(|_| Error::TableDoesNotExist { table_name: table_name.clone() })?;
+    let records: Vec<StringRecord> = complete_inserted_rows.iter()
+        .map(|row| StringRecord::from(row.iter().map(|r| r.value.as_deref().unwrap_or(NULL_VALUE)).collect::<Vec<&str>>()))
+        .collect();
 
-    let mut wtr = WriterBuilder::new().has_headers(false).from_writer(modified_file);
+    // Write rows through the table's storage backend
+    get_table_storage(table_schema).insert_rows(&schema_name, table_schema, &records)?;
 
-    // Iterate over complete_inserted_rows and write to CSV
-    for row in &complete_inserted_rows {
-        let row_value: Vec<String> = row.into_iter().map(|r| r.value.clone()).collect();
-        wtr.write_record(&row_value)
-            .map_err(|_| Error::FailedTableWrite { table_name: table_name.clone() })?;
-    }
-
-    wtr.flush().map_err(|_| Error::FailedTableWrite { table_name: table_name.clone() })?;
-
-    // Write index offsets
-    index_updater::update_indexes_on_insert(&complete_inserted_rows, &database.configuration.default_schema, &table_name, table_schema)?;
-
-    Ok(table_name)
+    Ok(ResultSet::from_rows_affected(complete_inserted_rows.len() as u64, format!("Success: records have been inserted into table {}.", table_name)))
 }
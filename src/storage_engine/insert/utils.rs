@@ -1,6 +1,6 @@
-use sqlparser::ast::{Expr, Query, SetExpr, Value, Values};
+use sqlparser::ast::{Query, SetExpr, Values};
 
-use crate::{database::types::InsertedRowColumn, shared::errors::Error};
+use crate::{database::types::InsertedRowColumn, network_protocol::types::CellValue, shared::errors::Error, storage_engine::expressions::{expression_evaluator::evaluate_expression, operation_handler::get_storage_value, types::RowContext}};
 
 
 pub fn extract_inserted_rows(source: &Option<Box<Query>>, column_names: &[String]) -> Result<Vec<Vec<InsertedRowColumn>>, Error> {
@@ -11,15 +11,15 @@ pub fn extract_inserted_rows(source: &Option<Box<Query>>, column_names: &[String
             for row in rows {
                 let mut row_values = Vec::new();
                 for (i, expr) in row.iter().enumerate() {
-                    let value_str = match expr {
-                        Expr::Value(val) => value_to_string(val),
-                        _ => "".to_string(),
+                    let value = match evaluate_expression(expr, &RowContext::empty())? {
+                        CellValue::Null => None,
+                        value => Some(get_storage_value(&value)),
                     };
 
                     if let Some(column_name) = column_names.get(i) {
                         row_values.push(InsertedRowColumn {
                             name: column_name.clone(),
-                            value: value_str,
+                            value,
                         });
                     } else {
                         return Err(Error::GenericUnsupported);
@@ -33,18 +33,10 @@ pub fn extract_inserted_rows(source: &Option<Box<Query>>, column_names: &[String
     Ok(all_rows_values)
 }
 
-fn value_to_string(value: &Value) -> String {
-    match value {
-        Value::Number(n, _) => n.clone(),
-        Value::SingleQuotedString(s) => s.clone(),
-        Value::Boolean(b) => b.to_string(),
-        _ => "".to_string(),
-    }
-}
-
 
-pub fn get_inserted_column_values_from_rows(rows: &Vec<Vec<InsertedRowColumn>>, column_name: &String) -> Result<Vec<Option<String>>, Error> {
-    let mut column_values: Vec<Option<String>> = Vec::new();
+// None where the row leaves the column out, Some(None) where it sets it to NULL
+pub fn get_inserted_column_values_from_rows(rows: &Vec<Vec<InsertedRowColumn>>, column_name: &String) -> Result<Vec<Option<Option<String>>>, Error> {
+    let mut column_values: Vec<Option<Option<String>>> = Vec::new();
 
     for row in rows {
         let ins_column = row.into_iter().find(|ins_column| &ins_column.name == column_name);
@@ -1,4 +1,4 @@
-use sqlparser::ast::{Ident, ObjectName, Query};
+use sqlparser::ast::{Ident, Query};
 
 use crate::database::types::{Database, InsertedRowColumn};
 use crate::database::utils::find_database_table;
@@ -7,15 +7,11 @@ use crate::storage_engine::validation;
 
 use super::utils;
 
-pub async fn validate_insert_into(database: &Database, name: &ObjectName, columns: &Vec<Ident>, source: &Option<Box<Query>>) -> Result<(String, Vec<String>, Vec<Vec<InsertedRowColumn>>), Error> {
-    // Unwrap table name
-    let first_identifier = name.0.first().ok_or(Error::MissingTableName)?;
-    let table_name = first_identifier.value.clone();
-    
+pub async fn validate_insert_into(database: &Database, schema_name: &String, table_name: &String, columns: &Vec<Ident>, source: &Option<Box<Query>>) -> Result<(Vec<String>, Vec<Vec<InsertedRowColumn>>), Error> {
     // Validate table exists
-    validation::common::validate_table_exists(database, &table_name)?;
+    validation::common::validate_table_exists(database, schema_name, table_name)?;
 
-    let table_schema = match find_database_table(database, &table_name) {
+    let table_schema = match find_database_table(database, schema_name, table_name) {
         Some(schema) => schema,
         None => return Err(Error::TableDoesNotExist { table_name: table_name.clone() }),
     };
@@ -34,7 +30,7 @@ pub async fn validate_insert_into(database: &Database, name: &ObjectName, column
 
     validation::column_types::validate_column_types(table_schema, &inserted_rows)?;
 
-    let complete_inserted_rows = validation::column_constraints::validate_column_constraints(&inserted_rows, &database.configuration.default_schema, table_schema, true).await?;
+    let complete_inserted_rows = validation::column_constraints::validate_column_constraints(&inserted_rows, schema_name, table_schema, true).await?;
 
-    Ok((table_name, column_names, complete_inserted_rows))
+    Ok((column_names, complete_inserted_rows))
 }
\ No newline at end of file
@@ -8,5 +8,9 @@ pub mod foreign_key;
 pub mod validation;
 pub mod index;
 pub mod filters;
+pub mod expressions;
+pub mod planner;
+pub mod statistics;
 pub mod utils;
-pub mod trigger;
\ No newline at end of file
+pub mod trigger;
+pub mod storage_backend;
\ No newline at end of file
@@ -0,0 +1,190 @@
+use sqlparser::ast::Statement;
+
+use crate::database::database_loader;
+use crate::database::types::DataType;
+use crate::network_protocol::types::{CellValue, ResultColumn, ResultSet};
+use crate::shared::errors::Error;
+use crate::storage_engine::select::subquery_handler;
+use crate::storage_engine::select::types::{JoinKind, SortKey, TableSource};
+use crate::transaction_manager::lock_manager;
+
+use super::types::{JoinMethod, NodeStatistics, PlanNode, ScanMethod, ScanNode};
+use super::{plan_builder, plan_executor};
+
+// One row per line of the plan tree; ANALYZE runs the query and adds the rows each node produced
+pub async fn handle_explain(statement: &Statement, analyze: bool) -> Result<ResultSet, Error> {
+    let query = match statement {
+        Statement::Query(query) => query,
+        _ => return Err(Error::GenericUnsupported),
+    };
+
+    let database = database_loader::get_database()?;
+    let plan = plan_builder::build_select_plan(&database, query)?;
+
+    let statistics = match analyze {
+        true => {
+            let _read_guards = lock_manager::lock_tables_for_read(&subquery_handler::get_query_tables(&database, query)?).await?;
+            Some(subquery_handler::with_subquery_cache(|| plan_executor::execute_plan(&plan))?.1)
+        },
+        false => None,
+    };
+
+    let mut lines: Vec<String> = Vec::new();
+    format_node(&plan.root, &plan.sources, statistics.as_ref(), 0, &mut lines);
+
+    let columns = vec![ResultColumn { name: String::from("QUERY PLAN"), data_type: DataType::Text }];
+    let rows = lines.into_iter().map(|line| vec![CellValue::Text(line)]).collect();
+    Ok(ResultSet::from_rows(columns, rows))
+}
+
+/*
+ * Formatting, close to Postgres' text format:
+ *  Node (actual rows=N time=T ms)
+ *    Detail: ...
+ *    ->  Child node
+ */
+fn format_node(node: &PlanNode, sources: &[TableSource], statistics: Option<&NodeStatistics>, depth: usize, lines: &mut Vec<String>) {
+    let child_statistics = |position: usize| statistics.and_then(|statistics| statistics.children.get(position));
+
+    let (title, details) = match node {
+        PlanNode::Scan(scan) => format_scan(scan, sources),
+        PlanNode::Join { join_step, method, left, right } => {
+            let join_kind = match join_step.kind {
+                JoinKind::Inner => "Inner",
+                JoinKind::Left => "Left",
+                JoinKind::Right => "Right",
+                JoinKind::Full => "Full",
+                JoinKind::Cross => "Cross",
+            };
+            let title = match method {
+                JoinMethod::IndexNestedLoop { .. } => format!("Index Nested Loop {} Join", join_kind),
+                JoinMethod::Hash { .. } => format!("Hash {} Join", join_kind),
+                JoinMethod::NestedLoop => format!("Nested Loop {} Join", join_kind),
+            };
+            let mut details: Vec<String> = Vec::new();
+            if let JoinMethod::Hash { keys } = method {
+                details.push(format!("Hash Cond: {}", keys.iter().map(|(left, right)| format!("{} = {}", left, right)).collect::<Vec<String>>().join(" AND ")));
+            }
+            if let Some(constraint) = &join_step.constraint {
+                details.push(format!("Join Filter: {}", constraint));
+            }
+
+            push_node_lines(&title, &details, statistics, depth, lines);
+            format_node(left, sources, child_statistics(0), depth + 1, lines);
+
+            // Index lookups run once per row, so they have no statistics of their own
+            match method {
+                JoinMethod::IndexNestedLoop { key, column_name, index_name, .. } => {
+                    let source = &sources[right.source_index];
+                    let mut right_details = vec![format!("Index Cond: {}.{} = {}", source.name, column_name, key)];
+                    right_details.extend(right.filter.iter().map(|filter| format!("Filter: {}", filter)));
+                    push_node_lines(&format!("Index Lookup using {} on {}", index_name, format_source(source)), &right_details, None, depth + 1, lines);
+                },
+                _ => {
+                    let (right_title, right_details) = format_scan(right, sources);
+                    push_node_lines(&right_title, &right_details, child_statistics(1), depth + 1, lines);
+                },
+            }
+            return;
+        },
+        PlanNode::Filter { condition, .. } => (String::from("Filter"), vec![format!("Filter: {}", condition)]),
+        PlanNode::Aggregate { group_by, having, .. } => {
+            let mut details: Vec<String> = Vec::new();
+            if !group_by.is_empty() {
+                details.push(format!("Group Key: {}", join_expressions(group_by.iter())));
+            }
+            if let Some(having) = having {
+                details.push(format!("Filter: {}", having));
+            }
+            let title = if group_by.is_empty() { "Aggregate" } else { "HashAggregate" };
+            (String::from(title), details)
+        },
+        PlanNode::Sort { sort_keys, count, .. } => {
+            let title = match count {
+                Some(count) => format!("Top-N Sort (rows={})", count),
+                None => String::from("Sort"),
+            };
+            (title, vec![format!("Sort Key: {}", sort_keys.iter().map(format_sort_key).collect::<Vec<String>>().join(", "))])
+        },
+        PlanNode::Limit { limit, offset, .. } => {
+            let mut details: Vec<String> = Vec::new();
+            if let Some(limit) = limit {
+                details.push(format!("Limit: {}", limit));
+            }
+            if *offset > 0 {
+                details.push(format!("Offset: {}", offset));
+            }
+            (String::from("Limit"), details)
+        },
+        PlanNode::Projection { projected_expressions, .. } => {
+            (String::from("Projection"), vec![format!("Output: {}", join_expressions(projected_expressions.iter().map(|(_, expr)| expr)))])
+        },
+    };
+
+    push_node_lines(&title, &details, statistics, depth, lines);
+    match node {
+        PlanNode::Filter { input, .. } | PlanNode::Aggregate { input, .. } | PlanNode::Sort { input, .. } |
+        PlanNode::Limit { input, .. } | PlanNode::Projection { input, .. } => format_node(input, sources, child_statistics(0), depth + 1, lines),
+        PlanNode::Scan(_) | PlanNode::Join { .. } => {},
+    }
+}
+
+fn format_scan(scan: &ScanNode, sources: &[TableSource]) -> (String, Vec<String>) {
+    let source = &sources[scan.source_index];
+    let mut details: Vec<String> = Vec::new();
+
+    let title = match &scan.method {
+        ScanMethod::Sequential => format!("Seq Scan on {}", format_source(source)),
+        ScanMethod::Index(index_lookup) => {
+            details.push(format!("Index Cond: {}", index_lookup.get_condition()));
+            let index_names: Vec<String> = index_lookup.get_index_names().into_iter().cloned().collect();
+            format!("Index Scan using {} on {}", index_names.join(", "), format_source(source))
+        },
+    };
+    if let Some(filter) = &scan.filter {
+        details.push(format!("Filter: {}", filter));
+    }
+
+    (title, details)
+}
+
+fn push_node_lines(title: &str, details: &[String], statistics: Option<&NodeStatistics>, depth: usize, lines: &mut Vec<String>) {
+    // Children are drawn with an arrow under their parent's title
+    let (title_prefix, detail_indent) = match depth {
+        0 => (String::new(), 2),
+        _ => (format!("{}->  ", " ".repeat(6 * depth - 4)), 6 * depth + 2),
+    };
+
+    let actual = match statistics {
+        Some(statistics) => format!(" (actual rows={} time={:.3} ms)", statistics.rows, statistics.duration.as_secs_f64() * 1000.0),
+        None => String::new(),
+    };
+    lines.push(format!("{}{}{}", title_prefix, title, actual));
+    for detail in details {
+        lines.push(format!("{}{}", " ".repeat(detail_indent), detail));
+    }
+}
+
+fn format_source(source: &TableSource) -> String {
+    match source.name == source.table_schema.name {
+        true => format!("{}.{}", source.schema_name, source.table_schema.name),
+        false => format!("{}.{} {}", source.schema_name, source.table_schema.name, source.name),
+    }
+}
+
+fn format_sort_key(sort_key: &SortKey) -> String {
+    let mut formatted_key = sort_key.expr.to_string();
+    if !sort_key.ascending {
+        formatted_key.push_str(" DESC");
+    }
+    // Only shown when not the default for the direction
+    if sort_key.nulls_first == sort_key.ascending {
+        formatted_key.push_str(if sort_key.nulls_first { " NULLS FIRST" } else { " NULLS LAST" });
+    }
+
+    formatted_key
+}
+
+fn join_expressions<'a>(exprs: impl Iterator<Item = &'a sqlparser::ast::Expr>) -> String {
+    exprs.map(|expr| expr.to_string()).collect::<Vec<String>>().join(", ")
+}
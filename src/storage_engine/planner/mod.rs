@@ -0,0 +1,5 @@
+pub mod plan_builder;
+pub mod plan_executor;
+pub mod operators;
+pub mod explain_handler;
+pub mod types;
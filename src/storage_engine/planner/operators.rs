@@ -0,0 +1,390 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use csv::StringRecord;
+use sqlparser::ast::Expr;
+
+use crate::database::types::DataType;
+use crate::network_protocol::types::{CellValue, ResultColumn};
+use crate::shared::errors::Error;
+use crate::storage_engine::expressions::{expression_evaluator, types::RowContext};
+use crate::storage_engine::index::btree::BTree;
+use crate::storage_engine::select::join_handler::{self, JoinBuildSide};
+use crate::storage_engine::select::types::{JoinStep, JoinedRow, SortKey, TableSource};
+use crate::storage_engine::select::{aggregate_handler, record_handler};
+use crate::storage_engine::storage_backend::table_storage::RowStream;
+
+use super::types::{NodeStatistics, PlanRow};
+
+/*
+ * Pull-based operators: each call returns the next row, reading from the inputs only as far as needed.
+ * Aggregates, sorts and the build side of hash joins read their whole input first
+ */
+pub trait Operator {
+    fn next_row(&mut self) -> Result<Option<PlanRow>, Error>;
+
+    // Statistics of the operators this one reads from, in plan order
+    fn child_statistics(&self) -> Vec<NodeStatistics>;
+}
+
+pub type BoxedOperator<'a> = TrackedOperator<dyn Operator + Send + 'a>;
+
+// Counts the rows an operator returns and the time spent in it, for EXPLAIN ANALYZE
+pub struct TrackedOperator<O: Operator + ?Sized> {
+    operator: Box<O>,
+    rows: usize,
+    duration: Duration,
+}
+
+impl<O: Operator + ?Sized> TrackedOperator<O> {
+    pub fn new(operator: Box<O>) -> Self {
+        TrackedOperator { operator, rows: 0, duration: Duration::ZERO }
+    }
+
+    pub fn next_row(&mut self) -> Result<Option<PlanRow>, Error> {
+        let start = Instant::now();
+        let row = self.operator.next_row();
+        self.duration += start.elapsed();
+
+        if let Ok(Some(_)) = row {
+            self.rows += 1;
+        }
+        row
+    }
+
+    pub fn statistics(&self) -> NodeStatistics {
+        NodeStatistics { rows: self.rows, duration: self.duration, children: self.operator.child_statistics() }
+    }
+
+    pub fn operator(&self) -> &O {
+        &self.operator
+    }
+}
+
+// Records of one table passing the filters that only involve it
+pub struct ScanOperator<'a> {
+    pub sources: &'a [TableSource<'a>],
+    pub source_index: usize,
+    pub rows: RowStream,
+    pub filter: &'a Option<Expr>,
+}
+
+impl Operator for ScanOperator<'_> {
+    fn next_row(&mut self) -> Result<Option<PlanRow>, Error> {
+        let source = &self.sources[self.source_index];
+
+        for row in self.rows.by_ref() {
+            let (_, record) = row?;
+            let passes = match self.filter {
+                Some(filter) => expression_evaluator::evaluate_condition(filter, &RowContext::from_source(source, Some(&record)))?,
+                None => true,
+            };
+            if passes {
+                return Ok(Some(PlanRow::Joined(join_handler::create_joined_row(self.sources.len(), self.source_index, record))));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn child_statistics(&self) -> Vec<NodeStatistics> {
+        Vec::new()
+    }
+}
+
+// Hash join, or nested loop join without equi-join keys: the joined table is read once, then each left row is matched against it
+pub struct HashJoinOperator<'a> {
+    sources: &'a [TableSource<'a>],
+    join_step: &'a JoinStep,
+    equi_join_keys: &'a [(Expr, Expr)],
+    left: BoxedOperator<'a>,
+    right: BoxedOperator<'a>,
+    build_side: Option<JoinBuildSide>,
+    pending_rows: VecDeque<JoinedRow>,
+    is_left_done: bool,
+}
+
+impl<'a> HashJoinOperator<'a> {
+    pub fn new(sources: &'a [TableSource<'a>], join_step: &'a JoinStep, equi_join_keys: &'a [(Expr, Expr)], left: BoxedOperator<'a>, right: BoxedOperator<'a>) -> Self {
+        HashJoinOperator { sources, join_step, equi_join_keys, left, right, build_side: None, pending_rows: VecDeque::new(), is_left_done: false }
+    }
+}
+
+impl Operator for HashJoinOperator<'_> {
+    fn next_row(&mut self) -> Result<Option<PlanRow>, Error> {
+        if !self.is_left_done && self.build_side.is_none() {
+            let mut records: Vec<StringRecord> = Vec::new();
+            while let Some(row) = self.right.next_row()? {
+                records.push(get_source_record(row, self.join_step.source_index)?);
+            }
+            self.build_side = Some(join_handler::build_join_side(records, self.sources, self.join_step, self.equi_join_keys)?);
+        }
+
+        loop {
+            if let Some(row) = self.pending_rows.pop_front() {
+                return Ok(Some(PlanRow::Joined(row)));
+            }
+            if self.is_left_done {
+                return Ok(None);
+            }
+
+            match self.left.next_row()? {
+                Some(row) => {
+                    if let Some(build_side) = self.build_side.as_mut() {
+                        self.pending_rows.extend(join_handler::probe_join_side(build_side, get_joined_row(row)?, self.sources, self.join_step, self.equi_join_keys)?);
+                    }
+                },
+                // Records left unmatched are only known once every left row was seen
+                None => {
+                    self.is_left_done = true;
+                    if let Some(build_side) = self.build_side.take() {
+                        self.pending_rows.extend(join_handler::get_unmatched_rows(build_side, self.sources, self.join_step));
+                    }
+                },
+            }
+        }
+    }
+
+    fn child_statistics(&self) -> Vec<NodeStatistics> {
+        vec![self.left.statistics(), self.right.statistics()]
+    }
+}
+
+// Index nested loop join: the joined table's records are looked up for each left row
+pub struct IndexJoinOperator<'a> {
+    sources: &'a [TableSource<'a>],
+    join_step: &'a JoinStep,
+    key: (&'a Expr, &'a DataType),
+    filter: &'a Option<Expr>,
+    index: BTree,
+    left: BoxedOperator<'a>,
+    pending_rows: VecDeque<JoinedRow>,
+}
+
+impl<'a> IndexJoinOperator<'a> {
+    pub fn new(sources: &'a [TableSource<'a>], join_step: &'a JoinStep, key: (&'a Expr, &'a DataType), filter: &'a Option<Expr>, index: BTree, left: BoxedOperator<'a>) -> Self {
+        IndexJoinOperator { sources, join_step, key, filter, index, left, pending_rows: VecDeque::new() }
+    }
+}
+
+impl Operator for IndexJoinOperator<'_> {
+    fn next_row(&mut self) -> Result<Option<PlanRow>, Error> {
+        loop {
+            if let Some(row) = self.pending_rows.pop_front() {
+                return Ok(Some(PlanRow::Joined(row)));
+            }
+
+            match self.left.next_row()? {
+                Some(row) => self.pending_rows.extend(join_handler::lookup_joined_rows(&mut self.index, get_joined_row(row)?, self.sources, self.join_step, self.key, self.filter)?),
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn child_statistics(&self) -> Vec<NodeStatistics> {
+        vec![self.left.statistics()]
+    }
+}
+
+pub struct FilterOperator<'a> {
+    pub sources: &'a [TableSource<'a>],
+    pub condition: &'a Expr,
+    pub input: BoxedOperator<'a>,
+}
+
+impl Operator for FilterOperator<'_> {
+    fn next_row(&mut self) -> Result<Option<PlanRow>, Error> {
+        while let Some(row) = self.input.next_row()? {
+            let row = get_joined_row(row)?;
+            if expression_evaluator::evaluate_condition(self.condition, &RowContext::from_sources(self.sources, Some(&row)))? {
+                return Ok(Some(PlanRow::Joined(row)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn child_statistics(&self) -> Vec<NodeStatistics> {
+        vec![self.input.statistics()]
+    }
+}
+
+// Groups are only complete once the whole input was read
+pub struct AggregateOperator<'a> {
+    sources: &'a [TableSource<'a>],
+    output_expressions: &'a [(String, Expr)],
+    group_by: &'a [Expr],
+    having: &'a Option<Expr>,
+    input: BoxedOperator<'a>,
+    output_rows: Option<std::vec::IntoIter<Vec<CellValue>>>,
+}
+
+impl<'a> AggregateOperator<'a> {
+    pub fn new(sources: &'a [TableSource<'a>], output_expressions: &'a [(String, Expr)], group_by: &'a [Expr], having: &'a Option<Expr>, input: BoxedOperator<'a>) -> Self {
+        AggregateOperator { sources, output_expressions, group_by, having, input, output_rows: None }
+    }
+}
+
+impl Operator for AggregateOperator<'_> {
+    fn next_row(&mut self) -> Result<Option<PlanRow>, Error> {
+        if self.output_rows.is_none() {
+            let mut rows: Vec<JoinedRow> = Vec::new();
+            while let Some(row) = self.input.next_row()? {
+                rows.push(get_joined_row(row)?);
+            }
+            let result_set = aggregate_handler::aggregate_records(&rows, self.output_expressions, self.group_by, self.having, self.sources)?;
+            self.output_rows = Some(result_set.rows.into_iter());
+        }
+
+        Ok(self.output_rows.as_mut().and_then(|output_rows| output_rows.next()).map(PlanRow::Output))
+    }
+
+    fn child_statistics(&self) -> Vec<NodeStatistics> {
+        vec![self.input.statistics()]
+    }
+}
+
+// With a count, only that many rows are kept while reading the input
+pub struct SortOperator<'a> {
+    sources: &'a [TableSource<'a>],
+    sort_keys: &'a [SortKey],
+    sort_columns: &'a [usize],
+    count: Option<usize>,
+    input: BoxedOperator<'a>,
+    output_rows: Option<std::vec::IntoIter<PlanRow>>,
+}
+
+impl<'a> SortOperator<'a> {
+    pub fn new(sources: &'a [TableSource<'a>], sort_keys: &'a [SortKey], sort_columns: &'a [usize], count: Option<usize>, input: BoxedOperator<'a>) -> Self {
+        SortOperator { sources, sort_keys, sort_columns, count, input, output_rows: None }
+    }
+}
+
+impl Operator for SortOperator<'_> {
+    fn next_row(&mut self) -> Result<Option<PlanRow>, Error> {
+        if self.output_rows.is_none() {
+            let mut keyed_rows: Vec<(Vec<CellValue>, PlanRow)> = Vec::new();
+            while let Some(row) = self.input.next_row()? {
+                let sort_values = match &row {
+                    PlanRow::Joined(joined_row) => record_handler::get_sort_values(joined_row, self.sort_keys, self.sources)?,
+                    PlanRow::Output(values) => self.sort_columns.iter().map(|&column_index| values[column_index].clone()).collect(),
+                };
+                keyed_rows.push((sort_values, row));
+
+                // Cut back to the first rows whenever the buffer doubles
+                if let Some(count) = self.count {
+                    if keyed_rows.len() > count * 2 {
+                        keyed_rows = sort_keyed_rows(keyed_rows, self.sort_keys, Some(count));
+                    }
+                }
+            }
+
+            let sorted_rows: Vec<PlanRow> = sort_keyed_rows(keyed_rows, self.sort_keys, self.count).into_iter().map(|(_, row)| row).collect();
+            self.output_rows = Some(sorted_rows.into_iter());
+        }
+
+        Ok(self.output_rows.as_mut().and_then(|output_rows| output_rows.next()))
+    }
+
+    fn child_statistics(&self) -> Vec<NodeStatistics> {
+        vec![self.input.statistics()]
+    }
+}
+
+// Stops reading the input once enough rows were returned
+pub struct LimitOperator<'a> {
+    limit: Option<usize>,
+    rows_to_skip: usize,
+    returned_rows: usize,
+    input: BoxedOperator<'a>,
+}
+
+impl<'a> LimitOperator<'a> {
+    pub fn new(limit: Option<usize>, offset: usize, input: BoxedOperator<'a>) -> Self {
+        LimitOperator { limit, rows_to_skip: offset, returned_rows: 0, input }
+    }
+}
+
+impl Operator for LimitOperator<'_> {
+    fn next_row(&mut self) -> Result<Option<PlanRow>, Error> {
+        if self.limit.is_some_and(|limit| self.returned_rows >= limit) {
+            return Ok(None);
+        }
+
+        while self.rows_to_skip > 0 {
+            if self.input.next_row()?.is_none() {
+                return Ok(None);
+            }
+            self.rows_to_skip -= 1;
+        }
+
+        let row = self.input.next_row()?;
+        if row.is_some() {
+            self.returned_rows += 1;
+        }
+        Ok(row)
+    }
+
+    fn child_statistics(&self) -> Vec<NodeStatistics> {
+        vec![self.input.statistics()]
+    }
+}
+
+pub struct ProjectionOperator<'a> {
+    sources: &'a [TableSource<'a>],
+    projected_expressions: &'a [(String, Expr)],
+    input: BoxedOperator<'a>,
+    columns: Option<Vec<ResultColumn>>,
+}
+
+impl<'a> ProjectionOperator<'a> {
+    pub fn new(sources: &'a [TableSource<'a>], projected_expressions: &'a [(String, Expr)], input: BoxedOperator<'a>) -> Self {
+        ProjectionOperator { sources, projected_expressions, input, columns: None }
+    }
+
+    pub fn get_columns(&self) -> Vec<ResultColumn> {
+        self.columns.clone().unwrap_or_default()
+    }
+}
+
+impl Operator for ProjectionOperator<'_> {
+    fn next_row(&mut self) -> Result<Option<PlanRow>, Error> {
+        let row = self.input.next_row()?;
+
+        // Typed once the input has started, so that its errors come first
+        if self.columns.is_none() {
+            self.columns = Some(record_handler::get_result_columns(self.projected_expressions, self.sources)?);
+        }
+
+        match row {
+            Some(PlanRow::Joined(row)) => Ok(Some(PlanRow::Output(record_handler::project_record(&row, self.projected_expressions, self.sources)?))),
+            // Aggregated rows are already computed, only the hidden sort columns are dropped
+            Some(PlanRow::Output(mut values)) => {
+                values.truncate(self.projected_expressions.len());
+                Ok(Some(PlanRow::Output(values)))
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn child_statistics(&self) -> Vec<NodeStatistics> {
+        vec![self.input.statistics()]
+    }
+}
+
+fn get_joined_row(row: PlanRow) -> Result<JoinedRow, Error> {
+    match row {
+        PlanRow::Joined(row) => Ok(row),
+        PlanRow::Output(_) => Err(Error::GenericUnsupported),
+    }
+}
+
+fn get_source_record(row: PlanRow, source_index: usize) -> Result<StringRecord, Error> {
+    get_joined_row(row)?.swap_remove(source_index).ok_or(Error::GenericUnsupported)
+}
+
+fn sort_keyed_rows(keyed_rows: Vec<(Vec<CellValue>, PlanRow)>, sort_keys: &[SortKey], count: Option<usize>) -> Vec<(Vec<CellValue>, PlanRow)> {
+    let sort_values: Vec<Vec<CellValue>> = keyed_rows.iter().map(|(values, _)| values.clone()).collect();
+
+    record_handler::sort_rows(keyed_rows, sort_values, sort_keys, count)
+}
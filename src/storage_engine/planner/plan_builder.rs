@@ -0,0 +1,165 @@
+use sqlparser::ast::{BinaryOperator, Expr, Query};
+
+use crate::database::types::Database;
+use crate::shared::errors::Error;
+use crate::storage_engine::expressions::expression_walker;
+use crate::storage_engine::index::index_reader::{self, IndexLookup};
+use crate::storage_engine::select::types::{JoinKind, JoinStep, SelectParameters, SortKey, TableSource};
+use crate::storage_engine::select::{aggregate_handler, join_handler, utils, validator};
+use crate::storage_engine::statistics::statistics_reader;
+use crate::storage_engine::utils::ast_unwrapper;
+
+use super::types::{JoinMethod, PlanNode, ScanMethod, ScanNode, SelectPlan};
+
+// Rows fetched through an index are read one by one, costing about as much as this many rows of a sequential scan
+const INDEX_FETCH_COST: f64 = 4.0;
+
+/*
+ * Plan shape, from the root: Projection, Limit, Sort, Aggregate, Filter, then the joins over the table scans.
+ * Filters are pushed down to the scans where possible, and each scan picks its indexes per predicate,
+ * falling back to a sequential scan when ANALYZE statistics show the lookup would read much of the table
+ */
+pub fn build_select_plan<'a>(database: &'a Database, query: &Query) -> Result<SelectPlan<'a>, Error> {
+    let SelectParameters { from, projection, filters, group_by, having, order_by, limit_value, offset_value } = ast_unwrapper::unwrap_select_query(query)?;
+
+    // Resolve the tables and the output columns
+    let (sources, join_steps) = join_handler::get_table_sources(database, &from)?;
+    let projected_expressions = utils::get_projected_expressions(&projection, &sources)?;
+    let sort_keys = utils::get_sort_keys(order_by, &projected_expressions)?;
+
+    validator::validate_select_query(&sources, &join_steps, &projected_expressions, &filters, &sort_keys)?;
+
+    // Read and join the tables
+    let (source_filters, remaining_filter) = split_filters(&filters, &sources, &join_steps)?;
+    let mut scans = source_filters.into_iter().enumerate()
+        .map(|(source_index, filter)| create_scan(&sources[source_index], source_index, filter));
+
+    let mut root = match scans.next() {
+        Some(scan) => PlanNode::Scan(scan),
+        None => return Err(Error::MissingTableName),
+    };
+    // Join steps follow the sources, one per table after the first
+    for (join_step, mut right) in join_steps.into_iter().zip(scans) {
+        let method = join_handler::get_join_method(&sources, &join_step)?;
+        if let JoinMethod::IndexNestedLoop { .. } = method {
+            right.method = ScanMethod::Sequential; // Looked up per row instead
+        }
+        root = PlanNode::Join { join_step, method, left: Box::new(root), right: Box::new(right) };
+    }
+
+    if let Some(condition) = remaining_filter {
+        root = PlanNode::Filter { condition, input: Box::new(root) };
+    }
+
+    // Group, sort and paginate
+    let (output_expressions, sort_columns) = get_output_expressions(&projected_expressions, &sort_keys);
+    let count = limit_value.map(|limit| limit.saturating_add(offset_value));
+
+    if aggregate_handler::is_aggregate_query(&output_expressions, &group_by, &having) {
+        root = PlanNode::Aggregate { output_expressions, group_by, having, input: Box::new(root) };
+        if !sort_keys.is_empty() {
+            root = PlanNode::Sort { sort_keys, sort_columns, count, input: Box::new(root) };
+        }
+    } else if !sort_keys.is_empty() {
+        root = PlanNode::Sort { sort_keys, sort_columns: Vec::new(), count, input: Box::new(root) };
+    }
+
+    if limit_value.is_some() || offset_value > 0 {
+        root = PlanNode::Limit { limit: limit_value, offset: offset_value, input: Box::new(root) };
+    }
+
+    root = PlanNode::Projection { projected_expressions, input: Box::new(root) };
+
+    Ok(SelectPlan { sources, root })
+}
+
+// WHERE conditions involving a single table, per table, and the ones left for after the joins
+fn split_filters(filters: &Option<Expr>, sources: &[TableSource], join_steps: &[JoinStep]) -> Result<(Vec<Option<Expr>>, Option<Expr>), Error> {
+    let mut source_filters: Vec<Option<Expr>> = vec![None; sources.len()];
+    let mut remaining_filter: Option<Expr> = None;
+
+    let conditions = match filters {
+        Some(filters) => split_conjunction(filters),
+        None => Vec::new(),
+    };
+    for condition in conditions {
+        let mut referenced_sources = join_handler::get_referenced_sources(condition, sources)?;
+        referenced_sources.sort_unstable();
+        referenced_sources.dedup();
+
+        match referenced_sources.as_slice() {
+            // Subqueries may reference any of the tables, so they are run on the joined rows
+            _ if expression_walker::contains_subquery(condition) => add_condition(&mut remaining_filter, condition),
+            // A table whose rows an outer join may replace by NULLs must be filtered after it
+            [source_index] if !is_nullable(*source_index, join_steps) => add_condition(&mut source_filters[*source_index], condition),
+            _ => add_condition(&mut remaining_filter, condition),
+        }
+    }
+
+    Ok((source_filters, remaining_filter))
+}
+
+fn split_conjunction(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::BinaryOp { left, op: BinaryOperator::And, right } => {
+            let mut conditions = split_conjunction(left);
+            conditions.extend(split_conjunction(right));
+            conditions
+        },
+        Expr::Nested(nested_expr) if matches!(nested_expr.as_ref(), Expr::BinaryOp { op: BinaryOperator::And, .. }) => split_conjunction(nested_expr),
+        _ => vec![expr],
+    }
+}
+
+fn add_condition(filter: &mut Option<Expr>, condition: &Expr) {
+    *filter = Some(match filter.take() {
+        Some(filter) => Expr::BinaryOp { left: Box::new(filter), op: BinaryOperator::And, right: Box::new(condition.clone()) },
+        None => condition.clone(),
+    });
+}
+
+fn is_nullable(source_index: usize, join_steps: &[JoinStep]) -> bool {
+    join_steps.iter().any(|join_step| match join_step.kind {
+        JoinKind::Left => join_step.source_index == source_index,
+        JoinKind::Right => source_index < join_step.source_index,
+        JoinKind::Full => source_index <= join_step.source_index,
+        JoinKind::Inner | JoinKind::Cross => false,
+    })
+}
+
+fn create_scan(source: &TableSource, source_index: usize, filter: Option<Expr>) -> ScanNode {
+    let index_lookup = filter.as_ref().and_then(|filter| index_reader::find_index_lookup(source.table_schema, filter));
+    let method = match index_lookup {
+        Some(index_lookup) if is_index_cheaper(source, &index_lookup) => ScanMethod::Index(index_lookup),
+        _ => ScanMethod::Sequential,
+    };
+
+    ScanNode { source_index, method, filter }
+}
+
+// Compares the rows the lookup is estimated to find with the table's size; without statistics, indexes are always used
+fn is_index_cheaper(source: &TableSource, index_lookup: &IndexLookup) -> bool {
+    match statistics_reader::read_table_statistics(&source.schema_name, &source.table_schema.name) {
+        Some(statistics) => statistics_reader::estimate_lookup_rows(&statistics, source.table_schema, index_lookup) * INDEX_FETCH_COST < statistics.row_count as f64,
+        None => true,
+    }
+}
+
+// The projected expressions followed by the sort expressions not already among them, with the sort keys' positions
+fn get_output_expressions(projected_expressions: &[(String, Expr)], sort_keys: &[SortKey]) -> (Vec<(String, Expr)>, Vec<usize>) {
+    let mut output_expressions = projected_expressions.to_vec();
+    let mut sort_columns: Vec<usize> = Vec::new();
+
+    for sort_key in sort_keys {
+        let column_index = match output_expressions.iter().position(|(_, expr)| expr == &sort_key.expr) {
+            Some(column_index) => column_index,
+            None => {
+                output_expressions.push((String::new(), sort_key.expr.clone()));
+                output_expressions.len() - 1
+            },
+        };
+        sort_columns.push(column_index);
+    }
+
+    (output_expressions, sort_columns)
+}
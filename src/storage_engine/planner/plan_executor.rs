@@ -0,0 +1,99 @@
+use crate::network_protocol::types::{CellValue, ResultColumn, ResultSet};
+use crate::shared::errors::Error;
+use crate::storage_engine::index::index_reader;
+use crate::storage_engine::select::table_reader;
+use crate::storage_engine::select::types::TableSource;
+
+use super::operators::{
+    AggregateOperator, BoxedOperator, FilterOperator, HashJoinOperator, IndexJoinOperator, LimitOperator, Operator,
+    ProjectionOperator, ScanOperator, SortOperator, TrackedOperator,
+};
+use super::types::{JoinMethod, NodeStatistics, PlanNode, PlanRow, ScanNode, SelectPlan};
+
+// A running plan, producing its output rows one at a time
+pub struct PlanExecution<'a> {
+    root: TrackedOperator<ProjectionOperator<'a>>,
+}
+
+impl PlanExecution<'_> {
+    pub fn next_row(&mut self) -> Result<Option<Vec<CellValue>>, Error> {
+        match self.root.next_row()? {
+            Some(PlanRow::Output(values)) => Ok(Some(values)),
+            Some(PlanRow::Joined(_)) => Err(Error::GenericUnsupported),
+            None => Ok(None),
+        }
+    }
+
+    // Known once the first row was requested
+    pub fn get_columns(&self) -> Vec<ResultColumn> {
+        self.root.operator().get_columns()
+    }
+
+    pub fn statistics(&self) -> NodeStatistics {
+        self.root.statistics()
+    }
+}
+
+pub fn start_plan<'a>(plan: &'a SelectPlan<'a>) -> Result<PlanExecution<'a>, Error> {
+    match &plan.root {
+        PlanNode::Projection { projected_expressions, input } => {
+            let input = create_operator(input, &plan.sources)?;
+            Ok(PlanExecution { root: TrackedOperator::new(Box::new(ProjectionOperator::new(&plan.sources, projected_expressions, input))) })
+        },
+        // Plans always end in a projection
+        _ => Err(Error::GenericUnsupported),
+    }
+}
+
+// Run the plan to completion, returning the result with what each node did
+pub fn execute_plan(plan: &SelectPlan) -> Result<(ResultSet, NodeStatistics), Error> {
+    let mut execution = start_plan(plan)?;
+
+    let mut rows: Vec<Vec<CellValue>> = Vec::new();
+    while let Some(row) = execution.next_row()? {
+        rows.push(row);
+    }
+
+    Ok((ResultSet::from_rows(execution.get_columns(), rows), execution.statistics()))
+}
+
+fn create_operator<'a>(node: &'a PlanNode, sources: &'a [TableSource<'a>]) -> Result<BoxedOperator<'a>, Error> {
+    let operator = match node {
+        PlanNode::Scan(scan) => create_scan_operator(scan, sources)?,
+        PlanNode::Join { join_step, method, left, right } => {
+            let left = create_operator(left, sources)?;
+
+            match method {
+                JoinMethod::IndexNestedLoop { key, data_type, index_name, .. } => {
+                    let source = &sources[join_step.source_index];
+                    let index = index_reader::open_index(&source.schema_name, &source.table_schema.name, index_name)?;
+                    track(IndexJoinOperator::new(sources, join_step, (key, data_type), &right.filter, index, left))
+                },
+                JoinMethod::Hash { keys } => track(HashJoinOperator::new(sources, join_step, keys, left, create_scan_operator(right, sources)?)),
+                JoinMethod::NestedLoop => track(HashJoinOperator::new(sources, join_step, &[], left, create_scan_operator(right, sources)?)),
+            }
+        },
+        PlanNode::Filter { condition, input } => track(FilterOperator { sources, condition, input: create_operator(input, sources)? }),
+        PlanNode::Aggregate { output_expressions, group_by, having, input } => {
+            track(AggregateOperator::new(sources, output_expressions, group_by, having, create_operator(input, sources)?))
+        },
+        PlanNode::Sort { sort_keys, sort_columns, count, input } => {
+            track(SortOperator::new(sources, sort_keys, sort_columns, *count, create_operator(input, sources)?))
+        },
+        PlanNode::Limit { limit, offset, input } => track(LimitOperator::new(*limit, *offset, create_operator(input, sources)?)),
+        // Only the root projects
+        PlanNode::Projection { .. } => return Err(Error::GenericUnsupported),
+    };
+
+    Ok(operator)
+}
+
+fn create_scan_operator<'a>(scan: &'a ScanNode, sources: &'a [TableSource<'a>]) -> Result<BoxedOperator<'a>, Error> {
+    let rows = table_reader::stream_source(&sources[scan.source_index], &scan.method)?;
+
+    Ok(track(ScanOperator { sources, source_index: scan.source_index, rows, filter: &scan.filter }))
+}
+
+fn track<'a>(operator: impl Operator + Send + 'a) -> BoxedOperator<'a> {
+    TrackedOperator::new(Box::new(operator))
+}
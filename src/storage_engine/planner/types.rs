@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use sqlparser::ast::Expr;
+
+use crate::database::types::DataType;
+use crate::network_protocol::types::CellValue;
+use crate::storage_engine::index::index_reader::IndexLookup;
+use crate::storage_engine::select::types::{JoinStep, JoinedRow, SortKey, TableSource};
+
+pub struct SelectPlan<'a> {
+    pub sources: Vec<TableSource<'a>>,
+    pub root: PlanNode,
+}
+
+/*
+ * Plan tree: each node reads the rows its children produce
+ */
+pub enum PlanNode {
+    Scan(ScanNode),
+    Join {
+        join_step: JoinStep,
+        method: JoinMethod,
+        left: Box<PlanNode>,
+        right: Box<ScanNode>, // The joined table, looked up per row with an index nested loop
+    },
+    Filter {
+        condition: Expr,
+        input: Box<PlanNode>,
+    },
+    Aggregate {
+        output_expressions: Vec<(String, Expr)>, // Selected columns, then hidden ones only used to sort
+        group_by: Vec<Expr>,
+        having: Option<Expr>,
+        input: Box<PlanNode>,
+    },
+    Sort {
+        sort_keys: Vec<SortKey>,
+        sort_columns: Vec<usize>, // Output columns holding the keys, once aggregated
+        count: Option<usize>, // Only the first rows are needed, for LIMIT plus OFFSET
+        input: Box<PlanNode>,
+    },
+    Limit {
+        limit: Option<usize>,
+        offset: usize,
+        input: Box<PlanNode>,
+    },
+    Projection {
+        projected_expressions: Vec<(String, Expr)>,
+        input: Box<PlanNode>,
+    },
+}
+
+// Reads one table, with the filters that only involve it
+pub struct ScanNode {
+    pub source_index: usize,
+    pub method: ScanMethod,
+    pub filter: Option<Expr>,
+}
+
+pub enum ScanMethod {
+    Sequential,
+    Index(IndexLookup),
+}
+
+pub enum JoinMethod {
+    IndexNestedLoop { key: Box<Expr>, column_name: String, data_type: DataType, index_name: String }, // Key computed from the earlier tables
+    Hash { keys: Vec<(Expr, Expr)> }, // Equal expressions on the earlier tables and the joined one
+    NestedLoop,
+}
+
+// Row passed between operators: joined table records, then output values once aggregated or projected
+pub enum PlanRow {
+    Joined(JoinedRow),
+    Output(Vec<CellValue>),
+}
+
+// What a node actually did, for EXPLAIN ANALYZE
+pub struct NodeStatistics {
+    pub rows: usize,
+    pub duration: Duration,
+    pub children: Vec<NodeStatistics>,
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use sqlparser::ast::Expr;
+
+use crate::network_protocol::types::{CellValue, ResultColumn, ResultSet};
+use crate::shared::errors::Error;
+use crate::storage_engine::expressions::{aggregate_functions, expression_evaluator, expression_walker, type_resolver, types::RowContext};
+
+use super::types::{JoinedRow, TableSource};
+
+pub fn is_aggregate_query(projected_expressions: &[(String, Expr)], group_by: &[Expr], having: &Option<Expr>) -> bool {
+    !group_by.is_empty()
+        || having.is_some()
+        || projected_expressions.iter().any(|(_, expr)| !expression_walker::find_expressions(expr, &aggregate_functions::is_aggregate).is_empty())
+}
+
+// One output row per group of rows sharing the GROUP BY values, or a single one without GROUP BY
+pub fn aggregate_records(
+    rows: &[JoinedRow],
+    projected_expressions: &[(String, Expr)],
+    group_by: &[Expr],
+    having: &Option<Expr>,
+    sources: &[TableSource],
+) -> Result<ResultSet, Error> {
+    // Validate and type the output columns
+    let output_expressions: Vec<&Expr> = projected_expressions.iter().map(|(_, expr)| expr).chain(having.as_ref()).collect();
+    let type_context = RowContext::from_sources(sources, None);
+    for expr in group_by {
+        type_resolver::get_expression_type(expr, &type_context)?;
+        if let Some(aggregate) = expression_walker::find_expressions(expr, &aggregate_functions::is_aggregate).first() {
+            return Err(Error::AggregateNotAllowed { function_name: aggregate.to_string() });
+        }
+    }
+    for expr in output_expressions.iter() {
+        validate_grouped_expression(expr, group_by)?;
+    }
+
+    let mut columns: Vec<ResultColumn> = Vec::new();
+    for (name, expr) in projected_expressions {
+        let data_type = type_resolver::get_expression_type(expr, &type_context)?;
+        columns.push(ResultColumn { name: name.clone(), data_type });
+    }
+
+    // Aggregates computed once per group, even if used several times
+    let mut aggregates: Vec<&Expr> = Vec::new();
+    for expr in output_expressions.iter() {
+        for aggregate in expression_walker::find_expressions(expr, &aggregate_functions::is_aggregate) {
+            if !aggregates.contains(&aggregate) {
+                aggregates.push(aggregate);
+            }
+        }
+    }
+
+    let mut output_rows: Vec<Vec<CellValue>> = Vec::new();
+    for group in group_rows(rows, group_by, sources)? {
+        let group_rows: Vec<RowContext> = group.iter().map(|row| RowContext::from_sources(sources, Some(row))).collect();
+
+        // Grouped columns are read from the group's first row
+        let mut context = RowContext::from_sources(sources, group.first().copied());
+        for aggregate in aggregates.iter() {
+            let function = match aggregate {
+                Expr::Function(function) => function,
+                _ => continue,
+            };
+            context.aggregate_values.push(((*aggregate).clone(), aggregate_functions::compute_aggregate(function, &group_rows)?));
+        }
+
+        if let Some(having) = having {
+            if !expression_evaluator::evaluate_condition(having, &context)? {
+                continue;
+            }
+        }
+
+        let row = projected_expressions.iter()
+            .map(|(_, expr)| expression_evaluator::evaluate_expression(expr, &context))
+            .collect::<Result<Vec<CellValue>, Error>>()?;
+        output_rows.push(row);
+    }
+
+    Ok(ResultSet::from_rows(columns, output_rows))
+}
+
+// Groups in the order their first row appears
+fn group_rows<'a>(rows: &'a [JoinedRow], group_by: &[Expr], sources: &[TableSource]) -> Result<Vec<Vec<&'a JoinedRow>>, Error> {
+    // Without GROUP BY, all rows (even none) form a single group
+    if group_by.is_empty() {
+        return Ok(vec![rows.iter().collect()]);
+    }
+
+    let mut groups: Vec<Vec<&JoinedRow>> = Vec::new();
+    let mut group_positions: HashMap<String, usize> = HashMap::new();
+
+    for row in rows {
+        let context = RowContext::from_sources(sources, Some(row));
+        let key = group_by.iter()
+            .map(|expr| expression_evaluator::evaluate_expression(expr, &context))
+            .collect::<Result<Vec<CellValue>, Error>>()?;
+
+        match group_positions.get(&format!("{:?}", key)) {
+            Some(&position) => groups[position].push(row),
+            None => {
+                group_positions.insert(format!("{:?}", key), groups.len());
+                groups.push(vec![row]);
+            },
+        }
+    }
+
+    Ok(groups)
+}
+
+// Columns outside aggregates must be part of the GROUP BY
+fn validate_grouped_expression(expr: &Expr, group_by: &[Expr]) -> Result<(), Error> {
+    if group_by.contains(expr) || aggregate_functions::is_aggregate(expr) {
+        return Ok(());
+    }
+
+    match expr {
+        Expr::Identifier(ident) => Err(Error::ColumnNotGrouped { column_name: ident.value.clone() }),
+        Expr::CompoundIdentifier(idents) => Err(Error::ColumnNotGrouped { column_name: idents.iter().map(|ident| ident.value.clone()).collect::<Vec<String>>().join(".") }),
+        _ => {
+            for child in expression_walker::get_child_expressions(expr) {
+                validate_grouped_expression(child, group_by)?;
+            }
+            Ok(())
+        },
+    }
+}
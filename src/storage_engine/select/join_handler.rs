@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::ops::Bound;
+
+use csv::StringRecord;
+use sqlparser::ast::{BinaryOperator, Expr, Ident, JoinConstraint, JoinOperator, TableFactor, TableWithJoins};
+
+use crate::database::types::{DataType, Database};
+use crate::database::utils::{find_database_table, get_schema_and_table_name};
+use crate::network_protocol::types::CellValue;
+use crate::shared::errors::Error;
+use crate::storage_engine::expressions::{expression_evaluator, expression_walker, operation_handler, type_resolver, types::RowContext};
+use crate::storage_engine::index::{btree::{BTree, IndexValue}, index_reader};
+use crate::storage_engine::planner::types::JoinMethod;
+use crate::storage_engine::storage_backend::table_storage::get_table_storage;
+
+use super::types::{JoinKind, JoinStep, JoinedRow, TableSource};
+
+// Tables of the FROM clause in order, with the joins combining them
+pub fn get_table_sources<'a>(database: &'a Database, from: &[TableWithJoins]) -> Result<(Vec<TableSource<'a>>, Vec<JoinStep>), Error> {
+    let mut sources: Vec<TableSource> = Vec::new();
+    let mut join_steps: Vec<JoinStep> = Vec::new();
+
+    for table_with_joins in from {
+        // Comma-separated tables are cross joined
+        if !sources.is_empty() {
+            join_steps.push(JoinStep { kind: JoinKind::Cross, source_index: sources.len(), constraint: None });
+        }
+        add_table_source(database, &table_with_joins.relation, &mut sources)?;
+
+        for join in &table_with_joins.joins {
+            add_table_source(database, &join.relation, &mut sources)?;
+
+            let (kind, constraint) = match &join.join_operator {
+                JoinOperator::Inner(constraint) => (JoinKind::Inner, constraint),
+                JoinOperator::LeftOuter(constraint) => (JoinKind::Left, constraint),
+                JoinOperator::RightOuter(constraint) => (JoinKind::Right, constraint),
+                JoinOperator::FullOuter(constraint) => (JoinKind::Full, constraint),
+                JoinOperator::CrossJoin => (JoinKind::Cross, &JoinConstraint::None),
+                _ => return Err(Error::UnsupportedSelectClause),
+            };
+            let constraint = get_join_condition(constraint, &sources)?;
+
+            join_steps.push(JoinStep { kind, source_index: sources.len() - 1, constraint });
+        }
+    }
+
+    Ok((sources, join_steps))
+}
+
+fn add_table_source<'a>(database: &'a Database, relation: &TableFactor, sources: &mut Vec<TableSource<'a>>) -> Result<(), Error> {
+    let (name, alias) = match relation {
+        TableFactor::Table { name, alias, .. } => (name, alias),
+        _ => return Err(Error::UnsupportedSelectClause),
+    };
+
+    let (schema_name, table_name) = get_schema_and_table_name(database, name)?;
+    let table_schema = find_database_table(database, &schema_name, &table_name)
+        .ok_or_else(|| Error::TableDoesNotExist { table_name: table_name.clone() })?;
+    let source_name = alias.as_ref().map(|alias| alias.name.value.clone()).unwrap_or(table_name);
+
+    if sources.iter().any(|source| source.name == source_name) {
+        return Err(Error::DuplicateTableReference { table_name: source_name });
+    }
+
+    sources.push(TableSource { name: source_name, schema_name, table_schema });
+    Ok(())
+}
+
+// USING (columns) becomes an equality between the new table and the latest earlier one having each column
+fn get_join_condition(constraint: &JoinConstraint, sources: &[TableSource]) -> Result<Option<Expr>, Error> {
+    match constraint {
+        JoinConstraint::On(expr) => Ok(Some(expr.clone())),
+        JoinConstraint::Using(column_names) => {
+            let (right_source, left_sources) = sources.split_last().ok_or(Error::MissingTableName)?;
+            let mut condition: Option<Expr> = None;
+
+            for column_name in column_names {
+                let left_source = left_sources.iter().rev()
+                    .find(|source| source.table_schema.columns.iter().any(|column| column.name == column_name.value))
+                    .ok_or_else(|| Error::ColumnDoesNotExist { column_name: column_name.value.clone(), table_name: right_source.name.clone() })?;
+
+                let equality = Expr::BinaryOp {
+                    left: Box::new(Expr::CompoundIdentifier(vec![Ident::new(&left_source.name), column_name.clone()])),
+                    op: BinaryOperator::Eq,
+                    right: Box::new(Expr::CompoundIdentifier(vec![Ident::new(&right_source.name), column_name.clone()])),
+                };
+                condition = Some(match condition {
+                    Some(condition) => Expr::BinaryOp { left: Box::new(condition), op: BinaryOperator::And, right: Box::new(equality) },
+                    None => equality,
+                });
+            }
+
+            Ok(condition)
+        },
+        JoinConstraint::None => Ok(None),
+        JoinConstraint::Natural => Err(Error::UnsupportedSelectClause),
+    }
+}
+
+// Index nested loop when an equality is on an indexed column of the joined table, else hash join on the equalities
+pub fn get_join_method(sources: &[TableSource], join_step: &JoinStep) -> Result<JoinMethod, Error> {
+    let equi_join_keys = match &join_step.constraint {
+        Some(constraint) => get_equi_join_keys(constraint, sources, join_step.source_index)?,
+        None => Vec::new(),
+    };
+
+    // Unmatched rows of the joined table can't be found through lookups
+    if matches!(join_step.kind, JoinKind::Inner | JoinKind::Left) {
+        let source = &sources[join_step.source_index];
+        let type_context = RowContext::from_sources(sources, None);
+
+        for (left, right) in equi_join_keys.iter() {
+            let (_, column) = match right {
+                Expr::Identifier(ident) => type_context.find_column(std::slice::from_ref(ident))?,
+                Expr::CompoundIdentifier(idents) => type_context.find_column(idents)?,
+                _ => continue,
+            };
+            if let Some(index_name) = index_reader::find_leading_column_index(source.table_schema, &column.name) {
+                return Ok(JoinMethod::IndexNestedLoop { key: Box::new(left.clone()), column_name: column.name.clone(), data_type: column.data_type.clone(), index_name });
+            }
+        }
+    }
+
+    match equi_join_keys.is_empty() {
+        true => Ok(JoinMethod::NestedLoop),
+        false => Ok(JoinMethod::Hash { keys: equi_join_keys }),
+    }
+}
+
+/*
+ * Join execution, one row of the earlier tables at a time
+ */
+// The joined table's records, hashed on their key values unless joined by a nested loop
+pub struct JoinBuildSide {
+    records: Vec<StringRecord>,
+    hashed_records: Option<HashMap<String, Vec<usize>>>,
+    matched_records: Vec<bool>,
+}
+
+pub fn build_join_side(records: Vec<StringRecord>, sources: &[TableSource], join_step: &JoinStep, equi_join_keys: &[(Expr, Expr)]) -> Result<JoinBuildSide, Error> {
+    let hashed_records = match equi_join_keys.is_empty() {
+        true => None,
+        false => {
+            let right_expressions: Vec<&Expr> = equi_join_keys.iter().map(|(_, right)| right).collect();
+            let mut hashed_records: HashMap<String, Vec<usize>> = HashMap::new();
+            for (position, record) in records.iter().enumerate() {
+                let row = create_joined_row(sources.len(), join_step.source_index, record.clone());
+                if let Some(key) = get_join_key(&right_expressions, sources, &row)? {
+                    hashed_records.entry(key).or_default().push(position);
+                }
+            }
+            Some(hashed_records)
+        },
+    };
+    let matched_records = vec![false; records.len()];
+
+    Ok(JoinBuildSide { records, hashed_records, matched_records })
+}
+
+// Hash join on the key pairs, or a nested loop comparing the row with every record without them
+pub fn probe_join_side(build_side: &mut JoinBuildSide, row: JoinedRow, sources: &[TableSource], join_step: &JoinStep, equi_join_keys: &[(Expr, Expr)]) -> Result<Vec<JoinedRow>, Error> {
+    let candidate_positions: Vec<usize> = match &build_side.hashed_records {
+        Some(hashed_records) => {
+            let left_expressions: Vec<&Expr> = equi_join_keys.iter().map(|(left, _)| left).collect();
+            match get_join_key(&left_expressions, sources, &row)? {
+                Some(key) => hashed_records.get(&key).cloned().unwrap_or_default(),
+                None => Vec::new(),
+            }
+        },
+        None => (0..build_side.records.len()).collect(),
+    };
+
+    let mut joined_rows: Vec<JoinedRow> = Vec::new();
+    for position in candidate_positions {
+        if let Some(joined_row) = combine_rows(&row, sources, join_step, &build_side.records[position])? {
+            joined_rows.push(joined_row);
+            build_side.matched_records[position] = true;
+        }
+    }
+
+    // Unmatched rows on the outer side are kept with NULLs for the joined table
+    if joined_rows.is_empty() && matches!(join_step.kind, JoinKind::Left | JoinKind::Full) {
+        joined_rows.push(row);
+    }
+
+    Ok(joined_rows)
+}
+
+// Records no row matched, kept with NULLs for the earlier tables by RIGHT and FULL joins
+pub fn get_unmatched_rows(build_side: JoinBuildSide, sources: &[TableSource], join_step: &JoinStep) -> Vec<JoinedRow> {
+    if !matches!(join_step.kind, JoinKind::Right | JoinKind::Full) {
+        return Vec::new();
+    }
+
+    build_side.records.into_iter().zip(build_side.matched_records)
+        .filter(|(_, is_matched)| !is_matched)
+        .map(|(record, _)| create_joined_row(sources.len(), join_step.source_index, record))
+        .collect()
+}
+
+// Index nested loop join: the joined table's records are looked up by the row's key, then checked against its own filter
+pub fn lookup_joined_rows(
+    index: &mut BTree,
+    row: JoinedRow,
+    sources: &[TableSource],
+    join_step: &JoinStep,
+    (key, data_type): (&Expr, &DataType),
+    filter: &Option<Expr>,
+) -> Result<Vec<JoinedRow>, Error> {
+    let source = &sources[join_step.source_index];
+    let value = expression_evaluator::evaluate_expression(key, &RowContext::from_sources(sources, Some(&row)))?;
+
+    let mut joined_rows: Vec<JoinedRow> = Vec::new();
+    if value != CellValue::Null {
+        let index_key = vec![IndexValue::from_value(&operation_handler::get_storage_value(&operation_handler::cast_value(&value, data_type)?), data_type)];
+        let row_ids = index.range(Bound::Included(&index_key), Bound::Included(&index_key))?;
+
+        for (_, record) in get_table_storage(source.table_schema).fetch_rows(&source.schema_name, source.table_schema, &row_ids)? {
+            if let Some(filter) = filter {
+                if !expression_evaluator::evaluate_condition(filter, &RowContext::from_source(source, Some(&record)))? {
+                    continue;
+                }
+            }
+            if let Some(joined_row) = combine_rows(&row, sources, join_step, &record)? {
+                joined_rows.push(joined_row);
+            }
+        }
+    }
+
+    if joined_rows.is_empty() && matches!(join_step.kind, JoinKind::Left) {
+        joined_rows.push(row);
+    }
+
+    Ok(joined_rows)
+}
+
+// The row extended with the joined record, if they pass the join condition
+fn combine_rows(row: &JoinedRow, sources: &[TableSource], join_step: &JoinStep, record: &StringRecord) -> Result<Option<JoinedRow>, Error> {
+    let mut joined_row = row.clone();
+    joined_row[join_step.source_index] = Some(record.clone());
+
+    if let Some(constraint) = &join_step.constraint {
+        if !expression_evaluator::evaluate_condition(constraint, &RowContext::from_sources(sources, Some(&joined_row)))? {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(joined_row))
+}
+
+// Equalities of the join condition between earlier tables (left) and the joined one (right)
+fn get_equi_join_keys(constraint: &Expr, sources: &[TableSource], source_index: usize) -> Result<Vec<(Expr, Expr)>, Error> {
+    let mut equi_join_keys: Vec<(Expr, Expr)> = Vec::new();
+
+    match constraint {
+        Expr::BinaryOp { left, op: BinaryOperator::And, right } => {
+            equi_join_keys.extend(get_equi_join_keys(left, sources, source_index)?);
+            equi_join_keys.extend(get_equi_join_keys(right, sources, source_index)?);
+        },
+        Expr::Nested(nested_expr) => equi_join_keys.extend(get_equi_join_keys(nested_expr, sources, source_index)?),
+        Expr::BinaryOp { left, op: BinaryOperator::Eq, right } => {
+            let left_sources = get_referenced_sources(left, sources)?;
+            let right_sources = get_referenced_sources(right, sources)?;
+            let is_earlier = |positions: &[usize]| !positions.is_empty() && positions.iter().all(|&position| position < source_index);
+            let is_joined = |positions: &[usize]| !positions.is_empty() && positions.iter().all(|&position| position == source_index);
+
+            // Hashing requires both sides to compare the same way
+            let type_context = RowContext::from_sources(sources, None);
+            if !have_comparable_types(&type_resolver::get_expression_type(left, &type_context)?, &type_resolver::get_expression_type(right, &type_context)?) {
+                return Ok(equi_join_keys);
+            }
+
+            if is_earlier(&left_sources) && is_joined(&right_sources) {
+                equi_join_keys.push(((**left).clone(), (**right).clone()));
+            } else if is_joined(&left_sources) && is_earlier(&right_sources) {
+                equi_join_keys.push(((**right).clone(), (**left).clone()));
+            }
+        },
+        _ => {},
+    }
+
+    Ok(equi_join_keys)
+}
+
+// Positions of the sources whose columns the expression reads
+pub fn get_referenced_sources(expr: &Expr, sources: &[TableSource]) -> Result<Vec<usize>, Error> {
+    let context = RowContext::from_sources(sources, None);
+    let mut positions: Vec<usize> = Vec::new();
+
+    let is_column = |expr: &Expr| matches!(expr, Expr::Identifier(_) | Expr::CompoundIdentifier(_));
+    for column_expr in expression_walker::find_expressions(expr, &is_column) {
+        let (table_row, _) = match column_expr {
+            Expr::Identifier(ident) => context.find_column(std::slice::from_ref(ident))?,
+            Expr::CompoundIdentifier(idents) => context.find_column(idents)?,
+            _ => continue,
+        };
+        if let Some(position) = sources.iter().position(|source| source.name == table_row.name) {
+            positions.push(position);
+        }
+    }
+
+    Ok(positions)
+}
+
+// Hashable form of the key values, None if any is NULL since NULL never matches
+fn get_join_key(expressions: &[&Expr], sources: &[TableSource], row: &JoinedRow) -> Result<Option<String>, Error> {
+    let context = RowContext::from_sources(sources, Some(row));
+    let mut key_parts: Vec<String> = Vec::new();
+
+    for expr in expressions {
+        let key_part = match expression_evaluator::evaluate_expression(expr, &context)? {
+            CellValue::Null => return Ok(None),
+            // Integers and floats holding the same number must hash alike
+            CellValue::Integer(i) => format!("n:{}", i as f64),
+            CellValue::Float(f) => format!("n:{}", f),
+            CellValue::Boolean(b) => format!("b:{}", b),
+            CellValue::Text(s) => format!("t:{}", s),
+        };
+        key_parts.push(key_part);
+    }
+
+    Ok(Some(format!("{:?}", key_parts)))
+}
+
+fn have_comparable_types(left_type: &DataType, right_type: &DataType) -> bool {
+    let is_numeric = |data_type: &DataType| matches!(data_type, DataType::Integer | DataType::Float);
+
+    left_type == right_type || (is_numeric(left_type) && is_numeric(right_type))
+}
+
+pub fn create_joined_row(source_count: usize, source_index: usize, record: StringRecord) -> JoinedRow {
+    let mut row: JoinedRow = vec![None; source_count];
+    row[source_index] = Some(record);
+
+    row
+}
@@ -1,6 +1,9 @@
 pub mod select_handler;
+pub mod aggregate_handler;
+pub mod join_handler;
 pub mod table_reader;
 pub mod record_handler;
-mod validator;
+pub mod subquery_handler;
+pub mod validator;
 pub mod utils;
 pub mod types;
\ No newline at end of file
@@ -1,68 +1,106 @@
-use std::{collections::HashMap, fs::OpenOptions};
+use std::cmp::Ordering;
 
-use csv::{ReaderBuilder, StringRecord, Writer};
+use sqlparser::ast::Expr;
 
-use crate::{database::database_navigator::get_table_data_path, shared::errors::Error};
+use crate::{database::{constants::NULL_VALUE, types::DataType}, network_protocol::types::{CellValue, ResultColumn}, shared::errors::Error, storage_engine::expressions::{expression_evaluator, operation_handler, type_resolver, types::RowContext}};
 
-// Select columns
-pub fn select_fields(record: &StringRecord, indices: &[usize]) -> StringRecord {
-    let selected_fields: Vec<String> = indices.iter()
-        .filter_map(|&i| record.get(i).map(|s| s.trim().to_string()))
-        .collect();
-    StringRecord::from(selected_fields)
+use super::types::{JoinedRow, SortKey, TableSource};
+
+// Sort key values of a joined row
+pub fn get_sort_values(row: &JoinedRow, sort_keys: &[SortKey], sources: &[TableSource]) -> Result<Vec<CellValue>, Error> {
+    let context = RowContext::from_sources(sources, Some(row));
+
+    sort_keys.iter()
+        .map(|sort_key| expression_evaluator::evaluate_expression(&sort_key.expr, &context))
+        .collect()
 }
 
-// Sort
-pub fn sort_records(records: &mut Vec<StringRecord>, column_index: usize, ascending: bool) {
-    records.sort_by(|a, b| {
-        let a_val = a.get(column_index).unwrap_or_default();
-        let b_val = b.get(column_index).unwrap_or_default();
+// Sort rows on their key values, ties kept in input order.
+// With a count (LIMIT plus OFFSET), only the first rows are selected and sorted, not the whole input
+pub fn sort_rows<T>(rows: Vec<T>, sort_values: Vec<Vec<CellValue>>, sort_keys: &[SortKey], count: Option<usize>) -> Vec<T> {
+    let mut keyed_rows: Vec<(Vec<CellValue>, usize, T)> = sort_values.into_iter().zip(rows).enumerate()
+        .map(|(position, (values, row))| (values, position, row))
+        .collect();
+    if sort_keys.is_empty() {
+        return keyed_rows.into_iter().take(count.unwrap_or(usize::MAX)).map(|(_, _, row)| row).collect();
+    }
+    let compare = |a: &(Vec<CellValue>, usize, T), b: &(Vec<CellValue>, usize, T)| {
+        compare_sort_values(&a.0, &b.0, sort_keys).then(a.1.cmp(&b.1))
+    };
 
-        if ascending {
-            a_val.cmp(&b_val)
-        } else {
-            b_val.cmp(&a_val)
+    if let Some(count) = count {
+        if count == 0 {
+            return Vec::new();
+        }
+        if count < keyed_rows.len() {
+            keyed_rows.select_nth_unstable_by(count - 1, compare);
+            keyed_rows.truncate(count);
         }
-    });
+    }
+    keyed_rows.sort_unstable_by(compare);
+
+    keyed_rows.into_iter().map(|(_, _, row)| row).collect()
 }
 
-// Attach column keys to rows and serialize
-pub fn format_response(rows: Vec<StringRecord>, selected_headers: Vec<String>, indices: Vec<usize>) -> Result<String, Error> {
-    let mut structured_rows: Vec<HashMap<String, String>> = Vec::new();
-    
-    for row in rows {
-        let mut row_map: HashMap<String, String> = HashMap::new();
-        indices.iter().enumerate().for_each(|(i, &index)| {
-            if let Some(value) = row.get(i) {
-                let header = &selected_headers[index]; // Map selected headers based on indices
-                row_map.insert(header.clone(), value.to_string());
-            }
-        });
-        structured_rows.push(row_map);
+// Values compared by type, NULLs larger than any value unless NULLS FIRST says otherwise
+fn compare_sort_values(a: &[CellValue], b: &[CellValue], sort_keys: &[SortKey]) -> Ordering {
+    for ((a_val, b_val), sort_key) in a.iter().zip(b).zip(sort_keys) {
+        let ordering = match (a_val, b_val) {
+            (CellValue::Null, CellValue::Null) => Ordering::Equal,
+            (CellValue::Null, _) if sort_key.nulls_first => Ordering::Less,
+            (CellValue::Null, _) => Ordering::Greater,
+            (_, CellValue::Null) if sort_key.nulls_first => Ordering::Greater,
+            (_, CellValue::Null) => Ordering::Less,
+            (a_val, b_val) => {
+                let ordering = operation_handler::compare_values(a_val, b_val).ok().flatten().unwrap_or(Ordering::Equal);
+                if sort_key.ascending { ordering } else { ordering.reverse() }
+            },
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
     }
 
-    serde_json::to_string(&structured_rows)
-        .map_err(|e| Error::SerdeJsonError(e))
+    Ordering::Equal
 }
 
+// Names and types of the projected expressions
+pub fn get_result_columns(projected_expressions: &[(String, Expr)], sources: &[TableSource]) -> Result<Vec<ResultColumn>, Error> {
+    let type_context = RowContext::from_sources(sources, None);
+
+    projected_expressions.iter()
+        .map(|(name, expr)| Ok(ResultColumn { name: name.clone(), data_type: type_resolver::get_expression_type(expr, &type_context)? }))
+        .collect()
+}
 
-pub fn rewrite_records(records: &Vec<StringRecord>, schema_name: &String, table_name: &String) -> Result<(), Error> {
-    // Read from file
-    let file_path = get_table_data_path(schema_name, table_name);
-    let mut rdr = ReaderBuilder::new().has_headers(true).from_path(&file_path)?;
-    let headers = rdr.headers()?.clone(); // Clone the headers to use them later for writing
+// Evaluate the projected expressions on a joined row
+pub fn project_record(row: &JoinedRow, projected_expressions: &[(String, Expr)], sources: &[TableSource]) -> Result<Vec<CellValue>, Error> {
+    let context = RowContext::from_sources(sources, Some(row));
 
-    // Overwrite original file
-    let file = OpenOptions::new().write(true).truncate(true).open(&file_path).map_err(|e| Error::IOError(e))?;
-    let mut wtr = Writer::from_writer(file);
-    wtr.write_record(&headers)?;
+    projected_expressions.iter()
+        .map(|(_, expr)| expression_evaluator::evaluate_expression(expr, &context))
+        .collect()
+}
 
-    // Step 4: Write records to the new file
-    for record in records {
-        wtr.write_record(record.iter())?;
+pub fn get_cell_value(value: &str, data_type: &DataType) -> CellValue {
+    if value == NULL_VALUE {
+        return CellValue::Null;
     }
 
-    wtr.flush()?;
+    let typed_value = match data_type {
+        DataType::Integer => value.parse::<i64>().ok().map(CellValue::Integer),
+        DataType::Float => value.parse::<f64>().ok().map(CellValue::Float),
+        DataType::Boolean => value.parse::<bool>().ok().map(CellValue::Boolean),
+        DataType::Text => Some(CellValue::Text(operation_handler::unescape_storage_text(value).to_string())),
+    };
 
-    Ok(())
-}
\ No newline at end of file
+    // Values that don't match the column type: empty ones are missing, others are kept as text
+    typed_value.unwrap_or_else(|| {
+        if value.is_empty() {
+            CellValue::Null
+        } else {
+            CellValue::Text(operation_handler::unescape_storage_text(value).to_string())
+        }
+    })
+}
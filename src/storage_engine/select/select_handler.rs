@@ -1,55 +1,57 @@
-use csv::StringRecord;
 use sqlparser::ast::Query;
 
-use crate::database::{self, database_loader};
+use crate::database::database_loader;
+use crate::network_protocol::constants::RESPONSE_BATCH_SIZE;
+use crate::network_protocol::result_stream::{self, ResultChunk};
+use crate::network_protocol::types::{CellValue, ResultSet};
 use crate::shared::errors::Error;
-use crate::storage_engine::{filters::filter_column_finder, select::table_reader, utils::ast_unwrapper};
+use crate::storage_engine::planner::{plan_builder, plan_executor};
+use crate::transaction_manager::lock_manager;
 
-use super::{record_handler, types::SelectParameters, utils, validator};
+use super::subquery_handler::{self, SubqueryCache};
 
-pub async fn handle_select(query: &Query) -> Result<String, Error> {
-    let SelectParameters {table_name, columns, filters, order_column_name, ascending, limit_value } = ast_unwrapper::unwrap_select_query(query)?;
-
-    // Prepare: get database blueprint and necessary data from it
+pub async fn handle_select(query: &Query) -> Result<ResultSet, Error> {
+    // Prepare: get database blueprint and plan the query against it
     let database = database_loader::get_database()?;
-    let schema_name = database.configuration.default_schema.clone();
-    let table_schema = match database::utils::find_database_table(&database, &table_name) {
-        Some(schema) => schema,
-        None => return Err(Error::TableDoesNotExist { table_name: table_name.clone() }),
-    };
-    let headers = database::utils::get_headers_from_table_schema(table_schema);
-    let column_indices = utils::get_column_indices(&headers, &columns);
-    
-    // Validate query
-    validator::validate_select_query(&database, &table_name, &columns, &order_column_name)?;
-    
-    // Get columns used for filtering
-    let filter_columns = filter_column_finder::find_filter_columns(&filters)?;
-
-    // Use indexes only if all filter columns are indexed
-    let use_indexes = filter_column_finder::use_indexes(&filter_columns, table_schema);
-    
-    // Read from table and filter
-    let filtered_records = if use_indexes {
-        table_reader::read_table_with_indexes(&schema_name, &table_name, &filters, &filter_columns, true).await?
-    } else {
-        table_reader::read_table(&schema_name, &table_name, &filters, true).await?
+    let plan = plan_builder::build_select_plan(&database, query)?;
+
+    // Read from tables, the subqueries' included, without seeing a commit half-applied
+    let tables = subquery_handler::get_query_tables(&database, query)?;
+    let mut read_guards = lock_manager::lock_tables_for_read(&tables).await?;
+
+    let result_stream = match result_stream::get_current_result_stream() {
+        Some(result_stream) => result_stream,
+        None => {
+            let (result_set, _) = subquery_handler::with_subquery_cache(|| plan_executor::execute_plan(&plan))?;
+            return Ok(result_set);
+        }
     };
 
-    // Select specified columns
-    let mut rows_with_selected_fields: Vec<StringRecord> = filtered_records.iter()
-        .map(|record| record_handler::select_fields(record, &column_indices))
-        .collect();
-
-    // Sort
-    if let Some(column_name) = order_column_name {
-        let column_index = headers.iter().position(|header| header == &column_name)
-                                    .ok_or_else(|| Error::ColumnDoesNotExist { column_name: column_name.clone(), table_name: table_schema.name.clone() })?;
-        record_handler::sort_records(&mut rows_with_selected_fields, column_index, ascending);
+    // Rows are pulled a batch at a time, each batch sent to the client before the next is read
+    let commit_counts = lock_manager::get_commit_counts(&tables)?;
+    let mut subquery_cache = SubqueryCache::new();
+    let mut execution = subquery_handler::with_kept_subquery_cache(&mut subquery_cache, || plan_executor::start_plan(&plan))?;
+    loop {
+        let rows = subquery_handler::with_kept_subquery_cache(&mut subquery_cache, || -> Result<Vec<Vec<CellValue>>, Error> {
+            let mut rows: Vec<Vec<CellValue>> = Vec::new();
+            while rows.len() < RESPONSE_BATCH_SIZE {
+                match execution.next_row()? {
+                    Some(row) => rows.push(row),
+                    None => break,
+                }
+            }
+            Ok(rows)
+        })?;
+
+        // The last rows are returned as the statement's result
+        let result_set = ResultSet::from_rows(execution.get_columns(), rows);
+        if result_set.rows.len() < RESPONSE_BATCH_SIZE {
+            return Ok(result_set);
+        }
+
+        // A slow client mustn't hold back commits to the tables, so they're unlocked while the batch is sent
+        drop(read_guards);
+        result_stream::send_result_chunk(&result_stream, ResultChunk::Partial(result_set)).await?;
+        read_guards = lock_manager::relock_tables_for_read(&tables, &commit_counts).await?;
     }
-
-    // Apply limit
-    let rows: Vec<StringRecord> = rows_with_selected_fields.into_iter().take(limit_value.unwrap_or(usize::MAX)).collect();
-    
-    record_handler::format_response(rows, headers, column_indices)
 }
\ No newline at end of file
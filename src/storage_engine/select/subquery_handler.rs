@@ -0,0 +1,316 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use sqlparser::ast::{CastKind, Expr, GroupByExpr, Ident, JoinConstraint, JoinOperator, Query, Select, SelectItem, SetExpr, TableFactor, Value};
+
+use crate::database::database_loader;
+use crate::database::types::{DataType, Database};
+use crate::database::utils::get_schema_and_table_name;
+use crate::network_protocol::types::{CellValue, ResultColumn};
+use crate::shared::errors::Error;
+use crate::storage_engine::expressions::{expression_walker, types::RowContext};
+use crate::storage_engine::planner::{plan_builder, plan_executor, types::PlanNode};
+
+use super::{join_handler, record_handler};
+
+/*
+ * Subqueries are run once per outer row, with the outer columns they reference (correlated subqueries)
+ * replaced by the row's values. Results are kept for the rest of the statement, by the subquery's text
+ * once bound, so that uncorrelated subqueries run only once
+ */
+pub type SubqueryCache = HashMap<(String, Option<usize>), Arc<Vec<Vec<CellValue>>>>;
+
+tokio::task_local! {
+    static SUBQUERY_RESULTS: RefCell<SubqueryCache>;
+}
+
+pub fn with_subquery_cache<T>(f: impl FnOnce() -> T) -> T {
+    SUBQUERY_RESULTS.sync_scope(RefCell::new(HashMap::new()), f)
+}
+
+// For statements running in several steps, the results are kept in between
+pub fn with_kept_subquery_cache<T>(cache: &mut SubqueryCache, f: impl FnOnce() -> T) -> T {
+    SUBQUERY_RESULTS.sync_scope(RefCell::new(std::mem::take(cache)), || {
+        let result = f();
+        *cache = SUBQUERY_RESULTS.with(|results| results.take());
+        result
+    })
+}
+
+// EXISTS: whether the subquery finds any row
+pub fn subquery_exists(query: &Query, context: &RowContext) -> Result<bool, Error> {
+    Ok(!execute_subquery(query, context, Some(1))?.is_empty())
+}
+
+// Values of the subquery's single column; at most max_rows are read
+pub fn get_subquery_values(query: &Query, context: &RowContext, max_rows: Option<usize>) -> Result<Vec<CellValue>, Error> {
+    let rows = execute_subquery(query, context, max_rows)?;
+
+    rows.iter()
+        .map(|row| match row.as_slice() {
+            [value] => Ok(value.clone()),
+            _ => Err(Error::InvalidSubqueryColumnCount { column_count: row.len() }),
+        })
+        .collect()
+}
+
+// Output columns, planning the subquery against the outer tables without reading them
+pub fn get_subquery_columns(query: &Query, context: &RowContext) -> Result<Vec<ResultColumn>, Error> {
+    let database = database_loader::get_database()?;
+    let query = bind_outer_columns(&database, query, context)?;
+    let plan = plan_builder::build_select_plan(&database, &query)?;
+
+    match &plan.root {
+        PlanNode::Projection { projected_expressions, .. } => record_handler::get_result_columns(projected_expressions, &plan.sources),
+        _ => Err(Error::GenericUnsupported),
+    }
+}
+
+// The single column of a subquery used as a value or with IN
+pub fn get_subquery_column(query: &Query, context: &RowContext) -> Result<ResultColumn, Error> {
+    let mut columns = get_subquery_columns(query, context)?;
+
+    match columns.len() {
+        1 => Ok(columns.remove(0)),
+        column_count => Err(Error::InvalidSubqueryColumnCount { column_count }),
+    }
+}
+
+fn execute_subquery(query: &Query, context: &RowContext, max_rows: Option<usize>) -> Result<Arc<Vec<Vec<CellValue>>>, Error> {
+    let database = database_loader::get_database()?;
+    let query = bind_outer_columns(&database, query, context)?;
+
+    let key = (query.to_string(), max_rows);
+    let cached_rows = SUBQUERY_RESULTS.try_with(|results| results.borrow().get(&key).cloned()).ok().flatten();
+    if let Some(rows) = cached_rows {
+        return Ok(rows);
+    }
+
+    // Only the rows needed are pulled from the plan
+    let plan = plan_builder::build_select_plan(&database, &query)?;
+    let mut execution = plan_executor::start_plan(&plan)?;
+    let mut rows: Vec<Vec<CellValue>> = Vec::new();
+    while max_rows.is_none_or(|max_rows| rows.len() < max_rows) {
+        match execution.next_row()? {
+            Some(row) => rows.push(row),
+            None => break,
+        }
+    }
+
+    let rows = Arc::new(rows);
+    let _ = SUBQUERY_RESULTS.try_with(|results| results.borrow_mut().insert(key, rows.clone()));
+    Ok(rows)
+}
+
+/*
+ * Binding: columns that don't resolve in the subquery's tables, nor in those of the subqueries enclosing them,
+ * are looked up in the outer row and replaced by its value. NULLs are cast to the column's type,
+ * so that the bound subquery keeps its types when only resolving them
+ */
+fn bind_outer_columns(database: &Database, query: &Query, context: &RowContext) -> Result<Query, Error> {
+    let mut query = query.clone();
+    bind_query(database, &mut query, &[], context)?;
+
+    Ok(query)
+}
+
+fn bind_query(database: &Database, query: &mut Query, scopes: &[&RowContext], context: &RowContext) -> Result<(), Error> {
+    let select = get_select_mut(query)?;
+    let (sources, _) = join_handler::get_table_sources(database, &select.from)?;
+    let scope = RowContext::from_sources(&sources, None);
+
+    let mut scopes = scopes.to_vec();
+    scopes.push(&scope);
+
+    // ORDER BY is left out, as it may name output columns instead
+    for expr in get_select_expressions_mut(select) {
+        bind_expression(database, expr, &scopes, context)?;
+    }
+
+    Ok(())
+}
+
+fn bind_expression(database: &Database, expr: &mut Expr, scopes: &[&RowContext], context: &RowContext) -> Result<(), Error> {
+    let bound_value = match expr {
+        Expr::Identifier(ident) => get_outer_value(std::slice::from_ref(ident), scopes, context),
+        Expr::CompoundIdentifier(idents) => get_outer_value(idents, scopes, context),
+        Expr::InSubquery { expr: operand, subquery, .. } => {
+            bind_expression(database, operand, scopes, context)?;
+            return bind_query(database, subquery, scopes, context);
+        },
+        Expr::Exists { subquery, .. } | Expr::Subquery(subquery) => return bind_query(database, subquery, scopes, context),
+        _ => {
+            for child in expression_walker::get_child_expressions_mut(expr) {
+                bind_expression(database, child, scopes, context)?;
+            }
+            return Ok(());
+        },
+    };
+
+    if let Some(bound_value) = bound_value {
+        *expr = bound_value;
+    }
+    Ok(())
+}
+
+fn get_outer_value(idents: &[Ident], scopes: &[&RowContext], context: &RowContext) -> Option<Expr> {
+    // Ambiguous names are left for planning to report
+    let is_local = scopes.iter().any(|scope| !matches!(scope.find_column(idents), Err(Error::ColumnDoesNotExist { .. })));
+    if is_local {
+        return None;
+    }
+
+    let (table_row, column) = context.find_column(idents).ok()?;
+    Some(get_value_expression(table_row.get_value(column), &column.data_type))
+}
+
+fn get_value_expression(value: CellValue, data_type: &DataType) -> Expr {
+    let value = match value {
+        CellValue::Integer(i) => Value::Number(i.to_string(), false),
+        CellValue::Float(f) => Value::Number(format!("{:?}", f), false), // Keeps the decimal point of whole numbers
+        CellValue::Text(s) => Value::SingleQuotedString(s),
+        CellValue::Boolean(b) => Value::Boolean(b),
+        CellValue::Null => {
+            let sql_data_type = match data_type {
+                DataType::Integer => sqlparser::ast::DataType::BigInt(None),
+                DataType::Float => sqlparser::ast::DataType::DoublePrecision,
+                DataType::Text => sqlparser::ast::DataType::Text,
+                DataType::Boolean => sqlparser::ast::DataType::Boolean,
+            };
+            return Expr::Cast { kind: CastKind::Cast, expr: Box::new(Expr::Value(Value::Null)), data_type: sql_data_type, format: None };
+        },
+    };
+
+    Expr::Value(value)
+}
+
+/*
+ * Tables read by subqueries, to be locked along with the ones of the statement
+ */
+// Tables of a query and of all its subqueries, each once
+pub fn get_query_tables(database: &Database, query: &Query) -> Result<Vec<(String, String)>, Error> {
+    let mut tables: Vec<(String, String)> = Vec::new();
+    add_query_tables(database, query, &mut tables)?;
+
+    Ok(tables)
+}
+
+// Tables of the subqueries found in the expressions
+pub fn get_subquery_tables(database: &Database, exprs: &[&Expr]) -> Result<Vec<(String, String)>, Error> {
+    let mut tables: Vec<(String, String)> = Vec::new();
+    for expr in exprs {
+        add_expression_tables(database, expr, &mut tables)?;
+    }
+
+    Ok(tables)
+}
+
+fn add_query_tables(database: &Database, query: &Query, tables: &mut Vec<(String, String)>) -> Result<(), Error> {
+    let select = match query.body.as_ref() {
+        SetExpr::Select(select) => select,
+        _ => return Err(Error::UnsupportedSelectClause),
+    };
+
+    for table_with_joins in &select.from {
+        let relations = std::iter::once(&table_with_joins.relation).chain(table_with_joins.joins.iter().map(|join| &join.relation));
+        for relation in relations {
+            if let TableFactor::Table { name, .. } = relation {
+                let table = get_schema_and_table_name(database, name)?;
+                if !tables.contains(&table) {
+                    tables.push(table);
+                }
+            }
+        }
+    }
+
+    let order_by_exprs = query.order_by.iter().flat_map(|order_by| order_by.exprs.iter().map(|order_by_expr| &order_by_expr.expr));
+    for expr in get_select_expressions(select).into_iter().chain(order_by_exprs) {
+        add_expression_tables(database, expr, tables)?;
+    }
+
+    Ok(())
+}
+
+fn add_expression_tables(database: &Database, expr: &Expr, tables: &mut Vec<(String, String)>) -> Result<(), Error> {
+    for subquery_expr in expression_walker::find_expressions(expr, &expression_walker::is_subquery) {
+        match subquery_expr {
+            Expr::InSubquery { expr: operand, subquery, .. } => {
+                add_expression_tables(database, operand, tables)?;
+                add_query_tables(database, subquery, tables)?;
+            },
+            Expr::Exists { subquery, .. } | Expr::Subquery(subquery) => add_query_tables(database, subquery, tables)?,
+            _ => {},
+        }
+    }
+
+    Ok(())
+}
+
+/*
+ * Expressions of a SELECT: projection, join conditions, WHERE, GROUP BY and HAVING
+ */
+fn get_select_expressions(select: &Select) -> Vec<&Expr> {
+    let mut exprs: Vec<&Expr> = select.projection.iter()
+        .filter_map(|item| match item {
+            SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => Some(expr),
+            _ => None,
+        })
+        .collect();
+    for join in select.from.iter().flat_map(|table_with_joins| &table_with_joins.joins) {
+        if let Some(JoinConstraint::On(expr)) = get_join_constraint(&join.join_operator) {
+            exprs.push(expr);
+        }
+    }
+    exprs.extend(&select.selection);
+    if let GroupByExpr::Expressions(group_by, _) = &select.group_by {
+        exprs.extend(group_by);
+    }
+    exprs.extend(&select.having);
+
+    exprs
+}
+
+fn get_select_expressions_mut(select: &mut Select) -> Vec<&mut Expr> {
+    let mut exprs: Vec<&mut Expr> = select.projection.iter_mut()
+        .filter_map(|item| match item {
+            SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => Some(expr),
+            _ => None,
+        })
+        .collect();
+    for join in select.from.iter_mut().flat_map(|table_with_joins| &mut table_with_joins.joins) {
+        if let Some(JoinConstraint::On(expr)) = get_join_constraint_mut(&mut join.join_operator) {
+            exprs.push(expr);
+        }
+    }
+    exprs.extend(&mut select.selection);
+    if let GroupByExpr::Expressions(group_by, _) = &mut select.group_by {
+        exprs.extend(group_by);
+    }
+    exprs.extend(&mut select.having);
+
+    exprs
+}
+
+fn get_select_mut(query: &mut Query) -> Result<&mut Select, Error> {
+    match query.body.as_mut() {
+        SetExpr::Select(select) => Ok(select),
+        _ => Err(Error::UnsupportedSelectClause),
+    }
+}
+
+fn get_join_constraint(join_operator: &JoinOperator) -> Option<&JoinConstraint> {
+    match join_operator {
+        JoinOperator::Inner(constraint) | JoinOperator::LeftOuter(constraint) |
+        JoinOperator::RightOuter(constraint) | JoinOperator::FullOuter(constraint) => Some(constraint),
+        _ => None,
+    }
+}
+
+fn get_join_constraint_mut(join_operator: &mut JoinOperator) -> Option<&mut JoinConstraint> {
+    match join_operator {
+        JoinOperator::Inner(constraint) | JoinOperator::LeftOuter(constraint) |
+        JoinOperator::RightOuter(constraint) | JoinOperator::FullOuter(constraint) => Some(constraint),
+        _ => None,
+    }
+}
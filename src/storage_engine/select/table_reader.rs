@@ -1,77 +1,86 @@
-use csv::{ReaderBuilder, StringRecord};
+use csv::StringRecord;
 use sqlparser::ast::Expr;
-use std::fs::File;
 
-use crate::database::database_navigator::{self, get_table_data_path};
+use crate::database::database_navigator;
 use crate::database::types::TableSchema;
 use crate::shared::errors::Error;
 use crate::shared::file_manager;
 use crate::storage_engine::filters::filter_manager;
 use crate::storage_engine::index::index_reader;
+use crate::storage_engine::planner::types::ScanMethod;
+use crate::storage_engine::storage_backend::table_storage::{get_table_storage, RowId, RowStream};
 
-pub async fn read_table(
+use super::types::TableSource;
+
+// Rows passing (or, if include is false, failing) the filters, looked up through indexes when possible
+pub fn read_table(
     schema_name: &String,
-    table_name: &String,
+    table_schema: &TableSchema,
     filters: &Option<Expr>,
     include: bool,
-) -> Result<Vec<StringRecord>, Error> {
-    // Read from file
-    let file_path = get_table_data_path(schema_name, &table_name);
-    let file = File::open(file_path).map_err(|e| Error::IOError(e))?;
-    let mut rdr: csv::Reader<File> = ReaderBuilder::new().has_headers(true).from_reader(file);
+) -> Result<Vec<(RowId, StringRecord)>, Error> {
+    if include {
+        if let Some(row_ids) = index_reader::find_row_ids(schema_name, table_schema, filters)? {
+            return read_table_with_indexes(schema_name, table_schema, filters, &row_ids);
+        }
+    }
 
-    // Trim spaces in CSV file and find indices
-    let headers = match rdr.headers() {
-        Ok(headers) => headers.iter().map(|h| h.trim().to_string()).collect::<Vec<String>>(),
-        Err(_) => return Err(Error::FailedTableRead { table_name: table_name.clone() }),
-    };
+    let rows = get_table_storage(table_schema).stream_rows(schema_name, table_schema)?;
 
-    // Perform filtering and select specified fields
-    let rows = filter_manager::filter_all_records(&mut rdr, &headers, filters, include)?;
-    
-    Ok(rows)
+    filter_rows(rows, table_schema, filters, include)
 }
 
-
-pub async fn read_table_with_indexes(
-    schema_name: &String,
-    table_name: &String,
+// Fetch only the rows found in the indexes, checking the full filters on them
+pub fn read_table_with_indexes(
+    schema_name: &str,
+    table_schema: &TableSchema,
     filters: &Option<Expr>,
-    filter_columns: &Vec<String>,
-    include: bool,
-) -> Result<Vec<StringRecord>, Error> {
-    let rows_index = index_reader::read_rows_index(&schema_name, &table_name)?;
-    
-    let restricted_rows = index_reader::get_restricted_rows(&filter_columns, &rows_index, &schema_name, &table_name)?;
-    
-    let row_offsets = filter_manager::filter_row_offsets(&restricted_rows, filters, rows_index, filter_columns, include)?;
-    
-    let rows = index_reader::get_rows_from_row_offsets(&row_offsets, &schema_name, &table_name)?;
-    
-    Ok(rows)
+    row_ids: &[RowId],
+) -> Result<Vec<(RowId, StringRecord)>, Error> {
+    let rows = get_table_storage(table_schema).fetch_rows(schema_name, table_schema, row_ids)?;
+
+    filter_rows(rows.into_iter().map(Ok), table_schema, filters, true)
 }
 
-pub async fn read_column_values(
-    schema_name: &String,
-    table_name: &String,
+// Rows of a FROM source, read lazily on a sequential scan
+pub fn stream_source(source: &TableSource, method: &ScanMethod) -> Result<RowStream, Error> {
+    let storage = get_table_storage(source.table_schema);
+
+    match method {
+        ScanMethod::Sequential => storage.stream_rows(&source.schema_name, source.table_schema),
+        ScanMethod::Index(index_lookup) => {
+            let row_ids: Vec<RowId> = index_reader::find_lookup_row_ids(&source.schema_name, source.table_schema, index_lookup)?.into_iter().collect();
+            let rows = storage.fetch_rows(&source.schema_name, source.table_schema, &row_ids)?;
+            Ok(Box::new(rows.into_iter().map(Ok)))
+        },
+    }
+}
+
+pub fn read_column_values(
+    schema_name: &str,
+    table_name: &str,
     column_name: &String,
 ) -> Result<Vec<String>, Error> {
-    let table_schema_file_path = database_navigator::get_table_schema_path(schema_name, &table_name);
+    let table_schema_file_path = database_navigator::get_table_schema_path(schema_name, table_name);
     let table_schema = file_manager::read_json_file::<TableSchema>(&table_schema_file_path)?;
-    let column = table_schema.columns.iter().find(|col| &col.name == column_name).ok_or(Error::ColumnDoesNotExist { table_name: table_name.clone(), column_name: column_name.clone() })?;
 
-    if column.is_indexed {
-        let index = index_reader::read_column_index(&schema_name, &table_name, &column_name)?;
-        
-        let column_values = index_reader::get_column_values_from_index(&index, &schema_name, &table_name)?;
-        
-        Ok(column_values)
-    } else {
-        let rows = read_table(&schema_name, &table_name, &None, true).await?;
-        
-        let column_index = table_schema.columns.iter().position(|col| &col.name == column_name).unwrap();
-        let column_values = rows.iter().map(|row| row.get(column_index).unwrap().to_string()).collect::<Vec<String>>();
-        
-        Ok(column_values)
-    }
+    get_table_storage(&table_schema).read_column_values(schema_name, &table_schema, column_name)
 }
+
+fn filter_rows(
+    rows: impl Iterator<Item = Result<(RowId, StringRecord), Error>>,
+    table_schema: &TableSchema,
+    filters: &Option<Expr>,
+    include: bool,
+) -> Result<Vec<(RowId, StringRecord)>, Error> {
+    let mut filtered_rows: Vec<(RowId, StringRecord)> = Vec::new();
+
+    for row in rows {
+        let (row_id, row) = row?;
+        if filter_manager::apply_filters(&row, table_schema, filters.as_ref())? == include {
+            filtered_rows.push((row_id, row));
+        }
+    }
+
+    Ok(filtered_rows)
+}
\ No newline at end of file
@@ -1,10 +1,46 @@
-use sqlparser::ast::Expr;
+use csv::StringRecord;
+use sqlparser::ast::{Expr, SelectItem, TableWithJoins};
+
+use crate::database::types::TableSchema;
 
 pub struct SelectParameters {
-    pub table_name: String,
-    pub columns: Vec<String>,
+    pub from: Vec<TableWithJoins>,
+    pub projection: Vec<SelectItem>,
     pub filters: Option<Expr>,
-    pub order_column_name: Option<String>,
-    pub ascending: bool,
+    pub group_by: Vec<Expr>,
+    pub having: Option<Expr>,
+    pub order_by: Vec<SortKey>,
     pub limit_value: Option<usize>,
-}
\ No newline at end of file
+    pub offset_value: usize,
+}
+
+pub struct SortKey {
+    pub expr: Expr,
+    pub ascending: bool,
+    pub nulls_first: bool,
+}
+
+// A table in the FROM clause, under its alias if it has one
+pub struct TableSource<'a> {
+    pub name: String,
+    pub schema_name: String,
+    pub table_schema: &'a TableSchema,
+}
+
+// Each source's record in FROM order, None where an outer join found no match
+pub type JoinedRow = Vec<Option<StringRecord>>;
+
+pub enum JoinKind {
+    Inner,
+    Left,
+    Right,
+    Full,
+    Cross,
+}
+
+// Joins the source at source_index to the rows built from the sources before it
+pub struct JoinStep {
+    pub kind: JoinKind,
+    pub source_index: usize,
+    pub constraint: Option<Expr>,
+}
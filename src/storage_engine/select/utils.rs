@@ -1,7 +1,91 @@
+use sqlparser::ast::{Expr, Ident, ObjectName, SelectItem, SetExpr, Value};
+
+use crate::database::utils::get_headers_from_table_schema;
+use crate::shared::errors::Error;
+
+use super::types::{SortKey, TableSource};
+
 pub fn get_column_indices(headers: &[String], columns: &[String]) -> Vec<usize> {
     if columns.contains(&"*".to_string()) {
         (0..headers.len()).collect()
     } else {
         columns.iter().filter_map(|col| headers.iter().position(|header| header == col)).collect()
     }
-}
\ No newline at end of file
+}
+
+// Output column names with their expressions, wildcards expanded to the columns of every table (or the named one)
+pub fn get_projected_expressions(projection: &[SelectItem], sources: &[TableSource]) -> Result<Vec<(String, Expr)>, Error> {
+    let mut projected_expressions: Vec<(String, Expr)> = Vec::new();
+
+    for item in projection {
+        match item {
+            SelectItem::UnnamedExpr(expr) => projected_expressions.push((get_expression_name(expr), expr.clone())),
+            SelectItem::ExprWithAlias { expr, alias } => projected_expressions.push((alias.value.clone(), expr.clone())),
+            SelectItem::Wildcard(_) => {
+                for source in sources {
+                    projected_expressions.extend(get_table_expressions(source));
+                }
+            },
+            SelectItem::QualifiedWildcard(ObjectName(idents), _) => {
+                let source = sources.iter()
+                    .find(|source| idents.last().map(|ident| &ident.value) == Some(&source.name))
+                    .ok_or_else(|| Error::TableDoesNotExist { table_name: item.to_string() })?;
+                projected_expressions.extend(get_table_expressions(source));
+            },
+        }
+    }
+
+    Ok(projected_expressions)
+}
+
+// Qualified, so that columns with the same name in joined tables stay apart
+fn get_table_expressions(source: &TableSource) -> Vec<(String, Expr)> {
+    get_headers_from_table_schema(source.table_schema).into_iter()
+        .map(|header| (header.clone(), Expr::CompoundIdentifier(vec![Ident::new(&source.name), Ident::new(header)])))
+        .collect()
+}
+
+// Output column names and positions (ORDER BY 2) stand for the projected expressions
+pub fn get_sort_keys(order_by: Vec<SortKey>, projected_expressions: &[(String, Expr)]) -> Result<Vec<SortKey>, Error> {
+    let mut sort_keys: Vec<SortKey> = Vec::new();
+
+    for sort_key in order_by {
+        let expr = match &sort_key.expr {
+            Expr::Identifier(ident) => projected_expressions.iter()
+                .find(|(name, _)| name == &ident.value)
+                .map(|(_, expr)| expr.clone())
+                .unwrap_or(sort_key.expr),
+            Expr::Value(Value::Number(position, _)) => position.parse::<usize>().ok()
+                .and_then(|position| position.checked_sub(1))
+                .and_then(|index| projected_expressions.get(index))
+                .map(|(_, expr)| expr.clone())
+                .ok_or_else(|| Error::InvalidOrderPosition { position: position.clone() })?,
+            _ => sort_key.expr,
+        };
+        sort_keys.push(SortKey { expr, ..sort_key });
+    }
+
+    Ok(sort_keys)
+}
+
+// Postgres names computed columns after the column or function they wrap
+fn get_expression_name(expr: &Expr) -> String {
+    match expr {
+        Expr::Identifier(ident) => ident.value.clone(),
+        Expr::CompoundIdentifier(idents) => idents.last().map(|ident| ident.value.clone()).unwrap_or_default(),
+        Expr::Nested(nested_expr) | Expr::Cast { expr: nested_expr, .. } => get_expression_name(nested_expr),
+        Expr::Function(function) => function.name.0.last().map(|ident| ident.value.to_lowercase()).unwrap_or_default(),
+        Expr::Case { .. } => String::from("case"),
+        Expr::Exists { .. } => String::from("exists"),
+        // Named after the subquery's own column
+        Expr::Subquery(query) => match query.body.as_ref() {
+            SetExpr::Select(select) => match select.projection.as_slice() {
+                [SelectItem::UnnamedExpr(expr)] => get_expression_name(expr),
+                [SelectItem::ExprWithAlias { alias, .. }] => alias.value.clone(),
+                _ => String::from("?column?"),
+            },
+            _ => String::from("?column?"),
+        },
+        _ => String::from("?column?"),
+    }
+}
@@ -1,32 +1,35 @@
-use crate::database::{types::Database, utils::find_database_table};
+use sqlparser::ast::Expr;
+
 use crate::shared::errors::Error;
-use crate::storage_engine::validation;
+use crate::storage_engine::expressions::{aggregate_functions, expression_walker, type_resolver, types::RowContext};
+
+use super::types::{JoinStep, SortKey, TableSource};
 
 
 pub fn validate_select_query(
-    database: &Database,
-    table_name: &String,
-    columns: &Vec<String>,
-    order_column_name: &Option<String>,
+    sources: &[TableSource],
+    join_steps: &[JoinStep],
+    projected_expressions: &[(String, Expr)],
+    filters: &Option<Expr>,
+    sort_keys: &[SortKey],
 ) -> Result<(), Error> {
-    // Ensure table exists
-    let table_schema = match find_database_table(database, &table_name) {
-        Some(schema) => schema,
-        None => return Err(Error::TableDoesNotExist { table_name: table_name.clone() }),
-    };
-
-    // Ensure selected columns exist
-    if !columns.contains(&"*".to_string()) {
-        for column in columns {
-            validation::common::validate_column_exists(table_schema, column)?;
+    // Ensure selected, joined and filtered columns exist
+    let context = RowContext::from_sources(sources, None);
+    for (_, expr) in projected_expressions {
+        type_resolver::get_expression_type(expr, &context)?;
+    }
+    let conditions = join_steps.iter().filter_map(|join_step| join_step.constraint.as_ref()).chain(filters.as_ref());
+    for condition in conditions {
+        type_resolver::get_expression_type(condition, &context)?;
+        if let Some(aggregate) = expression_walker::find_expressions(condition, &aggregate_functions::is_aggregate).first() {
+            return Err(Error::AggregateNotAllowed { function_name: aggregate.to_string() });
         }
     }
 
-    // Ensure order column exists
-    if let Some(column_name) = order_column_name {
-        // TODO: Add type validation
-        validation::common::validate_column_exists(table_schema, column_name)?;
+    // Ensure sorted columns exist
+    for sort_key in sort_keys {
+        type_resolver::get_expression_type(&sort_key.expr, &context)?;
     }
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,92 @@
+use sqlparser::ast::ObjectName;
+
+use crate::database::constants::NULL_VALUE;
+use crate::database::database_navigator::get_table_statistics_path;
+use crate::database::types::{Column, ColumnStatistics, TableSchema, TableStatistics};
+use crate::database::{database_loader, utils::{find_database_table, get_headers_from_table_schema, get_schema_and_table_name}};
+use crate::shared::{errors::Error, file_manager};
+use crate::storage_engine::index::btree::IndexValue;
+use crate::storage_engine::storage_backend::table_storage::get_table_storage;
+use crate::transaction_manager::lock_manager;
+
+const HISTOGRAM_BUCKETS: usize = 10;
+
+// ANALYZE [table]: without a table name, every table of the database is analyzed
+pub async fn analyze(table_name: &ObjectName) -> Result<String, Error> {
+    let database = database_loader::get_database()?;
+    let table_names: Vec<(String, String)> = match table_name.0.is_empty() {
+        true => database.schemas.iter()
+            .flat_map(|schema| schema.tables.iter().map(|table_schema| (schema.name.clone(), table_schema.name.clone())))
+            .collect(),
+        false => vec![get_schema_and_table_name(&database, table_name)?],
+    };
+
+    for (schema_name, table_name) in table_names.iter() {
+        analyze_table(schema_name, table_name).await?;
+    }
+
+    match table_names.as_slice() {
+        [(_, table_name)] => Ok(format!("Success: table {} has been analyzed.", table_name)),
+        _ => Ok(format!("Success: {} tables have been analyzed.", table_names.len())),
+    }
+}
+
+async fn analyze_table(schema_name: &str, table_name: &str) -> Result<(), Error> {
+    // Compute the statistics from the rows as last committed
+    let _lock = lock_manager::lock_table_for_read(schema_name, table_name).await?;
+    let database = database_loader::get_database()?;
+    let table_schema = find_database_table(&database, schema_name, table_name).ok_or(Error::TableDoesNotExist { table_name: table_name.to_string() })?;
+
+    let statistics = compute_table_statistics(schema_name, table_schema)?;
+
+    file_manager::write_json_into_file(&get_table_statistics_path(schema_name, table_name), &statistics)
+}
+
+// One pass over the rows, keeping each column's non-null values to count and bucket them
+fn compute_table_statistics(schema_name: &str, table_schema: &TableSchema) -> Result<TableStatistics, Error> {
+    let columns: Vec<&Column> = get_headers_from_table_schema(table_schema).iter()
+        .filter_map(|header| table_schema.columns.iter().find(|column| &column.name == header))
+        .collect();
+
+    let mut row_count: u64 = 0;
+    let mut column_values: Vec<Vec<(IndexValue, String)>> = vec![Vec::new(); columns.len()];
+    for row in get_table_storage(table_schema).stream_rows(schema_name, table_schema)? {
+        let (_, record) = row?;
+        row_count += 1;
+
+        for (position, column) in columns.iter().enumerate() {
+            let value = record.get(position).unwrap_or(NULL_VALUE);
+            let index_value = IndexValue::from_value(value, &column.data_type);
+            if !matches!(index_value, IndexValue::Null) {
+                column_values[position].push((index_value, value.to_string()));
+            }
+        }
+    }
+
+    let columns = columns.iter().zip(column_values)
+        .map(|(column, values)| compute_column_statistics(column, values, row_count))
+        .collect();
+
+    Ok(TableStatistics { row_count, columns })
+}
+
+fn compute_column_statistics(column: &Column, mut values: Vec<(IndexValue, String)>, row_count: u64) -> ColumnStatistics {
+    values.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let distinct_count = match values.is_empty() {
+        true => 0,
+        false => 1 + values.windows(2).filter(|pair| pair[0].0 != pair[1].0).count() as u64,
+    };
+    let null_fraction = match row_count {
+        0 => 0.0,
+        _ => (row_count - values.len() as u64) as f64 / row_count as f64,
+    };
+
+    // Bounds at evenly spaced positions of the sorted values, so that each bucket holds as many of them
+    let histogram_bounds = match values.is_empty() {
+        true => Vec::new(),
+        false => (0..=HISTOGRAM_BUCKETS).map(|bucket| values[bucket * (values.len() - 1) / HISTOGRAM_BUCKETS].1.clone()).collect(),
+    };
+
+    ColumnStatistics { column_name: column.name.clone(), distinct_count, null_fraction, histogram_bounds }
+}
@@ -0,0 +1,2 @@
+pub mod analyze_handler;
+pub mod statistics_reader;
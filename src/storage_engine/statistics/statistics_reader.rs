@@ -0,0 +1,95 @@
+use std::ops::Bound;
+
+use crate::database::database_navigator::get_table_statistics_path;
+use crate::database::types::{ColumnStatistics, TableSchema, TableStatistics};
+use crate::shared::file_manager;
+use crate::storage_engine::index::btree::{IndexKey, IndexValue};
+use crate::storage_engine::index::index_reader::IndexLookup;
+
+// Statistics of the last ANALYZE, None if the table was never analyzed
+pub fn read_table_statistics(schema_name: &str, table_name: &str) -> Option<TableStatistics> {
+    // They only guide planning, so unreadable statistics are treated as missing
+    file_manager::read_json_file::<TableStatistics>(&get_table_statistics_path(schema_name, table_name)).ok()
+}
+
+// Estimated number of rows an index lookup finds
+pub fn estimate_lookup_rows(statistics: &TableStatistics, table_schema: &TableSchema, index_lookup: &IndexLookup) -> f64 {
+    statistics.row_count as f64 * estimate_selectivity(statistics, table_schema, index_lookup)
+}
+
+// Fraction of the rows passing the lookup's condition, its parts assumed independent
+fn estimate_selectivity(statistics: &TableStatistics, table_schema: &TableSchema, index_lookup: &IndexLookup) -> f64 {
+    match index_lookup {
+        IndexLookup::Range { column_name, lower, upper, .. } => {
+            let column_statistics = statistics.columns.iter().find(|column_statistics| &column_statistics.column_name == column_name);
+            let column = table_schema.columns.iter().find(|column| &column.name == column_name);
+
+            match (column_statistics, column) {
+                (Some(column_statistics), Some(column)) => {
+                    let bounds: Vec<IndexValue> = column_statistics.histogram_bounds.iter()
+                        .map(|bound| IndexValue::from_value(bound, &column.data_type))
+                        .collect();
+                    estimate_range_selectivity(column_statistics, &bounds, lower, upper)
+                },
+                // Columns added since the last ANALYZE
+                _ => 1.0,
+            }
+        },
+        IndexLookup::Intersection(left, right) => estimate_selectivity(statistics, table_schema, left) * estimate_selectivity(statistics, table_schema, right),
+        IndexLookup::Union(left, right) => {
+            let left_selectivity = estimate_selectivity(statistics, table_schema, left);
+            let right_selectivity = estimate_selectivity(statistics, table_schema, right);
+            left_selectivity + right_selectivity - left_selectivity * right_selectivity
+        },
+    }
+}
+
+fn estimate_range_selectivity(column_statistics: &ColumnStatistics, bounds: &[IndexValue], lower: &Bound<IndexKey>, upper: &Bound<IndexKey>) -> f64 {
+    let non_null_fraction = 1.0 - column_statistics.null_fraction;
+
+    // Equal values are assumed to be spread evenly over the distinct ones
+    if let (Bound::Included(lower_key), Bound::Included(upper_key)) = (lower, upper) {
+        if lower_key == upper_key {
+            return non_null_fraction / column_statistics.distinct_count.max(1) as f64;
+        }
+    }
+
+    let lower_fraction = match lower {
+        Bound::Included(key) | Bound::Excluded(key) => key.first().map_or(0.0, |value| get_fraction_below(bounds, value)),
+        Bound::Unbounded => 0.0,
+    };
+    let upper_fraction = match upper {
+        Bound::Included(key) | Bound::Excluded(key) => key.first().map_or(1.0, |value| get_fraction_below(bounds, value)),
+        Bound::Unbounded => 1.0,
+    };
+
+    non_null_fraction * (upper_fraction - lower_fraction).max(0.0)
+}
+
+// Fraction of the values below the given one, interpolated inside its bucket
+fn get_fraction_below(bounds: &[IndexValue], value: &IndexValue) -> f64 {
+    let bucket_count = bounds.len().saturating_sub(1);
+    let bounds_below = bounds.partition_point(|bound| bound < value);
+    if bounds_below == 0 {
+        return 0.0;
+    }
+    if bounds_below > bucket_count {
+        return 1.0;
+    }
+
+    // Numbers are assumed to be spread evenly between the bucket's bounds, other values to sit in its middle
+    let position_in_bucket = match (get_number(&bounds[bounds_below - 1]), get_number(&bounds[bounds_below]), get_number(value)) {
+        (Some(low), Some(high), Some(number)) if high > low => ((number - low) / (high - low)).clamp(0.0, 1.0),
+        _ => 0.5,
+    };
+
+    ((bounds_below - 1) as f64 + position_in_bucket) / bucket_count as f64
+}
+
+fn get_number(value: &IndexValue) -> Option<f64> {
+    match value {
+        IndexValue::Integer(i) => Some(*i as f64),
+        IndexValue::Float(f) => Some(*f),
+        _ => None,
+    }
+}
@@ -0,0 +1,133 @@
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+
+use csv::{ReaderBuilder, StringRecord, WriterBuilder};
+
+use crate::database::database_navigator::get_table_data_path;
+use crate::database::types::{RowsIndex, TableSchema};
+use crate::database::utils::get_headers_from_table_schema;
+use crate::shared::{errors::Error, file_manager};
+use crate::storage_engine::index::{index_manager, index_reader, index_updater};
+
+use super::table_storage::{RowId, RowStream, TableStorage};
+
+/*
+ * Rows in data/<table>.csv, row ids are byte offsets; the rows index and column indexes are kept in sync on every write
+ */
+pub struct CsvStorage;
+
+impl TableStorage for CsvStorage {
+    fn create_storage(&self, schema_name: &str, table_schema: &TableSchema) -> Result<(), Error> {
+        self.rewrite_rows(schema_name, table_schema, &[])
+    }
+
+    fn stream_rows(&self, schema_name: &str, table_schema: &TableSchema) -> Result<RowStream, Error> {
+        let file_path = get_table_data_path(schema_name, &table_schema.name);
+        let file = file_manager::open_file_reader(&file_path)?;
+        let rdr = ReaderBuilder::new().has_headers(true).from_reader(file);
+
+        let rows = rdr.into_records()
+            .filter_map(Result::ok)
+            .map(|record| Ok((record.position().map_or(0, |position| position.byte()), record)));
+
+        Ok(Box::new(rows))
+    }
+
+    fn fetch_rows(&self, schema_name: &str, table_schema: &TableSchema, row_ids: &[RowId]) -> Result<Vec<(RowId, StringRecord)>, Error> {
+        let file_path = get_table_data_path(schema_name, &table_schema.name);
+        let mut file_reader = BufReader::new(file_manager::open_file_reader(&file_path)?);
+        let mut rows: Vec<(RowId, StringRecord)> = Vec::new();
+
+        for &row_id in row_ids {
+            file_reader.seek(SeekFrom::Start(row_id))?;
+
+            let mut line = String::new();
+            file_reader.read_line(&mut line)?;
+
+            // Parse the line into a StringRecord
+            let mut rdr = ReaderBuilder::new().has_headers(false).from_reader(line.as_bytes());
+            if let Some(record) = rdr.records().next() {
+                rows.push((row_id, record?));
+            }
+        }
+
+        Ok(rows)
+    }
+
+    fn insert_rows(&self, schema_name: &str, table_schema: &TableSchema, rows: &[StringRecord]) -> Result<(), Error> {
+        let table_name = &table_schema.name;
+        let mut rows_index = index_reader::read_rows_index(schema_name, table_name)?;
+        let end_of_file_offset = rows_index.row_offsets.last().copied().unwrap_or(0);
+
+        // Append rows to the CSV file
+        let (contents, record_ends) = write_records(rows.iter(), table_name)?;
+        file_manager::append_bytes_to_file(&get_table_data_path(schema_name, table_name), contents)?;
+
+        // Each row starts where the previous one ends
+        let mut inserted_rows: Vec<(RowId, StringRecord)> = Vec::new();
+        for (row, record_end) in rows.iter().zip(record_ends) {
+            let row_id = rows_index.row_offsets.last().copied().unwrap_or(end_of_file_offset);
+            inserted_rows.push((row_id, row.clone()));
+            rows_index.row_offsets.push(end_of_file_offset + record_end);
+        }
+
+        index_updater::write_rows_index(schema_name, table_name, &rows_index)?;
+        index_updater::insert_index_entries(schema_name, table_schema, &inserted_rows)
+    }
+
+    // Any change shifts the offsets of the following rows, so the whole file is rewritten
+    fn update_rows(&self, schema_name: &str, table_schema: &TableSchema, rows: &[(RowId, StringRecord)]) -> Result<(), Error> {
+        let mut records = self.scan_rows(schema_name, table_schema)?;
+        for (row_id, record) in records.iter_mut() {
+            if let Some((_, updated_record)) = rows.iter().find(|(updated_row_id, _)| updated_row_id == row_id) {
+                *record = updated_record.clone();
+            }
+        }
+
+        let records: Vec<StringRecord> = records.into_iter().map(|(_, record)| record).collect();
+        self.rewrite_rows(schema_name, table_schema, &records)
+    }
+
+    fn delete_rows(&self, schema_name: &str, table_schema: &TableSchema, row_ids: &[RowId]) -> Result<(), Error> {
+        let remaining_records: Vec<StringRecord> = self.scan_rows(schema_name, table_schema)?.into_iter()
+            .filter(|(row_id, _)| !row_ids.contains(row_id))
+            .map(|(_, record)| record)
+            .collect();
+
+        self.rewrite_rows(schema_name, table_schema, &remaining_records)
+    }
+
+    fn rewrite_rows(&self, schema_name: &str, table_schema: &TableSchema, rows: &[StringRecord]) -> Result<(), Error> {
+        let table_name = &table_schema.name;
+        let headers = StringRecord::from(get_headers_from_table_schema(table_schema));
+        let (contents, record_ends) = write_records(std::iter::once(&headers).chain(rows.iter()), table_name)?;
+        file_manager::write_bytes_into_file(&get_table_data_path(schema_name, table_name), contents)?;
+
+        // Rows start where the previous record (or the headers) end
+        let written_rows: Vec<(RowId, StringRecord)> = record_ends.iter().copied().zip(rows.iter().cloned()).collect();
+        index_updater::write_rows_index(schema_name, table_name, &RowsIndex { row_offsets: record_ends })?;
+
+        index_manager::create_indexes(schema_name, table_schema, &written_rows)
+    }
+
+    fn count_rows(&self, schema_name: &str, table_schema: &TableSchema) -> Result<u64, Error> {
+        let rows_index = index_reader::read_rows_index(schema_name, &table_schema.name)?;
+
+        Ok(rows_index.row_offsets.len().saturating_sub(1) as u64)
+    }
+}
+
+// CSV contents along with the offset at which each record ends
+fn write_records<'a>(records: impl Iterator<Item = &'a StringRecord>, table_name: &str) -> Result<(Vec<u8>, Vec<u64>), Error> {
+    let mut wtr = WriterBuilder::new().has_headers(false).from_writer(Vec::new());
+    let mut record_ends: Vec<u64> = Vec::new();
+
+    for record in records {
+        wtr.write_record(record).map_err(|_| Error::FailedTableWrite { table_name: table_name.to_string() })?;
+        wtr.flush()?;
+        record_ends.push(wtr.get_ref().len() as u64);
+    }
+
+    let contents = wtr.into_inner().map_err(|_| Error::FailedTableWrite { table_name: table_name.to_string() })?;
+
+    Ok((contents, record_ends))
+}
\ No newline at end of file
@@ -0,0 +1,4 @@
+pub mod table_storage;
+pub mod csv_storage;
+pub mod paged_storage;
+pub mod page;
\ No newline at end of file
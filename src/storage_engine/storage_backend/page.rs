@@ -0,0 +1,181 @@
+pub const PAGE_SIZE: usize = 4096;
+const PAGE_HEADER_SIZE: usize = 4;
+pub const SLOT_SIZE: usize = 4;
+
+/*
+ * Slotted page:
+ * [slot count: u16][start of tuple data: u16][slots: (offset: u16, length: u16)...] [free space] [tuples, growing backwards]
+ * Slot ids stay stable for the lifetime of a tuple; a deleted tuple leaves an empty slot that can be reused
+ */
+#[derive(Debug, Clone)]
+pub struct Page {
+    data: Vec<u8>,
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        let mut page = Page { data: vec![0; PAGE_SIZE] };
+        page.set_data_start(PAGE_SIZE);
+        page
+    }
+}
+
+impl Page {
+    pub fn from_bytes(bytes: &[u8]) -> Page {
+        let mut data = bytes.to_vec();
+        data.resize(PAGE_SIZE, 0);
+        let mut page = Page { data };
+
+        // Pages past the end of the file are empty
+        if page.get_data_start() == 0 {
+            page.set_data_start(PAGE_SIZE);
+        }
+        page
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn get_tuple(&self, slot: u16) -> Option<&[u8]> {
+        if slot >= self.get_slot_count() {
+            return None;
+        }
+        let (offset, length) = self.get_slot(slot);
+        if length == 0 {
+            return None;
+        }
+
+        Some(&self.data[offset..offset + length])
+    }
+
+    pub fn get_tuples(&self) -> Vec<(u16, &[u8])> {
+        (0..self.get_slot_count())
+            .filter_map(|slot| self.get_tuple(slot).map(|tuple| (slot, tuple)))
+            .collect()
+    }
+
+    // Space available to tuples once the page is compacted, not counting a new slot
+    pub fn get_free_space(&self) -> usize {
+        let live_bytes: usize = self.get_tuples().iter().map(|(_, tuple)| tuple.len()).sum();
+        PAGE_SIZE - self.get_header_end() - live_bytes
+    }
+
+    pub fn insert_tuple(&mut self, tuple: &[u8]) -> Option<u16> {
+        let free_slot = (0..self.get_slot_count()).find(|&slot| self.get_slot(slot).1 == 0);
+        let required_space = tuple.len() + if free_slot.is_some() { 0 } else { SLOT_SIZE };
+        if tuple.is_empty() || self.get_free_space() < required_space {
+            return None;
+        }
+
+        let slot = match free_slot {
+            Some(slot) => slot,
+            None => {
+                let slot = self.get_slot_count();
+                self.set_slot_count(slot + 1);
+                self.set_slot(slot, 0, 0);
+                slot
+            }
+        };
+        self.place_tuple(slot, tuple);
+
+        Some(slot)
+    }
+
+    pub fn update_tuple(&mut self, slot: u16, tuple: &[u8]) -> bool {
+        let (offset, length) = match self.get_tuple(slot) {
+            Some(old_tuple) => (self.get_slot(slot).0, old_tuple.len()),
+            None => return false,
+        };
+
+        // Shrinking tuples are rewritten in place
+        if !tuple.is_empty() && tuple.len() <= length {
+            self.data[offset..offset + tuple.len()].copy_from_slice(tuple);
+            self.set_slot(slot, offset, tuple.len());
+            return true;
+        }
+        if tuple.is_empty() || self.get_free_space() + length < tuple.len() {
+            return false;
+        }
+
+        self.set_slot(slot, 0, 0);
+        self.place_tuple(slot, tuple);
+        true
+    }
+
+    pub fn delete_tuple(&mut self, slot: u16) -> bool {
+        if self.get_tuple(slot).is_none() {
+            return false;
+        }
+        self.set_slot(slot, 0, 0);
+        true
+    }
+
+    // Write a tuple into the free space, compacting first if it is fragmented
+    fn place_tuple(&mut self, slot: u16, tuple: &[u8]) {
+        if self.get_data_start() - self.get_header_end() < tuple.len() {
+            self.compact();
+        }
+
+        let offset = self.get_data_start() - tuple.len();
+        self.data[offset..offset + tuple.len()].copy_from_slice(tuple);
+        self.set_slot(slot, offset, tuple.len());
+        self.set_data_start(offset);
+    }
+
+    fn compact(&mut self) {
+        let tuples: Vec<(u16, Vec<u8>)> = self.get_tuples().into_iter()
+            .map(|(slot, tuple)| (slot, tuple.to_vec()))
+            .collect();
+
+        let mut data_start = PAGE_SIZE;
+        for (slot, tuple) in tuples {
+            data_start -= tuple.len();
+            self.data[data_start..data_start + tuple.len()].copy_from_slice(&tuple);
+            self.set_slot(slot, data_start, tuple.len());
+        }
+        self.set_data_start(data_start);
+    }
+
+    /*
+     * Header access
+     */
+    fn get_slot_count(&self) -> u16 {
+        read_u16(&self.data, 0)
+    }
+
+    fn set_slot_count(&mut self, slot_count: u16) {
+        write_u16(&mut self.data, 0, slot_count);
+    }
+
+    fn get_data_start(&self) -> usize {
+        read_u16(&self.data, 2) as usize
+    }
+
+    fn set_data_start(&mut self, data_start: usize) {
+        write_u16(&mut self.data, 2, data_start as u16);
+    }
+
+    fn get_header_end(&self) -> usize {
+        PAGE_HEADER_SIZE + self.get_slot_count() as usize * SLOT_SIZE
+    }
+
+    fn get_slot(&self, slot: u16) -> (usize, usize) {
+        let position = PAGE_HEADER_SIZE + slot as usize * SLOT_SIZE;
+        (read_u16(&self.data, position) as usize, read_u16(&self.data, position + 2) as usize)
+    }
+
+    fn set_slot(&mut self, slot: u16, offset: usize, length: usize) {
+        let position = PAGE_HEADER_SIZE + slot as usize * SLOT_SIZE;
+        write_u16(&mut self.data, position, offset as u16);
+        write_u16(&mut self.data, position + 2, length as u16);
+    }
+}
+
+fn read_u16(data: &[u8], position: usize) -> u16 {
+    u16::from_be_bytes([data[position], data[position + 1]])
+}
+
+fn write_u16(data: &mut [u8], position: usize, value: u16) {
+    data[position..position + 2].copy_from_slice(&value.to_be_bytes());
+}
\ No newline at end of file
@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Seek, SeekFrom};
+
+use csv::StringRecord;
+
+use crate::database::database_navigator::{get_table_free_space_map_path, get_table_pages_path};
+use crate::database::types::{FreeSpaceMap, TableSchema};
+use crate::shared::{errors::Error, file_manager};
+use crate::storage_engine::index::{index_manager, index_updater};
+
+use super::page::{Page, PAGE_SIZE, SLOT_SIZE};
+use super::table_storage::{RowId, RowStream, TableStorage};
+
+/*
+ * Rows in fixed-size slotted pages (data/<table>.pages) with a free-space map (data/<table>.fsm.json).
+ * Row ids are (page number << 16 | slot), and writes only touch the pages they modify
+ */
+pub struct PagedStorage;
+
+impl TableStorage for PagedStorage {
+    fn create_storage(&self, schema_name: &str, table_schema: &TableSchema) -> Result<(), Error> {
+        file_manager::write_bytes_into_file(&get_table_pages_path(schema_name, &table_schema.name), Vec::new())?;
+        file_manager::write_json_into_file(&get_table_free_space_map_path(schema_name, &table_schema.name), &FreeSpaceMap::default())?;
+
+        index_manager::create_indexes(schema_name, table_schema, &[])
+    }
+
+    // Pages are read one at a time
+    fn stream_rows(&self, schema_name: &str, table_schema: &TableSchema) -> Result<RowStream, Error> {
+        let mut reader = file_manager::open_file_reader(&get_table_pages_path(schema_name, &table_schema.name))?;
+        let table_name = table_schema.name.clone();
+        let mut page_number: u64 = 0;
+        let mut page_rows: std::vec::IntoIter<Result<(RowId, StringRecord), Error>> = Vec::new().into_iter();
+
+        let rows = std::iter::from_fn(move || loop {
+            if let Some(row) = page_rows.next() {
+                return Some(row);
+            }
+
+            let mut page_bytes: Vec<u8> = Vec::with_capacity(PAGE_SIZE);
+            match reader.by_ref().take(PAGE_SIZE as u64).read_to_end(&mut page_bytes) {
+                Ok(0) => return None,
+                Ok(_) => {},
+                Err(e) => return Some(Err(Error::IOError(e))),
+            }
+
+            let page = Page::from_bytes(&page_bytes);
+            page_rows = page.get_tuples().into_iter()
+                .map(|(slot, tuple)| decode_row(tuple, &table_name).map(|row| (get_row_id(page_number, slot), row)))
+                .collect::<Vec<_>>()
+                .into_iter();
+            page_number += 1;
+        });
+
+        Ok(Box::new(rows))
+    }
+
+    fn fetch_rows(&self, schema_name: &str, table_schema: &TableSchema, row_ids: &[RowId]) -> Result<Vec<(RowId, StringRecord)>, Error> {
+        let mut pages = PageCache::load(schema_name, &table_schema.name)?;
+        let mut rows: Vec<(RowId, StringRecord)> = Vec::new();
+
+        for row_id in row_ids {
+            if let Some(row) = pages.get_row(*row_id, &table_schema.name)? {
+                rows.push((*row_id, row));
+            }
+        }
+
+        Ok(rows)
+    }
+
+    fn insert_rows(&self, schema_name: &str, table_schema: &TableSchema, rows: &[StringRecord]) -> Result<(), Error> {
+        let mut pages = PageCache::load(schema_name, &table_schema.name)?;
+        let mut inserted_rows: Vec<(RowId, StringRecord)> = Vec::new();
+        for row in rows {
+            let row_id = pages.insert_tuple(&encode_row(row, &table_schema.name)?)?;
+            inserted_rows.push((row_id, row.clone()));
+        }
+        pages.flush()?;
+
+        index_updater::insert_index_entries(schema_name, table_schema, &inserted_rows)
+    }
+
+    fn update_rows(&self, schema_name: &str, table_schema: &TableSchema, rows: &[(RowId, StringRecord)]) -> Result<(), Error> {
+        let mut pages = PageCache::load(schema_name, &table_schema.name)?;
+        let mut old_rows: Vec<(RowId, StringRecord)> = Vec::new();
+        let mut new_rows: Vec<(RowId, StringRecord)> = Vec::new();
+        for (row_id, row) in rows {
+            let old_row = pages.get_row(*row_id, &table_schema.name)?.ok_or(Error::FailedTableRead { table_name: table_schema.name.clone() })?;
+            old_rows.push((*row_id, old_row));
+
+            let new_row_id = pages.update_tuple(*row_id, &encode_row(row, &table_schema.name)?)?;
+            new_rows.push((new_row_id, row.clone()));
+        }
+        pages.flush()?;
+
+        index_updater::delete_index_entries(schema_name, table_schema, &old_rows)?;
+        index_updater::insert_index_entries(schema_name, table_schema, &new_rows)
+    }
+
+    fn delete_rows(&self, schema_name: &str, table_schema: &TableSchema, row_ids: &[RowId]) -> Result<(), Error> {
+        let mut pages = PageCache::load(schema_name, &table_schema.name)?;
+        let mut deleted_rows: Vec<(RowId, StringRecord)> = Vec::new();
+        for row_id in row_ids {
+            if let Some(row) = pages.get_row(*row_id, &table_schema.name)? {
+                deleted_rows.push((*row_id, row));
+            }
+            pages.delete_tuple(*row_id)?;
+        }
+        pages.flush()?;
+
+        index_updater::delete_index_entries(schema_name, table_schema, &deleted_rows)
+    }
+
+    fn rewrite_rows(&self, schema_name: &str, table_schema: &TableSchema, rows: &[StringRecord]) -> Result<(), Error> {
+        let mut pages: Vec<Page> = Vec::new();
+        let mut written_rows: Vec<(RowId, StringRecord)> = Vec::new();
+        for row in rows {
+            let tuple = encode_row(row, &table_schema.name)?;
+            let slot = match pages.last_mut() {
+                Some(page) => page.insert_tuple(&tuple),
+                None => None,
+            };
+            let slot = match slot {
+                Some(slot) => slot,
+                None => {
+                    let mut page = Page::default();
+                    let slot = page.insert_tuple(&tuple).ok_or(Error::RowTooLarge { size: tuple.len(), max_size: get_max_tuple_size() })?;
+                    pages.push(page);
+                    slot
+                }
+            };
+            written_rows.push((get_row_id(pages.len() as u64 - 1, slot), row.clone()));
+        }
+
+        let contents: Vec<u8> = pages.iter().flat_map(|page| page.as_bytes().to_vec()).collect();
+        file_manager::write_bytes_into_file(&get_table_pages_path(schema_name, &table_schema.name), contents)?;
+
+        let free_space_map = FreeSpaceMap { free_space: pages.iter().map(|page| page.get_free_space() as u16).collect() };
+        file_manager::write_json_into_file(&get_table_free_space_map_path(schema_name, &table_schema.name), &free_space_map)?;
+
+        index_manager::create_indexes(schema_name, table_schema, &written_rows)
+    }
+}
+
+// Pages modified by a single operation, written back together with the free-space map
+struct PageCache {
+    pages_path: String,
+    free_space_map_path: String,
+    free_space_map: FreeSpaceMap,
+    pages: BTreeMap<u64, Page>,
+}
+
+impl PageCache {
+    fn load(schema_name: &str, table_name: &str) -> Result<PageCache, Error> {
+        let free_space_map_path = get_table_free_space_map_path(schema_name, table_name);
+        let free_space_map = file_manager::read_json_file::<FreeSpaceMap>(&free_space_map_path)?;
+
+        Ok(PageCache {
+            pages_path: get_table_pages_path(schema_name, table_name),
+            free_space_map_path,
+            free_space_map,
+            pages: BTreeMap::new(),
+        })
+    }
+
+    fn get_page(&mut self, page_number: u64) -> Result<&mut Page, Error> {
+        if !self.pages.contains_key(&page_number) {
+            if page_number >= self.free_space_map.free_space.len() as u64 {
+                return Err(Error::FailedTableRead { table_name: self.pages_path.clone() });
+            }
+
+            let mut reader = file_manager::open_file_reader(&self.pages_path)?;
+            reader.seek(SeekFrom::Start(page_number * PAGE_SIZE as u64))?;
+            let mut page_bytes: Vec<u8> = Vec::with_capacity(PAGE_SIZE);
+            reader.take(PAGE_SIZE as u64).read_to_end(&mut page_bytes)?;
+
+            self.pages.insert(page_number, Page::from_bytes(&page_bytes));
+        }
+
+        self.pages.get_mut(&page_number).ok_or(Error::ServerError)
+    }
+
+    fn get_row(&mut self, row_id: RowId, table_name: &str) -> Result<Option<StringRecord>, Error> {
+        let (page_number, slot) = split_row_id(row_id);
+        match self.get_page(page_number)?.get_tuple(slot) {
+            Some(tuple) => Ok(Some(decode_row(tuple, table_name)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn insert_tuple(&mut self, tuple: &[u8]) -> Result<RowId, Error> {
+        if tuple.len() > get_max_tuple_size() {
+            return Err(Error::RowTooLarge { size: tuple.len(), max_size: get_max_tuple_size() });
+        }
+
+        // First page with enough room, otherwise a new page at the end
+        let required_space = tuple.len() + SLOT_SIZE;
+        let page_number = match self.free_space_map.free_space.iter().position(|&free_space| free_space as usize >= required_space) {
+            Some(page_number) => page_number as u64,
+            None => {
+                let page_number = self.free_space_map.free_space.len() as u64;
+                let page = Page::default();
+                self.free_space_map.free_space.push(page.get_free_space() as u16);
+                self.pages.insert(page_number, page);
+                page_number
+            }
+        };
+
+        let page = self.get_page(page_number)?;
+        let slot = page.insert_tuple(tuple).ok_or(Error::ServerError)?;
+        self.free_space_map.free_space[page_number as usize] = page.get_free_space() as u16;
+
+        Ok(get_row_id(page_number, slot))
+    }
+
+    // Rows that no longer fit in their page move elsewhere, so the row id may change
+    fn update_tuple(&mut self, row_id: RowId, tuple: &[u8]) -> Result<RowId, Error> {
+        let (page_number, slot) = split_row_id(row_id);
+        let page = self.get_page(page_number)?;
+        let is_updated = page.update_tuple(slot, tuple);
+        if !is_updated {
+            page.delete_tuple(slot);
+        }
+        self.free_space_map.free_space[page_number as usize] = page.get_free_space() as u16;
+
+        match is_updated {
+            true => Ok(row_id),
+            false => self.insert_tuple(tuple),
+        }
+    }
+
+    fn delete_tuple(&mut self, row_id: RowId) -> Result<(), Error> {
+        let (page_number, slot) = split_row_id(row_id);
+        let page = self.get_page(page_number)?;
+        page.delete_tuple(slot);
+        self.free_space_map.free_space[page_number as usize] = page.get_free_space() as u16;
+
+        Ok(())
+    }
+
+    fn flush(self) -> Result<(), Error> {
+        let mut free_space_map = self.free_space_map;
+        for (page_number, page) in self.pages.iter() {
+            free_space_map.free_space[*page_number as usize] = page.get_free_space() as u16;
+            file_manager::write_bytes_at(&self.pages_path, page_number * PAGE_SIZE as u64, page.as_bytes().to_vec())?;
+        }
+
+        file_manager::write_json_into_file(&self.free_space_map_path, &free_space_map)
+    }
+}
+
+fn get_row_id(page_number: u64, slot: u16) -> RowId {
+    (page_number << 16) | slot as u64
+}
+
+fn split_row_id(row_id: RowId) -> (u64, u16) {
+    (row_id >> 16, (row_id & 0xFFFF) as u16)
+}
+
+fn get_max_tuple_size() -> usize {
+    Page::default().get_free_space() - SLOT_SIZE
+}
+
+fn encode_row(row: &StringRecord, table_name: &str) -> Result<Vec<u8>, Error> {
+    let fields: Vec<&str> = row.iter().collect();
+    bincode::serialize(&fields).map_err(|_| Error::FailedTableWrite { table_name: table_name.to_string() })
+}
+
+fn decode_row(tuple: &[u8], table_name: &str) -> Result<StringRecord, Error> {
+    let fields: Vec<String> = bincode::deserialize(tuple).map_err(|_| Error::FailedTableRead { table_name: table_name.to_string() })?;
+    Ok(StringRecord::from(fields))
+}
\ No newline at end of file
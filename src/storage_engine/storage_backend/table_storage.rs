@@ -0,0 +1,64 @@
+use csv::StringRecord;
+
+use crate::database::types::{StorageEngine, TableSchema};
+use crate::database::utils::get_headers_from_table_schema;
+use crate::shared::errors::Error;
+
+use super::csv_storage::CsvStorage;
+use super::paged_storage::PagedStorage;
+
+// Location of a row inside its table's storage, stable until the row is updated or deleted
+pub type RowId = u64;
+
+// Rows read lazily in storage order, so readers that stop early don't load the whole table
+pub type RowStream = Box<dyn Iterator<Item = Result<(RowId, StringRecord), Error>> + Send>;
+
+static CSV_STORAGE: CsvStorage = CsvStorage;
+static PAGED_STORAGE: PagedStorage = PagedStorage;
+
+/*
+ * Storage backend of a table's rows, selected per table
+ */
+pub trait TableStorage: Send + Sync {
+    fn create_storage(&self, schema_name: &str, table_schema: &TableSchema) -> Result<(), Error>;
+
+    // All rows in storage order
+    fn scan_rows(&self, schema_name: &str, table_schema: &TableSchema) -> Result<Vec<(RowId, StringRecord)>, Error> {
+        self.stream_rows(schema_name, table_schema)?.collect()
+    }
+
+    fn stream_rows(&self, schema_name: &str, table_schema: &TableSchema) -> Result<RowStream, Error>;
+
+    // Rows at the given ids, skipping ids that no longer hold a row
+    fn fetch_rows(&self, schema_name: &str, table_schema: &TableSchema, row_ids: &[RowId]) -> Result<Vec<(RowId, StringRecord)>, Error>;
+
+    fn insert_rows(&self, schema_name: &str, table_schema: &TableSchema, rows: &[StringRecord]) -> Result<(), Error>;
+
+    fn update_rows(&self, schema_name: &str, table_schema: &TableSchema, rows: &[(RowId, StringRecord)]) -> Result<(), Error>;
+
+    fn delete_rows(&self, schema_name: &str, table_schema: &TableSchema, row_ids: &[RowId]) -> Result<(), Error>;
+
+    // Replace all rows, e.g. after the table's columns have changed
+    fn rewrite_rows(&self, schema_name: &str, table_schema: &TableSchema, rows: &[StringRecord]) -> Result<(), Error>;
+
+    fn read_column_values(&self, schema_name: &str, table_schema: &TableSchema, column_name: &String) -> Result<Vec<String>, Error> {
+        let headers = get_headers_from_table_schema(table_schema);
+        let column_index = headers.iter().position(|header| header == column_name)
+            .ok_or(Error::ColumnDoesNotExist { column_name: column_name.clone(), table_name: table_schema.name.clone() })?;
+
+        let rows = self.scan_rows(schema_name, table_schema)?;
+
+        Ok(rows.iter().map(|(_, row)| row.get(column_index).unwrap_or_default().to_string()).collect())
+    }
+
+    fn count_rows(&self, schema_name: &str, table_schema: &TableSchema) -> Result<u64, Error> {
+        Ok(self.scan_rows(schema_name, table_schema)?.len() as u64)
+    }
+}
+
+pub fn get_table_storage(table_schema: &TableSchema) -> &'static dyn TableStorage {
+    match table_schema.storage_engine {
+        StorageEngine::Csv => &CSV_STORAGE,
+        StorageEngine::Paged => &PAGED_STORAGE,
+    }
+}
\ No newline at end of file
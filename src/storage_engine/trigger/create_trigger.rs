@@ -1,12 +1,11 @@
 use sqlparser::ast::{ObjectName, TriggerEvent, TriggerExecBody, TriggerExecBodyType, TriggerPeriod};
 
-use crate::{database::{database_loader, database_navigator::get_table_schema_path, types::{CustomIdent, TableSchema, Trigger, TriggerAction, TriggerEvent as CustomTriggerEvent, TriggerExecBody as CustomTriggerExecBody, TriggerExecBodyType as CustomTriggerExecBodyType, TriggerPeriod as CustomTriggerPeriod}}, shared::{errors::Error, file_manager}};
+use crate::{database::{database_loader, database_navigator::get_table_schema_path, types::{CustomIdent, TableSchema, Trigger, TriggerAction, TriggerEvent as CustomTriggerEvent, TriggerExecBody as CustomTriggerExecBody, TriggerExecBodyType as CustomTriggerExecBodyType, TriggerPeriod as CustomTriggerPeriod}, utils::get_schema_and_table_name}, shared::{errors::Error, file_manager}};
 
 pub async fn create_trigger(name: &ObjectName, table_name: &ObjectName, period: &TriggerPeriod, events: &Vec<TriggerEvent>, exec_body: &TriggerExecBody) -> Result<String, Error> {
-    let trigger = validate_create_trigger(name, table_name, period, events, exec_body)?;
-    
     let database = database_loader::get_database()?;
-    let schema_name = database.configuration.default_schema.clone();
+    let (schema_name, _) = get_schema_and_table_name(&database, table_name)?;
+    let trigger = validate_create_trigger(name, table_name, period, events, exec_body)?;
     
     let table_schema_file_path = get_table_schema_path(&schema_name, &trigger.table_name);
     println!("table_schema_file_path: {:?}", table_schema_file_path);
@@ -23,8 +22,7 @@ fn validate_create_trigger(name: &ObjectName, table_name: &ObjectName, period: &
     let first_trigger_identifier = name.0.first().ok_or(Error::MissingTriggerName)?;
     let trigger_name = first_trigger_identifier.value.clone();
 
-    let first_table_identifier = table_name.0.first().ok_or(Error::MissingTableName)?;
-    let table_name = first_table_identifier.value.clone();
+    let table_name = table_name.0.last().ok_or(Error::MissingTableName)?.value.clone();
 
     let trigger_period: CustomTriggerPeriod = match period {
         TriggerPeriod::Before => CustomTriggerPeriod::Before,
@@ -1,93 +1,119 @@
-use std::{collections::HashMap, fs::File};
+use std::collections::HashMap;
 
-use csv::{ReaderBuilder, StringRecord};
+use csv::StringRecord;
 use sqlparser::ast::{Assignment, Expr, TableWithJoins};
 
-use crate::{database::{self, database_loader, database_navigator::get_table_data_path, types::{Database, InsertedRowColumn}, utils::find_database_table}, shared::errors::Error, storage_engine::{filters::filter_manager::apply_filters, index::index_updater, select::{record_handler, utils}, utils::ast_unwrapper::{get_new_column_values, get_table_name_from_from}, validation}};
+use crate::{database::{self, types::{Database, InsertedRowColumn, TableSchema}, utils::{find_database_table, get_headers_from_table_schema}}, network_protocol::types::{CellValue, ResultSet}, shared::errors::Error, storage_engine::{expressions::{expression_evaluator::evaluate_expression, operation_handler::{cast_value, get_storage_value}, type_resolver::get_expression_type, types::RowContext}, foreign_key::referential_action_handler, select::{subquery_handler, table_reader, utils}, storage_backend::table_storage::{get_table_storage, RowId}, utils::ast_unwrapper::{get_new_column_values, get_table_name_from_from}, validation}, transaction_manager::lock_manager};
 
+// Matched rows with their new values, the same rows as they were, and the values that changed
+type UpdatedRecords = (Vec<(RowId, StringRecord)>, Vec<StringRecord>, Vec<Vec<InsertedRowColumn>>);
 
-pub async fn update_records(table: &TableWithJoins, assignments: &Vec<Assignment>, filters: &Option<Expr>) -> Result<String, Error> {
+pub async fn update_records(table: &TableWithJoins, assignments: &Vec<Assignment>, filters: &Option<Expr>) -> Result<ResultSet, Error> {
     // Unwrap table name and new column values
-    let table_name = get_table_name_from_from(table)?;
+    let name = get_table_name_from_from(table)?;
     let new_column_values = get_new_column_values(assignments)?;
     let columns: Vec<String> = new_column_values.keys().cloned().collect();
     
-    // Prepare: get database blueprint and necessary data from it
-    let database = database_loader::get_database()?;
-    let schema_name = database.configuration.default_schema.clone();
-    let table_schema = match database::utils::find_database_table(&database, &table_name) {
+    // Prepare: lock table, get database blueprint and necessary data from it
+    let (database, schema_name, table_name) = lock_manager::lock_table_for_write_and_get_database(&name).await?;
+    let table_schema = match database::utils::find_database_table(&database, &schema_name, &table_name) {
         Some(schema) => schema,
         None => return Err(Error::TableDoesNotExist { table_name: table_name.clone() }),
     };
 
-    // Validate update 
-    validate_update(&database, &table_name, &new_column_values).await?;
+    // Validate update
+    validate_update(&database, &schema_name, &table_name, &new_column_values)?;
     
-    // Read from file
-    let file_path = get_table_data_path(&schema_name, &table_name);
-    let file = File::open(file_path).map_err(|e| Error::IOError(e))?;
-    let mut rdr: csv::Reader<File> = ReaderBuilder::new().has_headers(true).from_reader(file);
-
-    // Trim spaces in CSV file and find indices
-    let headers = match rdr.headers() {
-        Ok(headers) => headers.iter().map(|h| h.trim().to_string()).collect::<Vec<String>>(),
-        Err(_) => return Err(Error::FailedTableRead { table_name: table_name.clone() }),
-    };
+    // Subqueries in the filters and new values read the tables as they were before the update, and only until the rows are found
+    let subquery_exprs: Vec<&Expr> = filters.iter().chain(new_column_values.values()).collect();
+    let read_guards = lock_manager::lock_tables_for_read(&subquery_handler::get_subquery_tables(&database, &subquery_exprs)?).await?;
+
+    // Find rows matching the filters and compute their new values
+    let storage = get_table_storage(table_schema);
+    let headers = get_headers_from_table_schema(table_schema);
     let column_indices = utils::get_column_indices(&headers, &columns);
 
-    let mut records = rdr.records().filter_map(Result::ok).collect::<Vec<StringRecord>>();
+    let (records, old_records, changed_rows) = subquery_handler::with_subquery_cache(|| {
+        get_updated_records(&schema_name, table_schema, filters, &new_column_values, &headers, &column_indices)
+    })?;
+    let updated_rows = records.len() as u64;
+    drop(read_guards);
+
+    // Validate the changed values against column types and constraints
+    validation::column_types::validate_column_types(table_schema, &changed_rows)?;
+    validation::column_constraints::validate_column_constraints(&changed_rows, &schema_name, table_schema, false).await?;
+    validation::column_constraints::validate_updated_records(&schema_name, table_schema, &records, &changed_rows)?;
+
+    storage.update_rows(&schema_name, table_schema, &records)?;
+
+    // Apply the foreign keys of the tables referencing the updated rows
+    let updated_records = old_records.into_iter().zip(records.into_iter().map(|(_, record)| record)).collect();
+    referential_action_handler::apply_update_actions(&schema_name, table_schema, updated_records).await?;
+
+    Ok(ResultSet::from_rows_affected(updated_rows, String::from("Success: The records have been updated successfully.")))
+}
+
+fn get_updated_records(
+    schema_name: &String,
+    table_schema: &TableSchema,
+    filters: &Option<Expr>,
+    new_column_values: &HashMap<String, Expr>,
+    headers: &[String],
+    column_indices: &[usize],
+) -> Result<UpdatedRecords, Error> {
+    let mut records = table_reader::read_table(schema_name, table_schema, filters, true)?;
+    let old_records: Vec<StringRecord> = records.iter().map(|(_, record)| record.clone()).collect();
+    let mut changed_rows: Vec<Vec<InsertedRowColumn>> = Vec::new();
 
-    for record in &mut records {
+    for (_, record) in &mut records {
         let mut record_fields: Vec<String> = record.iter().map(|s| s.to_string()).collect();
-    
-        let is_hit = apply_filters(record, &headers, (*filters).as_ref())?;
-        if is_hit {
-            for &column_index in column_indices.iter() {
-                if let Some(column_name) = headers.get(column_index) {
-                    if let Some(new_value) = new_column_values.get(column_name) {
-                        // Update the value at the specified column index
-                        if column_index < record_fields.len() {
-                            record_fields[column_index] = new_value.clone();
-                        }
-                    }
+        let mut changed_row: Vec<InsertedRowColumn> = Vec::new();
+
+        // Compute the new values from the row as it was before the update
+        let context = RowContext::from_table(table_schema, Some(record));
+        for &column_index in column_indices.iter() {
+            let column = match table_schema.columns.iter().find(|column| Some(&column.name) == headers.get(column_index)) {
+                Some(column) => column,
+                None => continue,
+            };
+            if let Some(expr) = new_column_values.get(&column.name) {
+                let value = cast_value(&evaluate_expression(expr, &context)?, &column.data_type)?;
+                let new_value = get_storage_value(&value);
+
+                // Update the value at the specified column index
+                if column_index < record_fields.len() && record_fields[column_index].trim() != new_value {
+                    record_fields[column_index] = new_value.clone();
+                    changed_row.push(InsertedRowColumn { name: column.name.clone(), value: (value != CellValue::Null).then_some(new_value) });
                 }
             }
         }
 
         // Construct a new StringRecord from the modified record_fields
         *record = StringRecord::from(record_fields);
+        changed_rows.push(changed_row);
     }
 
-    record_handler::rewrite_records(&records, &schema_name, &table_name)?;
-
-    index_updater::update_indexes_on_update_or_delete(&records, &schema_name, &table_name, table_schema)?;
-
-    Ok(String::from("Success: The records have been updated successfully."))
+    Ok((records, old_records, changed_rows))
 }
 
-async fn validate_update(database: &Database, table_name: &String, new_column_values: &HashMap<String, String>) -> Result<Vec<Vec<InsertedRowColumn>>, Error> {
+fn validate_update(database: &Database, schema_name: &String, table_name: &String, new_column_values: &HashMap<String, Expr>) -> Result<(), Error> {
     // Validate table exists
-    validation::common::validate_table_exists(database, table_name)?;
+    validation::common::validate_table_exists(database, schema_name, table_name)?;
 
     // Validate columns exist
-    let table_schema = match find_database_table(database, &table_name) {
+    let table_schema = match find_database_table(database, schema_name, table_name) {
         Some(schema) => schema,
         None => return Err(Error::TableDoesNotExist { table_name: table_name.clone() }),
     };
-    for (key, _) in new_column_values {
+    for key in new_column_values.keys() {
         validation::common::validate_column_exists(table_schema, key)?;
     }
 
-    // Validate column types
-    // TODO: Fix this mess
-    let row: Vec<InsertedRowColumn> = new_column_values.into_iter().map(|(name, value)| {
-        InsertedRowColumn { name: name.to_string(), value: value.to_string() }
-    }).collect();
-    let inserted_rows = vec![row];
-    validation::column_types::validate_column_types(table_schema, &inserted_rows)?;
-    
-    // Validate column constraints
-    let validated_inserted_rows = validation::column_constraints::validate_column_constraints(&inserted_rows, &database.configuration.default_schema, table_schema, false).await?;
+    // Validate the assigned expressions reference existing columns
+    let context = RowContext::from_table(table_schema, None);
+    for expr in new_column_values.values() {
+        get_expression_type(expr, &context)?;
+    }
     
-    Ok(validated_inserted_rows)
-}
\ No newline at end of file
+    Ok(())
+}
@@ -1,39 +1,46 @@
 use std::collections::HashMap;
 
-use sqlparser::ast::{AlterTableOperation, Assignment, ColumnDef, ColumnOptionDef, Expr, Ident, OrderBy, Query, Select, SelectItem, TableFactor, TableWithJoins, Value};
+use sqlparser::ast::{AlterTableOperation, Assignment, AssignmentTarget, ColumnDef, ColumnOptionDef, Expr, GroupByExpr, Ident, ObjectName, Offset, OrderBy, Query, Select, TableFactor, TableWithJoins};
 
-use crate::{database::types::ReferentialAction, shared::errors::Error, storage_engine::select::types::SelectParameters};
+use crate::{database::types::ReferentialAction, shared::errors::Error, storage_engine::select::types::{SelectParameters, SortKey}};
 
 
 pub fn unwrap_select_query(query: &Query) -> Result<SelectParameters, Error> {
     let mut select_parameters = SelectParameters {
-        table_name: String::from(""),
-        columns: Vec::new(),
+        from: Vec::new(),
+        projection: Vec::new(),
         filters: None,
-        order_column_name: None,
-        ascending: false,
-        limit_value: None
+        group_by: Vec::new(),
+        having: None,
+        order_by: Vec::new(),
+        limit_value: None,
+        offset_value: 0,
     };
     
-    let Query { body, order_by, limit, .. } = query;
+    let Query { body, order_by, limit, offset, .. } = query;
 
     match &**body {
         sqlparser::ast::SetExpr::Select(select) => {
             let Select {
-                projection, from, selection, ..
+                projection, from, selection, group_by, having, ..
             } = &**select;
 
-            select_parameters.table_name = get_table_name_from_from_vector(from)?;
+            if from.is_empty() {
+                return Err(Error::MissingTableName);
+            }
+            select_parameters.from = from.clone();
 
-            select_parameters.columns = get_columns(projection);
+            select_parameters.projection = projection.clone();
 
             select_parameters.filters = selection.clone();
+
+            select_parameters.group_by = get_group_by(group_by)?;
+            select_parameters.having = having.clone();
             
-            let (order_column_name, ascending) = get_ordering(order_by);
-            select_parameters.order_column_name = order_column_name;
-            select_parameters.ascending = ascending;
+            select_parameters.order_by = get_ordering(order_by)?;
 
             select_parameters.limit_value = get_limit(limit)?; 
+            select_parameters.offset_value = get_offset(offset)?;
         }
         _ => Err(Error::UnsupportedSelectClause)?
     }
@@ -41,7 +48,7 @@ pub fn unwrap_select_query(query: &Query) -> Result<SelectParameters, Error> {
     Ok(select_parameters)
 }
 
-pub fn get_table_name_from_from_vector(from: &Vec<TableWithJoins>) -> Result<String, Error> {
+pub fn get_table_name_from_from_vector(from: &Vec<TableWithJoins>) -> Result<ObjectName, Error> {
     let table = if !from.is_empty() {
         get_table_name_from_from(&from[0])?
     } else {
@@ -51,45 +58,44 @@ pub fn get_table_name_from_from_vector(from: &Vec<TableWithJoins>) -> Result<Str
     Ok(table)
 }
 
-pub fn get_table_name_from_from(from: &TableWithJoins) -> Result<String, Error> {
+pub fn get_table_name_from_from(from: &TableWithJoins) -> Result<ObjectName, Error> {
     let table = &from.relation;
 
     let table_name = match table {
-        TableFactor::Table { name, .. } => {
-            let parts: Vec<String> = name.0.iter().map(|ident| ident.value.clone()).collect();
-            parts.join(".")
-        }
+        TableFactor::Table { name, .. } => name.clone(),
         _ => return Err(Error::GenericUnsupported),
     };
 
     Ok(table_name)
 }
 
-pub fn get_columns(projection: &Vec<SelectItem>) -> Vec<String> {
-    projection
-        .iter()
-        .filter_map(|item| match item {
-            SelectItem::UnnamedExpr(Expr::Identifier(ident)) => Some(ident.value.clone()),
-            SelectItem::Wildcard(_) => Some("*".to_string()),
-            _ => None,
-        })
-        .collect()
+pub fn get_group_by(group_by: &GroupByExpr) -> Result<Vec<Expr>, Error> {
+    match group_by {
+        GroupByExpr::Expressions(exprs, modifiers) if modifiers.is_empty() => Ok(exprs.clone()),
+        _ => Err(Error::UnsupportedSelectClause),
+    }
 }
 
-pub fn get_ordering(order_by: &Option<OrderBy>) -> (Option<String>, bool) {
-    let mut order_column_name: Option<String> = None;
-    let mut ascending = true;
+// NULLs sort as larger than any value by default, so they come last when ascending
+pub fn get_ordering(order_by: &Option<OrderBy>) -> Result<Vec<SortKey>, Error> {
+    let mut sort_keys: Vec<SortKey> = Vec::new();
 
-    if let Some(order_by_expr) = order_by {
-        if let Some(order_by_expr_first) = order_by_expr.exprs.get(0) {
-            if let Expr::Identifier(ident) = &order_by_expr_first.expr {
-                order_column_name = Some(ident.value.clone());
-                ascending = order_by_expr_first.asc.unwrap_or(true);
+    if let Some(order_by) = order_by {
+        for order_by_expr in &order_by.exprs {
+            if order_by_expr.with_fill.is_some() {
+                return Err(Error::UnsupportedSelectClause);
             }
+
+            let ascending = order_by_expr.asc.unwrap_or(true);
+            sort_keys.push(SortKey {
+                expr: order_by_expr.expr.clone(),
+                ascending,
+                nulls_first: order_by_expr.nulls_first.unwrap_or(!ascending),
+            });
         }
     }
 
-    (order_column_name, ascending)
+    Ok(sort_keys)
 }
 
 pub fn get_limit(limit: &Option<Expr>) -> Result<Option<usize>, Error> {
@@ -109,21 +115,32 @@ pub fn get_limit(limit: &Option<Expr>) -> Result<Option<usize>, Error> {
     Ok(limit_value)
 }
 
-pub fn get_new_column_values(assignments: &Vec<Assignment>) -> Result<HashMap<String, String>, Error> {
-    let mut new_column_values: HashMap<String, String> = HashMap::new();
+pub fn get_offset(offset: &Option<Offset>) -> Result<usize, Error> {
+    let offset_expr = match offset {
+        Some(offset) => &offset.value,
+        None => return Ok(0),
+    };
+
+    match offset_expr {
+        Expr::Value(sqlparser::ast::Value::Number(offset_str, _)) => offset_str.parse::<usize>()
+            .map_err(|_| Error::InvalidOffset { offset: offset_str.clone() }),
+        _ => Err(Error::InvalidOffset { offset: offset_expr.to_string() }),
+    }
+}
+
+// Assigned column names with the expressions computing their new values
+pub fn get_new_column_values(assignments: &Vec<Assignment>) -> Result<HashMap<String, Expr>, Error> {
+    let mut new_column_values: HashMap<String, Expr> = HashMap::new();
 
     for assignment in assignments {
-        let expr = &assignment.value;
-        let column_name = match expr {
-            Expr::Identifier(ident) => ident.value.clone(),
-            _ => return Err(Error::InvalidSQLSyntax),
-        };
-        let column_value = match &assignment.value {
-            Expr::Value(Value::SingleQuotedString(value)) => value.clone(),
-            Expr::Value(Value::Number(n, _)) => n.clone(),
-            _ => return Err(Error::UnsupportedValueType { value: assignment.value.to_string() }),
+        let column_name = match &assignment.target {
+            AssignmentTarget::ColumnName(ObjectName(idents)) => match idents.last() {
+                Some(ident) => ident.value.clone(),
+                None => return Err(Error::InvalidSQLSyntax),
+            },
+            AssignmentTarget::Tuple(_) => return Err(Error::UnsupportedExpression { expression: assignment.target.to_string() }),
         };
-        new_column_values.insert(column_name, column_value);
+        new_column_values.insert(column_name, assignment.value.clone());
     }
 
     Ok(new_column_values)
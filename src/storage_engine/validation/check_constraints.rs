@@ -0,0 +1,129 @@
+use csv::StringRecord;
+use sqlparser::{ast::{Expr, Ident}, dialect::PostgreSqlDialect, parser::Parser};
+
+use crate::{database::types::{CheckConstraint, Constraint, DataType, TableSchema}, shared::errors::Error, storage_engine::expressions::{expression_evaluator::evaluate_check, expression_walker::{contains_subquery, find_expressions, get_child_expressions_mut}, type_resolver::get_expression_type, types::RowContext}};
+
+// Validates the expression against the table's columns, naming the constraint like Postgres does if no name is given
+pub fn create_check_constraint(table_schema: &TableSchema, name: &Option<Ident>, expr: &Expr) -> Result<CheckConstraint, Error> {
+    validate_check_expression(table_schema, expr)?;
+
+    let name = match name {
+        Some(ident) => ident.value.clone(),
+        None => get_default_check_name(table_schema),
+    };
+
+    Ok(CheckConstraint { name, expression: expr.to_string() })
+}
+
+// Each row must not make any check false
+pub fn validate_check_constraints(table_schema: &TableSchema, records: &[StringRecord]) -> Result<(), Error> {
+    for check in get_table_checks(table_schema) {
+        let expr = parse_check_expression(&check.expression)?;
+
+        for record in records {
+            if !evaluate_check(&expr, &RowContext::from_table(table_schema, Some(record)))? {
+                return Err(Error::CheckConstraintNotSatisfied { constraint_name: check.name.clone() });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Table checks followed by column ones, the latter named after their column like Postgres does
+pub fn get_table_checks(table_schema: &TableSchema) -> Vec<CheckConstraint> {
+    let column_checks = table_schema.columns.iter()
+        .flat_map(|column| column.constraints.iter().filter_map(move |constraint| match constraint {
+            Constraint::Check(expression) => Some(CheckConstraint { name: format!("{}_{}_check", table_schema.name, column.name), expression: expression.clone() }),
+            _ => None,
+        }));
+
+    table_schema.checks.iter().cloned().chain(column_checks).collect()
+}
+
+// Validates the checks of the table's columns, which can refer to other columns of the table
+pub fn validate_column_checks(table_schema: &TableSchema) -> Result<(), Error> {
+    let expressions = table_schema.columns.iter()
+        .flat_map(|column| column.constraints.iter())
+        .filter_map(|constraint| match constraint {
+            Constraint::Check(expression) => Some(expression),
+            _ => None,
+        });
+    for expression in expressions {
+        validate_check_expression(table_schema, &parse_check_expression(expression)?)?;
+    }
+
+    Ok(())
+}
+
+// Columns the check expression refers to
+pub fn get_check_columns(expression: &str) -> Result<Vec<String>, Error> {
+    let expr = parse_check_expression(expression)?;
+
+    let columns = find_expressions(&expr, &|expr| matches!(expr, Expr::Identifier(_) | Expr::CompoundIdentifier(_))).into_iter()
+        .filter_map(|expr| match expr {
+            Expr::Identifier(ident) => Some(ident.value.clone()),
+            Expr::CompoundIdentifier(idents) => idents.last().map(|ident| ident.value.clone()),
+            _ => None,
+        })
+        .collect();
+
+    Ok(columns)
+}
+
+// The check expression with its columns renamed, given as (old name, new name)
+pub fn rename_check_columns(expression: &str, renamed_columns: &[(String, String)]) -> Result<String, Error> {
+    let mut expr = parse_check_expression(expression)?;
+    rename_columns(&mut expr, renamed_columns);
+
+    Ok(expr.to_string())
+}
+
+fn rename_columns(expr: &mut Expr, renamed_columns: &[(String, String)]) {
+    let ident = match expr {
+        Expr::Identifier(ident) => Some(ident),
+        Expr::CompoundIdentifier(idents) => idents.last_mut(),
+        _ => None,
+    };
+    if let Some(ident) = ident {
+        if let Some((_, new_name)) = renamed_columns.iter().find(|(old_name, _)| old_name == &ident.value) {
+            ident.value = new_name.clone();
+        }
+        return;
+    }
+
+    for child in get_child_expressions_mut(expr) {
+        rename_columns(child, renamed_columns);
+    }
+}
+
+fn validate_check_expression(table_schema: &TableSchema, expr: &Expr) -> Result<(), Error> {
+    if contains_subquery(expr) {
+        return Err(Error::UnsupportedConstraint { column_name: table_schema.name.clone(), column_constraint: expr.to_string() });
+    }
+    if get_expression_type(expr, &RowContext::from_table(table_schema, None))? != DataType::Boolean {
+        return Err(Error::InvalidOperandTypes { operation: String::from("CHECK") });
+    }
+
+    Ok(())
+}
+
+fn parse_check_expression(expression: &str) -> Result<Expr, Error> {
+    Parser::new(&PostgreSqlDialect {}).try_with_sql(expression)
+        .and_then(|mut parser| parser.parse_expr())
+        .map_err(|_| Error::InvalidSQLSyntax)
+}
+
+fn get_default_check_name(table_schema: &TableSchema) -> String {
+    let base_name = format!("{}_check", table_schema.name);
+
+    let mut name = base_name.clone();
+    let mut suffix = 1;
+    let checks = get_table_checks(table_schema);
+    while checks.iter().any(|check| check.name == name) {
+        name = format!("{}{}", base_name, suffix);
+        suffix += 1;
+    }
+
+    name
+}
@@ -1,6 +1,10 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 
-use crate::{database::types::{Column, Constraint, InsertedRowColumn, TableSchema}, shared::{errors::Error, utils::transpose_matrix}, storage_engine::{index::index_reader, insert::utils, select::table_reader}};
+use csv::StringRecord;
+
+use crate::{database::{constants::NULL_VALUE, database_loader, types::{Column, Constraint, ForeignKey, Index, InsertedRowColumn, TableSchema}, utils::{find_database_table, get_headers_from_table_schema}}, shared::{errors::Error, utils::transpose_matrix}, storage_engine::{foreign_key::foreign_key_manager::{find_referenced_index, get_foreign_schema_name}, index::{btree::{IndexKey, IndexValue}, index_manager, index_reader}, insert::utils, storage_backend::table_storage::{get_table_storage, RowId}}};
+
+use super::check_constraints;
 
 
 pub async fn validate_column_constraints(inserted_rows: &Vec<Vec<InsertedRowColumn>>, schema_name: &String, table_schema: &TableSchema, complete: bool) -> Result<Vec<Vec<InsertedRowColumn>>, Error> {
@@ -13,12 +17,11 @@ pub async fn validate_column_constraints(inserted_rows: &Vec<Vec<InsertedRowColu
         let complete_column_values = if complete {
             validate_null_and_default_constraints(&column, &inserted_column_values).await?
         } else {
-            inserted_column_values.into_iter().filter_map(|x| x).collect()
+            let column_values: Vec<Option<String>> = inserted_column_values.into_iter().flatten().collect();
+            validate_not_null_constraint(&column, &column_values)?;
+            column_values
         };
 
-        println!("Complete column values: {:?}", complete_column_values);
-        validate_foreign_key_constraint(&column, schema_name, table_schema, &complete_column_values).await?;
-
         validate_uniqueness_constraint(&column, schema_name, table_schema, &complete_column_values).await?;
         
         let complete_column_row_values = complete_column_values.iter()
@@ -34,14 +37,19 @@ pub async fn validate_column_constraints(inserted_rows: &Vec<Vec<InsertedRowColu
     // Transpose 
     let complete_inserted_rows = transpose_matrix(complete_inserted_rows_transposed);
 
+    // Updated rows are only partly known here, so their row constraints are checked separately
+    if complete {
+        let records: Vec<StringRecord> = complete_inserted_rows.iter()
+            .map(|row| StringRecord::from(row.iter().map(|column| column.value.as_deref().unwrap_or(NULL_VALUE)).collect::<Vec<&str>>()))
+            .collect();
+        validate_row_constraints(schema_name, table_schema, &records, None)?;
+    }
+
     Ok(complete_inserted_rows)
 }
 
 // - Null values
-async fn validate_null_and_default_constraints(column: &Column, inserted_column_values: &Vec<Option<String>>) -> Result<Vec<String>, Error> {
-    let mut complete_column_values: Vec<String> = Vec::new();
-
-    let is_not_null = column.constraints.contains(&Constraint::NotNull);
+async fn validate_null_and_default_constraints(column: &Column, inserted_column_values: &[Option<Option<String>>]) -> Result<Vec<Option<String>>, Error> {
     let default_value = column.constraints.iter().find_map(|constraint| {
         if let Constraint::DefaultValue(value) = constraint {
             Some(value.clone())
@@ -50,71 +58,191 @@ async fn validate_null_and_default_constraints(column: &Column, inserted_column_
         }
     });
 
-    // Insert value if it exists, otherwise default value, otherwise Null if no Not Null constraint
-    for inserted_value in inserted_column_values {
-        match inserted_value {
-            Some(value) => complete_column_values.push(value.clone()),
-            None => {
-                match &default_value {
-                    Some(default_value) => {
-                        complete_column_values.push(default_value.clone());
-                    },
-                    None => {
-                        if is_not_null {
-                            return Err(Error::ColumnNotNull { column_name: column.name.clone() });
-                        } else {
-                            complete_column_values.push(String::from("Null"));
-                        }
-                    }
-                }
-            }
-        }
-    }
+    // Inserted value if the row has the column, otherwise default value, otherwise NULL
+    let complete_column_values: Vec<Option<String>> = inserted_column_values.iter()
+        .map(|inserted_value| match inserted_value {
+            Some(value) => value.clone(),
+            None => default_value.clone().filter(|value| value != NULL_VALUE),
+        })
+        .collect();
+
+    validate_not_null_constraint(column, &complete_column_values)?;
 
     Ok(complete_column_values)
 }
 
+fn validate_not_null_constraint(column: &Column, column_values: &[Option<String>]) -> Result<(), Error> {
+    if column.constraints.contains(&Constraint::NotNull) && column_values.iter().any(Option::is_none) {
+        return Err(Error::ColumnNotNull { column_name: column.name.clone() });
+    }
+
+    Ok(())
+}
+
+
+// Checks the row constraints of the updated rows, with the values that changed in each
+pub fn validate_updated_records(schema_name: &String, table_schema: &TableSchema, updated_records: &[(RowId, StringRecord)], changed_rows: &[Vec<InsertedRowColumn>]) -> Result<(), Error> {
+    let records: Vec<StringRecord> = updated_records.iter().map(|(_, record)| record.clone()).collect();
+
+    validate_row_constraints(schema_name, table_schema, &records, Some(changed_rows))
+}
+
+// - Row constraints: checks, unique keys of several columns and foreign keys
+fn validate_row_constraints(schema_name: &String, table_schema: &TableSchema, records: &[StringRecord], changed_rows: Option<&[Vec<InsertedRowColumn>]>) -> Result<(), Error> {
+    check_constraints::validate_check_constraints(table_schema, records)?;
+
+    // Keys of updated rows only need checking if one of their columns changed
+    let get_key_records = |columns: &[String]| -> Vec<&StringRecord> {
+        records.iter().enumerate()
+            .filter(|(position, _)| changed_rows.is_none_or(|changed_rows| {
+                changed_rows.get(*position).is_some_and(|changed_row| changed_row.iter().any(|column| columns.contains(&column.name)))
+            }))
+            .map(|(_, record)| record)
+            .collect()
+    };
+
+    for index in table_schema.indexes.iter().filter(|index| index.is_unique) {
+        validate_unique_key(schema_name, table_schema, index, &get_key_records(&index.columns))?;
+    }
+    for foreign_key in &table_schema.foreign_keys {
+        validate_foreign_key(schema_name, table_schema, foreign_key, &get_key_records(&foreign_key.local_columns))?;
+    }
+
+    Ok(())
+}
+
+// - Unique keys
+fn validate_unique_key(schema_name: &str, table_schema: &TableSchema, index: &Index, records: &[&StringRecord]) -> Result<(), Error> {
+    if records.is_empty() {
+        return Ok(());
+    }
+
+    let columns = index_manager::get_index_columns(table_schema, index)?;
+    let mut btree = index_reader::open_index(schema_name, &table_schema.name, &index.name)?;
+    let mut keys: BTreeSet<IndexKey> = BTreeSet::new();
+
+    // Against each other, then against the existing rows
+    for record in records {
+        let key = index_manager::get_index_key(record, &columns);
+        if index_manager::is_null_key(&key) {
+            continue;
+        }
+        if !keys.insert(key.clone()) || !btree.find(&key)?.is_empty() {
+            return Err(Error::IndexUniquenessNotSatisfied { index_name: index.name.clone(), value: index_manager::get_key_values(record, &columns) });
+        }
+    }
+
+    Ok(())
+}
 
 // - Foreign key
-async fn validate_foreign_key_constraint(column: &Column, schema_name: &String, table_schema: &TableSchema, inserted_column_values: &Vec<String>) -> Result<(), Error> {
-    let foreign_key = table_schema.foreign_keys.iter().find(|foreign_key| foreign_key.local_columns.contains(&column.name));
-    println!("Foreign key: {:?}", foreign_key);
-    if let Some(foreign_key) = foreign_key {
-        for column_name in &foreign_key.foreign_columns {
-            let foreign_column_values = table_reader::read_column_values(schema_name, &foreign_key.foreign_table, column_name).await?;
-
-            for value in inserted_column_values {
-                if !foreign_column_values.contains(&value) {
-                    return Err(Error::ForeignKeyConstraintNotSatisfied { foreign_key_name: foreign_key.name.clone(), });
-                }
+fn validate_foreign_key(schema_name: &String, table_schema: &TableSchema, foreign_key: &ForeignKey, records: &[&StringRecord]) -> Result<(), Error> {
+    let headers = get_headers_from_table_schema(table_schema);
+    let positions: Vec<Option<usize>> = foreign_key.local_columns.iter()
+        .map(|column_name| headers.iter().position(|header| header == column_name))
+        .collect();
+
+    // Columns missing from the table read as NULL
+    let keys = records.iter()
+        .map(|record| positions.iter().map(|position| position.and_then(|position| record.get(position)).unwrap_or(NULL_VALUE).trim().to_string()).collect())
+        .collect();
+
+    validate_foreign_key_values(schema_name, foreign_key, keys)
+}
+
+// Each key, in the order of the local columns, must match a row of the referenced table as a whole
+fn validate_foreign_key_values(schema_name: &String, foreign_key: &ForeignKey, mut keys: Vec<Vec<String>>) -> Result<(), Error> {
+    // Keys with a NULL part reference nothing
+    keys.retain(|key| !key.iter().any(|value| value == NULL_VALUE));
+    if keys.is_empty() {
+        return Ok(());
+    }
+
+    let database = database_loader::get_database()?;
+    let foreign_schema_name = get_foreign_schema_name(foreign_key, schema_name);
+    let foreign_table_schema = find_database_table(&database, foreign_schema_name, &foreign_key.foreign_table)
+        .ok_or_else(|| Error::TableDoesNotExist { table_name: foreign_key.foreign_table.clone() })?;
+
+    let mut foreign_columns: Vec<&Column> = Vec::new();
+    for column_name in &foreign_key.foreign_columns {
+        let column = foreign_table_schema.columns.iter().find(|column| &column.name == column_name)
+            .ok_or_else(|| Error::ColumnDoesNotExist { column_name: column_name.clone(), table_name: foreign_table_schema.name.clone() })?;
+        foreign_columns.push(column);
+    }
+    let index_keys: Vec<IndexKey> = keys.iter()
+        .map(|key| key.iter().zip(&foreign_columns).map(|(value, column)| IndexValue::from_value(value, &column.data_type)).collect())
+        .collect();
+    let not_satisfied = || Error::ForeignKeyConstraintNotSatisfied { foreign_key_name: foreign_key.name.clone() };
+
+    // Look the keys up in the index on the referenced columns if there is one, with its column order
+    if let Some(index) = find_referenced_index(foreign_table_schema, &foreign_key.foreign_columns) {
+        let index_positions: Vec<usize> = index.columns.iter()
+            .filter_map(|column_name| foreign_key.foreign_columns.iter().position(|foreign_column| foreign_column == column_name))
+            .collect();
+        let mut btree = index_reader::open_index(foreign_schema_name, &foreign_table_schema.name, &index.name)?;
+
+        for index_key in &index_keys {
+            let lookup_key: IndexKey = index_positions.iter().filter_map(|&position| index_key.get(position).cloned()).collect();
+            if btree.find(&lookup_key)?.is_empty() {
+                return Err(not_satisfied());
             }
         }
+
+        return Ok(());
     }
 
-    Ok(())
+    // Otherwise collect the referenced keys in one pass over the table
+    let headers = get_headers_from_table_schema(foreign_table_schema);
+    let positions: Vec<usize> = foreign_key.foreign_columns.iter()
+        .filter_map(|column_name| headers.iter().position(|header| header == column_name))
+        .collect();
+    let mut referenced_keys: BTreeSet<IndexKey> = BTreeSet::new();
+    for row in get_table_storage(foreign_table_schema).stream_rows(foreign_schema_name, foreign_table_schema)? {
+        let (_, record) = row?;
+        let referenced_key = positions.iter().zip(&foreign_columns)
+            .map(|(&position, column)| IndexValue::from_value(record.get(position).unwrap_or(NULL_VALUE).trim(), &column.data_type))
+            .collect();
+        referenced_keys.insert(referenced_key);
+    }
+
+    match index_keys.iter().all(|index_key| referenced_keys.contains(index_key)) {
+        true => Ok(()),
+        false => Err(not_satisfied()),
+    }
 }
 
 // - Uniqueness
-async fn validate_uniqueness_constraint(column: &Column, schema_name: &String, table_schema: &TableSchema, inserted_column_values: &Vec<String>) -> Result<(), Error> {
+async fn validate_uniqueness_constraint(column: &Column, schema_name: &str, table_schema: &TableSchema, inserted_column_values: &[Option<String>]) -> Result<(), Error> {
     let is_unique_constraint = column.constraints.contains(&Constraint::Unique) || column.constraints.contains(&Constraint::PrimaryKey);
     if !is_unique_constraint {
         return Ok(());
     }
 
-    // Get column values
-    let column_index = index_reader::read_column_index(schema_name, &table_schema.name, &column.name)?;
-    let column_values = index_reader::get_column_values_from_index(&column_index, schema_name, &table_schema.name)?;
-
+    // NULLs are never equal to each other, so they can repeat
+    let inserted_column_values: Vec<&String> = inserted_column_values.iter().flatten().collect();
     let mut values_set = HashSet::new();
 
     // Check for duplicates among insert_values
-    for value in inserted_column_values {
+    for &value in &inserted_column_values {
         if !values_set.insert(value) {
             return Err(Error::ColumnUniquenessNotSatisfied { column_name: column.name.clone(), value: value.clone() })
         }
     }
 
-    // Check for duplicates with column values
+    // Check for duplicates with column values, looking them up in the index if there is one
+    if column.is_indexed {
+        let mut index = index_reader::open_index(schema_name, &table_schema.name, &column.name)?;
+        for value in inserted_column_values {
+            if !index.find(&vec![IndexValue::from_value(value, &column.data_type)])?.is_empty() {
+                return Err(Error::ColumnUniquenessNotSatisfied { column_name: column.name.clone(), value: value.clone() })
+            }
+        }
+
+        return Ok(());
+    }
+
+    // Otherwise compare against all column values
+    let column_values = get_table_storage(table_schema).read_column_values(schema_name, table_schema, &column.name)?;
     for value in column_values {
         if values_set.contains(&value) {
             return Err(Error::ColumnUniquenessNotSatisfied { column_name: column.name.clone(), value: value.clone() })
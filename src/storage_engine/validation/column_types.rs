@@ -5,7 +5,9 @@ pub fn validate_column_types(table_schema: &TableSchema, inserted_rows: &Vec<Vec
     for row in inserted_rows {
         for inserted_column in row {
             if let Some(schema_column) = table_schema.columns.iter().find(|c| c.name == inserted_column.name) {
-                validate_value_type(schema_column, &inserted_column.value)?;
+                if let Some(value) = &inserted_column.value {
+                    validate_value_type(schema_column, value)?;
+                }
             } else {
                 return Err(Error::ColumnDoesNotExist {
                     column_name: inserted_column.name.clone(),
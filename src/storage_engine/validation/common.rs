@@ -1,26 +1,33 @@
 use sqlparser::ast::ColumnDef;
 
-use crate::{database::{self, types::{Column, Database, TableSchema}}, shared::errors::Error, storage_engine::index::index_manager};
+use crate::{database::{self, types::{Column, Constraint, Database, TableSchema}}, shared::errors::Error, storage_engine::index::index_manager};
 
 // Table
-pub fn does_table_exist(database: &Database, table_name: &String) -> bool {
-    let default_schema = database.configuration.default_schema.clone();
-    if let Some(schema) = database.schemas.iter().find(|s| s.name == default_schema) {
+pub fn does_table_exist(database: &Database, schema_name: &String, table_name: &String) -> bool {
+    if let Some(schema) = database.schemas.iter().find(|s| &s.name == schema_name) {
         return schema.tables.iter().any(|table| &table.name == table_name);
     }
     false
 }
 
-pub fn validate_table_exists(database: &Database, table_name: &String) -> Result<(), Error> {
-    if !does_table_exist(database, table_name) {
+pub fn validate_schema_exists(database: &Database, schema_name: &String) -> Result<(), Error> {
+    if !database.schemas.iter().any(|schema| &schema.name == schema_name) {
+        return Err(Error::SchemaDoesNotExist { schema_name: schema_name.clone() });
+    }
+
+    Ok(())
+}
+
+pub fn validate_table_exists(database: &Database, schema_name: &String, table_name: &String) -> Result<(), Error> {
+    if !does_table_exist(database, schema_name, table_name) {
         return Err(Error::TableDoesNotExist { table_name: table_name.clone() });
     }
 
     Ok(())
 }
 
-pub fn validate_table_doesnt_exist(database: &Database, table_name: &String) -> Result<(), Error> {
-    if does_table_exist(database, table_name) {
+pub fn validate_table_doesnt_exist(database: &Database, schema_name: &String, table_name: &String) -> Result<(), Error> {
+    if does_table_exist(database, schema_name, table_name) {
         return Err(Error::TableNameAlreadyExists { table_name: table_name.clone() });
     }
 
@@ -70,14 +77,23 @@ pub fn validate_column_definitions(column_definitions: &Vec<ColumnDef>, order: &
     println!("Column def: {:?}, order: {:?}", column_definitions, order);
     for (column_index, column_def) in column_definitions.iter().enumerate() {
         let column = validate_column_definition(column_def, order[column_index])?;
-        // TODO: Validate there exists exactly one Primary Key
-
         schema_columns.push(column);
     }
 
     Ok(schema_columns)
 }
 
+// Tables have at most one primary key, on a single column or spanning several
+pub fn validate_primary_key_count(table_schema: &TableSchema) -> Result<(), Error> {
+    let primary_key_count = table_schema.columns.iter().filter(|column| column.constraints.contains(&Constraint::PrimaryKey)).count()
+        + usize::from(!table_schema.primary_key.is_empty());
+    if primary_key_count > 1 {
+        return Err(Error::MultiplePrimaryKeys { table_name: table_schema.name.clone() });
+    }
+
+    Ok(())
+}
+
 pub fn validate_column_definition(column_definition: &ColumnDef, order: usize) -> Result<Column, Error> {
     let data_type = database::utils::get_column_custom_data_type(&column_definition.data_type, &column_definition.name.value)?;
     let constraints = database::utils::get_column_custom_constraints(&column_definition.options, &column_definition.name.value)?;
@@ -1,3 +1,3 @@
 pub mod common;
 pub mod column_types;
-pub mod column_constraints;
\ No newline at end of file
+pub mod column_constraints;pub mod check_constraints;
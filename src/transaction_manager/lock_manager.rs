@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::sync::{atomic::{AtomicU64, Ordering}, Arc, Mutex};
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use sqlparser::ast::ObjectName;
+use tokio::sync::{Mutex as AsyncMutex, OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock};
+
+use crate::database::{database_loader, types::Database, utils::get_schema_and_table_name};
+use crate::shared::errors::Error;
+
+use super::transaction_context::get_current_transaction;
+
+// Waiting longer than this is treated as a deadlock
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+struct TableLock {
+    writer: Arc<AsyncMutex<()>>, // Held by the transaction writing the table until it ends
+    files: Arc<RwLock<()>>, // Shared by reads, exclusive while a commit applies its changes to the table
+    commits: AtomicU64, // Commits applied to the table so far
+}
+
+lazy_static! {
+    static ref TABLE_LOCKS: Mutex<HashMap<String, Arc<TableLock>>> = Mutex::new(HashMap::new());
+}
+
+/*
+ * Per-table locks: writers wait for each other until their transactions end, while readers read the last
+ * committed files and only wait for a commit that is being applied
+ */
+pub async fn lock_table_for_write(schema_name: &str, table_name: &str) -> Result<(), Error> {
+    let transaction = match get_current_transaction() {
+        Some(transaction) => transaction,
+        None => return Ok(()),
+    };
+    let lock_key = get_lock_key(schema_name, table_name);
+    if transaction.lock().map_err(|_| Error::ServerError)?.table_locks.contains_key(&lock_key) {
+        return Ok(());
+    }
+
+    let table_lock = get_table_lock(&lock_key)?;
+    let guard = tokio::time::timeout(LOCK_TIMEOUT, table_lock.writer.clone().lock_owned()).await
+        .map_err(|_| Error::LockTimeout { table_name: table_name.to_string() })?;
+
+    transaction.lock().map_err(|_| Error::ServerError)?.table_locks.insert(lock_key, guard);
+
+    Ok(())
+}
+
+// Lock the table, then get database blueprint so that it can't change underneath
+pub async fn lock_table_for_write_and_get_database(name: &ObjectName) -> Result<(Arc<Database>, String, String), Error> {
+    let database = database_loader::get_database()?;
+    let (schema_name, table_name) = get_schema_and_table_name(&database, name)?;
+    lock_table_for_write(&schema_name, &table_name).await?;
+
+    Ok((database_loader::get_database()?, schema_name, table_name))
+}
+
+// The returned guard protects a single read from seeing a commit half-applied
+pub async fn lock_table_for_read(schema_name: &str, table_name: &str) -> Result<OwnedRwLockReadGuard<()>, Error> {
+    let table_lock = get_table_lock(&get_lock_key(schema_name, table_name))?;
+
+    tokio::time::timeout(LOCK_TIMEOUT, table_lock.files.clone().read_owned()).await
+        .map_err(|_| Error::LockTimeout { table_name: table_name.to_string() })
+}
+
+// A table read twice is locked once, as a second read lock could wait behind a commit
+pub async fn lock_tables_for_read(tables: &[(String, String)]) -> Result<Vec<OwnedRwLockReadGuard<()>>, Error> {
+    let mut locked_tables: Vec<&(String, String)> = Vec::new();
+    let mut read_guards: Vec<OwnedRwLockReadGuard<()>> = Vec::new();
+
+    for table in tables {
+        if locked_tables.contains(&table) {
+            continue;
+        }
+        locked_tables.push(table);
+        read_guards.push(lock_table_for_read(&table.0, &table.1).await?);
+    }
+
+    Ok(read_guards)
+}
+
+// Commits applied to each table so far, to tell whether a read that released its locks can go on
+pub fn get_commit_counts(tables: &[(String, String)]) -> Result<Vec<u64>, Error> {
+    tables.iter()
+        .map(|(schema_name, table_name)| Ok(get_table_lock(&get_lock_key(schema_name, table_name))?.commits.load(Ordering::SeqCst)))
+        .collect()
+}
+
+// Lock the tables again for a read that released them, which can't go on if a commit changed one in the meantime
+pub async fn relock_tables_for_read(tables: &[(String, String)], commit_counts: &[u64]) -> Result<Vec<OwnedRwLockReadGuard<()>>, Error> {
+    let read_guards = lock_tables_for_read(tables).await?;
+
+    let current_commit_counts = get_commit_counts(tables)?;
+    let changed_table = tables.iter().zip(current_commit_counts.iter().zip(commit_counts))
+        .find(|(_, (commit_count, read_commit_count))| commit_count != read_commit_count);
+    if let Some(((_, table_name), _)) = changed_table {
+        return Err(Error::ReadConflict { table_name: table_name.clone() });
+    }
+
+    Ok(read_guards)
+}
+
+// Tables are locked in a fixed order, so two commits can't wait for each other
+pub async fn lock_tables_for_commit(lock_keys: &[String]) -> Result<Vec<OwnedRwLockWriteGuard<()>>, Error> {
+    let mut lock_keys = lock_keys.to_vec();
+    lock_keys.sort();
+
+    let mut write_guards: Vec<OwnedRwLockWriteGuard<()>> = Vec::new();
+    for lock_key in lock_keys.iter() {
+        let table_lock = get_table_lock(lock_key)?;
+        let guard = tokio::time::timeout(LOCK_TIMEOUT, table_lock.files.clone().write_owned()).await
+            .map_err(|_| Error::LockTimeout { table_name: lock_key.clone() })?;
+        table_lock.commits.fetch_add(1, Ordering::SeqCst);
+        write_guards.push(guard);
+    }
+
+    Ok(write_guards)
+}
+
+fn get_table_lock(lock_key: &str) -> Result<Arc<TableLock>, Error> {
+    let mut table_locks = TABLE_LOCKS.lock().map_err(|_| Error::ServerError)?;
+    let table_lock = table_locks.entry(lock_key.to_string()).or_default();
+
+    Ok(table_lock.clone())
+}
+
+fn get_lock_key(schema_name: &str, table_name: &str) -> String {
+    format!("{}.{}", schema_name, table_name)
+}
\ No newline at end of file
@@ -0,0 +1,6 @@
+pub mod types;
+pub mod staging;
+pub mod transaction_context;
+pub mod transaction_handler;
+pub mod wal_manager;
+pub mod lock_manager;
\ No newline at end of file
@@ -0,0 +1,145 @@
+use std::fs;
+use std::io::{Error as IOError, ErrorKind};
+
+use crate::shared::errors::Error;
+
+use super::types::{FileOperation, FileVersion, StagedFile, Transaction};
+
+/*
+ * File operations staged by a transaction, visible only to the transaction until commit
+ */
+impl Transaction {
+    // None means the file is untouched by the transaction and should be read from disk
+    pub fn read_staged_file(&self, file_path: &str) -> Result<Option<Vec<u8>>, Error> {
+        match self.staged_files.get(file_path) {
+            Some(StagedFile::Written(contents)) => Ok(Some(contents.clone())),
+            Some(StagedFile::Appended { base_length, contents }) => {
+                let mut file_contents = fs::read(file_path)?;
+                file_contents.truncate(*base_length as usize);
+                file_contents.extend_from_slice(contents);
+                Ok(Some(file_contents))
+            },
+            Some(StagedFile::Deleted) => Err(get_not_found_error(file_path)),
+            None => {
+                if self.is_in_removed_directory(file_path) {
+                    return Err(get_not_found_error(file_path));
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn stage_write(&mut self, file_path: &str, contents: Vec<u8>) {
+        self.record_base_version(file_path);
+        self.staged_files.insert(file_path.to_string(), StagedFile::Written(contents.clone()));
+        self.operations.push(FileOperation::Write { path: file_path.to_string(), contents });
+    }
+
+    pub fn stage_append(&mut self, file_path: &str, appended_contents: Vec<u8>) -> Result<(), Error> {
+        self.record_base_version(file_path);
+
+        let offset = match self.staged_files.get_mut(file_path) {
+            Some(StagedFile::Written(contents)) => {
+                let offset = contents.len() as u64;
+                contents.extend_from_slice(&appended_contents);
+                offset
+            },
+            Some(StagedFile::Appended { base_length, contents }) => {
+                let offset = *base_length + contents.len() as u64;
+                contents.extend_from_slice(&appended_contents);
+                offset
+            },
+            Some(StagedFile::Deleted) => return Err(get_not_found_error(file_path)),
+            None => {
+                if self.is_in_removed_directory(file_path) {
+                    return Err(get_not_found_error(file_path));
+                }
+                let base_length = fs::metadata(file_path)?.len();
+                self.staged_files.insert(file_path.to_string(), StagedFile::Appended { base_length, contents: appended_contents.clone() });
+                base_length
+            }
+        };
+
+        self.operations.push(FileOperation::Append { path: file_path.to_string(), offset, contents: appended_contents });
+
+        Ok(())
+    }
+
+    // Overwrite part of a file (e.g. a single page), extending it if needed
+    pub fn stage_write_at(&mut self, file_path: &str, offset: u64, contents: Vec<u8>) -> Result<(), Error> {
+        // Patch already materialized files in place
+        if let Some(StagedFile::Written(file_contents)) = self.staged_files.get_mut(file_path) {
+            write_bytes_at(file_contents, offset, &contents);
+            self.operations.push(FileOperation::WriteAt { path: file_path.to_string(), offset, contents });
+            return Ok(());
+        }
+
+        let mut file_contents = match self.read_staged_file(file_path)? {
+            Some(file_contents) => file_contents,
+            None => match fs::read(file_path) {
+                Ok(file_contents) => file_contents,
+                Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+                Err(e) => return Err(Error::IOError(e)),
+            },
+        };
+        self.record_base_version(file_path);
+
+        write_bytes_at(&mut file_contents, offset, &contents);
+
+        self.staged_files.insert(file_path.to_string(), StagedFile::Written(file_contents));
+        self.operations.push(FileOperation::WriteAt { path: file_path.to_string(), offset, contents });
+
+        Ok(())
+    }
+
+    pub fn stage_create_directory(&mut self, directory_path: &str) {
+        self.operations.push(FileOperation::CreateDirectory { path: directory_path.to_string() });
+    }
+
+    pub fn stage_remove_directory(&mut self, directory_path: &str) {
+        let prefix = format!("{}/", directory_path);
+        for (file_path, staged_file) in self.staged_files.iter_mut() {
+            if file_path.starts_with(&prefix) {
+                *staged_file = StagedFile::Deleted;
+            }
+        }
+
+        self.removed_directories.push(directory_path.to_string());
+        self.operations.push(FileOperation::RemoveDirectory { path: directory_path.to_string() });
+    }
+
+    pub fn stage_remove_file(&mut self, file_path: &str) {
+        self.record_base_version(file_path);
+        self.staged_files.insert(file_path.to_string(), StagedFile::Deleted);
+        self.operations.push(FileOperation::RemoveFile { path: file_path.to_string() });
+    }
+
+    fn is_in_removed_directory(&self, file_path: &str) -> bool {
+        self.removed_directories.iter().any(|directory_path| file_path.starts_with(&format!("{}/", directory_path)))
+    }
+
+    fn record_base_version(&mut self, file_path: &str) {
+        if !self.base_versions.contains_key(file_path) && !self.staged_files.contains_key(file_path) {
+            self.base_versions.insert(file_path.to_string(), get_file_version(file_path));
+        }
+    }
+}
+
+pub fn get_file_version(file_path: &str) -> Option<FileVersion> {
+    fs::metadata(file_path).ok().map(|metadata| FileVersion {
+        length: metadata.len(),
+        modified: metadata.modified().ok(),
+    })
+}
+
+fn get_not_found_error(file_path: &str) -> Error {
+    Error::IOError(IOError::new(ErrorKind::NotFound, format!("{} has been removed in this transaction", file_path)))
+}
+
+fn write_bytes_at(file_contents: &mut Vec<u8>, offset: u64, contents: &[u8]) {
+    let end = offset as usize + contents.len();
+    if file_contents.len() < end {
+        file_contents.resize(end, 0);
+    }
+    file_contents[offset as usize..end].copy_from_slice(contents);
+}
\ No newline at end of file
@@ -0,0 +1,32 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use crate::shared::errors::Error;
+
+use super::types::Transaction;
+
+pub type SharedTransaction = Arc<Mutex<Transaction>>;
+
+tokio::task_local! {
+    // Transaction of the connection whose statement is currently running
+    static CURRENT_TRANSACTION: SharedTransaction;
+}
+
+pub async fn run_in_transaction<F: Future>(transaction: SharedTransaction, future: F) -> F::Output {
+    CURRENT_TRANSACTION.scope(transaction, future).await
+}
+
+pub fn get_current_transaction() -> Option<SharedTransaction> {
+    CURRENT_TRANSACTION.try_with(|transaction| transaction.clone()).ok()
+}
+
+// Run a closure against the current transaction, if any
+pub fn with_current_transaction<T>(f: impl FnOnce(&mut Transaction) -> T) -> Result<Option<T>, Error> {
+    match get_current_transaction() {
+        Some(transaction) => {
+            let mut transaction = transaction.lock().map_err(|_| Error::ServerError)?;
+            Ok(Some(f(&mut transaction)))
+        },
+        None => Ok(None),
+    }
+}
\ No newline at end of file
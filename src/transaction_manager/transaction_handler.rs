@@ -0,0 +1,120 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Seek, SeekFrom, Write};
+use std::sync::{Arc, Mutex};
+
+use lazy_static::lazy_static;
+
+use crate::database::database_loader;
+use crate::shared::errors::Error;
+
+use super::lock_manager;
+use super::staging::get_file_version;
+use super::transaction_context::SharedTransaction;
+use super::types::{FileOperation, Transaction};
+use super::wal_manager;
+
+lazy_static! {
+    // Commits are applied one at a time
+    static ref COMMIT_LOCK: Mutex<()> = Mutex::new(());
+}
+
+pub fn begin_transaction() -> SharedTransaction {
+    Arc::new(Mutex::new(Transaction::default()))
+}
+
+pub async fn commit_transaction(transaction: SharedTransaction) -> Result<(), Error> {
+    // Readers of the written tables wait until the commit is applied and its schema changes are visible
+    let written_tables: Vec<String> = transaction.lock().map_err(|_| Error::ServerError)?.table_locks.keys().cloned().collect();
+    let _commit_guards = lock_manager::lock_tables_for_commit(&written_tables).await?;
+
+    let has_catalog_changes = {
+        let _commit_guard = COMMIT_LOCK.lock().map_err(|_| Error::ServerError)?;
+        let transaction = transaction.lock().map_err(|_| Error::ServerError)?;
+
+        validate_base_versions(&transaction)?;
+
+        if !transaction.operations.is_empty() {
+            // Once logged, the commit survives a crash: recovery replays the record on startup
+            wal_manager::append_record(&transaction.operations)?;
+            apply_file_operations(&transaction.operations)?;
+            wal_manager::truncate_log()?;
+        }
+
+        transaction.catalog.is_some()
+    };
+
+    // Make committed schema changes visible to other connections
+    if has_catalog_changes {
+        database_loader::reload_database().await?;
+    }
+
+    Ok(())
+}
+
+// Ensure no file staged by the transaction has been changed by someone else in the meantime
+fn validate_base_versions(transaction: &Transaction) -> Result<(), Error> {
+    for (file_path, base_version) in transaction.base_versions.iter() {
+        if &get_file_version(file_path) != base_version {
+            return Err(Error::TransactionConflict { file_path: file_path.clone() });
+        }
+    }
+
+    Ok(())
+}
+
+// Replay commits that were logged but possibly not (fully) applied before a crash
+pub fn recover_from_wal() -> Result<usize, Error> {
+    let _commit_guard = COMMIT_LOCK.lock().map_err(|_| Error::ServerError)?;
+
+    let records = wal_manager::read_records()?;
+    for record in records.iter() {
+        apply_file_operations(&record.operations)?;
+    }
+    wal_manager::truncate_log()?;
+
+    Ok(records.len())
+}
+
+// Operations are idempotent, so replaying a partially applied commit is safe
+pub fn apply_file_operations(operations: &Vec<FileOperation>) -> Result<(), Error> {
+    for operation in operations {
+        match operation {
+            FileOperation::CreateDirectory { path } => {
+                fs::create_dir_all(path)?;
+            },
+            FileOperation::Write { path, contents } => {
+                let mut file = File::create(path)?;
+                file.write_all(contents)?;
+                file.sync_all()?;
+            },
+            FileOperation::Append { path, offset, contents } => {
+                // Truncating to the offset first keeps the append idempotent
+                let mut file = OpenOptions::new().write(true).open(path)?;
+                file.set_len(*offset)?;
+                file.seek(SeekFrom::Start(*offset))?;
+                file.write_all(contents)?;
+                file.sync_all()?;
+            },
+            FileOperation::WriteAt { path, offset, contents } => {
+                let mut file = OpenOptions::new().write(true).create(true).truncate(false).open(path)?;
+                file.seek(SeekFrom::Start(*offset))?;
+                file.write_all(contents)?;
+                file.sync_all()?;
+            },
+            FileOperation::RemoveDirectory { path } => {
+                match fs::remove_dir_all(path) {
+                    Err(e) if e.kind() != ErrorKind::NotFound => return Err(Error::IOError(e)),
+                    _ => {},
+                }
+            },
+            FileOperation::RemoveFile { path } => {
+                match fs::remove_file(path) {
+                    Err(e) if e.kind() != ErrorKind::NotFound => return Err(Error::IOError(e)),
+                    _ => {},
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,46 @@
+use std::{collections::HashMap, sync::Arc, time::SystemTime};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::OwnedMutexGuard;
+
+use crate::database::types::Database;
+
+#[derive(Default)]
+pub struct Transaction {
+    pub operations: Vec<FileOperation>, // Applied in order on commit
+    pub staged_files: HashMap<String, StagedFile>, // Latest file contents visible inside the transaction
+    pub removed_directories: Vec<String>,
+    pub base_versions: HashMap<String, Option<FileVersion>>, // Disk state when first staged, checked on commit
+    pub catalog: Option<Arc<Database>>, // Private copy of the catalog once the transaction changes it
+    pub table_locks: HashMap<String, OwnedMutexGuard<()>>, // Tables written by the transaction, released when it ends
+    pub is_failed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum StagedFile {
+    Written(Vec<u8>),
+    Appended { base_length: u64, contents: Vec<u8> },
+    Deleted,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FileOperation {
+    CreateDirectory { path: String },
+    Write { path: String, contents: Vec<u8> },
+    Append { path: String, offset: u64, contents: Vec<u8> },
+    WriteAt { path: String, offset: u64, contents: Vec<u8> },
+    RemoveDirectory { path: String },
+    RemoveFile { path: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileVersion {
+    pub length: u64,
+    pub modified: Option<SystemTime>,
+}
+
+// File operations of one committed transaction, as stored in the write-ahead log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalRecord {
+    pub operations: Vec<FileOperation>,
+}
\ No newline at end of file
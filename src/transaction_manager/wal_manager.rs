@@ -0,0 +1,81 @@
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+
+use crate::database::database_navigator::{get_wal_dir_path, get_wal_path};
+use crate::shared::errors::Error;
+
+use super::types::{FileOperation, WalRecord};
+
+const RECORD_HEADER_SIZE: usize = 8;
+
+/*
+ * Write-ahead log: a commit's file operations are logged and synced before any of them touches the disk
+ */
+// Record layout: [payload length: u32 BE][CRC32 of payload: u32 BE][bincode payload]
+pub fn append_record(operations: &[FileOperation]) -> Result<(), Error> {
+    let record = WalRecord { operations: operations.to_vec() };
+    let payload = bincode::serialize(&record).map_err(|_| Error::FailedWalWrite)?;
+
+    let mut bytes = Vec::with_capacity(payload.len() + RECORD_HEADER_SIZE);
+    bytes.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(&crc32fast::hash(&payload).to_be_bytes());
+    bytes.extend_from_slice(&payload);
+
+    fs::create_dir_all(get_wal_dir_path())?;
+    let mut file = OpenOptions::new().create(true).append(true).open(get_wal_path())?;
+    file.write_all(&bytes)?;
+    file.sync_all()?;
+
+    Ok(())
+}
+
+pub fn read_records() -> Result<Vec<WalRecord>, Error> {
+    let contents = match fs::read(get_wal_path()) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::IOError(e)),
+    };
+
+    Ok(decode_records(&contents))
+}
+
+// Decoding stops at the first incomplete or corrupted record, i.e. a commit interrupted while being logged
+pub fn decode_records(contents: &[u8]) -> Vec<WalRecord> {
+    let mut records: Vec<WalRecord> = Vec::new();
+    let mut position = 0;
+
+    while contents.len() >= position + RECORD_HEADER_SIZE {
+        let length = u32::from_be_bytes([contents[position], contents[position + 1], contents[position + 2], contents[position + 3]]) as usize;
+        let checksum = u32::from_be_bytes([contents[position + 4], contents[position + 5], contents[position + 6], contents[position + 7]]);
+
+        let payload_start = position + RECORD_HEADER_SIZE;
+        if contents.len() < payload_start + length {
+            break;
+        }
+        let payload = &contents[payload_start..payload_start + length];
+        if crc32fast::hash(payload) != checksum {
+            break;
+        }
+
+        match bincode::deserialize::<WalRecord>(payload) {
+            Ok(record) => records.push(record),
+            Err(_) => break,
+        }
+        position = payload_start + length;
+    }
+
+    records
+}
+
+// Called once the logged operations have been applied and synced
+pub fn truncate_log() -> Result<(), Error> {
+    match OpenOptions::new().write(true).open(get_wal_path()) {
+        Ok(file) => {
+            file.set_len(0)?;
+            file.sync_all()?;
+            Ok(())
+        },
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(Error::IOError(e)),
+    }
+}
\ No newline at end of file
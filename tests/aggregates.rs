@@ -0,0 +1,63 @@
+mod common;
+
+use kodasql::{command_dispatcher::session::Session, database::{database_loader, types::DataType}, network_protocol::types::{CellValue, ResultColumn}, shared::errors::Error};
+use common::run_sql;
+
+/*
+ *  test_select_table:
+    id,username,email,age
+    1,John,john@email.com,4
+    2,Mary,mary@email.com,12
+    3,Jane,jane@email.com,20
+    4,Matt,matt@email.com,34
+    5,Andrew,andrew@email.com,21
+    6,Mike,mike@email.com,21
+    7,Mia,mia@email.com,21
+ *
+ */
+
+#[tokio::test]
+async fn test_aggregates() {
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+
+    // Aggregates over the whole table
+    let result_sets = run_sql(&mut session, "SELECT COUNT(*), COUNT(DISTINCT age), SUM(age), AVG(age), MIN(username), MAX(age), SUM(CASE WHEN age > 20 THEN 1 ELSE 0 END) AS adults FROM test_select_table").await.expect("Failed to select");
+    assert_eq!(result_sets[0].columns, vec![
+        ResultColumn { name: String::from("count"), data_type: DataType::Integer },
+        ResultColumn { name: String::from("count"), data_type: DataType::Integer },
+        ResultColumn { name: String::from("sum"), data_type: DataType::Integer },
+        ResultColumn { name: String::from("avg"), data_type: DataType::Float },
+        ResultColumn { name: String::from("min"), data_type: DataType::Text },
+        ResultColumn { name: String::from("max"), data_type: DataType::Integer },
+        ResultColumn { name: String::from("adults"), data_type: DataType::Integer },
+    ]);
+    assert_eq!(result_sets[0].rows, vec![vec![
+        CellValue::Integer(7), CellValue::Integer(5), CellValue::Integer(133), CellValue::Float(19.0),
+        CellValue::Text(String::from("Andrew")), CellValue::Integer(34), CellValue::Integer(4),
+    ]]);
+
+    // Groups filtered by HAVING
+    let result_sets = run_sql(&mut session, "SELECT age, COUNT(*) AS total FROM test_select_table GROUP BY age HAVING COUNT(*) > 1").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(21), CellValue::Integer(3)]]);
+
+    // Groups sorted and limited
+    let result_sets = run_sql(&mut session, "SELECT age, COUNT(*) AS total, MAX(id) - MIN(id) AS spread FROM test_select_table WHERE id > 1 GROUP BY age ORDER BY total DESC LIMIT 2").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows[0], vec![CellValue::Integer(21), CellValue::Integer(3), CellValue::Integer(2)]);
+    assert_eq!(result_sets[0].rows.len(), 2);
+    let result_sets = run_sql(&mut session, "SELECT age FROM test_select_table GROUP BY age ORDER BY COUNT(*) DESC, age LIMIT 2 OFFSET 1").await.expect("Failed to select");
+    assert_eq!(result_sets[0].columns.len(), 1);
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(4)], vec![CellValue::Integer(12)]]);
+
+    // Empty input: one row without GROUP BY, none with it
+    let result_sets = run_sql(&mut session, "SELECT COUNT(*), SUM(age) FROM test_select_table WHERE id > 100").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(0), CellValue::Null]]);
+    let result_sets = run_sql(&mut session, "SELECT age, COUNT(*) FROM test_select_table WHERE id > 100 GROUP BY age").await.expect("Failed to select");
+    assert!(result_sets[0].rows.is_empty());
+
+    // Errors
+    let error = run_sql(&mut session, "SELECT username, COUNT(*) FROM test_select_table GROUP BY age").await;
+    assert!(matches!(error, Err(Error::ColumnNotGrouped { .. })));
+    let error = run_sql(&mut session, "SELECT id FROM test_select_table WHERE COUNT(*) > 1").await;
+    assert!(matches!(error, Err(Error::AggregateNotAllowed { .. })));
+}
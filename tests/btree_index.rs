@@ -0,0 +1,58 @@
+use std::{fs, ops::Bound};
+
+use kodasql::{database::types::DataType, storage_engine::index::btree::{BTree, IndexKey, IndexValue}, transaction_manager::{transaction_context::run_in_transaction, transaction_handler::{begin_transaction, commit_transaction}}};
+
+fn integer_key(value: i64) -> IndexKey {
+    vec![IndexValue::Integer(value)]
+}
+
+#[tokio::test]
+async fn test_btree_lookups() {
+    // Prepare: enough entries for several levels, inserted out of order
+    let file_path = std::env::temp_dir().join("kodasql_test_btree.btree").to_string_lossy().to_string();
+    let _ = fs::remove_file(&file_path);
+    let transaction = begin_transaction();
+    run_in_transaction(transaction.clone(), async {
+        BTree::create(&file_path, (0..500).map(|value| (integer_key(value * 2), value as u64)).collect()).expect("Failed to create index");
+
+        let mut index = BTree::open(&file_path).expect("Failed to open index");
+        for value in (0..3000).rev() {
+            index.insert(integer_key(value * 2 + 1), 1000 + value as u64).expect("Failed to insert");
+        }
+        index.insert(integer_key(10), 7000).expect("Failed to insert duplicate key");
+        index.remove(integer_key(4), 2).expect("Failed to remove");
+        index.flush().expect("Failed to flush index");
+    }).await;
+    commit_transaction(transaction).await.expect("Failed to commit index");
+    assert!(fs::metadata(&file_path).expect("Index file missing").len() > 4096 * 10);
+
+    // Act
+    let mut index = BTree::open(&file_path).expect("Failed to open index");
+    let duplicates = index.find(&integer_key(10)).expect("Failed to find");
+    let removed = index.find(&integer_key(4)).expect("Failed to find");
+    let range = index.range(Bound::Excluded(&integer_key(995)), Bound::Included(&integer_key(1001))).expect("Failed to scan range");
+    let tail = index.range(Bound::Included(&integer_key(5990)), Bound::Unbounded).expect("Failed to scan range");
+    let head = index.range(Bound::Unbounded, Bound::Excluded(&integer_key(3))).expect("Failed to scan range");
+
+    // Assert
+    assert_eq!(duplicates, vec![5, 7000]);
+    assert!(removed.is_empty());
+    assert_eq!(range, vec![498, 1498, 499, 1499, 1500]);
+    assert_eq!(tail, vec![3995, 3996, 3997, 3998, 3999]);
+    assert_eq!(head, vec![0, 1000, 1]);
+
+    fs::remove_file(&file_path).expect("Failed to delete index");
+}
+
+#[test]
+fn test_index_values_are_typed() {
+    // Numbers sort numerically, NULL first
+    let mut values = vec![
+        IndexValue::from_value("10", &DataType::Integer),
+        IndexValue::from_value("9", &DataType::Integer),
+        IndexValue::from_value("Null", &DataType::Integer),
+    ];
+    values.sort();
+
+    assert_eq!(values, vec![IndexValue::Null, IndexValue::Integer(9), IndexValue::Integer(10)]);
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use kodasql::{command_dispatcher::session::Session, database::database_loader, network_protocol::types::CellValue, shared::errors::Error};
+use common::run_sql;
+
+#[tokio::test]
+async fn test_check_constraints() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_check_products").await;
+    run_sql(&mut session, "CREATE TABLE test_check_products (id INT PRIMARY KEY, price INT CHECK (price >= 0), discount INT CHECK (discount <= price), stock INT)").await.expect("Failed to create table");
+
+    // Act - column checks on insert, unknown (NULL) results passing
+    run_sql(&mut session, "INSERT INTO test_check_products (id, price, discount, stock) VALUES (1, 10, 2, 5), (2, 20, 20, 0)").await.expect("Failed to insert");
+    run_sql(&mut session, "INSERT INTO test_check_products (id, discount, stock) VALUES (3, 5, 1)").await.expect("Failed to insert");
+    let error = run_sql(&mut session, "INSERT INTO test_check_products (id, price, discount, stock) VALUES (4, -1, 0, 0)").await;
+    assert!(matches!(error, Err(Error::CheckConstraintNotSatisfied { constraint_name }) if constraint_name == "test_check_products_price_check"));
+    let error = run_sql(&mut session, "INSERT INTO test_check_products (id, price, discount, stock) VALUES (4, 5, 6, 0)").await;
+    assert!(matches!(error, Err(Error::CheckConstraintNotSatisfied { constraint_name }) if constraint_name == "test_check_products_discount_check"));
+
+    // Act - column checks on update, against the whole updated row
+    let error = run_sql(&mut session, "UPDATE test_check_products SET price = 1 WHERE id = 1").await;
+    assert!(matches!(error, Err(Error::CheckConstraintNotSatisfied { .. })));
+    run_sql(&mut session, "UPDATE test_check_products SET price = price * 2 WHERE id <= 2").await.expect("Failed to update");
+
+    // Act - added checks are verified against the existing rows, then enforced
+    let error = run_sql(&mut session, "ALTER TABLE test_check_products ADD CONSTRAINT in_stock CHECK (stock > 0)").await;
+    assert!(matches!(error, Err(Error::CheckConstraintNotSatisfied { constraint_name }) if constraint_name == "in_stock"));
+    run_sql(&mut session, "ALTER TABLE test_check_products ADD CONSTRAINT stock_limit CHECK (stock < 100)").await.expect("Failed to add constraint");
+    let error = run_sql(&mut session, "UPDATE test_check_products SET stock = 100 WHERE id = 1").await;
+    assert!(matches!(error, Err(Error::CheckConstraintNotSatisfied { constraint_name }) if constraint_name == "stock_limit"));
+    let error = run_sql(&mut session, "ALTER TABLE test_check_products ADD CONSTRAINT stock_limit CHECK (stock < 50)").await;
+    assert!(matches!(error, Err(Error::ConstraintAlreadyExists { .. })));
+    let error = run_sql(&mut session, "ALTER TABLE test_check_products ADD CONSTRAINT stock_missing CHECK (missing > 0)").await;
+    assert!(matches!(error, Err(Error::ColumnDoesNotExist { .. })));
+
+    // Act - checks on a dropped column are dropped with it
+    run_sql(&mut session, "ALTER TABLE test_check_products DROP COLUMN stock").await.expect("Failed to drop column");
+    run_sql(&mut session, "INSERT INTO test_check_products (id, price, discount) VALUES (5, 1, 1)").await.expect("Failed to insert");
+    let result_sets = run_sql(&mut session, "SELECT id, price FROM test_check_products ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![
+        vec![CellValue::Integer(1), CellValue::Integer(20)],
+        vec![CellValue::Integer(2), CellValue::Integer(40)],
+        vec![CellValue::Integer(3), CellValue::Null],
+        vec![CellValue::Integer(5), CellValue::Integer(1)],
+    ]);
+
+    // Clean up
+    run_sql(&mut session, "DROP TABLE test_check_products").await.expect("Failed to drop table");
+}
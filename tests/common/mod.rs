@@ -0,0 +1,7 @@
+use kodasql::{command_dispatcher::{request_handler, session::Session}, network_protocol::types::{MessageType, Request, ResultSet}, shared::errors::Error};
+
+// Runs the statements the way a client's simple query would
+pub async fn run_sql(session: &mut Session, sql: &str) -> Result<Vec<ResultSet>, Error> {
+    let request = Request { message_type: MessageType::Query, sql: String::from(sql) };
+    request_handler::process_request(request, session).await
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use std::time::Duration;
+
+use kodasql::{command_dispatcher::session::Session, database::database_loader, network_protocol::types::CellValue};
+use common::run_sql;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_writers_are_serialized() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+
+    // Act - concurrent autocommit inserts into the same table
+    let mut handles = Vec::new();
+    for id in 10..18 {
+        handles.push(tokio::spawn(async move {
+            let mut session = Session::default();
+            run_sql(&mut session, &format!("INSERT INTO test_concurrency_table (id, username, age) VALUES ({}, 'user_{}', 30)", id, id)).await
+        }));
+    }
+    for handle in handles {
+        handle.await.expect("Task panicked").expect("Concurrent insert failed");
+    }
+
+    // Assert - no insert was lost
+    let mut session = Session::default();
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_concurrency_table").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows.len(), 10);
+
+    // Act - a writer waits for the transaction holding the table
+    let mut holding_session = Session::default();
+    run_sql(&mut holding_session, "BEGIN").await.expect("Failed to begin transaction");
+    run_sql(&mut holding_session, "DELETE FROM test_concurrency_table WHERE id = 10").await.expect("Failed to delete");
+
+    let waiting_handle = tokio::spawn(async move {
+        let mut session = Session::default();
+        run_sql(&mut session, "DELETE FROM test_concurrency_table WHERE id = 11").await
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!waiting_handle.is_finished());
+
+    run_sql(&mut holding_session, "COMMIT").await.expect("Failed to commit transaction");
+    waiting_handle.await.expect("Task panicked").expect("Waiting delete failed");
+
+    // Restore
+    let restore_sql = (12..18).map(|id| format!("DELETE FROM test_concurrency_table WHERE id = {}", id)).collect::<Vec<String>>().join("; ");
+    run_sql(&mut session, &restore_sql).await.expect("Failed to restore table");
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_concurrency_table").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows.len(), 2);
+}
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_readers_dont_wait_for_writers() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_concurrent_reads_table").await;
+    run_sql(&mut session, "CREATE TABLE test_concurrent_reads_table (id INT PRIMARY KEY, username TEXT)").await.expect("Failed to create table");
+    run_sql(&mut session, "INSERT INTO test_concurrent_reads_table (id, username) VALUES (1, 'a')").await.expect("Failed to insert");
+
+    // Act - another session's write transaction holds the table
+    let mut writing_session = Session::default();
+    run_sql(&mut writing_session, "BEGIN").await.expect("Failed to begin transaction");
+    run_sql(&mut writing_session, "INSERT INTO test_concurrent_reads_table (id, username) VALUES (2, 'b')").await.expect("Failed to insert");
+    run_sql(&mut writing_session, "UPDATE test_concurrent_reads_table SET username = 'c' WHERE id = 1").await.expect("Failed to update");
+
+    // Assert - the reader doesn't wait and sees the last committed rows
+    let result_sets = tokio::time::timeout(Duration::from_secs(1), run_sql(&mut session, "SELECT id, username FROM test_concurrent_reads_table"))
+        .await.expect("Reader waited for the writer").expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(1), CellValue::Text(String::from("a"))]]);
+
+    run_sql(&mut writing_session, "COMMIT").await.expect("Failed to commit transaction");
+    let result_sets = run_sql(&mut session, "SELECT id, username FROM test_concurrent_reads_table ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![
+        vec![CellValue::Integer(1), CellValue::Text(String::from("c"))],
+        vec![CellValue::Integer(2), CellValue::Text(String::from("b"))],
+    ]);
+
+    // Clean up
+    run_sql(&mut session, "DROP TABLE test_concurrent_reads_table").await.expect("Failed to drop table");
+}
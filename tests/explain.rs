@@ -0,0 +1,79 @@
+mod common;
+
+use kodasql::{command_dispatcher::session::Session, database::database_loader, network_protocol::types::CellValue};
+use common::run_sql;
+
+async fn get_plan_lines(session: &mut Session, sql: &str) -> Vec<String> {
+    let result_sets = run_sql(session, sql).await.expect("Failed to explain");
+    result_sets[0].rows.iter()
+        .map(|row| match &row[0] {
+            CellValue::Text(line) => line.clone(),
+            value => panic!("Unexpected plan value {:?}", value),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_explain() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_explain_orders").await;
+    let _ = run_sql(&mut session, "DROP TABLE test_explain_users").await;
+    run_sql(&mut session, "CREATE TABLE test_explain_users (id INT PRIMARY KEY, name TEXT, age INT)").await.expect("Failed to create table");
+    run_sql(&mut session, "CREATE TABLE test_explain_orders (id INT PRIMARY KEY, user_id INT, total INT)").await.expect("Failed to create table");
+    run_sql(&mut session, "INSERT INTO test_explain_users (id, name, age) VALUES (1, 'Ann', 30), (2, 'Bob', 17), (3, 'Cid', 45)").await.expect("Failed to insert");
+    run_sql(&mut session, "INSERT INTO test_explain_orders (id, user_id, total) VALUES (10, 1, 5), (11, 1, 7), (12, 3, 3)").await.expect("Failed to insert");
+    run_sql(&mut session, "CREATE INDEX test_explain_users_age ON test_explain_users (age)").await.expect("Failed to create index");
+
+    // Act - the indexed predicate uses the index, the other one is only checked on the rows found
+    let lines = get_plan_lines(&mut session, "EXPLAIN SELECT name FROM test_explain_users WHERE age > 20 AND name <> 'Cid' ORDER BY name LIMIT 5").await;
+
+    // Assert
+    assert_eq!(lines, vec![
+        "Projection",
+        "  Output: name",
+        "  ->  Limit",
+        "        Limit: 5",
+        "        ->  Top-N Sort (rows=5)",
+        "              Sort Key: name",
+        "              ->  Index Scan using test_explain_users_age on schema_1.test_explain_users",
+        "                    Index Cond: age > 20",
+        "                    Filter: age > 20 AND name <> 'Cid'",
+    ]);
+
+    // Act - without a usable index, the table is scanned
+    let lines = get_plan_lines(&mut session, "EXPLAIN SELECT * FROM test_explain_users WHERE name = 'Ann' OR age > 20").await;
+    assert_eq!(lines[2], "  ->  Seq Scan on schema_1.test_explain_users");
+
+    // Act - filters on one table are pushed below the join, except onto the side an outer join fills with NULLs
+    let lines = get_plan_lines(&mut session, "EXPLAIN ANALYZE SELECT u.name, o.total FROM test_explain_users u JOIN test_explain_orders o ON u.id = o.user_id WHERE o.total > 4").await;
+    assert!(lines[0].starts_with("Projection (actual rows=2 "));
+    assert!(lines[2].starts_with("  ->  Hash Inner Join (actual rows=2 "));
+    assert!(lines.iter().any(|line| line.starts_with("        ->  Seq Scan on schema_1.test_explain_orders o (actual rows=2 ")));
+    assert!(lines.contains(&String::from("              Filter: o.total > 4")));
+
+    let lines = get_plan_lines(&mut session, "EXPLAIN SELECT u.name, COUNT(*) FROM test_explain_orders o LEFT JOIN test_explain_users u ON o.user_id = u.id WHERE u.age > 20 GROUP BY u.name").await;
+    assert_eq!(lines[2..], vec![
+        "  ->  HashAggregate",
+        "        Group Key: u.name",
+        "        ->  Filter",
+        "              Filter: u.age > 20",
+        "              ->  Index Nested Loop Left Join",
+        "                    Join Filter: o.user_id = u.id",
+        "                    ->  Seq Scan on schema_1.test_explain_orders o",
+        "                    ->  Index Lookup using id on schema_1.test_explain_users u",
+        "                          Index Cond: u.id = o.user_id",
+    ]);
+
+    // Plans give the same results as running the query
+    let result_sets = run_sql(&mut session, "SELECT u.name, COUNT(*) FROM test_explain_orders o LEFT JOIN test_explain_users u ON o.user_id = u.id WHERE u.age > 20 GROUP BY u.name").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![
+        vec![CellValue::Text(String::from("Ann")), CellValue::Integer(2)],
+        vec![CellValue::Text(String::from("Cid")), CellValue::Integer(1)],
+    ]);
+
+    // Clean up
+    run_sql(&mut session, "DROP TABLE test_explain_orders").await.expect("Failed to drop table");
+    run_sql(&mut session, "DROP TABLE test_explain_users").await.expect("Failed to drop table");
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use kodasql::{command_dispatcher::session::Session, database::{database_loader, types::DataType}, network_protocol::types::{CellValue, ResultColumn}, shared::errors::Error};
+use common::run_sql;
+
+fn text(value: &str) -> CellValue {
+    CellValue::Text(value.to_string())
+}
+
+#[tokio::test]
+async fn test_expressions() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_expressions_table").await;
+    run_sql(&mut session, "CREATE TABLE test_expressions_table (id INT PRIMARY KEY, first_name TEXT, last_name TEXT, quantity INT, price FLOAT, status TEXT DEFAULT 'new' || '_order')").await.expect("Failed to create table");
+    run_sql(&mut session, "INSERT INTO test_expressions_table (id, first_name, last_name, quantity, price) VALUES (1, 'Ann', 'Lee', 2, 1.5), (2, 'Bob', 'Bob', 10, -2 * 0.5), (1 + 2, 'Cid', 'Ray', 7 % 4, 4)").await.expect("Failed to insert");
+
+    // Act - projections
+    let result_sets = run_sql(&mut session, "SELECT id, first_name || ' ' || last_name AS full_name, quantity * price, CASE WHEN quantity > 5 THEN 'bulk' ELSE 'single' END AS size, CAST(quantity AS TEXT), status FROM test_expressions_table ORDER BY id").await.expect("Failed to select");
+
+    // Assert
+    assert_eq!(result_sets[0].columns, vec![
+        ResultColumn { name: String::from("id"), data_type: DataType::Integer },
+        ResultColumn { name: String::from("full_name"), data_type: DataType::Text },
+        ResultColumn { name: String::from("?column?"), data_type: DataType::Float },
+        ResultColumn { name: String::from("size"), data_type: DataType::Text },
+        ResultColumn { name: String::from("quantity"), data_type: DataType::Text },
+        ResultColumn { name: String::from("status"), data_type: DataType::Text },
+    ]);
+    assert_eq!(result_sets[0].rows, vec![
+        vec![CellValue::Integer(1), text("Ann Lee"), CellValue::Float(3.0), text("single"), text("2"), text("new_order")],
+        vec![CellValue::Integer(2), text("Bob Bob"), CellValue::Float(-10.0), text("bulk"), text("10"), text("new_order")],
+        vec![CellValue::Integer(3), text("Cid Ray"), CellValue::Float(12.0), text("single"), text("3"), text("new_order")],
+    ]);
+
+    // Act - filters with column-to-column comparisons and arithmetic
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_expressions_table WHERE first_name = last_name OR (quantity + 1) * 2 = 8 ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(2)], vec![CellValue::Integer(3)]]);
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_expressions_table WHERE price > quantity / 2 ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(1)], vec![CellValue::Integer(3)]]);
+
+    // Act - updates computed from the current row
+    run_sql(&mut session, "UPDATE test_expressions_table SET quantity = quantity + 1, price = price * 2, status = 'updated' WHERE id <> 2").await.expect("Failed to update");
+    run_sql(&mut session, "UPDATE test_expressions_table SET first_name = last_name, last_name = first_name WHERE id = 1").await.expect("Failed to update");
+
+    // Assert
+    let result_sets = run_sql(&mut session, "SELECT first_name, last_name, quantity, price, status FROM test_expressions_table WHERE id IN (1, 3) ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![
+        vec![text("Lee"), text("Ann"), CellValue::Integer(3), CellValue::Float(3.0), text("updated")],
+        vec![text("Cid"), text("Ray"), CellValue::Integer(4), CellValue::Float(8.0), text("updated")],
+    ]);
+
+    // Act - errors
+    let error = run_sql(&mut session, "SELECT quantity / 0 FROM test_expressions_table").await;
+    assert!(matches!(error, Err(Error::DivisionByZero)));
+    let error = run_sql(&mut session, "SELECT missing_column + 1 FROM test_expressions_table").await;
+    assert!(matches!(error, Err(Error::ColumnDoesNotExist { .. })));
+    let error = run_sql(&mut session, "UPDATE test_expressions_table SET quantity = first_name WHERE id = 1").await;
+    assert!(matches!(error, Err(Error::InvalidCast { .. })));
+    let error = run_sql(&mut session, "UPDATE test_expressions_table SET id = id + 1 WHERE id = 2").await;
+    assert!(matches!(error, Err(Error::ColumnUniquenessNotSatisfied { .. })));
+
+    // Restore
+    run_sql(&mut session, "DROP TABLE test_expressions_table").await.expect("Failed to drop table");
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use kodasql::{command_dispatcher::session::Session, database::database_loader, network_protocol::types::CellValue, shared::errors::Error};
+use common::run_sql;
+
+#[tokio::test]
+async fn test_composite_foreign_keys() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_fk_employees").await;
+    let _ = run_sql(&mut session, "DROP TABLE test_fk_departments").await;
+    run_sql(&mut session, "CREATE TABLE test_fk_departments (id INT PRIMARY KEY, org_id INT, dept_id INT, name TEXT)").await.expect("Failed to create table");
+    run_sql(&mut session, "CREATE UNIQUE INDEX ON test_fk_departments (org_id, dept_id)").await.expect("Failed to create unique index");
+    run_sql(&mut session, "CREATE TABLE test_fk_employees (id INT PRIMARY KEY, org_id INT, dept_id INT)").await.expect("Failed to create table");
+    run_sql(&mut session, "INSERT INTO test_fk_departments (id, org_id, dept_id, name) VALUES (1, 1, 10, 'Sales'), (2, 2, 20, 'Support')").await.expect("Failed to insert");
+
+    // Act - the referenced columns must form a unique key
+    let error = run_sql(&mut session, "ALTER TABLE test_fk_employees ADD CONSTRAINT fk_employees_names FOREIGN KEY (org_id) REFERENCES test_fk_departments (name)").await;
+    assert!(matches!(error, Err(Error::InvalidForeignKey { .. })));
+    let error = run_sql(&mut session, "ALTER TABLE test_fk_employees ADD CONSTRAINT fk_employees_orgs FOREIGN KEY (org_id) REFERENCES test_fk_departments (org_id)").await;
+    assert!(matches!(error, Err(Error::InvalidForeignKey { .. })));
+    run_sql(&mut session, "ALTER TABLE test_fk_employees ADD CONSTRAINT fk_employees_departments FOREIGN KEY (org_id, dept_id) REFERENCES test_fk_departments (org_id, dept_id)").await.expect("Failed to add foreign key");
+
+    // Act - keys are matched as a whole, so a pair of values existing in different rows is rejected
+    run_sql(&mut session, "INSERT INTO test_fk_employees (id, org_id, dept_id) VALUES (100, 1, 10), (101, 2, 20)").await.expect("Failed to insert");
+    let error = run_sql(&mut session, "INSERT INTO test_fk_employees (id, org_id, dept_id) VALUES (102, 1, 20)").await;
+    assert!(matches!(error, Err(Error::ForeignKeyConstraintNotSatisfied { .. })));
+
+    // Act - keys with a NULL part reference nothing
+    run_sql(&mut session, "INSERT INTO test_fk_employees (id, org_id) VALUES (103, 3)").await.expect("Failed to insert");
+
+    // Act - updating part of a key checks the whole new key
+    let error = run_sql(&mut session, "UPDATE test_fk_employees SET dept_id = 20 WHERE id = 100").await;
+    assert!(matches!(error, Err(Error::ForeignKeyConstraintNotSatisfied { .. })));
+    run_sql(&mut session, "UPDATE test_fk_employees SET org_id = 2, dept_id = 20 WHERE id = 100").await.expect("Failed to update");
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_fk_employees WHERE org_id = 2 ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(100)], vec![CellValue::Integer(101)]]);
+
+    // Act - referenced columns can't be dropped or changed, other columns can
+    let error = run_sql(&mut session, "ALTER TABLE test_fk_departments DROP COLUMN dept_id").await;
+    assert!(matches!(error, Err(Error::ColumnReferencedByForeignKey { .. })));
+    run_sql(&mut session, "ALTER TABLE test_fk_departments DROP COLUMN name").await.expect("Failed to drop column");
+
+    // Clean up
+    run_sql(&mut session, "DROP TABLE test_fk_employees").await.expect("Failed to drop table");
+    run_sql(&mut session, "DROP TABLE test_fk_departments").await.expect("Failed to drop table");
+}
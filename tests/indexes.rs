@@ -0,0 +1,103 @@
+mod common;
+
+use std::path::Path;
+
+use kodasql::{command_dispatcher::session::Session, database::{database_loader, database_navigator::get_table_index_path, types::Index, utils::find_database_table}, network_protocol::types::CellValue, shared::errors::Error};
+use common::run_sql;
+
+#[tokio::test]
+async fn test_create_and_drop_index() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let schema_name = String::from("schema_1");
+    let table_name = String::from("test_indexed_table");
+    let _ = run_sql(&mut session, "DROP TABLE test_indexed_table").await;
+
+    run_sql(&mut session, "CREATE TABLE test_indexed_table (id INT PRIMARY KEY, username TEXT, age INT)").await.expect("Failed to create table");
+    run_sql(&mut session, "INSERT INTO test_indexed_table (id, username, age) VALUES (1, 'Alice', 30), (2, 'Bob', 25), (3, 'Alice', 25)").await.expect("Failed to insert");
+
+    // Act - index existing data
+    run_sql(&mut session, "CREATE INDEX age_idx ON test_indexed_table (age)").await.expect("Failed to create index");
+    run_sql(&mut session, "CREATE UNIQUE INDEX ON test_indexed_table (username, age)").await.expect("Failed to create unique index");
+
+    // Assert
+    let database = database_loader::get_database().expect("Failed to get database");
+    let table_schema = find_database_table(&database, &schema_name, &table_name).expect("Table not found");
+    assert_eq!(table_schema.indexes, vec![
+        Index { name: String::from("age_idx"), columns: vec![String::from("age")], is_unique: false },
+        Index { name: String::from("test_indexed_table_username_age_idx"), columns: vec![String::from("username"), String::from("age")], is_unique: true },
+    ]);
+    let age_index_path = get_table_index_path(&schema_name, &table_name, &String::from("age_idx"));
+    assert!(Path::new(&age_index_path).exists());
+
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_indexed_table WHERE age = 25").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(2)], vec![CellValue::Integer(3)]]);
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_indexed_table WHERE username = 'Alice'").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(1)], vec![CellValue::Integer(3)]]);
+
+    // Act - maintain indexes on writes
+    run_sql(&mut session, "INSERT INTO test_indexed_table (id, username, age) VALUES (4, 'Bob', 40)").await.expect("Failed to insert");
+    let error = run_sql(&mut session, "INSERT INTO test_indexed_table (id, username, age) VALUES (5, 'Bob', 25)").await;
+    assert!(matches!(error, Err(Error::IndexUniquenessNotSatisfied { .. })));
+    run_sql(&mut session, "DELETE FROM test_indexed_table WHERE id = 2").await.expect("Failed to delete");
+    run_sql(&mut session, "INSERT INTO test_indexed_table (id, username, age) VALUES (5, 'Bob', 25)").await.expect("Failed to insert");
+
+    // Assert
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_indexed_table WHERE age = 25").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(3)], vec![CellValue::Integer(5)]]);
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_indexed_table WHERE age = 40").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(4)]]);
+
+    // Act - invalid statements
+    let error = run_sql(&mut session, "CREATE INDEX age_idx ON test_indexed_table (username)").await;
+    assert!(matches!(error, Err(Error::IndexAlreadyExists { .. })));
+    run_sql(&mut session, "CREATE INDEX IF NOT EXISTS age_idx ON test_indexed_table (username)").await.expect("Failed to skip existing index");
+    let error = run_sql(&mut session, "CREATE UNIQUE INDEX username_idx ON test_indexed_table (username)").await;
+    assert!(matches!(error, Err(Error::IndexUniquenessNotSatisfied { .. })));
+    let error = run_sql(&mut session, "CREATE INDEX email_idx ON test_indexed_table (email)").await;
+    assert!(matches!(error, Err(Error::ColumnDoesNotExist { .. })));
+
+    // Act - drop index
+    run_sql(&mut session, "DROP INDEX age_idx").await.expect("Failed to drop index");
+
+    // Assert
+    let database = database_loader::get_database().expect("Failed to get database");
+    let table_schema = find_database_table(&database, &schema_name, &table_name).expect("Table not found");
+    assert_eq!(table_schema.indexes.len(), 1);
+    assert!(!Path::new(&age_index_path).exists());
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_indexed_table WHERE age = 25").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(3)], vec![CellValue::Integer(5)]]);
+
+    let error = run_sql(&mut session, "DROP INDEX age_idx").await;
+    assert!(matches!(error, Err(Error::IndexDoesNotExist { .. })));
+    run_sql(&mut session, "DROP INDEX IF EXISTS age_idx").await.expect("Failed to skip missing index");
+
+    // Restore
+    run_sql(&mut session, "DROP TABLE test_indexed_table").await.expect("Failed to drop table");
+}
+
+#[tokio::test]
+async fn test_unique_index_on_paged_table() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_indexed_paged_table").await;
+
+    run_sql(&mut session, "CREATE TABLE test_indexed_paged_table (id INT PRIMARY KEY, email TEXT) WITH (storage_engine = 'paged')").await.expect("Failed to create table");
+    run_sql(&mut session, "INSERT INTO test_indexed_paged_table (id, email) VALUES (1, 'a@koda.sql'), (2, 'b@koda.sql')").await.expect("Failed to insert");
+
+    // Act
+    run_sql(&mut session, "CREATE UNIQUE INDEX email_idx ON test_indexed_paged_table (email)").await.expect("Failed to create index");
+    let error = run_sql(&mut session, "INSERT INTO test_indexed_paged_table (id, email) VALUES (3, 'c@koda.sql'), (4, 'c@koda.sql')").await;
+
+    // Assert
+    assert!(matches!(error, Err(Error::IndexUniquenessNotSatisfied { .. })));
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_indexed_paged_table WHERE email = 'b@koda.sql'").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(2)]]);
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_indexed_paged_table").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows.len(), 2);
+
+    // Restore
+    run_sql(&mut session, "DROP TABLE test_indexed_paged_table").await.expect("Failed to drop table");
+}
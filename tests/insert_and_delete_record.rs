@@ -28,7 +28,8 @@ pub async fn test_insert_and_delete_record() {
     ];
 
     // Act - insert record
-    let _ = statement_dispatcher::dispatch_statement(insert_statement).await.expect("Storage engine error");
+    let insert_result = statement_dispatcher::dispatch_statement(insert_statement).await.expect("Storage engine error");
+    assert_eq!(insert_result.rows_affected, Some(1));
 
     // Get results and expected results
     let table_data_file_path = get_table_data_path(&schema_name, &table_name);
@@ -45,7 +46,8 @@ pub async fn test_insert_and_delete_record() {
     }
 
     // Act - delete record
-    let _ = statement_dispatcher::dispatch_statement(delete_statement).await.expect("Storage engine error");
+    let delete_result = statement_dispatcher::dispatch_statement(delete_statement).await.expect("Storage engine error");
+    assert_eq!(delete_result.rows_affected, Some(1));
 
     // Get results and expected results
     let modified_file = File::open(&table_data_file_path).expect("Could not open table data file");
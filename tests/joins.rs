@@ -0,0 +1,76 @@
+mod common;
+
+use kodasql::{command_dispatcher::session::Session, database::{database_loader, types::DataType}, network_protocol::types::{CellValue, ResultColumn}, shared::errors::Error};
+use common::run_sql;
+
+fn text(value: &str) -> CellValue {
+    CellValue::Text(value.to_string())
+}
+
+#[tokio::test]
+async fn test_joins() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_join_orders").await;
+    let _ = run_sql(&mut session, "DROP TABLE test_join_users").await;
+    run_sql(&mut session, "CREATE TABLE test_join_users (id INT PRIMARY KEY, name TEXT)").await.expect("Failed to create table");
+    run_sql(&mut session, "CREATE TABLE test_join_orders (id INT PRIMARY KEY, user_id INT, total INT)").await.expect("Failed to create table");
+    run_sql(&mut session, "INSERT INTO test_join_users (id, name) VALUES (1, 'Ann'), (2, 'Bob'), (3, 'Cid')").await.expect("Failed to insert");
+    run_sql(&mut session, "INSERT INTO test_join_orders (id, user_id, total) VALUES (10, 1, 5), (11, 1, 7), (12, 2, 3), (13, 4, 9)").await.expect("Failed to insert");
+
+    // Act - inner join with aliases and qualified columns
+    let result_sets = run_sql(&mut session, "SELECT u.name, o.id AS order_id, o.total FROM test_join_users u INNER JOIN test_join_orders AS o ON u.id = o.user_id ORDER BY order_id").await.expect("Failed to select");
+
+    // Assert
+    assert_eq!(result_sets[0].columns, vec![
+        ResultColumn { name: String::from("name"), data_type: DataType::Text },
+        ResultColumn { name: String::from("order_id"), data_type: DataType::Integer },
+        ResultColumn { name: String::from("total"), data_type: DataType::Integer },
+    ]);
+    assert_eq!(result_sets[0].rows, vec![
+        vec![text("Ann"), CellValue::Integer(10), CellValue::Integer(5)],
+        vec![text("Ann"), CellValue::Integer(11), CellValue::Integer(7)],
+        vec![text("Bob"), CellValue::Integer(12), CellValue::Integer(3)],
+    ]);
+
+    // Act - outer joins keep unmatched rows with NULLs
+    let result_sets = run_sql(&mut session, "SELECT u.name, o.id FROM test_join_users u LEFT JOIN test_join_orders o ON u.id = o.user_id WHERE o.id IS NULL").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![text("Cid"), CellValue::Null]]);
+    let result_sets = run_sql(&mut session, "SELECT u.name, o.id FROM test_join_users u RIGHT JOIN test_join_orders o ON u.id = o.user_id ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows.len(), 4);
+    assert_eq!(result_sets[0].rows[3], vec![CellValue::Null, CellValue::Integer(13)]);
+
+    // Act - the same joins through an index on the joined column
+    run_sql(&mut session, "CREATE INDEX test_join_orders_user_id ON test_join_orders (user_id)").await.expect("Failed to create index");
+    let result_sets = run_sql(&mut session, "SELECT u.name, COUNT(o.id) AS orders FROM test_join_users u LEFT JOIN test_join_orders o ON o.user_id = u.id GROUP BY u.name ORDER BY u.name").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![
+        vec![text("Ann"), CellValue::Integer(2)],
+        vec![text("Bob"), CellValue::Integer(1)],
+        vec![text("Cid"), CellValue::Integer(0)],
+    ]);
+
+    // Act - cross and comma joins, with extra conditions in WHERE
+    let result_sets = run_sql(&mut session, "SELECT COUNT(*) FROM test_join_users CROSS JOIN test_join_orders").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(12)]]);
+    let result_sets = run_sql(&mut session, "SELECT test_join_orders.id FROM test_join_users, test_join_orders WHERE test_join_users.id = test_join_orders.user_id AND name = 'Bob'").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(12)]]);
+
+    // Act - wildcards expand to every joined table, or the named one
+    let result_sets = run_sql(&mut session, "SELECT * FROM test_join_users u JOIN test_join_orders o ON u.id = o.user_id WHERE o.id = 12").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(2), text("Bob"), CellValue::Integer(12), CellValue::Integer(2), CellValue::Integer(3)]]);
+    let result_sets = run_sql(&mut session, "SELECT o.* FROM test_join_users u JOIN test_join_orders o ON u.id = o.user_id WHERE u.name = 'Bob'").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(12), CellValue::Integer(2), CellValue::Integer(3)]]);
+
+    // Act - errors
+    let error = run_sql(&mut session, "SELECT id FROM test_join_users u JOIN test_join_orders o ON u.id = o.user_id").await;
+    assert!(matches!(error, Err(Error::AmbiguousColumnName { .. })));
+    let error = run_sql(&mut session, "SELECT u.name FROM test_join_users u JOIN test_join_orders u ON u.id = u.user_id").await;
+    assert!(matches!(error, Err(Error::DuplicateTableReference { .. })));
+    let error = run_sql(&mut session, "SELECT u.missing FROM test_join_users u JOIN test_join_orders o ON u.id = o.user_id").await;
+    assert!(matches!(error, Err(Error::ColumnDoesNotExist { .. })));
+
+    // Clean up
+    run_sql(&mut session, "DROP TABLE test_join_orders").await.expect("Failed to drop table");
+    run_sql(&mut session, "DROP TABLE test_join_users").await.expect("Failed to drop table");
+}
@@ -0,0 +1,124 @@
+use kodasql::{network_protocol::{framing::{encode_frame, FrameDecoder}, parsing::{format_request, format_response, parse_request, parse_response}, types::{CellValue, MessageType, Request, Response, ResponseStatus, ResultColumn, ResultSet}}, database::types::DataType, shared::errors::Error};
+
+fn make_request(sql: String) -> Request {
+    Request { message_type: MessageType::Query, sql }
+}
+
+#[test]
+fn test_reassemble_request_split_across_reads() {
+    // Prepare: SQL well beyond the old 4096 byte read buffer
+    let values = (0..1000).map(|i| format!("({}, 'user_{}', {})", i, i, i % 90)).collect::<Vec<String>>().join(", ");
+    let sql = format!("INSERT INTO test_table (id, username, age) VALUES {}", values);
+    let frame = format_request(&make_request(sql.clone()));
+
+    let mut decoder = FrameDecoder::new(1024 * 1024);
+    let mut payloads: Vec<Vec<u8>> = Vec::new();
+
+    // Act: feed the frame in small chunks
+    for chunk in frame.chunks(1000) {
+        decoder.extend(chunk);
+        while let Some(payload) = decoder.next_frame().expect("Failed to decode frame") {
+            payloads.push(payload);
+        }
+    }
+
+    // Assert
+    assert_eq!(payloads.len(), 1);
+    let request = parse_request(&payloads[0]).expect("Failed to parse request");
+    assert_eq!(request.sql, sql);
+}
+
+#[test]
+fn test_decode_multiple_frames_from_single_read() {
+    let mut bytes = format_request(&make_request(String::from("SELECT * FROM a")));
+    bytes.extend(format_request(&make_request(String::from("SELECT * FROM b"))));
+
+    let mut decoder = FrameDecoder::new(1024);
+    decoder.extend(&bytes);
+
+    let first = decoder.next_frame().expect("Failed to decode frame").expect("Missing first frame");
+    let second = decoder.next_frame().expect("Failed to decode frame").expect("Missing second frame");
+
+    assert_eq!(parse_request(&first).expect("Failed to parse request").sql, "SELECT * FROM a");
+    assert_eq!(parse_request(&second).expect("Failed to parse request").sql, "SELECT * FROM b");
+    assert!(decoder.next_frame().expect("Failed to decode frame").is_none());
+}
+
+#[test]
+fn test_reject_message_exceeding_max_size() {
+    let oversized_frame = encode_frame(&vec![b'x'; 200]);
+    let valid_frame = format_request(&make_request(String::from("SELECT * FROM a")));
+
+    let mut decoder = FrameDecoder::new(100);
+    decoder.extend(&oversized_frame[..50]);
+
+    // Rejected as soon as the header is read
+    match decoder.next_frame() {
+        Err(Error::MessageTooLarge { size, max_size }) => {
+            assert_eq!(size, 200);
+            assert_eq!(max_size, 100);
+        },
+        other => panic!("Expected MessageTooLarge, got {:?}", other),
+    }
+
+    // Remaining oversized payload is skipped and the next frame decodes normally
+    decoder.extend(&oversized_frame[50..]);
+    decoder.extend(&valid_frame);
+    let payload = decoder.next_frame().expect("Failed to decode frame").expect("Missing frame");
+    assert_eq!(parse_request(&payload).expect("Failed to parse request").sql, "SELECT * FROM a");
+}
+
+#[test]
+fn test_reject_unsupported_protocol_version() {
+    let mut frame = encode_frame(b"payload");
+    frame[4] = 99;
+
+    let mut decoder = FrameDecoder::new(1024);
+    decoder.extend(&frame);
+
+    assert!(matches!(decoder.next_frame(), Err(Error::UnsupportedProtocolVersion { version: 99 })));
+    assert!(decoder.next_frame().expect("Failed to decode frame").is_none());
+}
+
+#[test]
+fn test_stream_response_in_batches() {
+    // Prepare: a large result followed by a small one and a message
+    let columns = vec![ResultColumn { name: String::from("id"), data_type: DataType::Integer }];
+    let large_rows: Vec<Vec<CellValue>> = (0..2500).map(|i| vec![CellValue::Integer(i)]).collect();
+    let small_rows: Vec<Vec<CellValue>> = (0..10).map(|i| vec![CellValue::Integer(i)]).collect();
+    let data = vec![
+        ResultSet::from_rows(columns.clone(), large_rows),
+        ResultSet::from_rows(columns, small_rows),
+        ResultSet::from_message(String::from("CREATE TABLE")),
+    ];
+    let response = Response { status: ResponseStatus::Success, data: data.clone(), error: None };
+
+    // Act
+    let batches = response.into_batches(1000);
+
+    // Assert: every batch is its own frame, only the last one completes the response
+    let row_counts: Vec<usize> = batches.iter().map(|batch| batch.data.iter().map(|result_set| result_set.rows.len()).sum()).collect();
+    assert_eq!(row_counts, vec![1000, 1000, 510]);
+    assert!(matches!(batches[0].status, ResponseStatus::Partial));
+    assert!(matches!(batches[2].status, ResponseStatus::Success));
+
+    let mut bytes: Vec<u8> = Vec::new();
+    for batch in batches.iter() {
+        bytes.extend(format_response(batch));
+    }
+    let mut decoder = FrameDecoder::new(1024 * 1024);
+    decoder.extend(&bytes);
+
+    let mut reassembled: Option<Response> = None;
+    while let Some(payload) = decoder.next_frame().expect("Failed to decode frame") {
+        let batch = parse_response(&payload).expect("Failed to parse response");
+        match reassembled.as_mut() {
+            Some(response) => response.append_batch(batch),
+            None => reassembled = Some(batch),
+        }
+    }
+
+    let reassembled = reassembled.expect("Missing response");
+    assert!(matches!(reassembled.status, ResponseStatus::Success));
+    assert_eq!(reassembled.data, data);
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use kodasql::{command_dispatcher::session::Session, database::database_loader, network_protocol::types::CellValue, shared::errors::Error};
+use common::run_sql;
+
+#[tokio::test]
+async fn test_null_values() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_null_values").await;
+    run_sql(&mut session, "CREATE TABLE test_null_values (id INT PRIMARY KEY, amount INT, active BOOLEAN, code TEXT UNIQUE, note TEXT, label TEXT NOT NULL DEFAULT 'none')").await.expect("Failed to create table");
+
+    // Act - NULL can be inserted into columns of any type, and NULLs don't collide in UNIQUE columns
+    run_sql(&mut session, "INSERT INTO test_null_values (id, amount, active, code, note) VALUES (1, NULL, NULL, NULL, NULL), (2, 5, true, NULL, 'Null'), (3, 1, false, 'a', '\\Null')").await.expect("Failed to insert");
+    run_sql(&mut session, "INSERT INTO test_null_values (id, code) VALUES (4, NULL)").await.expect("Failed to insert");
+    let error = run_sql(&mut session, "INSERT INTO test_null_values (id, code) VALUES (5, 'a')").await;
+    assert!(matches!(error, Err(Error::ColumnUniquenessNotSatisfied { .. })));
+
+    // Act - NULL is told apart from the text 'Null', and text starting with the escape round-trips
+    let result_sets = run_sql(&mut session, "SELECT id, amount, active, note, label FROM test_null_values WHERE id < 4 ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![
+        vec![CellValue::Integer(1), CellValue::Null, CellValue::Null, CellValue::Null, CellValue::Text(String::from("none"))],
+        vec![CellValue::Integer(2), CellValue::Integer(5), CellValue::Boolean(true), CellValue::Text(String::from("Null")), CellValue::Text(String::from("none"))],
+        vec![CellValue::Integer(3), CellValue::Integer(1), CellValue::Boolean(false), CellValue::Text(String::from("\\Null")), CellValue::Text(String::from("none"))],
+    ]);
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_null_values WHERE note IS NULL ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(1)], vec![CellValue::Integer(4)]]);
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_null_values WHERE note = 'Null'").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(2)]]);
+
+    // Act - comparisons with NULL are unknown, so filters leave those rows out either way
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_null_values WHERE amount <> 1 ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(2)]]);
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_null_values WHERE NOT (amount = 1) ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(2)]]);
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_null_values WHERE amount = 1 OR active ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(2)], vec![CellValue::Integer(3)]]);
+
+    // Act - NOT NULL rejects explicit NULLs on insert and update
+    let error = run_sql(&mut session, "INSERT INTO test_null_values (id, label) VALUES (5, NULL)").await;
+    assert!(matches!(error, Err(Error::ColumnNotNull { .. })));
+    let error = run_sql(&mut session, "UPDATE test_null_values SET label = NULL WHERE id = 1").await;
+    assert!(matches!(error, Err(Error::ColumnNotNull { .. })));
+
+    // Act - columns can be set back to NULL
+    run_sql(&mut session, "UPDATE test_null_values SET amount = NULL, code = NULL WHERE id = 3").await.expect("Failed to update");
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_null_values WHERE amount IS NULL AND code IS NULL ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(1)], vec![CellValue::Integer(3)], vec![CellValue::Integer(4)]]);
+
+    // Act - indexed text columns are looked up by the text as stored
+    run_sql(&mut session, "INSERT INTO test_null_values (id, code) VALUES (5, '\\abc'), (6, 'Null')").await.expect("Failed to insert");
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_null_values WHERE code = '\\abc'").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(5)]]);
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_null_values WHERE code = 'Null'").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(6)]]);
+
+    // Clean up
+    run_sql(&mut session, "DROP TABLE test_null_values").await.expect("Failed to drop table");
+}
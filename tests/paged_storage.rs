@@ -0,0 +1,103 @@
+mod common;
+
+use std::fs;
+
+use csv::StringRecord;
+use kodasql::{command_dispatcher::session::Session, database::{database_loader, database_navigator::get_table_pages_path, types::StorageEngine, utils::find_database_table}, network_protocol::types::CellValue, shared::errors::Error, storage_engine::storage_backend::{page::{Page, PAGE_SIZE}, table_storage::get_table_storage}, transaction_manager::{transaction_context::run_in_transaction, transaction_handler::{begin_transaction, commit_transaction}}};
+use common::run_sql;
+
+#[tokio::test]
+async fn test_paged_table() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let schema_name = String::from("schema_1");
+    let table_name = String::from("test_paged_table");
+    let _ = run_sql(&mut session, "DROP TABLE test_paged_table").await;
+
+    // Act - create and fill table across several pages
+    run_sql(&mut session, "CREATE TABLE test_paged_table (id INT PRIMARY KEY, username TEXT, age INT) WITH (storage_engine = 'paged')").await.expect("Failed to create table");
+    let values: Vec<String> = (1..=200).map(|id| format!("({}, 'user_with_a_rather_long_name_{}', {})", id, id, id % 50)).collect();
+    run_sql(&mut session, &format!("INSERT INTO test_paged_table (id, username, age) VALUES {}", values.join(", "))).await.expect("Failed to insert");
+
+    // Assert
+    let database = database_loader::get_database().expect("Failed to get database");
+    let table_schema = find_database_table(&database, &schema_name, &table_name).expect("Table not found").clone();
+    assert_eq!(table_schema.storage_engine, StorageEngine::Paged);
+
+    let pages_length = fs::metadata(get_table_pages_path(&schema_name, &table_name)).expect("Pages file not found").len();
+    assert!(pages_length > PAGE_SIZE as u64);
+    assert_eq!(pages_length % PAGE_SIZE as u64, 0);
+
+    let result_sets = run_sql(&mut session, "SELECT username FROM test_paged_table WHERE id = 150").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Text(String::from("user_with_a_rather_long_name_150"))]]);
+
+    // Act - grow a row so that it no longer fits in its page
+    let storage = get_table_storage(&table_schema);
+    let (row_id, _) = storage.scan_rows(&schema_name, &table_schema).expect("Failed to scan").remove(0);
+    let long_username = "x".repeat(1000);
+    let transaction = begin_transaction();
+    run_in_transaction(transaction.clone(), async {
+        storage.update_rows(&schema_name, &table_schema, &[(row_id, StringRecord::from(vec!["1", long_username.as_str(), "20"]))])
+    }).await.expect("Failed to update");
+    commit_transaction(transaction).await.expect("Failed to commit");
+
+    run_sql(&mut session, "DELETE FROM test_paged_table WHERE id = 2").await.expect("Failed to delete");
+
+    // Assert
+    let result_sets = run_sql(&mut session, "SELECT id, username FROM test_paged_table WHERE id = 1").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(1), CellValue::Text(long_username)]]);
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_paged_table").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows.len(), 199);
+
+    let error = run_sql(&mut session, "INSERT INTO test_paged_table (id, username, age) VALUES (3, 'Duplicate', 1)").await;
+    assert!(matches!(error, Err(Error::ColumnUniquenessNotSatisfied { .. })));
+
+    // Restore
+    run_sql(&mut session, "DROP TABLE test_paged_table").await.expect("Failed to drop table");
+}
+
+#[tokio::test]
+async fn test_paged_table_growing_updates() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_paged_growing_table").await;
+    run_sql(&mut session, "CREATE TABLE test_paged_growing_table (id INT PRIMARY KEY, name TEXT) WITH (storage_engine = 'paged')").await.expect("Failed to create table");
+    let values: Vec<String> = (1..=10).map(|id| format!("({}, '{}')", id, "a".repeat(100))).collect();
+    run_sql(&mut session, &format!("INSERT INTO test_paged_growing_table (id, name) VALUES {}", values.join(", "))).await.expect("Failed to insert");
+
+    // Act - rows grow in place until their page is full, then move to pages with room
+    let long_name = "b".repeat(600);
+    run_sql(&mut session, &format!("UPDATE test_paged_growing_table SET name = '{}'", long_name)).await.expect("Failed to update");
+    run_sql(&mut session, &format!("UPDATE test_paged_growing_table SET name = '{}' WHERE id <= 5", "c".repeat(1200))).await.expect("Failed to update");
+
+    // Assert
+    let result_sets = run_sql(&mut session, "SELECT COUNT(*) FROM test_paged_growing_table WHERE id > 5").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(5)]]);
+    let result_sets = run_sql(&mut session, "SELECT name FROM test_paged_growing_table WHERE id = 10").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Text(long_name)]]);
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_paged_growing_table ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, (1..=10).map(|id| vec![CellValue::Integer(id)]).collect::<Vec<_>>());
+
+    // Clean up
+    run_sql(&mut session, "DROP TABLE test_paged_growing_table").await.expect("Failed to drop table");
+}
+
+#[test]
+fn test_page_reuses_freed_space() {
+    // Prepare
+    let mut page = Page::default();
+    let tuple = vec![7u8; 1000];
+    let slots: Vec<u16> = (0..4).map(|_| page.insert_tuple(&tuple).expect("Page full")).collect();
+
+    // Act
+    assert!(page.insert_tuple(&tuple).is_none());
+    assert!(page.delete_tuple(slots[1]));
+
+    // Assert
+    let mut page = Page::from_bytes(page.as_bytes());
+    assert_eq!(page.insert_tuple(&tuple), Some(slots[1]));
+    assert_eq!(page.get_tuples().len(), 4);
+    assert_eq!(page.get_tuple(slots[2]), Some(tuple.as_slice()));
+}
@@ -0,0 +1,65 @@
+use kodasql::network_protocol::postgres::{constants::PROTOCOL_VERSION_3, messages::{decode_message, decode_startup_message, BackendMessage, FrontendMessage}};
+
+fn make_startup_message(parameters: &[(&str, &str)]) -> Vec<u8> {
+    let mut body: Vec<u8> = PROTOCOL_VERSION_3.to_be_bytes().to_vec();
+    for (name, value) in parameters {
+        body.extend_from_slice(name.as_bytes());
+        body.push(0);
+        body.extend_from_slice(value.as_bytes());
+        body.push(0);
+    }
+    body.push(0);
+
+    let mut message = ((body.len() + 4) as i32).to_be_bytes().to_vec();
+    message.extend(body);
+    message
+}
+
+fn make_query_message(sql: &str) -> Vec<u8> {
+    let mut message = vec![b'Q'];
+    message.extend_from_slice(&((sql.len() + 5) as i32).to_be_bytes());
+    message.extend_from_slice(sql.as_bytes());
+    message.push(0);
+    message
+}
+
+#[test]
+fn test_decode_startup_message() {
+    let mut buffer = make_startup_message(&[("user", "koda"), ("database", "schema_1")]);
+
+    let message = decode_startup_message(&mut buffer, 1024).expect("Failed to decode message");
+
+    let expected_parameters = vec![
+        (String::from("user"), String::from("koda")),
+        (String::from("database"), String::from("schema_1")),
+    ];
+    assert_eq!(message, Some(FrontendMessage::Startup { parameters: expected_parameters }));
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn test_decode_query_split_across_reads() {
+    let bytes = make_query_message("SELECT id FROM test_select_table");
+    let mut buffer: Vec<u8> = Vec::new();
+
+    buffer.extend_from_slice(&bytes[..7]);
+    assert_eq!(decode_message(&mut buffer, 1024).expect("Failed to decode message"), None);
+
+    buffer.extend_from_slice(&bytes[7..]);
+    buffer.extend_from_slice(&[b'X', 0, 0, 0, 4]);
+    assert_eq!(decode_message(&mut buffer, 1024).expect("Failed to decode message"), Some(FrontendMessage::Query(String::from("SELECT id FROM test_select_table"))));
+    assert_eq!(decode_message(&mut buffer, 1024).expect("Failed to decode message"), Some(FrontendMessage::Terminate));
+}
+
+#[test]
+fn test_encode_data_row_with_null() {
+    let message = BackendMessage::DataRow(vec![Some(String::from("7")), None]).encode();
+
+    let expected: Vec<u8> = vec![
+        b'D', 0, 0, 0, 15, // Type and length
+        0, 2, // Column count
+        0, 0, 0, 1, b'7', // First value
+        255, 255, 255, 255, // NULL
+    ];
+    assert_eq!(message, expected);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use kodasql::{command_dispatcher::session::Session, database::{database_loader, utils::find_database_table}, network_protocol::types::CellValue, shared::errors::Error};
+use common::run_sql;
+
+#[tokio::test]
+async fn test_schema_qualified_tables() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_qualified_table").await;
+    let _ = run_sql(&mut session, "DROP TABLE schema_2.test_qualified_table").await;
+
+    // Act - same table name in two schemas
+    run_sql(&mut session, "CREATE TABLE schema_2.test_qualified_table (id INT PRIMARY KEY, username TEXT)").await.expect("Failed to create table");
+    run_sql(&mut session, "CREATE TABLE test_qualified_table (id INT PRIMARY KEY, owner_id INT REFERENCES schema_2.test_qualified_table (id))").await.expect("Failed to create table");
+    run_sql(&mut session, "INSERT INTO schema_2.test_qualified_table (id, username) VALUES (1, 'Alice'), (2, 'Bob')").await.expect("Failed to insert");
+    run_sql(&mut session, "INSERT INTO schema_1.test_qualified_table (id, owner_id) VALUES (10, 2)").await.expect("Failed to insert");
+
+    // Assert
+    let database = database_loader::get_database().expect("Failed to get database");
+    let table_name = String::from("test_qualified_table");
+    assert!(find_database_table(&database, &String::from("schema_2"), &table_name).is_some());
+    assert!(find_database_table(&database, &String::from("schema_1"), &table_name).is_some());
+
+    let result_sets = run_sql(&mut session, "SELECT username FROM schema_2.test_qualified_table WHERE id = 2").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Text(String::from("Bob"))]]);
+    let result_sets = run_sql(&mut session, "SELECT * FROM test_qualified_table").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(10), CellValue::Integer(2)]]);
+
+    // Act - foreign key into another schema
+    run_sql(&mut session, "INSERT INTO test_qualified_table (id, owner_id) VALUES (11, 2)").await.expect("Failed to insert");
+    let error = run_sql(&mut session, "INSERT INTO test_qualified_table (id, owner_id) VALUES (12, 3)").await;
+    assert!(matches!(error, Err(Error::ForeignKeyConstraintNotSatisfied { .. })));
+
+    // Act - alter and delete through qualified names
+    run_sql(&mut session, "ALTER TABLE schema_2.test_qualified_table ADD COLUMN age INT").await.expect("Failed to alter table");
+    run_sql(&mut session, "DELETE FROM schema_2.test_qualified_table WHERE id = 1").await.expect("Failed to delete");
+
+    // Assert
+    let result_sets = run_sql(&mut session, "SELECT * FROM schema_2.test_qualified_table").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(2), CellValue::Text(String::from("Bob")), CellValue::Null]]);
+    let result_sets = run_sql(&mut session, "SELECT id FROM schema_1.test_qualified_table").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows.len(), 2);
+
+    let error = run_sql(&mut session, "CREATE TABLE missing_schema.test_qualified_table (id INT PRIMARY KEY)").await;
+    assert!(matches!(error, Err(Error::SchemaDoesNotExist { .. })));
+    let error = run_sql(&mut session, "SELECT * FROM schema_2.missing_table").await;
+    assert!(matches!(error, Err(Error::TableDoesNotExist { .. })));
+
+    // Restore
+    run_sql(&mut session, "DROP TABLE test_qualified_table").await.expect("Failed to drop table");
+    run_sql(&mut session, "DROP TABLE schema_2.test_qualified_table").await.expect("Failed to drop table");
+
+    let database = database_loader::get_database().expect("Failed to get database");
+    assert!(find_database_table(&database, &String::from("schema_2"), &table_name).is_none());
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use kodasql::{command_dispatcher::session::Session, database::database_loader, network_protocol::types::CellValue, shared::errors::Error};
+use common::run_sql;
+
+async fn select_rows(session: &mut Session, sql: &str) -> Vec<Vec<CellValue>> {
+    run_sql(session, sql).await.expect("Failed to select").remove(0).rows
+}
+
+#[tokio::test]
+async fn test_referential_actions() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    for table_name in ["test_ref_items", "test_ref_orders", "test_ref_notes", "test_ref_tickets", "test_ref_users"] {
+        let _ = run_sql(&mut session, &format!("DROP TABLE {}", table_name)).await;
+    }
+    run_sql(&mut session, "CREATE TABLE test_ref_users (id INT PRIMARY KEY, name TEXT)").await.expect("Failed to create table");
+    run_sql(&mut session, "CREATE TABLE test_ref_orders (id INT PRIMARY KEY, user_id INT REFERENCES test_ref_users (id) ON DELETE CASCADE ON UPDATE CASCADE)").await.expect("Failed to create table");
+    run_sql(&mut session, "CREATE TABLE test_ref_items (id INT PRIMARY KEY, order_id INT REFERENCES test_ref_orders (id) ON DELETE CASCADE)").await.expect("Failed to create table");
+    run_sql(&mut session, "CREATE TABLE test_ref_notes (id INT PRIMARY KEY, user_id INT REFERENCES test_ref_users (id) ON DELETE SET NULL ON UPDATE SET DEFAULT)").await.expect("Failed to create table");
+    run_sql(&mut session, "CREATE TABLE test_ref_tickets (id INT PRIMARY KEY, user_id INT REFERENCES test_ref_users (id) ON DELETE RESTRICT)").await.expect("Failed to create table");
+    run_sql(&mut session, "INSERT INTO test_ref_users (id, name) VALUES (1, 'Ann'), (2, 'Bob'), (3, 'Cid'), (4, 'Dan')").await.expect("Failed to insert");
+    run_sql(&mut session, "INSERT INTO test_ref_orders (id, user_id) VALUES (10, 1), (11, 1), (12, 2)").await.expect("Failed to insert");
+    run_sql(&mut session, "INSERT INTO test_ref_items (id, order_id) VALUES (100, 10), (101, 11), (102, 12)").await.expect("Failed to insert");
+    run_sql(&mut session, "INSERT INTO test_ref_notes (id, user_id) VALUES (20, 1), (21, 2)").await.expect("Failed to insert");
+    run_sql(&mut session, "INSERT INTO test_ref_tickets (id, user_id) VALUES (30, 3)").await.expect("Failed to insert");
+
+    // Act - deletes cascade through the orders to their items, and set the notes' reference to NULL
+    let result_sets = run_sql(&mut session, "DELETE FROM test_ref_users WHERE id = 1").await.expect("Failed to delete");
+    assert_eq!(result_sets[0].rows_affected, Some(1));
+    assert_eq!(select_rows(&mut session, "SELECT id FROM test_ref_orders ORDER BY id").await, vec![vec![CellValue::Integer(12)]]);
+    assert_eq!(select_rows(&mut session, "SELECT id FROM test_ref_items ORDER BY id").await, vec![vec![CellValue::Integer(102)]]);
+    assert_eq!(select_rows(&mut session, "SELECT id, user_id FROM test_ref_notes ORDER BY id").await, vec![
+        vec![CellValue::Integer(20), CellValue::Null],
+        vec![CellValue::Integer(21), CellValue::Integer(2)],
+    ]);
+
+    // Act - updates cascade to the orders, and set the notes' reference to its default, NULL here
+    run_sql(&mut session, "UPDATE test_ref_users SET id = 5 WHERE id = 2").await.expect("Failed to update");
+    assert_eq!(select_rows(&mut session, "SELECT id, user_id FROM test_ref_orders").await, vec![vec![CellValue::Integer(12), CellValue::Integer(5)]]);
+    assert_eq!(select_rows(&mut session, "SELECT user_id FROM test_ref_notes WHERE id = 21").await, vec![vec![CellValue::Null]]);
+
+    // Act - updates leaving the referenced column as it was don't touch the referencing rows
+    run_sql(&mut session, "UPDATE test_ref_users SET name = 'Bo' WHERE id = 5").await.expect("Failed to update");
+    assert_eq!(select_rows(&mut session, "SELECT user_id FROM test_ref_orders").await, vec![vec![CellValue::Integer(5)]]);
+
+    // Act - RESTRICT and the default NO ACTION reject changes to referenced rows, leaving everything as it was
+    let error = run_sql(&mut session, "DELETE FROM test_ref_users WHERE id = 3").await;
+    assert!(matches!(error, Err(Error::ForeignKeyConstraintNotSatisfied { .. })));
+    let error = run_sql(&mut session, "UPDATE test_ref_orders SET id = 13 WHERE id = 12").await;
+    assert!(matches!(error, Err(Error::ForeignKeyConstraintNotSatisfied { .. })));
+    assert_eq!(select_rows(&mut session, "SELECT COUNT(*) FROM test_ref_users").await, vec![vec![CellValue::Integer(3)]]);
+    assert_eq!(select_rows(&mut session, "SELECT id FROM test_ref_orders").await, vec![vec![CellValue::Integer(12)]]);
+
+    // Act - unreferenced rows are deleted freely
+    let result_sets = run_sql(&mut session, "DELETE FROM test_ref_users WHERE id = 4").await.expect("Failed to delete");
+    assert_eq!(result_sets[0].rows_affected, Some(1));
+
+    // Clean up
+    for table_name in ["test_ref_items", "test_ref_orders", "test_ref_notes", "test_ref_tickets", "test_ref_users"] {
+        run_sql(&mut session, &format!("DROP TABLE {}", table_name)).await.expect("Failed to drop table");
+    }
+}
+
+#[tokio::test]
+async fn test_referential_actions_validate_new_keys() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    for table_name in ["test_ref_default_children", "test_ref_required_children", "test_ref_parents"] {
+        let _ = run_sql(&mut session, &format!("DROP TABLE {}", table_name)).await;
+    }
+    run_sql(&mut session, "CREATE TABLE test_ref_parents (id INT PRIMARY KEY)").await.expect("Failed to create table");
+    run_sql(&mut session, "CREATE TABLE test_ref_default_children (id INT PRIMARY KEY, parent_id INT DEFAULT 99 REFERENCES test_ref_parents (id) ON DELETE SET DEFAULT)").await.expect("Failed to create table");
+    run_sql(&mut session, "CREATE TABLE test_ref_required_children (id INT PRIMARY KEY, parent_id INT NOT NULL REFERENCES test_ref_parents (id) ON DELETE SET NULL)").await.expect("Failed to create table");
+    run_sql(&mut session, "INSERT INTO test_ref_parents (id) VALUES (1), (2), (99)").await.expect("Failed to insert");
+    run_sql(&mut session, "INSERT INTO test_ref_default_children (id, parent_id) VALUES (10, 1)").await.expect("Failed to insert");
+    run_sql(&mut session, "INSERT INTO test_ref_required_children (id, parent_id) VALUES (20, 2)").await.expect("Failed to insert");
+
+    // Act - SET DEFAULT to a key no row has is rejected, leaving everything as it was
+    run_sql(&mut session, "DELETE FROM test_ref_parents WHERE id = 99").await.expect("Failed to delete");
+    let error = run_sql(&mut session, "DELETE FROM test_ref_parents WHERE id = 1").await;
+    assert!(matches!(error, Err(Error::ForeignKeyConstraintNotSatisfied { .. })));
+    assert_eq!(select_rows(&mut session, "SELECT parent_id FROM test_ref_default_children").await, vec![vec![CellValue::Integer(1)]]);
+
+    // Act - SET NULL on a NOT NULL column is rejected
+    let error = run_sql(&mut session, "DELETE FROM test_ref_parents WHERE id = 2").await;
+    assert!(matches!(error, Err(Error::ColumnNotNull { .. })));
+    assert_eq!(select_rows(&mut session, "SELECT COUNT(*) FROM test_ref_parents").await, vec![vec![CellValue::Integer(2)]]);
+
+    // Clean up
+    for table_name in ["test_ref_default_children", "test_ref_required_children", "test_ref_parents"] {
+        run_sql(&mut session, &format!("DROP TABLE {}", table_name)).await.expect("Failed to drop table");
+    }
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use kodasql::{command_dispatcher::session::Session, database::{database_loader, utils::find_database_table}, network_protocol::types::CellValue, shared::errors::Error};
+use common::run_sql;
+
+#[tokio::test]
+async fn test_per_session_search_path() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut first_session = Session::default();
+    let mut second_session = Session::default();
+    let _ = run_sql(&mut first_session, "DROP TABLE schema_1.test_search_path_table").await;
+    let _ = run_sql(&mut first_session, "DROP TABLE schema_2.test_search_path_table").await;
+    run_sql(&mut first_session, "CREATE TABLE schema_1.test_search_path_table (id INT PRIMARY KEY, name TEXT)").await.expect("Failed to create table");
+    run_sql(&mut first_session, "CREATE TABLE schema_2.test_search_path_table (id INT PRIMARY KEY, name TEXT)").await.expect("Failed to create table");
+    run_sql(&mut first_session, "INSERT INTO schema_1.test_search_path_table (id, name) VALUES (1, 'first')").await.expect("Failed to insert");
+    run_sql(&mut first_session, "INSERT INTO schema_2.test_search_path_table (id, name) VALUES (2, 'second')").await.expect("Failed to insert");
+
+    // Act - each session resolves the unqualified name against its own path
+    run_sql(&mut second_session, "SET search_path TO schema_2, schema_1").await.expect("Failed to set search path");
+
+    // Assert
+    let result_sets = run_sql(&mut first_session, "SELECT name FROM test_search_path_table").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Text(String::from("first"))]]);
+    let result_sets = run_sql(&mut second_session, "SELECT name FROM test_search_path_table").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Text(String::from("second"))]]);
+
+    let result_sets = run_sql(&mut second_session, "SHOW search_path").await.expect("Failed to show search path");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Text(String::from("schema_2, schema_1"))]]);
+
+    // Act - new tables go to the first schema on the path
+    run_sql(&mut second_session, "INSERT INTO test_search_path_table (id, name) VALUES (3, 'third')").await.expect("Failed to insert");
+    let _ = run_sql(&mut second_session, "DROP TABLE test_search_path_created").await;
+    run_sql(&mut second_session, "CREATE TABLE test_search_path_created (id INT PRIMARY KEY)").await.expect("Failed to create table");
+
+    // Assert
+    let database = database_loader::get_database().expect("Failed to get database");
+    let created_table_name = String::from("test_search_path_created");
+    assert!(find_database_table(&database, &String::from("schema_2"), &created_table_name).is_some());
+    assert!(find_database_table(&database, &String::from("schema_1"), &created_table_name).is_none());
+    let result_sets = run_sql(&mut first_session, "SELECT id FROM schema_2.test_search_path_table").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows.len(), 2);
+
+    // Act - USE and DEFAULT switch the path back
+    run_sql(&mut second_session, "DROP TABLE test_search_path_created").await.expect("Failed to drop table");
+    run_sql(&mut second_session, "USE schema_1").await.expect("Failed to use schema");
+    let result_sets = run_sql(&mut second_session, "SELECT name FROM test_search_path_table").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Text(String::from("first"))]]);
+
+    run_sql(&mut second_session, "SET search_path = 'schema_2'").await.expect("Failed to set search path");
+    run_sql(&mut second_session, "SET search_path TO DEFAULT").await.expect("Failed to reset search path");
+    let result_sets = run_sql(&mut second_session, "SHOW search_path").await.expect("Failed to show search path");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Text(String::from("schema_1"))]]);
+
+    let error = run_sql(&mut second_session, "SET search_path TO missing_schema").await;
+    assert!(matches!(error, Err(Error::SchemaDoesNotExist { .. })));
+
+    // Restore
+    run_sql(&mut first_session, "DROP TABLE schema_1.test_search_path_table").await.expect("Failed to drop table");
+    run_sql(&mut first_session, "DROP TABLE schema_2.test_search_path_table").await.expect("Failed to drop table");
+}
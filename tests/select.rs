@@ -1,27 +1,31 @@
-use std::collections::HashMap;
-
-use kodasql::{command_dispatcher::statement_dispatcher, database::database_loader, shared::errors::Error};
+use kodasql::{command_dispatcher::statement_dispatcher, database::{database_loader, types::DataType}, network_protocol::types::{CellValue, ResultColumn}, shared::errors::Error};
 use sqlparser::{dialect::GenericDialect, parser::Parser};
 
 
 async fn execute_select_statement_and_assert(
     sql_command: &str,
-    expected_results_json: &str,
+    expected_columns: Vec<(&str, DataType)>,
+    expected_rows: Vec<Vec<CellValue>>,
 ) -> Result<(), Error> {
     let dialect = GenericDialect {};
     let ast = Parser::parse_sql(&dialect, sql_command).expect("Failed to parse SQL");
     let statement = ast.first().expect("No statements found");
 
-    let string_result = statement_dispatcher::dispatch_statement(statement).await.expect("Storage engine error");
+    let result_set = statement_dispatcher::dispatch_statement(statement).await.expect("Storage engine error");
 
-    // Deserialize both the expected results and the actual results to Vec<HashMap<String, String>>
-    let expected_results: Vec<HashMap<String, String>> = serde_json::from_str(expected_results_json).expect("Failed to deserialize expected results");
-    let actual_results: Vec<HashMap<String, String>> = serde_json::from_str(&string_result).expect("Failed to deserialize actual results");
+    let expected_columns: Vec<ResultColumn> = expected_columns.into_iter()
+        .map(|(name, data_type)| ResultColumn { name: name.to_string(), data_type })
+        .collect();
 
-    assert_eq!(actual_results, expected_results, "The result rows do not match the expected rows");
+    assert_eq!(result_set.columns, expected_columns, "The result columns do not match the expected columns");
+    assert_eq!(result_set.rows, expected_rows, "The result rows do not match the expected rows");
     Ok(())
 }
 
+fn text(value: &str) -> CellValue {
+    CellValue::Text(value.to_string())
+}
+
 /*
  *  test_select_table:
     id,username,email,age
@@ -45,25 +49,148 @@ async fn test_select_statements() {
             // Statement
             "SELECT id, username FROM test_select_table WHERE id = 2 OR (username = 'Andrew' AND age = 21) ORDER BY id ASC;",
             // Results
-            r#"[{"id": "2", "username": "Mary"}, {"id": "5", "username": "Andrew"}]"#,
+            vec![("id", DataType::Integer), ("username", DataType::Text)],
+            vec![vec![CellValue::Integer(2), text("Mary")], vec![CellValue::Integer(5), text("Andrew")]],
         ),
-        // Test limit
+        // Test limit and projection order
         (
             // Statement
-            "SELECT id, age FROM test_select_table WHERE age = 21 LIMIT 2",
+            "SELECT age, id FROM test_select_table WHERE age = 21 LIMIT 2",
             // Results
-            r#"[{"id": "5", "age": "21"}, {"id": "6", "age": "21"}]"#,
+            vec![("age", DataType::Integer), ("id", DataType::Integer)],
+            vec![vec![CellValue::Integer(21), CellValue::Integer(5)], vec![CellValue::Integer(21), CellValue::Integer(6)]],
         ),
         // Test table reader with indexes
         (
             // Statement
             "SELECT id, username FROM test_select_table WHERE id = 2 OR username = 'Jane'",
             // Results
-            r#"[{"id": "2", "username": "Mary"}, {"id": "3", "username": "Jane"}]"#
+            vec![("id", DataType::Integer), ("username", DataType::Text)],
+            vec![vec![CellValue::Integer(2), text("Mary")], vec![CellValue::Integer(3), text("Jane")]],
+        ),
+        // Test numeric comparison and inequality
+        (
+            // Statement
+            "SELECT id FROM test_select_table WHERE age > 9 AND age <> 21 ORDER BY id",
+            // Results
+            vec![("id", DataType::Integer)],
+            vec![vec![CellValue::Integer(2)], vec![CellValue::Integer(3)], vec![CellValue::Integer(4)]],
         ),
+        // Test ranges
+        (
+            // Statement
+            "SELECT id FROM test_select_table WHERE age BETWEEN 10 AND 20 OR id >= 7 ORDER BY id",
+            // Results
+            vec![("id", DataType::Integer)],
+            vec![vec![CellValue::Integer(2)], vec![CellValue::Integer(3)], vec![CellValue::Integer(7)]],
+        ),
+        // Test lists and negation
+        (
+            // Statement
+            "SELECT id FROM test_select_table WHERE id IN (1, 4, 7) AND NOT (age NOT BETWEEN 1 AND 21) ORDER BY id",
+            // Results
+            vec![("id", DataType::Integer)],
+            vec![vec![CellValue::Integer(1)], vec![CellValue::Integer(7)]],
+        ),
+        // Test patterns
+        (
+            // Statement
+            "SELECT username FROM test_select_table WHERE username LIKE 'M%' AND username NOT LIKE '%a%' OR email ILIKE 'JANE@%' ORDER BY id",
+            // Results
+            vec![("username", DataType::Text)],
+            vec![vec![text("Jane")], vec![text("Mike")]],
+        ),
+        // Test single character wildcards
+        (
+            // Statement
+            "SELECT username FROM test_select_table WHERE username ILIKE 'm_a' OR username NOT IN ('John', 'Mary', 'Jane', 'Matt', 'Andrew', 'Mike', 'Mia') ORDER BY id",
+            // Results
+            vec![("username", DataType::Text)],
+            vec![vec![text("Mia")]],
+        ),
+    ];
+
+    for (sql_command, expected_columns, expected_rows) in test_cases {
+        execute_select_statement_and_assert(sql_command, expected_columns, expected_rows).await.expect("Test case failed");
+    }
+}
+
+#[tokio::test]
+async fn test_select_null_filters() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let dialect = GenericDialect {};
+    let prepare_statements = vec![
+        "DROP TABLE test_select_null_table",
+        "CREATE TABLE test_select_null_table (id INT PRIMARY KEY, score INT, label TEXT)",
+        "INSERT INTO test_select_null_table (id, score, label) VALUES (1, 2, 'first'), (2, 10, 'second')",
+        "INSERT INTO test_select_null_table (id) VALUES (3)",
     ];
+    for sql_command in prepare_statements {
+        let ast = Parser::parse_sql(&dialect, sql_command).expect("Failed to parse SQL");
+        let _ = statement_dispatcher::dispatch_statement(&ast[0]).await;
+    }
 
-    for (sql_command, expected_results_json) in test_cases {
-        execute_select_statement_and_assert(sql_command, expected_results_json).await.expect("Test case failed");
+    let test_cases = vec![
+        ("SELECT id FROM test_select_null_table WHERE score IS NULL", vec![vec![CellValue::Integer(3)]]),
+        ("SELECT id FROM test_select_null_table WHERE score IS NOT NULL AND score < 3", vec![vec![CellValue::Integer(1)]]),
+        // Comparisons with NULL are unknown, so neither side of a condition matches
+        ("SELECT id FROM test_select_null_table WHERE score <> 2 ORDER BY id", vec![vec![CellValue::Integer(2)]]),
+        ("SELECT id FROM test_select_null_table WHERE NOT (label = 'first') ORDER BY id", vec![vec![CellValue::Integer(2)]]),
+        ("SELECT id FROM test_select_null_table WHERE id NOT IN (1, NULL)", vec![]),
+        ("SELECT id FROM test_select_null_table WHERE id IN (1, NULL) OR label IS NULL ORDER BY id", vec![vec![CellValue::Integer(1)], vec![CellValue::Integer(3)]]),
+    ];
+
+    for (sql_command, expected_rows) in test_cases {
+        execute_select_statement_and_assert(sql_command, vec![("id", DataType::Integer)], expected_rows).await.expect("Test case failed");
     }
+
+    // Restore
+    let ast = Parser::parse_sql(&dialect, "DROP TABLE test_select_null_table").expect("Failed to parse SQL");
+    statement_dispatcher::dispatch_statement(&ast[0]).await.expect("Failed to drop table");
+}
+
+#[tokio::test]
+async fn test_select_ordering() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let dialect = GenericDialect {};
+    let prepare_statements = vec![
+        "DROP TABLE test_select_order_table",
+        "CREATE TABLE test_select_order_table (id INT PRIMARY KEY, score INT, label TEXT)",
+        "INSERT INTO test_select_order_table (id, score, label) VALUES (1, 9, 'b'), (2, 10, 'a'), (3, 100, 'b')",
+        "INSERT INTO test_select_order_table (id, label) VALUES (4, 'a')",
+        "INSERT INTO test_select_order_table (id, score, label) VALUES (5, 10, 'c')",
+    ];
+    for sql_command in prepare_statements {
+        let ast = Parser::parse_sql(&dialect, sql_command).expect("Failed to parse SQL");
+        let _ = statement_dispatcher::dispatch_statement(&ast[0]).await;
+    }
+
+    let ids = |ids: Vec<i64>| ids.into_iter().map(|id| vec![CellValue::Integer(id)]).collect::<Vec<Vec<CellValue>>>();
+    let test_cases = vec![
+        // Numbers compare as numbers, NULLs come last when ascending and first when descending
+        ("SELECT id FROM test_select_order_table ORDER BY score", ids(vec![1, 2, 5, 3, 4])),
+        ("SELECT id FROM test_select_order_table ORDER BY score DESC", ids(vec![4, 3, 2, 5, 1])),
+        ("SELECT id FROM test_select_order_table ORDER BY score DESC NULLS LAST, id DESC", ids(vec![3, 5, 2, 1, 4])),
+        ("SELECT id FROM test_select_order_table ORDER BY label, score DESC NULLS LAST", ids(vec![2, 4, 3, 1, 5])),
+        ("SELECT id FROM test_select_order_table ORDER BY score * -1 NULLS FIRST", ids(vec![4, 3, 2, 5, 1])),
+        // Pagination
+        ("SELECT id FROM test_select_order_table ORDER BY score LIMIT 2 OFFSET 1", ids(vec![2, 5])),
+        ("SELECT id FROM test_select_order_table ORDER BY 1 DESC LIMIT 2", ids(vec![5, 4])),
+        ("SELECT id FROM test_select_order_table ORDER BY id OFFSET 3", ids(vec![4, 5])),
+        ("SELECT id FROM test_select_order_table ORDER BY id LIMIT 3 OFFSET 10", ids(vec![])),
+    ];
+
+    for (sql_command, expected_rows) in test_cases {
+        execute_select_statement_and_assert(sql_command, vec![("id", DataType::Integer)], expected_rows).await.expect("Test case failed");
+    }
+
+    let ast = Parser::parse_sql(&dialect, "SELECT id FROM test_select_order_table ORDER BY 2").expect("Failed to parse SQL");
+    let error = statement_dispatcher::dispatch_statement(&ast[0]).await;
+    assert!(matches!(error, Err(Error::InvalidOrderPosition { .. })));
+
+    // Restore
+    let ast = Parser::parse_sql(&dialect, "DROP TABLE test_select_order_table").expect("Failed to parse SQL");
+    statement_dispatcher::dispatch_statement(&ast[0]).await.expect("Failed to drop table");
 }
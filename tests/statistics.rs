@@ -0,0 +1,88 @@
+mod common;
+
+use kodasql::{command_dispatcher::{request_handler, session::Session}, database::database_loader, network_protocol::types::CellValue, storage_engine::statistics::statistics_reader};
+use sqlparser::ast::Statement;
+use common::run_sql;
+
+async fn get_scan_line(session: &mut Session, sql: &str) -> String {
+    let result_sets = run_sql(session, sql).await.expect("Failed to explain");
+    result_sets[0].rows.iter()
+        .filter_map(|row| match &row[0] {
+            CellValue::Text(line) if line.contains("Scan") => Some(line.trim().to_string()),
+            _ => None,
+        })
+        .next()
+        .expect("Missing scan")
+}
+
+#[tokio::test]
+async fn test_analyze() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_statistics_items").await;
+    run_sql(&mut session, "CREATE TABLE test_statistics_items (id INT PRIMARY KEY, category INT, label TEXT)").await.expect("Failed to create table");
+    let values = (0..200)
+        .map(|i| match i % 4 {
+            0 => format!("({}, {}, NULL)", i, i % 2),
+            _ => format!("({}, {}, 'item_{}')", i, i % 2, i),
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+    run_sql(&mut session, &format!("INSERT INTO test_statistics_items (id, category, label) VALUES {}", values)).await.expect("Failed to insert");
+    run_sql(&mut session, "CREATE INDEX test_statistics_items_category ON test_statistics_items (category)").await.expect("Failed to create index");
+
+    // Without statistics, indexes are always used
+    let scan_line = get_scan_line(&mut session, "EXPLAIN SELECT id FROM test_statistics_items WHERE category = 1").await;
+    assert_eq!(scan_line, "->  Index Scan using test_statistics_items_category on schema_1.test_statistics_items");
+
+    // Act
+    let result_sets = run_sql(&mut session, "ANALYZE test_statistics_items").await.expect("Failed to analyze");
+    assert_eq!(result_sets[0].message, Some(String::from("Success: table test_statistics_items has been analyzed.")));
+
+    // Assert
+    let statistics = statistics_reader::read_table_statistics(&String::from("schema_1"), &String::from("test_statistics_items")).expect("Missing statistics");
+    assert_eq!(statistics.row_count, 200);
+
+    let id_statistics = &statistics.columns[0];
+    assert_eq!(id_statistics.column_name, "id");
+    assert_eq!(id_statistics.distinct_count, 200);
+    assert_eq!(id_statistics.null_fraction, 0.0);
+    assert_eq!(id_statistics.histogram_bounds.len(), 11);
+    assert_eq!(id_statistics.histogram_bounds.first(), Some(&String::from("0")));
+    assert_eq!(id_statistics.histogram_bounds.last(), Some(&String::from("199")));
+
+    assert_eq!(statistics.columns[1].distinct_count, 2);
+    assert_eq!(statistics.columns[2].null_fraction, 0.25);
+    assert_eq!(statistics.columns[2].distinct_count, 150);
+
+    // Lookups finding few rows use the index, the others scan the table
+    let scan_line = get_scan_line(&mut session, "EXPLAIN SELECT id FROM test_statistics_items WHERE category = 1").await;
+    assert_eq!(scan_line, "->  Seq Scan on schema_1.test_statistics_items");
+    let scan_line = get_scan_line(&mut session, "EXPLAIN SELECT id FROM test_statistics_items WHERE id = 5").await;
+    assert_eq!(scan_line, "->  Index Scan using id on schema_1.test_statistics_items");
+    let scan_line = get_scan_line(&mut session, "EXPLAIN SELECT id FROM test_statistics_items WHERE id < 10").await;
+    assert_eq!(scan_line, "->  Index Scan using id on schema_1.test_statistics_items");
+    let scan_line = get_scan_line(&mut session, "EXPLAIN SELECT id FROM test_statistics_items WHERE id >= 50").await;
+    assert_eq!(scan_line, "->  Seq Scan on schema_1.test_statistics_items");
+
+    // Both plans give the same rows
+    let result_sets = run_sql(&mut session, "SELECT COUNT(*) FROM test_statistics_items WHERE category = 1").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(100)]]);
+
+    // Clean up
+    run_sql(&mut session, "DROP TABLE test_statistics_items").await.expect("Failed to drop table");
+}
+
+#[test]
+fn test_parse_analyze() {
+    let statements = request_handler::parse_sql("ANALYZE; ANALYZE test_table; ANALYZE TABLE schema_1.test_table").expect("Failed to parse");
+
+    let table_names: Vec<String> = statements.iter()
+        .map(|statement| match statement {
+            Statement::Analyze { table_name, .. } => table_name.to_string(),
+            statement => panic!("Unexpected statement {}", statement),
+        })
+        .collect();
+    assert_eq!(table_names, vec!["", "test_table", "schema_1.test_table"]);
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use std::time::Duration;
+
+use kodasql::{command_dispatcher::session::Session, database::database_loader, network_protocol::{result_stream::{self, ResultChunk}, types::{CellValue, ResultSet}}, shared::errors::Error};
+use common::run_sql;
+
+fn get_ids(result_set: &ResultSet) -> Vec<i64> {
+    result_set.rows.iter()
+        .map(|row| match row[0] {
+            CellValue::Integer(id) => id,
+            ref value => panic!("Unexpected id {:?}", value),
+        })
+        .collect()
+}
+
+// Runs the statements with a client reading the results as they come, returning what it received
+async fn run_streamed_sql(session: &mut Session, sql: &str) -> (Result<Vec<ResultSet>, Error>, Vec<ResultChunk>) {
+    let (sender, mut receiver) = result_stream::create_result_stream();
+    let processing = result_stream::run_with_result_stream(sender, run_sql(session, sql));
+    let receiving = async {
+        let mut chunks: Vec<ResultChunk> = Vec::new();
+        while let Some(chunk) = receiver.recv().await {
+            chunks.push(chunk);
+        }
+        chunks
+    };
+
+    tokio::join!(processing, receiving)
+}
+
+#[tokio::test]
+async fn test_streaming_execution() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_streaming_items").await;
+    let _ = run_sql(&mut session, "DROP TABLE test_streaming_pages").await;
+    run_sql(&mut session, "CREATE TABLE test_streaming_items (id INT PRIMARY KEY, label TEXT)").await.expect("Failed to create table");
+    run_sql(&mut session, "CREATE TABLE test_streaming_pages (id INT PRIMARY KEY, label TEXT) WITH (storage_engine = 'paged')").await.expect("Failed to create table");
+    let values = (0..2000).map(|i| format!("({}, 'item_{}')", i, i)).collect::<Vec<String>>().join(", ");
+    run_sql(&mut session, &format!("INSERT INTO test_streaming_items (id, label) VALUES {}", values)).await.expect("Failed to insert");
+    run_sql(&mut session, &format!("INSERT INTO test_streaming_pages (id, label) VALUES {}", values)).await.expect("Failed to insert");
+
+    for table_name in ["test_streaming_items", "test_streaming_pages"] {
+        // Act - the scan stops once the limit is reached
+        let result_sets = run_sql(&mut session, &format!("SELECT id FROM {} WHERE id % 2 = 0 LIMIT 5 OFFSET 1", table_name)).await.expect("Failed to select");
+        assert_eq!(get_ids(&result_sets[0]), vec![2, 4, 6, 8, 10]);
+
+        let result_sets = run_sql(&mut session, &format!("EXPLAIN ANALYZE SELECT id FROM {} WHERE id % 2 = 0 LIMIT 5 OFFSET 1", table_name)).await.expect("Failed to explain");
+        let scan_line = result_sets[0].rows.iter()
+            .filter_map(|row| match &row[0] {
+                CellValue::Text(line) if line.contains("Seq Scan") => Some(line.clone()),
+                _ => None,
+            })
+            .next()
+            .expect("Missing scan");
+        assert!(scan_line.contains("(actual rows=6 "), "Unexpected scan {}", scan_line);
+
+        // Act - top-N sorts keep only the first rows while reading the whole table
+        let result_sets = run_sql(&mut session, &format!("SELECT id FROM {} ORDER BY id DESC LIMIT 3 OFFSET 1", table_name)).await.expect("Failed to select");
+        assert_eq!(get_ids(&result_sets[0]), vec![1998, 1997, 1996]);
+
+        let result_sets = run_sql(&mut session, &format!("SELECT COUNT(*) FROM {}", table_name)).await.expect("Failed to select");
+        assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(2000)]]);
+    }
+
+    // Clean up
+    run_sql(&mut session, "DROP TABLE test_streaming_items").await.expect("Failed to drop table");
+    run_sql(&mut session, "DROP TABLE test_streaming_pages").await.expect("Failed to drop table");
+}
+
+#[tokio::test]
+async fn test_streamed_results() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_streamed_results").await;
+    run_sql(&mut session, "CREATE TABLE test_streamed_results (id INT PRIMARY KEY)").await.expect("Failed to create table");
+    let values = (0..5000).map(|i| format!("({})", i)).collect::<Vec<String>>().join(", ");
+    run_sql(&mut session, &format!("INSERT INTO test_streamed_results (id) VALUES {}", values)).await.expect("Failed to insert");
+
+    // Act - rows are sent in batches while the scan goes on, so those before a failing row reach the client
+    let (result, chunks) = run_streamed_sql(&mut session, "SELECT id, 1 / (id - 4500) FROM test_streamed_results").await;
+
+    // Assert
+    assert!(matches!(result, Err(Error::DivisionByZero)));
+    let sent_ids: Vec<i64> = chunks.iter()
+        .flat_map(|chunk| match chunk {
+            ResultChunk::Partial(result_set) => get_ids(result_set),
+            ResultChunk::Complete(_) => panic!("Unexpected complete result"),
+        })
+        .collect();
+    assert_eq!(chunks.len(), 4);
+    assert_eq!(sent_ids, (0..4000).collect::<Vec<i64>>());
+
+    // Act - the statement's result holds the rows after the last full batch
+    let (result, chunks) = run_streamed_sql(&mut session, "SELECT id FROM test_streamed_results WHERE id < 2500; SELECT COUNT(*) FROM test_streamed_results").await;
+
+    // Assert
+    assert!(result.expect("Failed to select").is_empty());
+    let chunk_rows: Vec<(bool, usize)> = chunks.iter()
+        .map(|chunk| match chunk {
+            ResultChunk::Partial(result_set) => (false, result_set.rows.len()),
+            ResultChunk::Complete(result_set) => (true, result_set.rows.len()),
+        })
+        .collect();
+    assert_eq!(chunk_rows, vec![(false, 1000), (false, 1000), (true, 500), (true, 1)]);
+
+    // Act - the results of a batch that writes are only sent once it's committed, so the query's rows come whole
+    let (result, chunks) = run_streamed_sql(&mut session, "INSERT INTO test_streamed_results (id) VALUES (5000); SELECT id FROM test_streamed_results").await;
+
+    // Assert
+    assert!(result.expect("Failed to insert and select").is_empty());
+    let chunk_rows: Vec<(bool, usize)> = chunks.iter()
+        .map(|chunk| match chunk {
+            ResultChunk::Partial(result_set) => (false, result_set.rows.len()),
+            ResultChunk::Complete(result_set) => (true, result_set.rows.len()),
+        })
+        .collect();
+    assert_eq!(chunk_rows, vec![(true, 0), (true, 5001)]);
+
+    // Act - a client that stops reading doesn't hold back writers, but the query can't go on past their commit
+    let (sender, mut receiver) = result_stream::create_result_stream();
+    let processing = result_stream::run_with_result_stream(sender, run_sql(&mut session, "SELECT id FROM test_streamed_results"));
+    let receiving = async {
+        let first_chunk = receiver.recv().await;
+        let mut writing_session = Session::default();
+        let write = tokio::time::timeout(Duration::from_secs(2), run_sql(&mut writing_session, "DELETE FROM test_streamed_results WHERE id = 5000")).await;
+        while receiver.recv().await.is_some() {}
+        (first_chunk, write)
+    };
+    let (result, (first_chunk, write)) = tokio::join!(processing, receiving);
+
+    // Assert
+    assert!(matches!(first_chunk, Some(ResultChunk::Partial(_))));
+    write.expect("Writer waited for the reading client").expect("Failed to delete");
+    assert!(matches!(result, Err(Error::ReadConflict { .. })));
+
+    // Clean up
+    run_sql(&mut session, "DROP TABLE test_streamed_results").await.expect("Failed to drop table");
+}
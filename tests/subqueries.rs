@@ -0,0 +1,87 @@
+mod common;
+
+use kodasql::{command_dispatcher::session::Session, database::{database_loader, types::DataType}, network_protocol::types::{CellValue, ResultColumn}, shared::errors::Error};
+use common::run_sql;
+
+fn text(value: &str) -> CellValue {
+    CellValue::Text(value.to_string())
+}
+
+#[tokio::test]
+async fn test_subqueries() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_subquery_orders").await;
+    let _ = run_sql(&mut session, "DROP TABLE test_subquery_users").await;
+    run_sql(&mut session, "CREATE TABLE test_subquery_users (id INT PRIMARY KEY, name TEXT, active BOOLEAN)").await.expect("Failed to create table");
+    run_sql(&mut session, "CREATE TABLE test_subquery_orders (id INT PRIMARY KEY, user_id INT, total INT)").await.expect("Failed to create table");
+    run_sql(&mut session, "INSERT INTO test_subquery_users (id, name, active) VALUES (1, 'Ann', true), (2, 'Bob', true), (3, 'Cid', false)").await.expect("Failed to insert");
+    run_sql(&mut session, "INSERT INTO test_subquery_orders (id, user_id, total) VALUES (10, 1, 5), (11, 1, 7), (12, 2, 3)").await.expect("Failed to insert");
+
+    // Act - IN and NOT IN
+    let result_sets = run_sql(&mut session, "SELECT name FROM test_subquery_users WHERE id IN (SELECT user_id FROM test_subquery_orders) ORDER BY name").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![text("Ann")], vec![text("Bob")]]);
+    let result_sets = run_sql(&mut session, "SELECT name FROM test_subquery_users WHERE id NOT IN (SELECT user_id FROM test_subquery_orders WHERE total > 4)").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![text("Bob")], vec![text("Cid")]]);
+
+    // Act - correlated EXISTS and NOT EXISTS, referencing the outer table by alias and by name
+    let result_sets = run_sql(&mut session, "SELECT u.name FROM test_subquery_users u WHERE EXISTS (SELECT 1 FROM test_subquery_orders o WHERE o.user_id = u.id AND o.total > 6)").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![text("Ann")]]);
+    let result_sets = run_sql(&mut session, "SELECT name FROM test_subquery_users WHERE NOT EXISTS (SELECT id FROM test_subquery_orders WHERE user_id = test_subquery_users.id)").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![text("Cid")]]);
+
+    // Act - correlated scalar subquery in the projection, NULL when it finds no row
+    let result_sets = run_sql(&mut session, "SELECT name, (SELECT SUM(total) FROM test_subquery_orders o WHERE o.user_id = u.id) FROM test_subquery_users u ORDER BY name").await.expect("Failed to select");
+    assert_eq!(result_sets[0].columns, vec![
+        ResultColumn { name: String::from("name"), data_type: DataType::Text },
+        ResultColumn { name: String::from("sum"), data_type: DataType::Integer },
+    ]);
+    assert_eq!(result_sets[0].rows, vec![
+        vec![text("Ann"), CellValue::Integer(12)],
+        vec![text("Bob"), CellValue::Integer(3)],
+        vec![text("Cid"), CellValue::Null],
+    ]);
+
+    // Act - scalar subqueries in WHERE, uncorrelated and correlated
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_subquery_orders WHERE total = (SELECT MAX(total) FROM test_subquery_orders)").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(11)]]);
+    let result_sets = run_sql(&mut session, "SELECT o.id FROM test_subquery_orders o JOIN test_subquery_users u ON u.id = o.user_id WHERE o.total < (SELECT AVG(total) FROM test_subquery_orders other WHERE other.user_id = u.id) ORDER BY o.id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(10)]]);
+
+    // Act - nested subqueries reaching the outermost row
+    let result_sets = run_sql(&mut session, "SELECT name FROM test_subquery_users u WHERE EXISTS (SELECT 1 FROM test_subquery_orders o WHERE o.user_id IN (SELECT id FROM test_subquery_users inner_users WHERE inner_users.id = u.id AND u.active))").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![text("Ann")], vec![text("Bob")]]);
+
+    // Act - subqueries in UPDATE filters and values
+    let result_sets = run_sql(&mut session, "UPDATE test_subquery_users SET name = (SELECT name FROM test_subquery_users next_users WHERE next_users.id = test_subquery_users.id + 1) WHERE id IN (SELECT user_id FROM test_subquery_orders WHERE total > 6)").await.expect("Failed to update");
+    assert_eq!(result_sets[0].rows_affected, Some(1));
+    let result_sets = run_sql(&mut session, "UPDATE test_subquery_users SET active = EXISTS (SELECT 1 FROM test_subquery_orders WHERE user_id = test_subquery_users.id)").await.expect("Failed to update");
+    assert_eq!(result_sets[0].rows_affected, Some(3));
+    let result_sets = run_sql(&mut session, "SELECT name, active FROM test_subquery_users ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![
+        vec![text("Bob"), CellValue::Boolean(true)],
+        vec![text("Bob"), CellValue::Boolean(true)],
+        vec![text("Cid"), CellValue::Boolean(false)],
+    ]);
+
+    // Act - correlated subquery in a DELETE filter
+    let result_sets = run_sql(&mut session, "DELETE FROM test_subquery_users WHERE NOT EXISTS (SELECT 1 FROM test_subquery_orders WHERE user_id = test_subquery_users.id)").await.expect("Failed to delete");
+    assert_eq!(result_sets[0].rows_affected, Some(1));
+    let result_sets = run_sql(&mut session, "DELETE FROM test_subquery_orders WHERE total < (SELECT MAX(total) FROM test_subquery_orders)").await.expect("Failed to delete");
+    assert_eq!(result_sets[0].rows_affected, Some(2));
+    let result_sets = run_sql(&mut session, "SELECT COUNT(*) FROM test_subquery_orders").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(1)]]);
+
+    // Act - errors
+    let error = run_sql(&mut session, "SELECT name FROM test_subquery_users WHERE id = (SELECT id FROM test_subquery_users)").await;
+    assert!(matches!(error, Err(Error::SubqueryReturnedMultipleRows)));
+    let error = run_sql(&mut session, "SELECT name FROM test_subquery_users WHERE id IN (SELECT id, name FROM test_subquery_users)").await;
+    assert!(matches!(error, Err(Error::InvalidSubqueryColumnCount { column_count: 2 })));
+    let error = run_sql(&mut session, "SELECT name FROM test_subquery_users WHERE EXISTS (SELECT missing FROM test_subquery_orders)").await;
+    assert!(matches!(error, Err(Error::ColumnDoesNotExist { .. })));
+
+    // Clean up
+    run_sql(&mut session, "DROP TABLE test_subquery_orders").await.expect("Failed to drop table");
+    run_sql(&mut session, "DROP TABLE test_subquery_users").await.expect("Failed to drop table");
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use kodasql::{command_dispatcher::session::Session, database::{database_loader, types::{CheckConstraint, Index}, utils::find_database_table}, network_protocol::types::CellValue, shared::errors::Error};
+use common::run_sql;
+
+#[tokio::test]
+async fn test_table_constraints() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    for table_name in ["test_constraint_assignments", "test_constraint_members", "test_constraint_invalid"] {
+        let _ = run_sql(&mut session, &format!("DROP TABLE {}", table_name)).await;
+    }
+
+    // Act - composite primary key, unique key and check, stored on the table schema
+    run_sql(&mut session, "CREATE TABLE test_constraint_members (team_id INT, member_id INT, email TEXT, seat INT, age INT, PRIMARY KEY (team_id, member_id), UNIQUE (team_id, seat), UNIQUE (email), CONSTRAINT adult CHECK (age >= 18))").await.expect("Failed to create table");
+    let database = database_loader::get_database().expect("Failed to get database");
+    let table_schema = find_database_table(&database, "schema_1", "test_constraint_members").expect("Table not found");
+    assert_eq!(table_schema.primary_key, vec![String::from("team_id"), String::from("member_id")]);
+    assert_eq!(table_schema.indexes, vec![
+        Index { name: String::from("test_constraint_members_pkey"), columns: vec![String::from("team_id"), String::from("member_id")], is_unique: true },
+        Index { name: String::from("test_constraint_members_team_id_seat_key"), columns: vec![String::from("team_id"), String::from("seat")], is_unique: true },
+    ]);
+    assert!(table_schema.columns.iter().any(|column| column.name == "email" && column.is_indexed));
+    assert_eq!(table_schema.checks, vec![CheckConstraint { name: String::from("adult"), expression: String::from("age >= 18") }]);
+
+    run_sql(&mut session, "INSERT INTO test_constraint_members (team_id, member_id, email, seat, age) VALUES (1, 1, 'a@x', 1, 30), (1, 2, 'b@x', 2, 40), (2, 1, 'c@x', 1, 18)").await.expect("Failed to insert");
+
+    // Act - keys are unique as a whole, within the inserted rows and against existing ones
+    let error = run_sql(&mut session, "INSERT INTO test_constraint_members (team_id, member_id, email, seat, age) VALUES (1, 2, 'd@x', 3, 20)").await;
+    assert!(matches!(error, Err(Error::IndexUniquenessNotSatisfied { index_name, .. }) if index_name == "test_constraint_members_pkey"));
+    let error = run_sql(&mut session, "INSERT INTO test_constraint_members (team_id, member_id, email, seat, age) VALUES (3, 1, 'd@x', 1, 20), (3, 2, 'e@x', 1, 20)").await;
+    assert!(matches!(error, Err(Error::IndexUniquenessNotSatisfied { index_name, .. }) if index_name == "test_constraint_members_team_id_seat_key"));
+    let error = run_sql(&mut session, "UPDATE test_constraint_members SET seat = 2 WHERE team_id = 1 AND member_id = 1").await;
+    assert!(matches!(error, Err(Error::IndexUniquenessNotSatisfied { .. })));
+    let error = run_sql(&mut session, "INSERT INTO test_constraint_members (team_id, member_id, email, seat, age) VALUES (3, 1, 'a@x', 1, 20)").await;
+    assert!(matches!(error, Err(Error::ColumnUniquenessNotSatisfied { .. })));
+
+    // Act - primary key columns can't be NULL
+    let error = run_sql(&mut session, "INSERT INTO test_constraint_members (team_id, email, seat, age) VALUES (3, 'd@x', 1, 20)").await;
+    assert!(matches!(error, Err(Error::ColumnNotNull { .. })));
+
+    // Act - checks on insert and update
+    let error = run_sql(&mut session, "INSERT INTO test_constraint_members (team_id, member_id, email, seat, age) VALUES (3, 1, 'd@x', 1, 17)").await;
+    assert!(matches!(error, Err(Error::CheckConstraintNotSatisfied { constraint_name }) if constraint_name == "adult"));
+    let error = run_sql(&mut session, "UPDATE test_constraint_members SET age = age - 1 WHERE team_id = 2").await;
+    assert!(matches!(error, Err(Error::CheckConstraintNotSatisfied { .. })));
+    run_sql(&mut session, "UPDATE test_constraint_members SET age = age + 1, seat = 3 WHERE team_id = 2").await.expect("Failed to update");
+    let result_sets = run_sql(&mut session, "SELECT age, seat FROM test_constraint_members WHERE team_id = 2").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(19), CellValue::Integer(3)]]);
+
+    // Act - table-level foreign key referencing the composite primary key
+    run_sql(&mut session, "CREATE TABLE test_constraint_assignments (id INT PRIMARY KEY, team_id INT, member_id INT, FOREIGN KEY (team_id, member_id) REFERENCES test_constraint_members (team_id, member_id))").await.expect("Failed to create table");
+    run_sql(&mut session, "INSERT INTO test_constraint_assignments (id, team_id, member_id) VALUES (1, 1, 2)").await.expect("Failed to insert");
+    let error = run_sql(&mut session, "INSERT INTO test_constraint_assignments (id, team_id, member_id) VALUES (2, 2, 2)").await;
+    assert!(matches!(error, Err(Error::ForeignKeyConstraintNotSatisfied { .. })));
+
+    // Act - invalid definitions
+    let error = run_sql(&mut session, "CREATE TABLE test_constraint_invalid (a INT PRIMARY KEY, b INT, PRIMARY KEY (a, b))").await;
+    assert!(matches!(error, Err(Error::MultiplePrimaryKeys { .. })));
+    let error = run_sql(&mut session, "CREATE TABLE test_constraint_invalid (a INT PRIMARY KEY, b INT PRIMARY KEY)").await;
+    assert!(matches!(error, Err(Error::MultiplePrimaryKeys { .. })));
+    let error = run_sql(&mut session, "CREATE TABLE test_constraint_invalid (a INT, UNIQUE (missing))").await;
+    assert!(matches!(error, Err(Error::ColumnDoesNotExist { .. })));
+    let error = run_sql(&mut session, "CREATE TABLE test_constraint_invalid (a INT, CHECK (a + 1))").await;
+    assert!(matches!(error, Err(Error::InvalidOperandTypes { .. })));
+
+    // Clean up
+    for table_name in ["test_constraint_assignments", "test_constraint_members"] {
+        run_sql(&mut session, &format!("DROP TABLE {}", table_name)).await.expect("Failed to drop table");
+    }
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use std::fs;
+
+use kodasql::{command_dispatcher::session::Session, database::{database_loader, database_navigator::get_table_data_path}, network_protocol::types::CellValue, shared::errors::Error};
+use common::run_sql;
+
+fn read_table_data() -> String {
+    let file_path = get_table_data_path(&String::from("schema_1"), &String::from("test_transactions_commit_table"));
+    fs::read_to_string(file_path).expect("Could not read table data file")
+}
+
+#[tokio::test]
+async fn test_rollback_and_commit() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+    let _ = run_sql(&mut session, "DROP TABLE test_transactions_commit_table").await;
+    run_sql(&mut session, "CREATE TABLE test_transactions_commit_table (id INT PRIMARY KEY, username TEXT, age INT)").await.expect("Failed to create table");
+    run_sql(&mut session, "INSERT INTO test_transactions_commit_table (id, username, age) VALUES (1, 'John', 20), (2, 'Mary', 32)").await.expect("Failed to insert");
+    let initial_data = read_table_data();
+
+    // Act - rolled back insert
+    run_sql(&mut session, "BEGIN").await.expect("Failed to begin transaction");
+    run_sql(&mut session, "INSERT INTO test_transactions_commit_table (id, username, age) VALUES (3, 'Matt', 14)").await.expect("Failed to insert");
+
+    // Assert - visible inside the transaction only
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_transactions_commit_table").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows.len(), 3);
+    assert_eq!(read_table_data(), initial_data);
+
+    run_sql(&mut session, "ROLLBACK").await.expect("Failed to roll back transaction");
+    assert_eq!(read_table_data(), initial_data);
+    assert!(!session.is_in_transaction());
+
+    // Act - committed insert and delete
+    run_sql(&mut session, "BEGIN").await.expect("Failed to begin transaction");
+    run_sql(&mut session, "INSERT INTO test_transactions_commit_table (id, username, age) VALUES (3, 'Matt', 14)").await.expect("Failed to insert");
+    run_sql(&mut session, "DELETE FROM test_transactions_commit_table WHERE id = 1").await.expect("Failed to delete");
+    assert_eq!(read_table_data(), initial_data);
+    run_sql(&mut session, "COMMIT").await.expect("Failed to commit transaction");
+
+    // Assert
+    assert_eq!(read_table_data(), "id,username,age\n2,Mary,32\n3,Matt,14\n");
+
+    let result_sets = run_sql(&mut session, "SELECT id FROM test_transactions_commit_table ORDER BY id").await.expect("Failed to select");
+    assert_eq!(result_sets[0].rows, vec![vec![CellValue::Integer(2)], vec![CellValue::Integer(3)]]);
+
+    // Clean up
+    run_sql(&mut session, "DROP TABLE test_transactions_commit_table").await.expect("Failed to drop table");
+}
+
+#[tokio::test]
+async fn test_failed_statement_aborts_transaction() {
+    // Prepare
+    database_loader::load_database().await.expect("Failed to load database");
+    let mut session = Session::default();
+
+    // Act
+    run_sql(&mut session, "BEGIN").await.expect("Failed to begin transaction");
+    let failed_result = run_sql(&mut session, "SELECT * FROM missing_transactions_table").await;
+    let ignored_result = run_sql(&mut session, "SELECT id FROM test_transactions_table").await;
+
+    // Assert
+    assert!(matches!(failed_result, Err(Error::TableDoesNotExist { .. })));
+    assert!(matches!(ignored_result, Err(Error::TransactionAborted)));
+    assert!(session.is_transaction_failed());
+
+    // Commit of an aborted transaction rolls it back
+    run_sql(&mut session, "COMMIT").await.expect("Failed to end transaction");
+    assert!(!session.is_in_transaction());
+    assert!(matches!(run_sql(&mut session, "COMMIT").await, Err(Error::NoActiveTransaction)));
+}
\ No newline at end of file
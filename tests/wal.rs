@@ -0,0 +1,75 @@
+use std::{fs, io::Write};
+
+use kodasql::{database::{database_loader, database_navigator::get_wal_path}, shared::{errors::Error, file_manager}, transaction_manager::{transaction_context::run_in_transaction, transaction_handler::{begin_transaction, commit_transaction}, types::FileOperation, wal_manager}};
+
+#[tokio::test]
+async fn test_replay_logged_commit_on_load() {
+    // Prepare: a commit logged right before a crash, none of it applied
+    let directory_path = std::env::temp_dir().join("kodasql_test_wal").to_string_lossy().to_string();
+    let file_path = format!("{}/data.csv", directory_path);
+    let _ = fs::remove_dir_all(&directory_path);
+
+    let operations = vec![
+        FileOperation::CreateDirectory { path: directory_path.clone() },
+        FileOperation::Write { path: file_path.clone(), contents: b"id,age\n1,20\n".to_vec() },
+        FileOperation::Append { path: file_path.clone(), offset: 12, contents: b"2,32\n".to_vec() },
+    ];
+    wal_manager::append_record(&operations).expect("Failed to append WAL record");
+
+    // Torn record from a second commit interrupted while logging
+    let mut wal_file = fs::OpenOptions::new().append(true).open(get_wal_path()).expect("Failed to open WAL");
+    wal_file.write_all(&[0, 0, 0, 64, 1, 2]).expect("Failed to write torn record");
+
+    // Act
+    database_loader::load_database().await.expect("Failed to load database");
+
+    // Assert
+    assert_eq!(fs::read_to_string(&file_path).expect("Replayed file missing"), "id,age\n1,20\n2,32\n");
+    assert!(wal_manager::read_records().expect("Failed to read WAL").is_empty());
+    assert_eq!(fs::metadata(get_wal_path()).expect("WAL missing").len(), 0);
+
+    fs::remove_dir_all(&directory_path).expect("Failed to clean up");
+}
+
+#[tokio::test]
+async fn test_writes_require_a_transaction() {
+    let directory_path = std::env::temp_dir().join("kodasql_test_wal_writes").to_string_lossy().to_string();
+    let file_path = format!("{}/data.csv", directory_path);
+    let _ = fs::remove_dir_all(&directory_path);
+
+    // Act - nothing is written behind the write-ahead log's back
+    let error = file_manager::create_dir_all(&directory_path);
+    assert!(matches!(error, Err(Error::NoActiveTransaction)));
+    let error = file_manager::write_string_into_file(&file_path, "id\n");
+    assert!(matches!(error, Err(Error::NoActiveTransaction)));
+    assert!(!fs::exists(&directory_path).expect("Failed to check directory"));
+
+    // Act - the same writes go through once committed
+    let transaction = begin_transaction();
+    run_in_transaction(transaction.clone(), async {
+        file_manager::create_dir_all(&directory_path)?;
+        file_manager::write_string_into_file(&file_path, "id\n")
+    }).await.expect("Failed to stage writes");
+    commit_transaction(transaction).await.expect("Failed to commit");
+
+    // Assert
+    assert_eq!(fs::read_to_string(&file_path).expect("Committed file missing"), "id\n");
+
+    fs::remove_dir_all(&directory_path).expect("Failed to clean up");
+}
+
+#[test]
+fn test_decode_records_stops_at_corruption() {
+    let operations = vec![FileOperation::RemoveDirectory { path: String::from("a") }];
+    let payload = bincode::serialize(&kodasql::transaction_manager::types::WalRecord { operations }).expect("Failed to serialize");
+
+    let mut bytes = (payload.len() as u32).to_be_bytes().to_vec();
+    bytes.extend_from_slice(&crc32fast::hash(&payload).to_be_bytes());
+    bytes.extend_from_slice(&payload);
+    let valid_length = bytes.len();
+    bytes.extend_from_slice(&bytes[..valid_length].to_vec());
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xFF; // Corrupt the second record
+
+    assert_eq!(wal_manager::decode_records(&bytes).len(), 1);
+}
\ No newline at end of file